@@ -0,0 +1,53 @@
+//! This module provides an adapter implementing the `Allocator` trait of the `allocator-api2`
+//! crate for arena handles, enabled by the `allocator-api2` feature.
+//!
+//! Unlike the `allocator_api` feature, this works on stable compilers, and is understood by
+//! third-party collections supporting custom allocators through `allocator-api2`, such as
+//! `hashbrown`. Deallocation returns the memory to the arena if it is the most recent
+//! allocation, and does nothing otherwise.
+use crate::common::AllocHandle;
+
+use allocator_api2::alloc::{AllocError, Allocator};
+
+use std::alloc::Layout;
+use std::ptr::NonNull;
+
+/// An `allocator-api2` allocator allocating from the arena of a handle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HandleAllocator<H>(pub H);
+
+impl<H> HandleAllocator<H> {
+    /// Create an allocator allocating through the given handle.
+    pub fn new(handle: H) -> Self {
+        HandleAllocator(handle)
+    }
+
+    /// Return the handle the allocator allocates through.
+    pub fn handle(&self) -> &H {
+        &self.0
+    }
+
+    /// Consume the allocator and return its handle.
+    pub fn into_handle(self) -> H {
+        self.0
+    }
+}
+
+unsafe impl<H: AllocHandle> Allocator for HandleAllocator<H> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        // zero-sized allocations don't need to consume arena space.
+        let ptr = if layout.size() == 0 {
+            unsafe { NonNull::new_unchecked(layout.align() as *mut u8) }
+        } else {
+            self.0.try_allocate_layout(layout).map_err(|_| AllocError)?
+        };
+
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            self.0.dealloc_last(ptr, layout.size());
+        }
+    }
+}
@@ -0,0 +1,1241 @@
+//! This module provides a thread-safe variant of the reference-counted arena in the `rc` module.
+//!
+//! The arena works just like the one in the `rc` module, but its allocation position is updated
+//! atomically and its handles are reference-counted using `Arc`. Hence, handles are `Send` and
+//! `Sync`, so that an arena can be built on one thread and consumed (or cleared) on another, and
+//! multiple threads can allocate from the same arena concurrently.
+//!
+//! Compared to the `sync` module, which only supports allocation and clearing, this arena
+//! supports most features of the `rc` arena: quotas, markers, clear observers, snapshots,
+//! budgeted child references, carving, forking, growing and serialization. Operations moving or
+//! clearing the arena borrow it exclusively, so that no other thread can create new references
+//! in the meantime. Operations copying the arena memory as a whole are unsafe, as other threads
+//! must not write to the arena while they are running.
+//!
+//! Allocations from the back end of the arena, freezing and sealing are not available, since
+//! they would have to update the arena position together with other state. Neither are
+//! allocation statistics and tracing.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{
+    self, AllocHandle, ArenaBacking, ArenaError, ClearPhase, Marker, OwnedBacking, Snapshot,
+    SERIALIZED_MAGIC, SERIALIZED_VERSION,
+};
+use crate::compact::ArenaBase;
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+use crate::tree;
+use crate::vecmap;
+
+use std::alloc::Layout;
+#[cfg(feature = "serde")]
+use std::cell::RefCell;
+use std::cmp;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{self, AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(feature = "serde")]
+use serde::de::{self, Deserialize, Deserializer};
+
+/// A reference-counting, thread-safe arena.
+///
+/// This is the only object that can be used to clear the arena. All other objects referring to
+/// the arena merely allow for allocation, and are present to avoid arena clearing while they are
+/// live.
+#[derive(Debug)]
+pub struct Arena(InnerRef, ArenaBacking);
+
+/// A non-owning object referring to the arena.
+///
+/// A reference to the arena that allows its holder to allocate memory from the arena. While
+/// it is live, the arena cannot be cleared (as it is associated with an arena-allocated
+/// object).
+#[derive(Clone, Debug)]
+pub struct InnerRef {
+    inner: Arc<Inner>,
+
+    /// Generation of the arena the reference has been created in
+    generation: u64,
+}
+
+/// A reference to the arena which can only allocate up to a budget of bytes.
+///
+/// Obtained using `InnerRef::child`, this allows for handing a component a share of an arena
+/// shared with other components, without letting it exhaust the arena. Clones of the reference
+/// share its budget, also across threads. Allocations are charged with their size (excluding
+/// alignment padding), and memory returned to the arena by dropping (or shrinking) the last
+/// allocation is credited back. Allocations failing for lack of budget fail with
+/// `ArenaError::QuotaExceeded`.
+#[derive(Clone, Debug)]
+pub struct ChildRef {
+    inner: InnerRef,
+
+    /// Number of bytes the reference can still allocate
+    budget: Arc<AtomicUsize>,
+}
+
+/// A child arena occupying a contiguous range of memory carved out of another arena.
+///
+/// Obtained using `Arena::carve`, the child arena has its own position, quota and handles, so
+/// that a subsystem allocating from it can exhaust it (or clear it) without affecting the
+/// parent arena or its siblings. The parent arena can't be cleared while the child arena is
+/// live.
+#[derive(Debug)]
+pub struct SubArena {
+    /// The child arena
+    arena: Arena,
+
+    /// The parent arena, kept from being cleared while the child arena is live
+    _parent: InnerRef,
+}
+
+/// Limits on the memory handed out by an arena.
+///
+/// This works like `common::Quota`, except that the callback can be invoked on any thread
+/// allocating from the arena, and hence has to be `Send` and `Sync`.
+#[derive(Clone, Default)]
+pub struct Quota {
+    /// The soft limit
+    soft: Option<usize>,
+
+    /// The callback to invoke when the soft limit is exceeded
+    on_soft: Option<Arc<dyn Fn(usize) + Send + Sync>>,
+
+    /// The hard limit
+    hard: Option<usize>,
+}
+
+/// A callback invoked when an arena is cleared.
+type ClearObserver = Arc<dyn Fn(ClearPhase) + Send + Sync>;
+
+/// An arena's guts
+struct Inner {
+    /// Head of the arena space
+    head: NonNull<u8>,
+
+    /// Offset into the arena space
+    pos: AtomicUsize,
+
+    /// Offset up to which the arena space may have been written to before the last rewind
+    dirty: AtomicUsize,
+
+    /// Limits on the memory handed out by the arena
+    quota: Mutex<Quota>,
+
+    /// The hard limit of the quota, or `usize::MAX`, kept apart to avoid locking the quota
+    hard_limit: AtomicUsize,
+
+    /// The soft limit of the quota, or `usize::MAX`
+    soft_limit: AtomicUsize,
+
+    /// Whether to release the physical memory backing the arena when it is cleared
+    decommit: AtomicBool,
+
+    /// Whether the arena memory is a private mapping created by forking an arena
+    forked: AtomicBool,
+
+    /// Number of clears, references from earlier generations can't be used anymore
+    generation: AtomicU64,
+
+    /// Callbacks to invoke when the arena is cleared
+    observers: Mutex<Vec<ClearObserver>>,
+
+    /// Total capacity of the arena
+    cap: AtomicUsize,
+}
+
+// the arena space is only ever handed out in disjoint pieces, the position being updated
+// atomically.
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
+
+#[cfg(feature = "serde")]
+thread_local! {
+    /// The arena vectors are deserialized into on the current thread, if any.
+    static DESERIALIZE_ARENA: RefCell<Option<InnerRef>> = const { RefCell::new(None) };
+}
+
+/// An arena allocated, fixed-size sequence of objects
+pub type Slice<T> = common::Slice<T, InnerRef>;
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub type SliceVec<T> = common::SliceVec<T, InnerRef>;
+
+/// An arena allocated, owned object
+pub type ArenaBox<T> = boxed::ArenaBox<T, InnerRef>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'b, T> = cow::ArenaCow<'b, T, InnerRef>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<T> = deque::ArenaVecDeque<T, InnerRef>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<K, V> = btree::ArenaBTreeMap<K, V, InnerRef>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<T> = heap::ArenaBinaryHeap<T, InnerRef>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<T> = slab::ArenaSlab<T, InnerRef>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<T> = generational::GenArena<T, InnerRef>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec = bitvec::ArenaBitVec<InnerRef>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<T> = grid::ArenaGrid<T, InnerRef>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<K, V> = vecmap::ArenaVecMap<K, V, InnerRef>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<T> = vecmap::ArenaVecSet<T, InnerRef>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<K, V> = hash::ArenaHashMap<K, V, InnerRef>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<T> = hash::ArenaHashSet<T, InnerRef>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<T> = chunkvec::ChunkedVec<T, InnerRef>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<T> = ring::ArenaRingBuffer<T, InnerRef>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<N, E> = graph::ArenaGraph<N, E, InnerRef>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<T> = tree::NodeArena<T, InnerRef>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor = cursor::ArenaCursor<InnerRef>;
+
+/// Run `f` with `arena` set up as the arena `SliceVec`s are deserialized into on the current
+/// thread.
+///
+/// This works like `rc::with_deserialize_arena`.
+#[cfg(feature = "serde")]
+pub fn with_deserialize_arena<R, F: FnOnce() -> R>(arena: &InnerRef, f: F) -> R {
+    /// Restores the previous arena, even if `f` panics.
+    struct Restore(Option<InnerRef>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            DESERIALIZE_ARENA.with(|cell| *cell.borrow_mut() = prev);
+        }
+    }
+
+    let prev = DESERIALIZE_ARENA.with(|cell| cell.replace(Some(arena.clone())));
+    let _restore = Restore(prev);
+
+    f()
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SliceVec<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match DESERIALIZE_ARENA.with(|cell| cell.borrow().clone()) {
+            Some(arena) => Self::deserialize_in(arena, deserializer),
+            None => Err(de::Error::custom(
+                "no arena has been set up using `with_deserialize_arena`",
+            )),
+        }
+    }
+}
+
+impl Arena {
+    /// Create an `Arena` backed by a memory mapping of the default capacity.
+    ///
+    /// See `rc::Arena::init`.
+    pub fn init() -> Result<Self, ArenaError> {
+        Self::init_capacity(ArenaBacking::MemoryMap, common::default_capacity()?)
+    }
+
+    /// Create an `Arena` with specified capacity.
+    ///
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. It can be
+    /// grown after the fact using `grow`, if the arena is backed by a memory mapping.
+    pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
+        let dirty = if backing.is_zeroed() { 0 } else { cap };
+
+        Ok(Arena(
+            InnerRef {
+                inner: Arc::new(Inner {
+                    head,
+                    pos: AtomicUsize::new(0),
+                    dirty: AtomicUsize::new(dirty),
+                    quota: Mutex::new(Quota::new()),
+                    hard_limit: AtomicUsize::new(usize::MAX),
+                    soft_limit: AtomicUsize::new(usize::MAX),
+                    decommit: AtomicBool::new(false),
+                    forked: AtomicBool::new(false),
+                    generation: AtomicU64::new(0),
+                    observers: Mutex::new(Vec::new()),
+                    cap: AtomicUsize::new(cap),
+                }),
+                generation: 0,
+            },
+            backing,
+        ))
+    }
+
+    /// Create another reference to the arena.
+    pub fn inner(&self) -> InnerRef {
+        self.0.clone()
+    }
+
+    /// Reserve `bytes` bytes of the arena and return a child arena allocating from them.
+    ///
+    /// The reserved memory is aligned to the page size and rounded up to a multiple of it. It
+    /// can't be grown, and is given back to the parent arena only when it is cleared.
+    pub fn carve(&self, bytes: usize) -> Result<SubArena, ArenaError> {
+        let bytes = common::round_capacity(bytes)?;
+        let layout = Layout::from_size_align(bytes, common::get_page_size())
+            .map_err(|_| ArenaError::InvalidLayout)?;
+        let base = self.try_allocate_layout(layout)?;
+
+        Ok(SubArena {
+            arena: Arena::init_capacity(common::Carved::backing(base, bytes), bytes)?,
+            _parent: self.inner(),
+        })
+    }
+
+    /// Create a copy of the arena sharing its memory copy-on-write.
+    ///
+    /// This works like `rc::Arena::fork`: only arenas backed by private memory mappings (or
+    /// forked themselves) on Linux can be forked, and neither arena can be grown afterwards.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may write to the arena while it is forked, as such writes may be lost.
+    pub unsafe fn fork(&mut self) -> Result<Arena, ArenaError> {
+        let forkable = matches!(
+            self.1,
+            ArenaBacking::MemoryMap | ArenaBacking::PopulatedMemoryMap
+        ) || self.inner.forked.load(Ordering::Relaxed);
+
+        if !forkable {
+            return Err(ArenaError::CannotFork);
+        }
+
+        let head = self.inner.head;
+        let cap = self.capacity();
+        let pos = self.inner.pos.load(Ordering::Acquire);
+
+        let copy = NonNull::new(common::fork_mapping(head, cap, &[(0, pos)]))
+            .ok_or(ArenaError::CannotFork)?;
+
+        // the original mapping has been replaced, and has to be released as such.
+        self.1 = common::Forked::backing(head, cap, true);
+        self.inner.forked.store(true, Ordering::Relaxed);
+
+        let arena = Arena::init_capacity(common::Forked::backing(copy, cap, false), cap)?;
+
+        // the memory past the position reads as zeroed in both arenas.
+        for inner in &[&self.inner, &arena.inner] {
+            inner.pos.store(pos, Ordering::Relaxed);
+            inner.dirty.store(pos, Ordering::Relaxed);
+            inner.forked.store(true, Ordering::Relaxed);
+        }
+
+        Ok(arena)
+    }
+
+    /// Return the number of live references to the arena, not counting the arena itself.
+    ///
+    /// These include references held by containers allocated from the arena, in any thread. The
+    /// arena can only be cleared if there are none.
+    pub fn live_handles(&self) -> usize {
+        Arc::strong_count(&self.inner) - 1
+    }
+
+    /// Take the memory out of the arena without releasing it.
+    ///
+    /// This is only possible if no other reference to the arena is live, otherwise the arena is
+    /// handed back. The memory can then be repurposed, for instance by another allocator, and is
+    /// released when the returned object is dropped.
+    pub fn into_inner(self) -> Result<OwnedBacking, Self> {
+        if Arc::strong_count(&self.inner) != 1 {
+            return Err(self);
+        }
+
+        // synchronize with the drops of references in other threads, see `clear`.
+        atomic::fence(Ordering::Acquire);
+
+        let head = self.inner.head;
+        let cap = self.capacity();
+        let mut arena = mem::ManuallyDrop::new(self);
+
+        // the backing is moved out, and the last reference to the arena's guts dropped, without
+        // running the arena's `drop`, which would release the memory.
+        unsafe {
+            let backing = ptr::read(&arena.1);
+            ptr::drop_in_place(&mut arena.0);
+
+            Ok(OwnedBacking::from_raw_parts(head, cap, backing))
+        }
+    }
+
+    /// Clear the arena.
+    ///
+    /// This fails if any other reference to the arena is still live, in any thread. The arena
+    /// is borrowed exclusively, so that no other thread can create new references while it is
+    /// being cleared.
+    pub fn clear(&mut self) -> Result<(), ArenaError> {
+        if Arc::strong_count(&self.inner) == 1 {
+            // synchronize with the drops of references in other threads, so that their accesses
+            // to the arena happen before it is reused, like `Arc::get_mut` does.
+            atomic::fence(Ordering::Acquire);
+
+            self.clear_inner();
+
+            Ok(())
+        } else {
+            Err(ArenaError::CannotClear {
+                live_handles: self.live_handles(),
+            })
+        }
+    }
+
+    /// Clear the arena, even if references to it are still live.
+    ///
+    /// This works like `rc::Arena::clear_force`: references created before refuse to allocate
+    /// with `ArenaError::StaleHandle`, and checked containers from earlier generations refuse
+    /// to access their contents.
+    ///
+    /// # Safety
+    ///
+    /// The memory of the arena is reused, so no object allocated from it before may be accessed
+    /// afterwards, except through the checked containers mentioned above. Other containers from
+    /// earlier generations have to be forgotten using `mem::forget`, unless their elements don't
+    /// need to be dropped. Child arenas carved out of the arena are invalidated as well. No
+    /// other thread may use a reference to the arena while it is being cleared.
+    pub unsafe fn clear_force(&mut self) {
+        self.clear_inner();
+    }
+
+    /// Clear the arena and start a new generation, making older references stale.
+    fn clear_inner(&mut self) {
+        self.notify(ClearPhase::Before);
+
+        self.inner.rewind(0);
+
+        if self.inner.decommit.load(Ordering::Relaxed) {
+            let dirty = self.inner.dirty.load(Ordering::Relaxed);
+
+            // the memory reads as zeroed afterwards, so it doesn't need to be zeroed on
+            // allocation.
+            if dirty > 0 && self.1.decommit(self.inner.head, dirty) {
+                self.inner.dirty.store(0, Ordering::Relaxed);
+            }
+        }
+
+        let generation = self.inner.generation.fetch_add(1, Ordering::Relaxed) + 1;
+        self.0.generation = generation;
+
+        self.notify(ClearPhase::After);
+    }
+
+    /// Register a callback to invoke just before and after the arena is cleared.
+    ///
+    /// This works like `rc::Arena::on_clear`, except that the callback has to be `Send` and
+    /// `Sync`, since the arena may be cleared on another thread.
+    pub fn on_clear<F>(&self, callback: F)
+    where
+        F: Fn(ClearPhase) + Send + Sync + 'static,
+    {
+        lock(&self.inner.observers).push(Arc::new(callback));
+    }
+
+    /// Invoke all clear observers in the order they have been registered.
+    fn notify(&self, phase: ClearPhase) {
+        // callbacks may register further callbacks, which are only invoked on the next clear.
+        let observers = lock(&self.inner.observers).clone();
+
+        for observer in observers {
+            observer(phase);
+        }
+    }
+
+    /// Grow the capacity of the arena by `additional` bytes.
+    ///
+    /// If the arena is backed by a memory mapping and the address space following it is unused,
+    /// the mapping is extended in place. Otherwise, the arena has to be moved to a larger
+    /// mapping (or allocation), which is only possible if no objects allocated from the arena are
+    /// live, and clears the arena. Shared memory arenas can't be grown.
+    pub fn grow(&mut self, additional: usize) -> Result<(), ArenaError> {
+        let head = self.inner.head;
+        let cap = self.capacity();
+        let new_cap = cap
+            .checked_add(additional)
+            .ok_or(ArenaError::CapacityOverflow)?;
+
+        if self.1.grow_in_place(head, cap, new_cap) {
+            // other threads only allocate from the new memory once they observe the capacity.
+            self.inner.cap.store(new_cap, Ordering::Release);
+            return Ok(());
+        }
+
+        if !self.1.is_movable() {
+            return Err(ArenaError::CannotGrow);
+        }
+
+        let inner = Arc::get_mut(&mut self.0.inner).ok_or(ArenaError::CannotGrow)?;
+        let new_head = self.1.try_create(new_cap)?;
+
+        self.1.destroy(head, cap);
+
+        // no object has been allocated from the new memory yet.
+        inner.head = new_head;
+        *inner.cap.get_mut() = new_cap;
+        *inner.pos.get_mut() = 0;
+        *inner.dirty.get_mut() = if self.1.is_zeroed() { 0 } else { new_cap };
+
+        Ok(())
+    }
+
+    /// Change the capacity of the arena to `new_cap` bytes, keeping its contents.
+    ///
+    /// The new capacity is rounded up to a multiple of the page size, and has to fit all
+    /// allocations made from the arena. Growing the arena extends its memory in place if
+    /// possible (see `grow`), otherwise the arena is moved to new memory of the requested size,
+    /// and its contents are copied over. This is only possible if no references to the arena are
+    /// live, since objects allocated from it change their address.
+    pub fn resize(&mut self, new_cap: usize) -> Result<(), ArenaError> {
+        let new_cap = common::round_capacity(new_cap)?;
+        let head = self.inner.head;
+        let cap = self.capacity();
+
+        if new_cap == cap {
+            return Ok(());
+        }
+
+        if !self.1.is_movable() {
+            return Err(ArenaError::CannotGrow);
+        }
+
+        let inner = Arc::get_mut(&mut self.0.inner).ok_or(ArenaError::CannotGrow)?;
+        let pos = *inner.pos.get_mut();
+
+        if pos > new_cap {
+            return Err(common::capacity_exceeded(pos, 0, new_cap));
+        }
+
+        if new_cap > cap && self.1.grow_in_place(head, cap, new_cap) {
+            *inner.cap.get_mut() = new_cap;
+            return Ok(());
+        }
+
+        let new_head = self.1.try_create(new_cap)?;
+
+        unsafe { ptr::copy_nonoverlapping(head.as_ptr(), new_head.as_ptr(), pos) };
+
+        self.1.destroy(head, cap);
+
+        inner.head = new_head;
+        *inner.cap.get_mut() = new_cap;
+        *inner.dirty.get_mut() = if self.1.is_zeroed() { pos } else { new_cap };
+
+        Ok(())
+    }
+
+    /// Return the total capacity of the arena.
+    pub fn capacity(&self) -> usize {
+        self.inner.cap.load(Ordering::Acquire)
+    }
+
+    /// Return the number of bytes allocated from the arena, including alignment padding.
+    ///
+    /// Other threads may allocate concurrently, so this is merely a snapshot.
+    pub fn used(&self) -> usize {
+        self.inner.pos.load(Ordering::Relaxed)
+    }
+
+    /// Set whether the physical memory backing the arena is returned to the operating system when
+    /// the arena is cleared.
+    ///
+    /// See `rc::Arena::set_decommit_on_clear`.
+    pub fn set_decommit_on_clear(&self, decommit: bool) {
+        self.inner.decommit.store(decommit, Ordering::Relaxed);
+    }
+
+    /// Set the quota limiting the memory handed out by the arena, replacing the previous one.
+    pub fn set_quota(&self, quota: Quota) {
+        let mut current = lock(&self.inner.quota);
+
+        self.inner
+            .hard_limit
+            .store(quota.hard.unwrap_or(usize::MAX), Ordering::Relaxed);
+        self.inner
+            .soft_limit
+            .store(quota.soft.unwrap_or(usize::MAX), Ordering::Relaxed);
+        *current = quota;
+    }
+
+    /// Return a marker for the current position in the arena.
+    pub fn mark(&self) -> Marker {
+        Marker {
+            pos: self.inner.pos.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Rewind the arena to a previously obtained marker, releasing all memory allocated since.
+    ///
+    /// Panics if the arena has been cleared or rewound past the marker in the meantime.
+    ///
+    /// # Safety
+    ///
+    /// No memory allocated after the marker has been obtained may be accessed afterwards, by any
+    /// thread. This includes memory that objects allocated before have grown into, and implies
+    /// that all objects allocated since have to be dropped or forgotten. No other thread may
+    /// allocate from the arena while it is rewound.
+    pub unsafe fn reset_to(&self, marker: Marker) {
+        assert!(
+            marker.pos <= self.inner.pos.load(Ordering::Relaxed),
+            "marker lies beyond the arena position"
+        );
+
+        self.inner.rewind(marker.pos);
+    }
+
+    /// Write the memory allocated from the arena, along with its position and capacity, to
+    /// `writer`.
+    ///
+    /// This works like `rc::Arena::serialize_to`, and the output can be loaded using either
+    /// `load_from` or `rc::Arena::load_from`.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may write to the arena while it is being serialized.
+    pub unsafe fn serialize_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let head = self.inner.head;
+        let cap = self.capacity();
+        let pos = self.inner.pos.load(Ordering::Acquire);
+
+        writer.write_all(SERIALIZED_MAGIC)?;
+
+        for &field in &[SERIALIZED_VERSION, cap as u64, pos as u64, cap as u64] {
+            writer.write_all(&field.to_le_bytes())?;
+        }
+
+        writer.write_all(std::slice::from_raw_parts(head.as_ptr(), pos))
+    }
+
+    /// Create an arena from the output of `serialize_to` read from `reader`.
+    ///
+    /// This works like `rc::Arena::load_from`. Since this arena can't allocate from its back
+    /// end, serialized arenas with allocations from the back are rejected with
+    /// `io::ErrorKind::InvalidData`.
+    pub fn load_from<R: Read>(backing: ArenaBacking, mut reader: R) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != SERIALIZED_MAGIC {
+            return Err(invalid("not a serialized arena"));
+        }
+
+        let mut fields = [0; 4];
+
+        for field in fields.iter_mut() {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            *field = u64::from_le_bytes(bytes);
+        }
+
+        let [version, cap, pos, back] = fields;
+
+        if version != SERIALIZED_VERSION {
+            return Err(invalid("unsupported serialized arena version"));
+        }
+
+        if back != cap {
+            return Err(invalid("arena has allocations from the back end"));
+        }
+
+        let cap = usize::try_from(cap).map_err(|_| invalid("arena capacity out of range"))?;
+        let pos = usize::try_from(pos).map_err(|_| invalid("arena position out of range"))?;
+
+        if pos > cap {
+            return Err(invalid("arena position out of range"));
+        }
+
+        let arena = Arena::init_capacity(backing, cap)
+            .map_err(|err| io::Error::other(format!("{:?}", err)))?;
+        let head = arena.inner.head;
+
+        unsafe { reader.read_exact(std::slice::from_raw_parts_mut(head.as_ptr(), pos))? };
+
+        arena.inner.pos.store(pos, Ordering::Relaxed);
+        arena.inner.dirty.fetch_max(pos, Ordering::Relaxed);
+
+        Ok(arena)
+    }
+
+    /// Take a snapshot of the contents of the arena and its position.
+    ///
+    /// This copies all memory allocated from the arena so far, so that speculative computations
+    /// can be undone using `restore`.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may write to the arena while the snapshot is taken.
+    pub unsafe fn snapshot(&self) -> Snapshot {
+        Snapshot::take(self.inner.head, self.inner.pos.load(Ordering::Acquire))
+    }
+
+    /// Restore the contents of the arena and its position from a snapshot.
+    ///
+    /// Panics if the snapshot has been taken from another arena.
+    ///
+    /// # Safety
+    ///
+    /// See `rc::Arena::restore`. Additionally, no other thread may access the arena while it is
+    /// restored.
+    pub unsafe fn restore(&self, snapshot: &Snapshot) {
+        let pos = snapshot.restore(self.inner.head);
+
+        self.inner.rewind(pos);
+    }
+}
+
+impl Deref for Arena {
+    type Target = InnerRef;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        self.1.destroy(self.inner.head, self.capacity());
+    }
+}
+
+impl Deref for SubArena {
+    type Target = Arena;
+
+    fn deref(&self) -> &Self::Target {
+        &self.arena
+    }
+}
+
+impl SubArena {
+    /// Return the child arena, which allows for clearing and growing it.
+    pub fn arena_mut(&mut self) -> &mut Arena {
+        &mut self.arena
+    }
+}
+
+impl Quota {
+    /// Create a quota without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the soft limit, invoking `callback` with the arena usage once it is exceeded.
+    pub fn soft<F>(mut self, limit: usize, callback: F) -> Self
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.soft = Some(limit);
+        self.on_soft = Some(Arc::new(callback));
+        self
+    }
+
+    /// Set the hard limit.
+    pub fn hard(mut self, limit: usize) -> Self {
+        self.hard = Some(limit);
+        self
+    }
+
+    /// Return the soft limit, if any.
+    pub fn soft_limit(&self) -> Option<usize> {
+        self.soft
+    }
+
+    /// Return the hard limit, if any.
+    pub fn hard_limit(&self) -> Option<usize> {
+        self.hard
+    }
+}
+
+impl fmt::Debug for Quota {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Quota")
+            .field("soft", &self.soft)
+            .field("hard", &self.hard)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Inner {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Inner")
+            .field("head", &self.head)
+            .field("pos", &self.pos)
+            .field("cap", &self.cap)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl Inner {
+    /// Reserve `size` bytes aligned to `align` from the arena.
+    fn bump(&self, size: usize, align: usize) -> Result<NonNull<u8>, ArenaError> {
+        let head = self.head.as_ptr();
+        let limit = self.limit();
+        let mut pos = self.pos.load(Ordering::Relaxed);
+
+        loop {
+            let skip = (head as usize).wrapping_add(pos).wrapping_neg() & (align - 1);
+            let end = match (pos + skip).checked_add(size) {
+                Some(end) if end <= limit => end,
+                _ => return Err(self.exceeded(size, pos, limit)),
+            };
+
+            // synchronize with the thread that released the memory last, see `dealloc_last`.
+            match self
+                .pos
+                .compare_exchange_weak(pos, end, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => {
+                    self.check_soft_limit(pos, end);
+                    return unsafe { Ok(NonNull::new_unchecked(head.add(pos + skip))) };
+                }
+                Err(p) => pos = p,
+            }
+        }
+    }
+
+    /// Return the capacity of the arena as limited by the hard limit of the quota.
+    fn limit(&self) -> usize {
+        cmp::min(
+            self.cap.load(Ordering::Acquire),
+            self.hard_limit.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Return the error for a failed allocation of `size` bytes at `pos`, given the capacity
+    /// `limit` imposed by the quota.
+    fn exceeded(&self, size: usize, pos: usize, limit: usize) -> ArenaError {
+        match common::capacity_exceeded(size, pos, limit) {
+            ArenaError::CapacityExceeded {
+                requested,
+                remaining,
+            } if limit < self.cap.load(Ordering::Relaxed) => ArenaError::QuotaExceeded {
+                requested,
+                remaining,
+            },
+            err => err,
+        }
+    }
+
+    /// Invoke the soft limit callback if an allocation moving the position from `old_pos` to
+    /// `pos` crossed the soft limit.
+    fn check_soft_limit(&self, old_pos: usize, pos: usize) {
+        let soft = self.soft_limit.load(Ordering::Relaxed);
+
+        if old_pos <= soft && pos > soft {
+            // the quota is not locked while running the callback, so it can be replaced.
+            let callback = lock(&self.quota).on_soft.clone();
+
+            if let Some(callback) = callback {
+                callback(pos);
+            }
+        }
+    }
+
+    /// Move the position back to `pos`, keeping track of the memory written to so far.
+    ///
+    /// With the `sanitize` feature, the released memory is overwritten with `POISON`.
+    fn rewind(&self, pos: usize) {
+        let old_pos = self.pos.load(Ordering::Relaxed);
+
+        self.dirty.fetch_max(old_pos, Ordering::Relaxed);
+
+        if pos < old_pos {
+            unsafe {
+                common::poison(
+                    NonNull::new_unchecked(self.head.as_ptr().add(pos)),
+                    old_pos - pos,
+                )
+            };
+        }
+
+        // synchronize with the threads allocating the memory next, see `dealloc_last`.
+        self.pos.store(pos, Ordering::Release);
+    }
+}
+
+impl InnerRef {
+    /// Return a pointer to the start of the arena memory, which `ArenaOffset`s are relative to.
+    pub fn base(&self) -> NonNull<u8> {
+        self.inner.head
+    }
+
+    /// Return `true` if the arena has been force-cleared since the reference has been created.
+    pub fn is_stale(&self) -> bool {
+        self.generation != self.inner.generation.load(Ordering::Relaxed)
+    }
+
+    /// Create a reference to the arena which can allocate at most `budget` bytes.
+    pub fn child(&self, budget: usize) -> ChildRef {
+        ChildRef {
+            inner: self.clone(),
+            budget: Arc::new(AtomicUsize::new(budget)),
+        }
+    }
+
+    /// Refuse to allocate through a stale reference.
+    fn check_generation(&self) -> Result<(), ArenaError> {
+        if self.is_stale() {
+            Err(ArenaError::StaleHandle)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl ChildRef {
+    /// Return the number of bytes the reference can still allocate.
+    pub fn remaining(&self) -> usize {
+        self.budget.load(Ordering::Relaxed)
+    }
+
+    /// Take `size` bytes from the budget, if possible.
+    fn charge(&self, size: usize) -> Result<(), ArenaError> {
+        self.budget
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |budget| {
+                budget.checked_sub(size)
+            })
+            .map(|_| ())
+            .map_err(|budget| ArenaError::QuotaExceeded {
+                requested: size,
+                remaining: budget,
+            })
+    }
+
+    /// Give `size` bytes back to the budget.
+    fn credit(&self, size: usize) {
+        self.budget.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Charge the budget with `size` bytes for the allocation made by `allocate`, which is
+    /// credited back if the allocation fails.
+    fn charged<R, F>(&self, size: usize, allocate: F) -> Result<R, ArenaError>
+    where
+        F: FnOnce() -> Result<R, ArenaError>,
+    {
+        self.charge(size)?;
+
+        allocate().inspect_err(|_| self.credit(size))
+    }
+}
+
+/// Lock `mutex`, ignoring poisoning, as the data it protects is never left inconsistent.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+unsafe impl AllocHandle for InnerRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+
+        // zero-sized objects don't take up any arena space, so they are placed at a dangling
+        // (but well-aligned) pointer instead.
+        if mem::size_of::<T>() == 0 {
+            return Ok(NonNull::dangling());
+        }
+
+        let layout = Layout::array::<T>(count).map_err(|_| ArenaError::CapacityOverflow)?;
+
+        Ok(self.inner.bump(layout.size(), layout.align())?.cast())
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+
+        let size = mem::size_of::<T>();
+
+        if size == 0 {
+            return Ok(ptr);
+        }
+
+        // `ptr` may be dangling if nothing has been allocated yet, in which case the offset is
+        // meaningless, but doesn't match the position either.
+        let offset = (ptr.as_ptr() as usize).wrapping_sub(self.inner.head.as_ptr() as usize);
+        let old_end = offset.wrapping_add(old_count * size);
+        let additional = count
+            .saturating_sub(old_count)
+            .checked_mul(size)
+            .ok_or(ArenaError::CapacityOverflow)?;
+
+        // if another thread has allocated in the meantime, the sequence is moved instead.
+        if let Some(new_end) = old_end.checked_add(additional) {
+            if new_end <= self.inner.limit()
+                && self
+                    .inner
+                    .pos
+                    .compare_exchange(old_end, new_end, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                self.inner.check_soft_limit(old_end, new_end);
+                return Ok(ptr);
+            }
+        }
+
+        self.try_allocate(count)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr: NonNull<T> = self.try_allocate(count)?;
+
+        if mem::size_of::<T>() == 0 {
+            return Ok(ptr);
+        }
+
+        // only the memory that may have been written to before has to be zeroed.
+        let start = ptr.as_ptr() as usize - self.inner.head.as_ptr() as usize;
+        let end = cmp::min(
+            start + count * mem::size_of::<T>(),
+            self.inner.dirty.load(Ordering::Relaxed),
+        );
+
+        if start < end {
+            unsafe { ptr::write_bytes(ptr.as_ptr() as *mut u8, 0, end - start) };
+        }
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.check_generation()?;
+
+        self.inner.bump(layout.size(), layout.align())
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        // the allocation has been released by a forced clear already.
+        if self.is_stale() {
+            return false;
+        }
+
+        let size = count * mem::size_of::<T>();
+        let pos = self.inner.pos.load(Ordering::Relaxed);
+
+        match common::last_allocation_offset(self.inner.head, pos, ptr.cast(), size) {
+            // another thread might have allocated in the meantime, in which case the allocation
+            // is no longer the last one. The memory is poisoned (and marked as written to) up
+            // front, as another thread may allocate it as soon as it is released. Releasing the
+            // memory synchronizes with the thread allocating it next, so that our accesses to it
+            // happen before theirs. See `sync::InnerRef::dealloc_last`.
+            Some(offset) => {
+                self.inner.dirty.fetch_max(pos, Ordering::Relaxed);
+                common::poison(ptr.cast(), size);
+
+                self.inner
+                    .pos
+                    .compare_exchange(pos, offset, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            }
+            None => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        if self.is_stale() {
+            return false;
+        }
+
+        let size = mem::size_of::<T>();
+        let pos = self.inner.pos.load(Ordering::Relaxed);
+
+        match common::last_allocation_offset(self.inner.head, pos, ptr.cast(), old_count * size) {
+            // the tail is released like in `dealloc_last`.
+            Some(offset) => {
+                self.inner.dirty.fetch_max(pos, Ordering::Relaxed);
+                common::poison(
+                    NonNull::new_unchecked(ptr.as_ptr().add(count)).cast(),
+                    (old_count - count) * size,
+                );
+
+                self.inner
+                    .pos
+                    .compare_exchange(
+                        pos,
+                        offset + count * size,
+                        Ordering::AcqRel,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+            }
+            None => false,
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        common::owns_inner(
+            self.inner.head,
+            self.inner.cap.load(Ordering::Relaxed),
+            ptr,
+            size,
+        )
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(Arc::as_ptr(&self.inner) as usize)
+    }
+
+    fn is_stale(&self) -> bool {
+        Self::is_stale(self)
+    }
+}
+
+unsafe impl AllocHandle for ChildRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let size = Layout::array::<T>(count)
+            .map_err(|_| ArenaError::CapacityOverflow)?
+            .size();
+
+        self.charged(size, || self.inner.try_allocate(count))
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let size = Layout::array::<T>(count)
+            .map_err(|_| ArenaError::CapacityOverflow)?
+            .size();
+        let old_size = old_count * mem::size_of::<T>();
+        let extra = size.saturating_sub(old_size);
+
+        // an allocation extended in place has been charged with its old size already.
+        let new_ptr = self.charged(extra, || {
+            self.inner.try_allocate_or_extend(ptr, old_count, count)
+        })?;
+
+        if new_ptr != ptr {
+            if let Err(err) = self.charge(size - extra) {
+                // the moved allocation is handed back, unless another thread has allocated in
+                // the meantime.
+                unsafe { self.inner.dealloc_last(new_ptr, count) };
+                self.credit(extra);
+
+                return Err(err);
+            }
+        }
+
+        Ok(new_ptr)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let size = Layout::array::<T>(count)
+            .map_err(|_| ArenaError::CapacityOverflow)?
+            .size();
+
+        self.charged(size, || self.inner.try_allocate_zeroed(count))
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.charged(layout.size(), || self.inner.try_allocate_layout(layout))
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let released = self.inner.dealloc_last(ptr, count);
+
+        if released {
+            self.credit(count * mem::size_of::<T>());
+        }
+
+        released
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let released = self.inner.allocate_or_shrink(ptr, old_count, count);
+
+        if released {
+            self.credit((old_count - count) * mem::size_of::<T>());
+        }
+
+        released
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.inner.owns(ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        self.inner.arena_id()
+    }
+
+    fn is_stale(&self) -> bool {
+        self.inner.is_stale()
+    }
+}
+
+impl ArenaBase for InnerRef {
+    fn base(&self) -> NonNull<u8> {
+        self.inner.head
+    }
+}
+
+impl ArenaBase for ChildRef {
+    fn base(&self) -> NonNull<u8> {
+        self.inner.base()
+    }
+}
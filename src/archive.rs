@@ -0,0 +1,102 @@
+//! This module provides support for zero-copy archives built with the `rkyv` crate, enabled by
+//! the `rkyv` feature.
+//!
+//! `Slice`s and `SliceVec`s are archived the same way as `Vec`s, so archives can be accessed as
+//! if they were built from standard vectors. Archived vectors can be deserialized into any arena
+//! using `SliceVec::deserialize_archived_in`, or through `rkyv::Deserialize` if the handle type
+//! implements `Default`.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use rkyv::rancor::{Fallible, Source};
+use rkyv::ser::{Allocator, Writer};
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::{Archive, Deserialize, Place, Serialize};
+
+impl<T: Archive, H> Archive for Slice<T, H> {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_slice(self, resolver, out);
+    }
+}
+
+impl<T, H, S> Serialize<S> for Slice<T, H>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_slice(self, serializer)
+    }
+}
+
+impl<T: Archive, H> Archive for SliceVec<T, H> {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_slice(self, resolver, out);
+    }
+}
+
+impl<T, H, S> Serialize<S> for SliceVec<T, H>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_slice(self, serializer)
+    }
+}
+
+impl<T: Archive, H: AllocHandle> SliceVec<T, H> {
+    /// Deserialize an archived vector, allocating the result using the given handle.
+    ///
+    /// Exhausting the arena is reported as an error of the deserializer.
+    pub fn deserialize_archived_in<D>(
+        handle: H,
+        archived: &ArchivedVec<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Self, D::Error>
+    where
+        T::Archived: Deserialize<T, D>,
+        D: Fallible + ?Sized,
+        D::Error: Source,
+    {
+        let mut res = SliceVec::try_with_capacity(handle, archived.len()).map_err(Source::new)?;
+
+        for elem in archived.iter() {
+            res.push(elem.deserialize(deserializer)?);
+        }
+
+        Ok(res)
+    }
+}
+
+impl<T, H, D> Deserialize<SliceVec<T, H>, D> for ArchivedVec<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    H: AllocHandle + Default,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<SliceVec<T, H>, D::Error> {
+        SliceVec::deserialize_archived_in(H::default(), self, deserializer)
+    }
+}
+
+impl<T, H, D> Deserialize<Slice<T, H>, D> for ArchivedVec<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    H: AllocHandle + Default,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<Slice<T, H>, D::Error> {
+        SliceVec::deserialize_archived_in(H::default(), self, deserializer)
+            .map(SliceVec::into_slice)
+    }
+}
@@ -0,0 +1,278 @@
+//! This module contains a growable bit vector backed by arena memory.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::fmt;
+
+/// The number of bits stored in a word.
+const BITS: usize = 64;
+
+/// An arena allocated, growable vector of bits, stored in 64-bit words.
+///
+/// Bits beyond the length of the vector are always kept zero, so that counting operations can
+/// work on whole words.
+pub struct ArenaBitVec<H> {
+    words: SliceVec<u64, H>,
+    len: usize,
+}
+
+impl<H> ArenaBitVec<H> {
+    /// Return the number of bits in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the vector contains no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the bit at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len {
+            Some(self.words[index / BITS] & (1 << (index % BITS)) != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Set the bit at `index` to `value`.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(
+            index < self.len,
+            "index out of bounds: {} >= {}",
+            index,
+            self.len
+        );
+
+        let mask = 1 << (index % BITS);
+
+        if value {
+            self.words[index / BITS] |= mask;
+        } else {
+            self.words[index / BITS] &= !mask;
+        }
+    }
+
+    /// Return the number of bits set in the vector.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Return the number of bits not set in the vector.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Return the number of bits set before position `index`.
+    ///
+    /// Panics if `index` is greater than the length of the vector.
+    pub fn rank(&self, index: usize) -> usize {
+        assert!(
+            index <= self.len,
+            "index out of bounds: {} > {}",
+            index,
+            self.len
+        );
+
+        let full: usize = self.words[..index / BITS]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        let rest = index % BITS;
+
+        if rest == 0 {
+            full
+        } else {
+            full + (self.words[index / BITS] & ((1 << rest) - 1)).count_ones() as usize
+        }
+    }
+
+    /// Return `true` if all bits in the vector are set.
+    pub fn all(&self) -> bool {
+        self.count_ones() == self.len
+    }
+
+    /// Return `true` if any bit in the vector is set.
+    pub fn any(&self) -> bool {
+        self.words.iter().any(|&w| w != 0)
+    }
+
+    /// Set all bits in the vector to `value`.
+    pub fn fill(&mut self, value: bool) {
+        for w in self.words.iter_mut() {
+            *w = if value { !0 } else { 0 };
+        }
+
+        if value {
+            self.clear_tail();
+        }
+    }
+
+    /// Zero the bits in the last word beyond the length of the vector.
+    fn clear_tail(&mut self) {
+        let rest = self.len % BITS;
+
+        if rest != 0 {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << rest) - 1;
+        }
+    }
+
+    /// Combine the vector with `other` word by word.
+    fn combine<F: Fn(u64, u64) -> u64>(&mut self, other: &Self, f: F) {
+        assert_eq!(self.len, other.len, "bit vector lengths differ");
+
+        for (a, &b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a = f(*a, b);
+        }
+    }
+
+    /// Set every bit to the conjunction of itself and the corresponding bit in `other`.
+    ///
+    /// Panics if the lengths of the vectors differ.
+    pub fn and(&mut self, other: &Self) {
+        self.combine(other, |a, b| a & b);
+    }
+
+    /// Set every bit to the disjunction of itself and the corresponding bit in `other`.
+    ///
+    /// Panics if the lengths of the vectors differ.
+    pub fn or(&mut self, other: &Self) {
+        self.combine(other, |a, b| a | b);
+    }
+
+    /// Set every bit to the exclusive disjunction of itself and the corresponding bit in
+    /// `other`.
+    ///
+    /// Panics if the lengths of the vectors differ.
+    pub fn xor(&mut self, other: &Self) {
+        self.combine(other, |a, b| a ^ b);
+    }
+
+    /// Invert every bit in the vector.
+    pub fn negate(&mut self) {
+        for w in self.words.iter_mut() {
+            *w = !*w;
+        }
+
+        self.clear_tail();
+    }
+
+    /// Create an iterator over the bits in the vector.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.words[i / BITS] & (1 << (i % BITS)) != 0)
+    }
+
+    /// Create an iterator over the positions of the bits set in the vector.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            let mut w = w;
+
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    None
+                } else {
+                    let bit = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    Some(i * BITS + bit)
+                }
+            })
+        })
+    }
+}
+
+impl<H: AllocHandle> ArenaBitVec<H> {
+    /// Create a new empty bit vector using the given handle.
+    pub fn new(handle: H) -> Self {
+        ArenaBitVec {
+            words: SliceVec::new(handle),
+            len: 0,
+        }
+    }
+
+    /// Create a new bit vector of `len` bits, all set to `value`, using the given handle.
+    pub fn from_elem(handle: H, len: usize, value: bool) -> Self {
+        let mut words = SliceVec::with_capacity(handle, len.div_ceil(BITS));
+        words.resize(len.div_ceil(BITS), if value { !0 } else { 0 });
+
+        let mut res = ArenaBitVec { words, len };
+        res.clear_tail();
+
+        res
+    }
+
+    /// Return the number of bits the vector can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.words.capacity() * BITS
+    }
+
+    /// Append a bit to the vector.
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(BITS) {
+            self.words.push(0);
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, value);
+    }
+
+    /// Remove the last bit from the vector and return it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let value = self.get(self.len - 1);
+        self.set(self.len - 1, false);
+        self.len -= 1;
+
+        if self.len.is_multiple_of(BITS) {
+            self.words.pop();
+        }
+
+        value
+    }
+
+    /// Remove all bits from the vector.
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+}
+
+impl<H: AllocHandle + Clone> Clone for ArenaBitVec<H> {
+    fn clone(&self) -> Self {
+        ArenaBitVec {
+            words: self.words.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<H> fmt::Debug for ArenaBitVec<H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for bit in self.iter() {
+            fmt.write_str(if bit { "1" } else { "0" })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<H> PartialEq for ArenaBitVec<H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && *self.words == *other.words
+    }
+}
+
+impl<H> Eq for ArenaBitVec<H> {}
+
+impl<H: AllocHandle> Extend<bool> for ArenaBitVec<H> {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for bit in iter {
+            self.push(bit);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! This module contains an owning pointer type for single arena-allocated objects.
+use crate::common::AllocHandle;
+
+use std::any::Any;
+use std::cmp;
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::ptr::{self, NonNull};
+
+/// An arena allocated, owned object.
+///
+/// This is the arena analogue of `Box`: dropping it runs the destructor of the contained
+/// object, but the memory it occupies is only reclaimed once the arena is cleared.
+///
+/// Arena allocations never move. An object allocated in an arena keeps its address until the
+/// arena is cleared, and none of the arenas in this crate can be cleared while a handle to them
+/// is live. However, leaking a box (for instance using `mem::forget`) releases its handle
+/// without running the destructor of the contained object, after which the arena can be cleared
+/// and its memory reused. This breaks the drop guarantee of `Pin`, which is why pinning a box
+/// (see `ArenaBox::pin_in`) is unsafe.
+///
+/// Boxed slices (`ArenaBox<[T], H>`) store their length in the arena, in front of the
+/// elements. This keeps the box a single pointer (plus handle) wide, as opposed to the
+/// pointer-length-handle layout of `Slice`, which makes them a good fit for memory-dense
+/// containers of containers.
+///
+/// Boxes can also hold type-erased objects (`ArenaBox<dyn Any, H>`, see `ArenaBox::into_any`),
+/// which can be downcast to their concrete type again.
+pub struct ArenaBox<T: Pointee + ?Sized, H> {
+    ptr: T::Ptr,
+    handle: H,
+}
+
+/// Types that can be stored in an `ArenaBox`.
+///
+/// This determines the representation of the pointer kept in the box.
+///
+/// # Safety
+///
+/// `as_ptr` has to return a pointer to a valid, initialized object owned by the box the
+/// pointer has been taken from.
+pub unsafe trait Pointee {
+    /// The pointer representation stored in the box.
+    type Ptr: Copy;
+
+    /// Turn the stored pointer into a (possibly fat) raw pointer to the object.
+    fn as_ptr(ptr: Self::Ptr) -> *mut Self;
+}
+
+/// The header placed in front of the elements of a boxed slice.
+///
+/// The zero-length array makes sure that the header is padded to the alignment of `T`, so that
+/// the elements can be placed directly after it.
+#[derive(Debug)]
+#[repr(C)]
+pub struct SliceHeader<T> {
+    len: usize,
+    elems: [T; 0],
+}
+
+unsafe impl<T> Pointee for T {
+    type Ptr = NonNull<T>;
+
+    fn as_ptr(ptr: NonNull<T>) -> *mut T {
+        ptr.as_ptr()
+    }
+}
+
+unsafe impl<T> Pointee for [T] {
+    type Ptr = NonNull<SliceHeader<T>>;
+
+    fn as_ptr(ptr: NonNull<SliceHeader<T>>) -> *mut [T] {
+        unsafe {
+            let len = (*ptr.as_ptr()).len;
+            let data = ptr.as_ptr().add(1) as *mut T;
+
+            ptr::slice_from_raw_parts_mut(data, len)
+        }
+    }
+}
+
+unsafe impl Pointee for dyn Any {
+    type Ptr = NonNull<dyn Any>;
+
+    fn as_ptr(ptr: NonNull<dyn Any>) -> *mut dyn Any {
+        ptr.as_ptr()
+    }
+}
+
+// a box owns its object, and the handle is only used for allocation.
+unsafe impl<T: Pointee + Send + ?Sized, H: Send> Send for ArenaBox<T, H> {}
+unsafe impl<T: Pointee + Sync + ?Sized, H: Sync> Sync for ArenaBox<T, H> {}
+
+impl<T, H: AllocHandle> ArenaBox<T, H> {
+    /// Move `value` into the arena using the provided handle.
+    pub fn new(handle: H, value: T) -> Self {
+        let ptr: NonNull<T> = handle.allocate(1);
+
+        unsafe {
+            ptr::write(ptr.as_ptr(), value);
+        }
+
+        ArenaBox { ptr, handle }
+    }
+
+    /// Move `value` into the arena using the provided handle and pin it.
+    ///
+    /// This allows for the construction of self-referential objects (such as futures) and
+    /// intrusive data structures in the arena.
+    ///
+    /// # Safety
+    ///
+    /// See `ArenaBox::into_pin`.
+    pub unsafe fn pin_in(handle: H, value: T) -> Pin<Self> {
+        Self::into_pin(Self::new(handle, value))
+    }
+}
+
+impl<T, H> ArenaBox<T, H> {
+    /// Move the contained object out of the arena.
+    pub fn into_inner(boxed: Self) -> T {
+        let (ptr, handle) = Self::into_raw_parts(boxed);
+        // the handle may be what keeps the arena alive, so it's only dropped afterwards.
+        let value = unsafe { ptr::read(ptr.as_ptr()) };
+        mem::drop(handle);
+
+        value
+    }
+
+    /// Erase the type of the contained object, allowing it to be downcast later.
+    pub fn into_any(boxed: Self) -> ArenaBox<dyn Any, H>
+    where
+        T: Any,
+    {
+        let (ptr, handle) = Self::into_raw_parts(boxed);
+
+        ArenaBox { ptr, handle }
+    }
+}
+
+impl<H> ArenaBox<dyn Any, H> {
+    /// Attempt to downcast the box to a concrete type.
+    ///
+    /// If the contained object is not of type `T`, the box is returned unchanged.
+    pub fn downcast<T: Any>(self) -> Result<ArenaBox<T, H>, Self> {
+        if self.is::<T>() {
+            let (ptr, handle) = Self::into_raw_parts(self);
+
+            Ok(ArenaBox {
+                ptr: ptr.cast(),
+                handle,
+            })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T, H: AllocHandle> ArenaBox<[T], H> {
+    /// Create a boxed slice from the elements of an iterator using the provided handle.
+    ///
+    /// The number of elements is taken from the iterator's reported length, if the iterator
+    /// yields fewer elements, the slice is shortened accordingly. Panics if the size of the
+    /// slice overflows `usize`, or the arena is exhausted.
+    pub fn from_iter<I>(handle: H, iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let cap = iter.len();
+        let header_size = mem::size_of::<SliceHeader<T>>();
+        let count = cap
+            .checked_mul(mem::size_of::<T>())
+            .and_then(|size| size.div_ceil(header_size).checked_add(1))
+            .expect("capacity overflow");
+        let ptr: NonNull<SliceHeader<T>> = handle.allocate(count);
+
+        let mut len = 0;
+
+        unsafe {
+            let data = ptr.as_ptr().add(1) as *mut T;
+
+            for elem in iter.take(cap) {
+                ptr::write(data.add(len), elem);
+                len += 1;
+            }
+
+            ptr::write(ptr.as_ptr(), SliceHeader { len, elems: [] });
+        }
+
+        ArenaBox { ptr, handle }
+    }
+
+    /// Create a boxed slice by cloning the elements of `slice` using the provided handle.
+    pub fn from_slice(handle: H, slice: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_iter(handle, slice.iter().cloned())
+    }
+}
+
+impl<T: Pointee + ?Sized, H> ArenaBox<T, H> {
+    /// Pin an already allocated box.
+    ///
+    /// # Safety
+    ///
+    /// The pinned box must never be leaked, unless the arena it has been allocated in is never
+    /// cleared afterwards. Otherwise the memory of the pinned object can be reused without its
+    /// destructor having run, see the type-level documentation.
+    pub unsafe fn into_pin(boxed: Self) -> Pin<Self> {
+        // as long as the box isn't leaked, the object is never moved out of the arena before it
+        // is dropped.
+        Pin::new_unchecked(boxed)
+    }
+
+    /// Take the box apart without dropping the contained object.
+    fn into_raw_parts(boxed: Self) -> (T::Ptr, H) {
+        let ptr = boxed.ptr;
+        let handle = unsafe { ptr::read(&boxed.handle) };
+        mem::forget(boxed);
+
+        (ptr, handle)
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaBox<T, H> {
+    fn clone(&self) -> Self {
+        ArenaBox::new(self.handle.clone(), self.deref().clone())
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaBox<[T], H> {
+    fn clone(&self) -> Self {
+        ArenaBox::from_slice(self.handle.clone(), self)
+    }
+}
+
+impl<T: Pointee + fmt::Debug + ?Sized, H> fmt::Debug for ArenaBox<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(fmt)
+    }
+}
+
+impl<T: Pointee + ?Sized, H> Deref for ArenaBox<T, H> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*T::as_ptr(self.ptr) }
+    }
+}
+
+impl<T: Pointee + ?Sized, H> DerefMut for ArenaBox<T, H> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *T::as_ptr(self.ptr) }
+    }
+}
+
+impl<T: Pointee + Eq + ?Sized, H> Eq for ArenaBox<T, H> {}
+
+impl<T: Pointee + PartialEq + ?Sized, H> PartialEq for ArenaBox<T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl<T: Pointee + PartialOrd + ?Sized, H> PartialOrd for ArenaBox<T, H> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<T: Pointee + ?Sized, H> Drop for ArenaBox<T, H> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(T::as_ptr(self.ptr));
+        }
+    }
+}
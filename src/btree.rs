@@ -0,0 +1,577 @@
+//! This module contains an ordered map whose nodes are allocated in an arena.
+use crate::common::AllocHandle;
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::{self, MaybeUninit};
+use std::ops::{Bound, RangeBounds};
+use std::ptr::{self, NonNull};
+
+/// The minimum degree of the tree.
+const B: usize = 6;
+
+/// The maximum number of entries in a node.
+const CAPACITY: usize = 2 * B - 1;
+
+/// A node of the tree.
+///
+/// Leaves and internal nodes share the same layout, leaves just don't use their edges.
+struct Node<K, V> {
+    /// The parent of the node, if any
+    parent: Option<NonNull<Node<K, V>>>,
+
+    /// The index of the node in its parent's edges
+    parent_idx: usize,
+
+    /// The number of entries in the node
+    len: usize,
+
+    /// Whether the node is a leaf
+    leaf: bool,
+
+    /// The keys stored in the node, initialized up to `len`
+    keys: [MaybeUninit<K>; CAPACITY],
+
+    /// The values stored in the node, initialized up to `len`
+    vals: [MaybeUninit<V>; CAPACITY],
+
+    /// The children of the node, initialized up to `len + 1` in internal nodes
+    edges: [Option<NonNull<Node<K, V>>>; CAPACITY + 1],
+}
+
+/// A position of an entry in the tree.
+type Position<K, V> = (NonNull<Node<K, V>>, usize);
+
+/// An arena allocated, ordered map.
+///
+/// The map is implemented as a B-tree whose nodes are allocated using the provided handle.
+/// Nodes are never freed individually, which fits the arena's allocation model: entries can
+/// be inserted and looked up, but not removed, except by clearing the map as a whole.
+pub struct ArenaBTreeMap<K, V, H> {
+    root: Option<NonNull<Node<K, V>>>,
+    len: usize,
+    handle: H,
+}
+
+/// An iterator over (a range of) the entries of an `ArenaBTreeMap`, in key order.
+pub struct Iter<'a, K, V> {
+    front: Option<Position<K, V>>,
+    back: Option<Position<K, V>>,
+    _marker: PhantomData<&'a (K, V)>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(leaf: bool) -> Self {
+        Node {
+            parent: None,
+            parent_idx: 0,
+            len: 0,
+            leaf,
+            keys: unsafe { MaybeUninit::uninit().assume_init() },
+            vals: unsafe { MaybeUninit::uninit().assume_init() },
+            edges: [None; CAPACITY + 1],
+        }
+    }
+
+    unsafe fn key<'a>(node: NonNull<Self>, i: usize) -> &'a K {
+        &*(*node.as_ptr()).keys[i].as_ptr()
+    }
+
+    unsafe fn val<'a>(node: NonNull<Self>, i: usize) -> &'a mut V {
+        &mut *(*node.as_ptr()).vals[i].as_mut_ptr()
+    }
+
+    unsafe fn edge(node: NonNull<Self>, i: usize) -> NonNull<Self> {
+        (*node.as_ptr()).edges[i].unwrap()
+    }
+
+    /// Find `key` in the node, or the edge to descend into to find it.
+    unsafe fn search<Q>(node: NonNull<Self>, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        for i in 0..(*node.as_ptr()).len {
+            match key.cmp(Self::key(node, i).borrow()) {
+                Ordering::Greater => (),
+                Ordering::Equal => return Ok(i),
+                Ordering::Less => return Err(i),
+            }
+        }
+
+        Err((*node.as_ptr()).len)
+    }
+
+    /// Set the edge `i` of `node` and update the child's parent link.
+    unsafe fn set_edge(node: NonNull<Self>, i: usize, child: NonNull<Self>) {
+        (*node.as_ptr()).edges[i] = Some(child);
+        (*child.as_ptr()).parent = Some(node);
+        (*child.as_ptr()).parent_idx = i;
+    }
+
+    /// Return the position of the leftmost entry in the subtree rooted at `node`.
+    unsafe fn first(mut node: NonNull<Self>) -> Option<Position<K, V>> {
+        while !(*node.as_ptr()).leaf {
+            node = Self::edge(node, 0);
+        }
+
+        if (*node.as_ptr()).len > 0 {
+            Some((node, 0))
+        } else {
+            None
+        }
+    }
+
+    /// Return the position of the rightmost entry in the subtree rooted at `node`.
+    unsafe fn last(mut node: NonNull<Self>) -> Option<Position<K, V>> {
+        while !(*node.as_ptr()).leaf {
+            node = Self::edge(node, (*node.as_ptr()).len);
+        }
+
+        (*node.as_ptr()).len.checked_sub(1).map(|i| (node, i))
+    }
+
+    /// Return the position following `(node, i)` in key order.
+    unsafe fn next((node, i): Position<K, V>) -> Option<Position<K, V>> {
+        if !(*node.as_ptr()).leaf {
+            return Self::first(Self::edge(node, i + 1));
+        }
+
+        if i + 1 < (*node.as_ptr()).len {
+            return Some((node, i + 1));
+        }
+
+        let mut node = node;
+
+        while let Some(parent) = (*node.as_ptr()).parent {
+            let idx = (*node.as_ptr()).parent_idx;
+
+            if idx < (*parent.as_ptr()).len {
+                return Some((parent, idx));
+            }
+
+            node = parent;
+        }
+
+        None
+    }
+
+    /// Return the position of the first entry whose key is not below `bound`.
+    unsafe fn lower_bound<Q>(mut node: NonNull<Self>, bound: Bound<&Q>) -> Option<Position<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut candidate = None;
+
+        loop {
+            let len = (*node.as_ptr()).len;
+            let i = (0..len)
+                .find(|&i| {
+                    let key = Self::key(node, i).borrow();
+
+                    match bound {
+                        Bound::Included(b) => key >= b,
+                        Bound::Excluded(b) => key > b,
+                        Bound::Unbounded => true,
+                    }
+                })
+                .unwrap_or(len);
+
+            if i < len {
+                candidate = Some((node, i));
+            }
+
+            if (*node.as_ptr()).leaf {
+                return candidate;
+            }
+
+            node = Self::edge(node, i);
+        }
+    }
+
+    /// Split the full child `i` of `node`, moving its median entry into `node`.
+    unsafe fn split_child(node: NonNull<Self>, i: usize, sibling: NonNull<Self>) {
+        let child = Self::edge(node, i);
+        let (n, c, s) = (node.as_ptr(), child.as_ptr(), sibling.as_ptr());
+
+        ptr::copy_nonoverlapping((*c).keys.as_ptr().add(B), (*s).keys.as_mut_ptr(), B - 1);
+        ptr::copy_nonoverlapping((*c).vals.as_ptr().add(B), (*s).vals.as_mut_ptr(), B - 1);
+
+        if !(*c).leaf {
+            for j in 0..B {
+                Self::set_edge(sibling, j, (*c).edges[B + j].take().unwrap());
+            }
+        }
+
+        (*s).len = B - 1;
+        (*c).len = B - 1;
+
+        let len = (*n).len;
+
+        ptr::copy(
+            (*n).keys.as_ptr().add(i),
+            (*n).keys.as_mut_ptr().add(i + 1),
+            len - i,
+        );
+        ptr::copy(
+            (*n).vals.as_ptr().add(i),
+            (*n).vals.as_mut_ptr().add(i + 1),
+            len - i,
+        );
+
+        for j in (i + 1..=len).rev() {
+            Self::set_edge(node, j + 1, Self::edge(node, j));
+        }
+
+        ptr::copy_nonoverlapping(
+            (*c).keys.as_ptr().add(B - 1),
+            (*n).keys.as_mut_ptr().add(i),
+            1,
+        );
+        ptr::copy_nonoverlapping(
+            (*c).vals.as_ptr().add(B - 1),
+            (*n).vals.as_mut_ptr().add(i),
+            1,
+        );
+        Self::set_edge(node, i + 1, sibling);
+
+        (*n).len += 1;
+    }
+
+    /// Drop all entries in the subtree rooted at `node`.
+    unsafe fn drop_subtree(node: NonNull<Self>) {
+        let n = &mut *node.as_ptr();
+
+        for i in 0..n.len {
+            ptr::drop_in_place(n.keys[i].as_mut_ptr());
+            ptr::drop_in_place(n.vals[i].as_mut_ptr());
+        }
+
+        if !n.leaf {
+            for i in 0..=n.len {
+                Self::drop_subtree(n.edges[i].unwrap());
+            }
+        }
+
+        n.len = 0;
+    }
+}
+
+impl<K, V, H> ArenaBTreeMap<K, V, H> {
+    /// Return the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Find the position of the entry for `key`, if any.
+    fn find<Q>(&self, key: &Q) -> Option<Position<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = self.root?;
+
+        unsafe {
+            loop {
+                match Node::search(node, key) {
+                    Ok(i) => return Some((node, i)),
+                    Err(_) if (*node.as_ptr()).leaf => return None,
+                    Err(i) => node = Node::edge(node, i),
+                }
+            }
+        }
+    }
+
+    /// Return a reference to the value corresponding to `key`, if any.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key)
+            .map(|(node, i)| unsafe { &*Node::val(node, i) })
+    }
+
+    /// Return a mutable reference to the value corresponding to `key`, if any.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key)
+            .map(|(node, i)| unsafe { Node::val(node, i) })
+    }
+
+    /// Return `true` if the map contains an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Return the entry with the smallest key, if any.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let pos = unsafe { Node::first(self.root?) };
+        pos.map(|(node, i)| unsafe { (Node::key(node, i), &*Node::val(node, i)) })
+    }
+
+    /// Return the entry with the largest key, if any.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let pos = unsafe { Node::last(self.root?) };
+        pos.map(|(node, i)| unsafe { (Node::key(node, i), &*Node::val(node, i)) })
+    }
+
+    /// Create an iterator over the entries of the map, in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            front: self.root.and_then(|root| unsafe { Node::first(root) }),
+            back: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create an iterator over the keys of the map, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Create an iterator over the values of the map, in key order.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Create an iterator over the entries of the map whose keys fall into `range`.
+    ///
+    /// If the start of the range lies beyond its end, the iterator is empty.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let root = match self.root {
+            Some(root) => root,
+            None => return self.iter(),
+        };
+
+        let mut front = unsafe { Node::lower_bound(root, range.start_bound()) };
+        let back = match range.end_bound() {
+            Bound::Included(b) => unsafe { Node::lower_bound(root, Bound::Excluded(b)) },
+            Bound::Excluded(b) => unsafe { Node::lower_bound(root, Bound::Included(b)) },
+            Bound::Unbounded => None,
+        };
+
+        if let (Some(f), Some(b)) = (front, back) {
+            let (f, b): (&Q, &Q) =
+                unsafe { (Node::key(f.0, f.1).borrow(), Node::key(b.0, b.1).borrow()) };
+
+            if f >= b {
+                front = None;
+            }
+        }
+
+        Iter {
+            front,
+            back,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Remove all entries from the map.
+    ///
+    /// The memory occupied by the nodes is only reclaimed once the arena is cleared.
+    pub fn clear(&mut self) {
+        if let Some(root) = self.root.take() {
+            unsafe {
+                Node::drop_subtree(root);
+            }
+        }
+
+        self.len = 0;
+    }
+}
+
+impl<K: Ord, V, H: AllocHandle> ArenaBTreeMap<K, V, H> {
+    /// Create a new empty map using the given handle.
+    pub fn new(handle: H) -> Self {
+        ArenaBTreeMap {
+            root: None,
+            len: 0,
+            handle,
+        }
+    }
+
+    /// Allocate a fresh node in the arena.
+    fn allocate_node(&self, leaf: bool) -> NonNull<Node<K, V>> {
+        let node: NonNull<Node<K, V>> = self.handle.allocate(1);
+
+        unsafe {
+            ptr::write(node.as_ptr(), Node::new(leaf));
+        }
+
+        node
+    }
+
+    /// Insert an entry into the map.
+    ///
+    /// If the map already contained an entry for `key`, its value is replaced and the old
+    /// value returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some((node, i)) = self.find(&key) {
+            return Some(mem::replace(unsafe { Node::val(node, i) }, value));
+        }
+
+        let mut root = match self.root {
+            Some(root) => root,
+            None => self.allocate_node(true),
+        };
+
+        unsafe {
+            if (*root.as_ptr()).len == CAPACITY {
+                let new_root = self.allocate_node(false);
+                Node::set_edge(new_root, 0, root);
+                Node::split_child(new_root, 0, self.allocate_node((*root.as_ptr()).leaf));
+                root = new_root;
+            }
+
+            self.root = Some(root);
+
+            let mut node = root;
+
+            loop {
+                let mut i = match Node::search(node, &key) {
+                    Ok(_) => unreachable!(),
+                    Err(i) => i,
+                };
+
+                let n = &mut *node.as_ptr();
+
+                if n.leaf {
+                    ptr::copy(
+                        n.keys.as_ptr().add(i),
+                        n.keys.as_mut_ptr().add(i + 1),
+                        n.len - i,
+                    );
+                    ptr::copy(
+                        n.vals.as_ptr().add(i),
+                        n.vals.as_mut_ptr().add(i + 1),
+                        n.len - i,
+                    );
+                    n.keys[i] = MaybeUninit::new(key);
+                    n.vals[i] = MaybeUninit::new(value);
+                    n.len += 1;
+
+                    break;
+                }
+
+                let child = Node::edge(node, i);
+
+                if (*child.as_ptr()).len == CAPACITY {
+                    Node::split_child(node, i, self.allocate_node((*child.as_ptr()).leaf));
+
+                    if key > *Node::key(node, i) {
+                        i += 1;
+                    }
+                }
+
+                node = Node::edge(node, i);
+            }
+        }
+
+        self.len += 1;
+
+        None
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, H: AllocHandle + Clone> Clone for ArenaBTreeMap<K, V, H> {
+    fn clone(&self) -> Self {
+        let mut map = ArenaBTreeMap::new(self.handle.clone());
+
+        for (k, v) in self.iter() {
+            map.insert(k.clone(), v.clone());
+        }
+
+        map
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, H> fmt::Debug for ArenaBTreeMap<K, V, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, H> PartialEq for ArenaBTreeMap<K, V, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, H> Eq for ArenaBTreeMap<K, V, H> {}
+
+impl<'a, K, V, H> IntoIterator for &'a ArenaBTreeMap<K, V, H> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V, H: AllocHandle> Extend<(K, V)> for ArenaBTreeMap<K, V, H> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, H> Drop for ArenaBTreeMap<K, V, H> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.front?;
+
+        if Some(pos) == self.back {
+            self.front = None;
+            return None;
+        }
+
+        self.front = unsafe { Node::next(pos) };
+
+        unsafe { Some((Node::key(pos.0, pos.1), &*Node::val(pos.0, pos.1))) }
+    }
+}
+
+impl<'a, K, V> Clone for Iter<'a, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            front: self.front,
+            back: self.back,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> fmt::Debug for Iter<'a, K, V> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter").finish()
+    }
+}
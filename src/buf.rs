@@ -0,0 +1,112 @@
+//! This module provides implementations of the buffer traits of the `bytes` crate for arena
+//! allocated byte buffers, enabled by the `bytes` feature.
+//!
+//! `SliceVec<u8, H>` implements `BufMut`, so that protocol frames can be written straight into
+//! arena memory, growing the vector as needed. `ByteCursor` reads from a `Slice<u8, H>` through
+//! `Buf`.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use bytes::buf::UninitSlice;
+use bytes::{Buf, BufMut};
+
+use std::ptr;
+
+/// A cursor reading from an arena allocated byte slice.
+#[derive(Debug)]
+pub struct ByteCursor<H> {
+    slice: Slice<u8, H>,
+    pos: usize,
+}
+
+impl<H> ByteCursor<H> {
+    /// Create a cursor positioned at the start of `slice`.
+    pub fn new(slice: Slice<u8, H>) -> Self {
+        ByteCursor { slice, pos: 0 }
+    }
+
+    /// Return the number of bytes read so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Move the cursor to `pos`.
+    ///
+    /// Panics if `pos` is greater than the length of the slice.
+    pub fn set_position(&mut self, pos: usize) {
+        assert!(pos <= self.slice.len(), "position out of bounds");
+
+        self.pos = pos;
+    }
+
+    /// Return a reference to the underlying slice.
+    pub fn get_ref(&self) -> &Slice<u8, H> {
+        &self.slice
+    }
+
+    /// Consume the cursor and return the underlying slice.
+    pub fn into_inner(self) -> Slice<u8, H> {
+        self.slice
+    }
+}
+
+impl<H> Buf for ByteCursor<H> {
+    fn remaining(&self) -> usize {
+        self.slice.len() - self.pos
+    }
+
+    fn chunk(&self) -> &[u8] {
+        &self.slice[self.pos..]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.remaining(),
+            "cannot advance past the end of the slice: {} > {}",
+            cnt,
+            self.remaining()
+        );
+
+        self.pos += cnt;
+    }
+}
+
+unsafe impl<H: AllocHandle> BufMut for SliceVec<u8, H> {
+    fn remaining_mut(&self) -> usize {
+        // the vector can grow until the arena is exhausted, which `bytes` can't express.
+        isize::MAX as usize - self.len()
+    }
+
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        let len = self.len();
+        let remaining = self.capacity() - len;
+
+        assert!(
+            cnt <= remaining,
+            "cannot advance past the spare capacity: {} > {}",
+            cnt,
+            remaining
+        );
+
+        self.set_len(len + cnt);
+    }
+
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        if self.capacity() == self.len() {
+            // grow by the same minimal amount `Vec` does.
+            self.reserve(64);
+        }
+
+        UninitSlice::uninit(self.spare_capacity_mut())
+    }
+
+    fn put_slice(&mut self, src: &[u8]) {
+        self.reserve(src.len());
+
+        let len = self.len();
+
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), self.as_mut_ptr().add(len), src.len());
+            self.set_len(len + src.len());
+        }
+    }
+}
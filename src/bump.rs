@@ -0,0 +1,100 @@
+//! This module provides a `bumpalo`-style allocation API for arena handles.
+//!
+//! Projects migrating from `bumpalo` can import `BumpAlloc` and keep calling `alloc`,
+//! `alloc_slice_copy` and friends on the handles of any arena in this crate. As with `bumpalo`,
+//! the objects are handed out as references borrowing from the handle, and their destructors
+//! are never run.
+//!
+//! The `region` arena's `ArenaToken` and `ArenaHandle` have inherent methods of the same names,
+//! which take precedence. Their `alloc` runs the destructor of the object when the generation
+//! ends, and thus requires the object to be `'static`, and references returned by `ArenaHandle`
+//! live as long as its generation. Objects borrowing from the arena can still be allocated by
+//! calling `BumpAlloc::alloc(&token, value)` explicitly.
+use crate::common::AllocHandle;
+
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::str;
+
+/// Allocation methods mirroring those of `bumpalo::Bump`, implemented for all arena handles.
+///
+/// All methods panic if the arena is exhausted.
+#[allow(clippy::mut_from_ref)]
+pub trait BumpAlloc: AllocHandle {
+    /// Move `value` into the arena, and return a reference to it.
+    fn alloc<T>(&self, value: T) -> &mut T {
+        let ptr = bump_allocate::<T, Self>(self, 1);
+
+        unsafe {
+            ptr::write(ptr.as_ptr(), value);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copy `values` into the arena.
+    fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> &mut [T] {
+        let ptr = bump_allocate::<T, Self>(self, values.len());
+
+        unsafe {
+            ptr::copy_nonoverlapping(values.as_ptr(), ptr.as_ptr(), values.len());
+            slice::from_raw_parts_mut(ptr.as_ptr(), values.len())
+        }
+    }
+
+    /// Clone `values` into the arena.
+    ///
+    /// If cloning an element panics, the elements cloned so far are leaked.
+    fn alloc_slice_clone<T: Clone>(&self, values: &[T]) -> &mut [T] {
+        let ptr = bump_allocate::<T, Self>(self, values.len());
+
+        unsafe {
+            for (i, value) in values.iter().enumerate() {
+                ptr::write(ptr.as_ptr().add(i), value.clone());
+            }
+
+            slice::from_raw_parts_mut(ptr.as_ptr(), values.len())
+        }
+    }
+
+    /// Allocate a slice of `len` elements in the arena, initializing the element at index `i`
+    /// with `f(i)`.
+    ///
+    /// If `f` panics, the elements initialized so far are leaked.
+    fn alloc_slice_fill_with<T, F>(&self, len: usize, mut f: F) -> &mut [T]
+    where
+        F: FnMut(usize) -> T,
+    {
+        let ptr = bump_allocate::<T, Self>(self, len);
+
+        unsafe {
+            for i in 0..len {
+                ptr::write(ptr.as_ptr().add(i), f(i));
+            }
+
+            slice::from_raw_parts_mut(ptr.as_ptr(), len)
+        }
+    }
+
+    /// Copy the string `s` into the arena.
+    fn alloc_str(&self, s: &str) -> &mut str {
+        unsafe { str::from_utf8_unchecked_mut(self.alloc_slice_copy(s.as_bytes())) }
+    }
+}
+
+impl<H: AllocHandle> BumpAlloc for H {}
+
+/// Allocate room for `count` objects of type `T` using `handle`, panicking if the arena is
+/// exhausted.
+///
+/// Empty allocations don't consume arena space.
+fn bump_allocate<T, H: AllocHandle + ?Sized>(handle: &H, count: usize) -> NonNull<T> {
+    if count == 0 || mem::size_of::<T>() == 0 {
+        return NonNull::dangling();
+    }
+
+    match handle.try_allocate(count) {
+        Ok(ptr) => ptr,
+        Err(_) => panic!("arena is exhausted"),
+    }
+}
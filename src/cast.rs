@@ -0,0 +1,42 @@
+//! This module provides checked reinterpretation of arena allocated byte buffers as slices of
+//! plain data types, built on the `zerocopy` crate and enabled by the `zerocopy` feature.
+//!
+//! Binary formats read into a `Slice<u8, H>` or `SliceVec<u8, H>` can be viewed as typed
+//! records without copying. The conversions fail if the buffer isn't suitably aligned for the
+//! target type, or its length isn't a multiple of the size of the target type. Since byte
+//! buffers are only guaranteed to be byte-aligned, callers expecting aligned records should
+//! make sure the buffer is allocated at a suitable offset in the arena. As in `zerocopy`,
+//! casting to zero-sized types is rejected at compile time.
+use crate::common::{Slice, SliceVec};
+
+use zerocopy::{FromBytes, Immutable, IntoBytes};
+
+impl<H> Slice<u8, H> {
+    /// View the bytes of the slice as a slice of `T`s.
+    ///
+    /// Returns `None` if the slice is misaligned for `T`, or its length is not a multiple of the
+    /// size of `T`.
+    pub fn cast<T: FromBytes + Immutable>(&self) -> Option<&[T]> {
+        <[T]>::ref_from_bytes(self).ok()
+    }
+
+    /// View the bytes of the slice as a mutable slice of `T`s, see `cast`.
+    pub fn cast_mut<T: FromBytes + IntoBytes>(&mut self) -> Option<&mut [T]> {
+        <[T]>::mut_from_bytes(self).ok()
+    }
+}
+
+impl<H> SliceVec<u8, H> {
+    /// View the bytes of the vector as a slice of `T`s.
+    ///
+    /// Returns `None` if the buffer of the vector is misaligned for `T`, or its length is not a
+    /// multiple of the size of `T`.
+    pub fn as_slice_of<T: FromBytes + Immutable>(&self) -> Option<&[T]> {
+        <[T]>::ref_from_bytes(self).ok()
+    }
+
+    /// View the bytes of the vector as a mutable slice of `T`s, see `as_slice_of`.
+    pub fn as_mut_slice_of<T: FromBytes + IntoBytes>(&mut self) -> Option<&mut [T]> {
+        <[T]>::mut_from_bytes(self).ok()
+    }
+}
@@ -0,0 +1,122 @@
+//! This module contains a handle adapter validating the memory handed out by another handle.
+use crate::common::{AllocHandle, ArenaError};
+
+use std::alloc::Layout;
+use std::mem;
+use std::ptr::NonNull;
+
+/// A handle wrapping another handle, and checking the pointers it returns in debug builds.
+///
+/// Every pointer returned by the wrapped handle is checked to be aligned, and to lie within
+/// the arena according to `AllocHandle::owns`. A violation of the `AllocHandle` contract panics
+/// instead of causing memory corruption further down the line, which is useful when developing
+/// or fuzzing custom handles. In release builds, the adapter forwards all calls unchecked.
+#[derive(Clone, Debug)]
+pub struct CheckedHandle<H> {
+    inner: H,
+}
+
+impl<H> CheckedHandle<H> {
+    /// Wrap the given handle.
+    pub fn new(inner: H) -> Self {
+        CheckedHandle { inner }
+    }
+
+    /// Return a reference to the wrapped handle.
+    pub fn get_ref(&self) -> &H {
+        &self.inner
+    }
+
+    /// Unwrap the wrapped handle.
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+
+impl<H: AllocHandle> CheckedHandle<H> {
+    /// Check that `ptr` points to `size` bytes of arena memory aligned to `align`.
+    fn check(&self, ptr: NonNull<u8>, size: usize, align: usize) {
+        debug_assert_eq!(
+            ptr.as_ptr() as usize & (align - 1),
+            0,
+            "handle returned a misaligned pointer"
+        );
+        debug_assert!(
+            (ptr.as_ptr() as usize).checked_add(size).is_some(),
+            "handle returned memory wrapping around the address space"
+        );
+        debug_assert!(
+            self.inner.owns(ptr, size),
+            "handle returned memory outside of its arena"
+        );
+    }
+
+    /// Check that `ptr` points to `count` objects of type `T` in arena memory.
+    fn check_array<T>(&self, ptr: NonNull<T>, count: usize) {
+        let size = mem::size_of::<T>().checked_mul(count);
+
+        debug_assert!(
+            size.is_some(),
+            "handle returned an allocation of overflowing size"
+        );
+
+        self.check(ptr.cast(), size.unwrap_or(0), mem::align_of::<T>());
+    }
+}
+
+// all calls are forwarded to the wrapped handle, which upholds the contract.
+unsafe impl<H: AllocHandle> AllocHandle for CheckedHandle<H> {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr = self.inner.try_allocate(count)?;
+        self.check_array(ptr, count);
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let new_ptr = self.inner.try_allocate_or_extend(ptr, old_count, count)?;
+        self.check_array(new_ptr, count);
+
+        Ok(new_ptr)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr = self.inner.try_allocate_zeroed(count)?;
+        self.check_array(ptr, count);
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        let ptr = self.inner.try_allocate_layout(layout)?;
+        self.check(ptr, layout.size(), layout.align());
+
+        Ok(ptr)
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        self.inner.dealloc_last(ptr, count)
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        self.inner.allocate_or_shrink(ptr, old_count, count)
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.inner.owns(ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        self.inner.arena_id()
+    }
+}
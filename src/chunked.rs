@@ -0,0 +1,342 @@
+//! This module provides a reference-counted arena that grows on demand.
+//!
+//! Like the arena in the `rc` module, this arena hands out reference-counted handles and can
+//! only be cleared once all of them are gone. Instead of failing once its initial capacity is
+//! exhausted, it allocates additional chunks of memory, each at least twice the size of the
+//! previous one. This avoids having to over-provision the arena up front, at the cost of a
+//! slightly slower allocation path when switching chunks, and of allocations no longer being
+//! contiguous across chunk boundaries.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{self, AllocHandle, ArenaBacking, ArenaError};
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+use crate::tree;
+use crate::vecmap;
+
+use std::alloc::Layout;
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::rc::Rc;
+
+/// A reference-counting arena (non-MT-safe) that grows by allocating additional chunks.
+///
+/// This is the only object that can be used to clear the arena. All other objects referring to
+/// the arena merely allow for allocation, and are present to avoid arena clearing while they are
+/// live.
+#[derive(Debug)]
+pub struct Arena(InnerRef);
+
+/// A non-owning object referring to the arena.
+///
+/// A reference to the arena that allows its holder to allocate memory from the arena. While
+/// it is live, the arena cannot be cleared (as it is associated with an arena-allocated
+/// object).
+#[derive(Clone, Debug)]
+pub struct InnerRef {
+    inner: Rc<Inner>,
+}
+
+/// An arena's guts
+#[derive(Debug)]
+struct Inner {
+    /// Head of the current chunk
+    head: Cell<NonNull<u8>>,
+
+    /// Offset into the current chunk
+    pos: Cell<usize>,
+
+    /// Capacity of the current chunk
+    cap: Cell<usize>,
+
+    /// All chunks allocated so far, with their capacities, the current one last
+    chunks: RefCell<Vec<(NonNull<u8>, usize)>>,
+
+    /// The type of backing storage used for the chunks
+    backing: ArenaBacking,
+}
+
+/// An arena allocated, fixed-size sequence of objects
+pub type Slice<T> = common::Slice<T, InnerRef>;
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub type SliceVec<T> = common::SliceVec<T, InnerRef>;
+
+/// An arena allocated, owned object
+pub type ArenaBox<T> = boxed::ArenaBox<T, InnerRef>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'b, T> = cow::ArenaCow<'b, T, InnerRef>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<T> = deque::ArenaVecDeque<T, InnerRef>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<K, V> = btree::ArenaBTreeMap<K, V, InnerRef>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<T> = heap::ArenaBinaryHeap<T, InnerRef>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<T> = slab::ArenaSlab<T, InnerRef>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<T> = generational::GenArena<T, InnerRef>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec = bitvec::ArenaBitVec<InnerRef>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<T> = grid::ArenaGrid<T, InnerRef>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<K, V> = vecmap::ArenaVecMap<K, V, InnerRef>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<T> = vecmap::ArenaVecSet<T, InnerRef>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<K, V> = hash::ArenaHashMap<K, V, InnerRef>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<T> = hash::ArenaHashSet<T, InnerRef>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<T> = chunkvec::ChunkedVec<T, InnerRef>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<T> = ring::ArenaRingBuffer<T, InnerRef>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<N, E> = graph::ArenaGraph<N, E, InnerRef>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<T> = tree::NodeArena<T, InnerRef>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor = cursor::ArenaCursor<InnerRef>;
+
+impl Arena {
+    /// Create an `Arena` whose first chunk has the specified capacity.
+    ///
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. Once it is
+    /// exhausted, the arena grows by allocating further chunks from the same kind of backing.
+    pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
+
+        Ok(Arena(InnerRef {
+            inner: Rc::new(Inner {
+                head: Cell::new(head),
+                pos: Cell::new(0),
+                cap: Cell::new(cap),
+                chunks: RefCell::new(vec![(head, cap)]),
+                backing,
+            }),
+        }))
+    }
+
+    /// Create another reference to the arena.
+    pub fn inner(&self) -> InnerRef {
+        self.0.clone()
+    }
+
+    /// Clear the arena.
+    ///
+    /// All chunks but the last (and largest) one are released, so that an arena that is
+    /// repeatedly filled to a similar extent settles on a single chunk.
+    pub fn clear(&self) -> Result<(), ArenaError> {
+        if Rc::strong_count(&self.inner) == 1 {
+            let mut chunks = self.inner.chunks.borrow_mut();
+            let last = chunks.len() - 1;
+
+            for (head, cap) in chunks.drain(..last) {
+                self.inner.backing.destroy(head, cap);
+            }
+
+            let pos = self.inner.pos.replace(0);
+
+            unsafe { common::poison(self.inner.head.get(), pos) };
+
+            Ok(())
+        } else {
+            Err(ArenaError::CannotClear {
+                live_handles: Rc::strong_count(&self.inner) - 1,
+            })
+        }
+    }
+}
+
+impl Deref for Arena {
+    type Target = InnerRef;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl InnerRef {
+    /// Return the total capacity of all chunks of the arena.
+    pub fn capacity(&self) -> usize {
+        self.inner.chunks.borrow().iter().map(|&(_, cap)| cap).sum()
+    }
+
+    /// Return the number of chunks the arena currently consists of.
+    pub fn chunk_count(&self) -> usize {
+        self.inner.chunks.borrow().len()
+    }
+
+    /// Switch to a fresh chunk large enough to hold an allocation of the given layout.
+    fn grow(&self, layout: Layout) -> Result<(), ArenaError> {
+        let needed = layout
+            .size()
+            .checked_add(layout.align())
+            .and_then(usize::checked_next_power_of_two)
+            .ok_or(ArenaError::CapacityOverflow)?;
+        let cap = cmp::max(self.inner.cap.get() * 2, needed);
+        let head = self.inner.backing.try_create(cap)?;
+
+        self.inner.chunks.borrow_mut().push((head, cap));
+        self.inner.head.set(head);
+        self.inner.pos.set(0);
+        self.inner.cap.set(cap);
+
+        Ok(())
+    }
+}
+
+unsafe impl AllocHandle for InnerRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let layout = Layout::array::<T>(count).map_err(|_| ArenaError::CapacityOverflow)?;
+
+        Ok(self.try_allocate_layout(layout)?.cast())
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let inner = &self.inner;
+        let layout = Layout::array::<T>(count).map_err(|_| ArenaError::CapacityOverflow)?;
+        let old_size = old_count * mem::size_of::<T>();
+
+        if let Some(new_ptr) = common::allocate_or_extend_layout_inner(
+            inner.head.get(),
+            &inner.pos,
+            inner.cap.get(),
+            ptr.cast(),
+            old_size,
+            layout,
+        ) {
+            return Ok(new_ptr.cast());
+        }
+
+        // the current chunk is exhausted, so the object has to be moved to a new one.
+        let new_ptr = self.try_allocate_layout(layout)?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(ptr.cast().as_ptr(), new_ptr.as_ptr(), old_size);
+        }
+
+        Ok(new_ptr.cast())
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        let inner = &self.inner;
+
+        if let Some(ptr) =
+            common::allocate_layout_inner(inner.head.get(), &inner.pos, inner.cap.get(), layout)
+        {
+            return Ok(ptr);
+        }
+
+        self.grow(layout)?;
+
+        Ok(
+            common::allocate_layout_inner(inner.head.get(), &inner.pos, inner.cap.get(), layout)
+                .unwrap(),
+        )
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let inner = &self.inner;
+        let size = count * mem::size_of::<T>();
+
+        match common::last_allocation_offset(inner.head.get(), inner.pos.get(), ptr.cast(), size) {
+            Some(offset) => {
+                inner.pos.set(offset);
+                common::poison(ptr.cast(), size);
+                true
+            }
+            None => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let inner = &self.inner;
+        let size = mem::size_of::<T>();
+
+        if let Some(offset) = common::last_allocation_offset(
+            inner.head.get(),
+            inner.pos.get(),
+            ptr.cast(),
+            old_count * size,
+        ) {
+            inner.pos.set(offset + count * size);
+            common::poison(
+                NonNull::new_unchecked(ptr.as_ptr().add(count)).cast(),
+                (old_count - count) * size,
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.inner
+            .chunks
+            .borrow()
+            .iter()
+            .any(|&(head, cap)| common::owns_inner(head, cap, ptr, size))
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(Rc::as_ptr(&self.inner) as usize)
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        for &(head, cap) in self.chunks.get_mut().iter() {
+            self.backing.destroy(head, cap);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! This module contains a vector whose elements never move, backed by arena memory.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// The default number of elements in a chunk.
+const DEFAULT_CHUNK_SIZE: usize = 64;
+
+/// An arena allocated, append-only vector with stable element addresses.
+///
+/// Instead of reallocating its storage as it grows, the vector allocates additional
+/// fixed-size chunks from the arena. Hence, elements never move, and no arena memory is leaked
+/// when growing, as opposed to `SliceVec`. Since elements never move, `push` only requires a
+/// shared reference, so references to elements can be held across pushes.
+pub struct ChunkedVec<T, H> {
+    chunks: RefCell<SliceVec<NonNull<T>, H>>,
+    chunk_size: usize,
+    len: Cell<usize>,
+    handle: H,
+}
+
+impl<T, H> ChunkedVec<T, H> {
+    /// Return the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Return `true` if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Return the number of elements in each chunk.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Return a pointer to the element slot at `index`.
+    fn slot(&self, index: usize) -> *mut T {
+        let chunk = self.chunks.borrow()[index / self.chunk_size];
+        unsafe { chunk.as_ptr().add(index % self.chunk_size) }
+    }
+
+    /// Return a reference to the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len.get() {
+            unsafe { Some(&*self.slot(index)) }
+        } else {
+            None
+        }
+    }
+
+    /// Return a mutable reference to the element at `index`, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len.get() {
+            unsafe { Some(&mut *self.slot(index)) }
+        } else {
+            None
+        }
+    }
+
+    /// Create an immutable iterator over the elements of the vector.
+    ///
+    /// Elements pushed after the creation of the iterator are not visited.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len.get()).map(move |i| unsafe { &*self.slot(i) })
+    }
+
+    /// Create a mutable iterator over the elements of the vector.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        let chunk_size = self.chunk_size;
+        let mut remaining = self.len.get();
+
+        self.chunks
+            .get_mut()
+            .iter()
+            .map(move |chunk| {
+                let len = remaining.min(chunk_size);
+                remaining -= len;
+
+                unsafe { slice::from_raw_parts_mut(chunk.as_ptr(), len) }
+            })
+            .flat_map(|chunk| chunk.iter_mut())
+    }
+
+    /// Clear the vector.
+    ///
+    /// The chunks allocated so far are kept around for reuse.
+    pub fn clear(&mut self) {
+        let len = self.len.replace(0);
+
+        for i in 0..len {
+            unsafe {
+                ptr::drop_in_place(self.slot(i));
+            }
+        }
+    }
+}
+
+impl<T, H: AllocHandle + Clone> ChunkedVec<T, H> {
+    /// Create a new empty vector using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_chunk_size(handle, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new empty vector using the given handle, allocating `chunk_size` elements at
+    /// a time.
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size(handle: H, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be positive");
+
+        ChunkedVec {
+            chunks: RefCell::new(SliceVec::new(handle.clone())),
+            chunk_size,
+            len: Cell::new(0),
+            handle,
+        }
+    }
+
+    /// Return the number of elements the vector can hold without allocating another chunk.
+    pub fn capacity(&self) -> usize {
+        self.chunks.borrow().len() * self.chunk_size
+    }
+
+    /// Push an element into the vector and return a reference to it.
+    pub fn push(&self, elem: T) -> &T {
+        let len = self.len.get();
+
+        if len == self.capacity() {
+            let chunk = self.handle.allocate(self.chunk_size);
+            self.chunks.borrow_mut().push(chunk);
+        }
+
+        let slot = self.slot(len);
+
+        unsafe {
+            ptr::write(slot, elem);
+        }
+
+        self.len.set(len + 1);
+
+        unsafe { &*slot }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ChunkedVec<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, H> Index<usize> for ChunkedVec<T, H> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("out of bounds access")
+    }
+}
+
+impl<T, H> IndexMut<usize> for ChunkedVec<T, H> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("out of bounds access")
+    }
+}
+
+impl<T, H: AllocHandle + Clone> Extend<T> for ChunkedVec<T, H> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for e in iter {
+            self.push(e);
+        }
+    }
+}
+
+impl<T, H> Drop for ChunkedVec<T, H> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,201 @@
+//! This module provides binary encodings of arena containers compatible with the `borsh` and
+//! `bincode` crates, enabled by the features of the same names.
+//!
+//! `Slice`s and `SliceVec`s are encoded the same way as `Vec`s, as a length prefix followed by
+//! the elements. Decoding reads the length prefix first, and allocates the whole buffer from the
+//! arena up front, so that no memory is allocated on the heap for the container itself. An
+//! input announcing more elements than fit into the arena is rejected with an error before any
+//! element is decoded.
+//!
+//! Containers can be decoded into any arena using `SliceVec::deserialize_borsh_in` and
+//! `SliceVec::decode_in`, or through the decoding traits if the handle type implements `Default`.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+#[cfg(feature = "bincode")]
+use bincode::de::{BorrowDecoder, Decoder};
+#[cfg(feature = "bincode")]
+use bincode::enc::Encoder;
+#[cfg(feature = "bincode")]
+use bincode::error::{DecodeError, EncodeError};
+#[cfg(feature = "bincode")]
+use bincode::{BorrowDecode, Decode, Encode};
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[cfg(feature = "bincode")]
+use std::convert::TryFrom;
+#[cfg(feature = "borsh")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "bincode")]
+use std::mem;
+
+#[cfg(feature = "borsh")]
+impl<T: BorshSerialize, H> BorshSerialize for Slice<T, H> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        <[T]>::serialize(self, writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: BorshSerialize, H> BorshSerialize for SliceVec<T, H> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        <[T]>::serialize(self, writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: BorshDeserialize, H: AllocHandle> SliceVec<T, H> {
+    /// Decode a borsh-encoded vector, allocating it using the given handle.
+    ///
+    /// Exhausting the arena is reported as an `io::Error` of kind `Other`.
+    pub fn deserialize_borsh_in<R: Read>(handle: H, reader: &mut R) -> io::Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        let mut res = SliceVec::try_with_capacity(handle, len).map_err(io::Error::other)?;
+
+        for _ in 0..len {
+            res.push(T::deserialize_reader(reader)?);
+        }
+
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T, H> BorshDeserialize for SliceVec<T, H>
+where
+    T: BorshDeserialize,
+    H: AllocHandle + Default,
+{
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Self::deserialize_borsh_in(H::default(), reader)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T, H> BorshDeserialize for Slice<T, H>
+where
+    T: BorshDeserialize,
+    H: AllocHandle + Default,
+{
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        SliceVec::deserialize_borsh_in(H::default(), reader).map(SliceVec::into_slice)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<T: Encode, H> Encode for Slice<T, H> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        <[T]>::encode(self, encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<T: Encode, H> Encode for SliceVec<T, H> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        <[T]>::encode(self, encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<T, H: AllocHandle> SliceVec<T, H> {
+    /// Decode a bincode-encoded vector, allocating it using the given handle.
+    ///
+    /// The length prefix counts against the decoder's limit before anything is allocated.
+    /// Exhausting the arena is reported as `DecodeError::OtherString`.
+    pub fn decode_in<C, D>(handle: H, decoder: &mut D) -> Result<Self, DecodeError>
+    where
+        T: Decode<C>,
+        D: Decoder<Context = C>,
+    {
+        let (mut res, len) = Self::with_decoded_len(handle, decoder)?;
+
+        for _ in 0..len {
+            // the claim for the element is made again while decoding it, if it is a container.
+            decoder.unclaim_bytes_read(mem::size_of::<T>());
+            res.push(T::decode(decoder)?);
+        }
+
+        Ok(res)
+    }
+
+    /// Decode a bincode-encoded vector whose elements may borrow from the input, allocating it
+    /// using the given handle.
+    pub fn borrow_decode_in<'de, C, D>(handle: H, decoder: &mut D) -> Result<Self, DecodeError>
+    where
+        T: BorrowDecode<'de, C>,
+        D: BorrowDecoder<'de, Context = C>,
+    {
+        let (mut res, len) = Self::with_decoded_len(handle, decoder)?;
+
+        for _ in 0..len {
+            // the claim for the element is made again while decoding it, if it is a container.
+            decoder.unclaim_bytes_read(mem::size_of::<T>());
+            res.push(T::borrow_decode(decoder)?);
+        }
+
+        Ok(res)
+    }
+
+    /// Decode the length prefix of a vector, and create an empty vector of that capacity.
+    fn with_decoded_len<D: Decoder>(
+        handle: H,
+        decoder: &mut D,
+    ) -> Result<(Self, usize), DecodeError> {
+        let len = u64::decode(decoder)?;
+        let len = usize::try_from(len).map_err(|_| DecodeError::OutsideUsizeRange(len))?;
+
+        decoder.claim_container_read::<T>(len)?;
+
+        SliceVec::try_with_capacity(handle, len)
+            .map(|res| (res, len))
+            .map_err(|err| DecodeError::OtherString(err.to_string()))
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<C, T, H> Decode<C> for SliceVec<T, H>
+where
+    T: Decode<C>,
+    H: AllocHandle + Default,
+{
+    fn decode<D: Decoder<Context = C>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        Self::decode_in(H::default(), decoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de, C, T, H> BorrowDecode<'de, C> for SliceVec<T, H>
+where
+    T: BorrowDecode<'de, C>,
+    H: AllocHandle + Default,
+{
+    fn borrow_decode<D: BorrowDecoder<'de, Context = C>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        Self::borrow_decode_in(H::default(), decoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<C, T, H> Decode<C> for Slice<T, H>
+where
+    T: Decode<C>,
+    H: AllocHandle + Default,
+{
+    fn decode<D: Decoder<Context = C>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        SliceVec::decode_in(H::default(), decoder).map(SliceVec::into_slice)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl<'de, C, T, H> BorrowDecode<'de, C> for Slice<T, H>
+where
+    T: BorrowDecode<'de, C>,
+    H: AllocHandle + Default,
+{
+    fn borrow_decode<D: BorrowDecoder<'de, Context = C>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        SliceVec::borrow_decode_in(H::default(), decoder).map(SliceVec::into_slice)
+    }
+}
@@ -1,26 +1,155 @@
 //! This module contains shared data structures and other functionality for use with the allocators
 //! implemented in this crate.
-use std::alloc::{alloc, dealloc, Layout};
-use std::cell::Cell;
+use std::alloc::{alloc_zeroed, dealloc, Layout};
+use std::borrow::Borrow;
+use std::cell::{Cell, RefCell};
 use std::cmp;
+#[cfg(unix)]
+use std::ffi::CString;
 use std::fmt;
+#[cfg(feature = "serde")]
+use std::marker::PhantomData;
 use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::ptr::{self, NonNull};
+use std::rc::Rc;
 use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+#[cfg(feature = "serde")]
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Serializer};
 
+/// The capacity of arenas created using `init`, unless overridden by the environment.
+pub const DEFAULT_CAPACITY: usize = 1 << 28;
+
+/// The environment variable overriding the capacity of arenas created using `init`.
+///
+/// It holds a number of bytes, optionally followed by one of the binary suffixes `K`, `M` or `G`.
+pub const DEFAULT_CAPACITY_VAR: &str = "ARENAVEC_DEFAULT_CAPACITY";
+
 /// An error type representing errors possible during arena creation or other arena operations.
 #[derive(Debug)]
 pub enum ArenaError {
     /// The backing storage for the arena could not be allocated.
-    AllocationFailed,
+    AllocationFailed {
+        /// The number of bytes requested from the backing, zero if existing memory has been mapped
+        size: usize,
+        /// The kind of backing, as returned by `ArenaBacking::kind`
+        backing: &'static str,
+        /// The error code reported by the operating system (`errno` or `GetLastError()`), if
+        /// the backing is provided by it
+        os_error: Option<i32>,
+    },
     /// If an arena is locked by some token type, it refuses locking when already locked.
     AlreadyLocked,
     /// The arena is blocked from clearing by objects that are still live.
-    CannotClear,
+    CannotClear {
+        /// The number of live handles blocking the arena (or the part of it to be cleared) from
+        /// being cleared, not counting the arena itself. If this is zero, the arena is blocked
+        /// by other means, for instance by being frozen.
+        live_handles: usize,
+    },
+    /// The arena can't be forked, because of its backing, or because it is frozen.
+    CannotFork,
+    /// The arena can't be grown in place, and is blocked from moving by objects that are still
+    /// live, or by its backing.
+    CannotGrow,
+    /// The memory protection of the arena could not be changed.
+    CannotProtect,
+    /// The arena does not have enough capacity left for an allocation.
+    CapacityExceeded {
+        /// The number of bytes requested, not counting alignment padding
+        requested: usize,
+        /// The number of bytes left in the arena
+        remaining: usize,
+    },
+    /// The number of elements requested, or the size of the allocation in bytes, overflows
+    /// `usize`.
+    CapacityOverflow,
+    /// The requested capacity is zero, or overflows when rounded up to a multiple of the page
+    /// size.
+    InvalidCapacity,
+    /// The requested alignment is not a power of two, or the size overflows when padded to it.
+    InvalidLayout,
+    /// The allocation would take the arena beyond its hard quota.
+    QuotaExceeded {
+        /// The number of bytes requested, not counting alignment padding
+        requested: usize,
+        /// The number of bytes left before the quota is reached
+        remaining: usize,
+    },
+    /// The handle refers to an arena that has been force-cleared or destroyed since the handle
+    /// was created.
+    StaleHandle,
+}
+
+impl fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArenaError::AllocationFailed {
+                size,
+                backing,
+                os_error,
+            } => {
+                write!(
+                    f,
+                    "failed to allocate {} bytes of {} arena backing",
+                    size, backing
+                )?;
+
+                if let Some(code) = os_error {
+                    write!(f, " (os error {})", code)?;
+                }
+
+                Ok(())
+            }
+            ArenaError::AlreadyLocked => write!(f, "arena is locked already"),
+            ArenaError::CannotClear { live_handles } => {
+                write!(
+                    f,
+                    "arena can't be cleared, {} handles are still live",
+                    live_handles
+                )
+            }
+            ArenaError::CannotFork => write!(f, "arena can't be forked"),
+            ArenaError::CannotGrow => write!(f, "arena can't be grown"),
+            ArenaError::CannotProtect => write!(f, "arena memory protection can't be changed"),
+            ArenaError::CapacityExceeded {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "arena capacity exceeded: requested {} bytes, {} bytes remaining",
+                requested, remaining
+            ),
+            ArenaError::CapacityOverflow => write!(f, "capacity overflow"),
+            ArenaError::InvalidCapacity => write!(f, "invalid arena capacity"),
+            ArenaError::InvalidLayout => write!(f, "invalid allocation layout"),
+            ArenaError::QuotaExceeded {
+                requested,
+                remaining,
+            } => write!(
+                f,
+                "arena quota exceeded: requested {} bytes, {} bytes remaining",
+                requested, remaining
+            ),
+            ArenaError::StaleHandle => write!(f, "arena handle is stale"),
+        }
+    }
+}
+
+impl std::error::Error for ArenaError {}
+
+/// Return the error for a failed allocation of `requested` bytes from an arena of capacity `cap`
+/// at position `pos`.
+pub(crate) fn capacity_exceeded(requested: usize, pos: usize, cap: usize) -> ArenaError {
+    ArenaError::CapacityExceeded {
+        requested,
+        remaining: cap.saturating_sub(pos),
+    }
 }
 
 /// The kind of backing requested for an arena.
@@ -28,696 +157,2870 @@ pub enum ArenaError {
 pub enum ArenaBacking {
     /// Create a virtual memory mapping via `mmap()` or `VirtualAlloc()`.
     MemoryMap,
+    /// Create a virtual memory mapping whose pages are faulted in right away, using
+    /// `MAP_POPULATE` on Linux, and by touching every page elsewhere.
+    ///
+    /// This makes creating the arena more expensive, but spares latency-critical code the page
+    /// faults on first use of the memory.
+    PopulatedMemoryMap,
+    /// Create a virtual memory mapping that is marked for backing by transparent huge pages
+    /// via `madvise(MADV_HUGEPAGE)` on Linux.
+    ///
+    /// This reduces TLB misses for large arenas, without having to reserve huge pages up front.
+    /// The hint is only honoured if transparent huge pages are enabled for such mappings, and
+    /// ignored on other systems, where this is equivalent to `MemoryMap`.
+    HugePageMemoryMap,
+    /// Create a virtual memory mapping whose pages are locked into physical memory via `mlock()`
+    /// or `VirtualLock()`, so that they can't be swapped out.
+    ///
+    /// This keeps sensitive data such as key material off disk, and spares latency-critical code
+    /// page faults. Creating the arena fails if the pages can't be locked, for instance because
+    /// of the limit on locked memory. Arenas with locked memory aren't grown in place.
+    LockedMemoryMap,
+    /// Create a virtual memory mapping whose pages are bound to the given NUMA node via
+    /// `mbind()`, so that they are only allocated from the node's memory.
+    ///
+    /// Creating the arena fails if the binding is rejected, for instance because the node
+    /// doesn't exist. The binding is only supported on Linux, on other systems this is equivalent
+    /// to `MemoryMap`.
+    NumaMemoryMap(u32),
+    /// Create a virtual memory mapping surrounded by inaccessible guard pages, so that accesses
+    /// just beyond either end of the arena fault instead of corrupting adjacent memory.
+    ///
+    /// The trailing guard page follows the last page of the arena, so overflows are caught
+    /// immediately if the capacity is a multiple of the page size.
+    GuardedMemoryMap,
     /// Ask the system allocator for the memory.
     SystemAllocation,
+    /// Create a named shared memory object via `shm_open()` (or a named file mapping on
+    /// Windows) and map it, so that other processes can attach to the arena memory using
+    /// `SharedMapping::open`.
+    ///
+    /// The name has to be unused, and on POSIX systems start with a slash. The shared memory
+    /// object is removed when the arena is dropped, while processes already attached to it keep
+    /// their mapping. Since the memory is mapped at different addresses in each process,
+    /// structures meant to be shared should refer to each other using `ArenaOffset`s.
+    SharedMemory(String),
+    /// Create a shared mapping of an anonymous file via `memfd_create()`, which can be sealed
+    /// read-only using `rc::Arena::seal`.
+    ///
+    /// The file descriptor of a sealed arena can be passed to other processes, which can map
+    /// the arena memory using `SharedMapping::from_sealed_fd`, and rely on it not changing
+    /// anymore, which allows for handing data to sandboxed processes. Only supported on Linux,
+    /// on other systems creating the arena fails.
+    SealableMemoryMap,
+    /// Obtain the memory from a user-supplied source.
+    Custom(Arc<dyn BackingSource>),
 }
 
-/// Every arena-allocated object has some form of handle to the arena containing it.
+/// A provider of the memory backing an arena.
 ///
-/// Depending on the type of arena, the actual functionality of the handle can be different,
-/// but it at least allows for allocation and object resizing.
+/// This allows arenas to be placed in memory obtained from sources other than the ones
+/// covered by `ArenaBacking`, such as pre-allocated buffers, or instrumented allocators. Use
+/// `ArenaBacking::Custom` to create an arena from a source.
 ///
-/// To be useful, handles need to implement `Clone`.
-pub trait AllocHandle {
-    /// Allocate memory from the arena.
+/// # Safety
+///
+/// `allocate` has to return memory valid for reads and writes of `capacity` bytes, which stays
+/// valid until it is passed to `release`, and isn't accessed otherwise in the meantime. The
+/// memory has to be aligned at least as strictly as the objects allocated from the arena, which
+/// page alignment is always sufficient for. If `is_zeroed` returns `true`, the memory has to be
+/// zeroed.
+pub unsafe trait BackingSource: fmt::Debug + Send + Sync {
+    /// Provide `capacity` bytes of memory, or `None` if this is not possible.
+    fn allocate(&self, capacity: usize) -> Option<NonNull<u8>>;
+
+    /// Release memory of `capacity` bytes at `base` provided by `allocate`.
     ///
-    /// Allocate `count` objects of type `T` from the arena, and panic if this is not possible.
-    fn allocate<T>(&self, count: usize) -> NonNull<T>;
-    /// Reallocate memory in the arena.
+    /// # Safety
     ///
-    /// Resize the object sequence pointed to by `ptr` of `old_count` elements of type `T` to
-    /// `count` objects, and copy the sequence if no resizing in place is possible.
+    /// `base` and `capacity` have to match a previous call to `allocate`, and the memory can't
+    /// be accessed afterwards.
+    unsafe fn release(&self, base: NonNull<u8>, capacity: usize);
+
+    /// Extend the memory of `capacity` bytes at `base` to `new_capacity` bytes without moving
+    /// it, and return `true` if this was possible.
     ///
-    /// `ptr` must point into the arena.
-    fn allocate_or_extend<T>(&self, ptr: NonNull<T>, old_count: usize, count: usize) -> NonNull<T>;
+    /// By default, memory isn't extended in place.
+    ///
+    /// # Safety
+    ///
+    /// `base` and `capacity` have to match a previous call to `allocate` (or a successful call
+    /// to this method), and `new_capacity` has to be passed to `release` from then on.
+    unsafe fn grow_in_place(
+        &self,
+        _base: NonNull<u8>,
+        _capacity: usize,
+        _new_capacity: usize,
+    ) -> bool {
+        false
+    }
+
+    /// Return `true` if the memory provided is zeroed, which allows arenas to skip zeroing
+    /// memory that hasn't been handed out yet.
+    fn is_zeroed(&self) -> bool {
+        false
+    }
+
+    /// Release the physical memory backing the `len` bytes at `base`, keeping the memory
+    /// itself valid, and return `true` if this was possible and the memory is zeroed afterwards.
+    ///
+    /// By default, nothing is released.
+    ///
+    /// # Safety
+    ///
+    /// `base` has to be page-aligned, and the memory has to be provided by `allocate`, and not
+    /// in use.
+    unsafe fn decommit(&self, _base: NonNull<u8>, _len: usize) -> bool {
+        false
+    }
 }
 
-/// An arena allocated, fixed-size sequence of objects.
-pub struct Slice<T, H> {
-    ptr: NonNull<T>,
+/// The backing of a child arena, handing out memory carved out of its parent arena.
+#[derive(Debug)]
+pub(crate) struct Carved {
+    /// Address of the carved memory
+    base: usize,
+
+    /// Size of the carved memory
     len: usize,
-    handle: H,
+
+    /// Whether the memory has been handed out already
+    taken: AtomicBool,
 }
 
-/// An arena allocated, sequential, resizable vector
+/// The backing of an arena forked from another arena, a private mapping of the arena contents.
+#[derive(Debug)]
+pub(crate) struct Forked {
+    /// Address of the mapping
+    base: usize,
+
+    /// Size of the mapping
+    len: usize,
+
+    /// Whether the mapping has been handed out already
+    taken: AtomicBool,
+}
+
+/// The magic number identifying serialized arenas.
+pub(crate) const SERIALIZED_MAGIC: &[u8; 8] = b"ARENAVEC";
+
+/// The version of the format of serialized arenas.
+pub(crate) const SERIALIZED_VERSION: u64 = 1;
+
+/// The byte pattern released arena memory is overwritten with when the `sanitize` feature is
+/// enabled.
 ///
-/// Since the arena does not support resizing, or freeing memory, this implementation just
-/// creates new slices as necessary and leaks the previous arena allocation, trading memory
-/// for speed.
-pub struct SliceVec<T, H> {
-    slice: Slice<T, H>,
-    // owo what's this
-    capacity: usize,
+/// Reading memory through stale pointers after the arena has been cleared, rewound, or the
+/// generation has ended yields this pattern instead of plausible values, so use-after-clear bugs
+/// surface in tests. Under Miri, the pattern is invalid for types such as `bool` and turns
+/// references into dangling ones, so using such values is reported as undefined behaviour.
+#[cfg(feature = "sanitize")]
+pub const POISON: u8 = 0xa5;
+
+/// A position in an arena, which the arena can be rewound to.
+///
+/// Markers are obtained using the `mark` method of an arena (or token), and consumed by its
+/// `reset_to` method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Marker {
+    pub(crate) pos: usize,
 }
 
-impl<T, H: AllocHandle> Slice<T, H> {
-    /// Create a new slice of default-initialized objects using the provided handle.
-    pub fn new(handle: H, len: usize) -> Self
-    where
-        T: Default,
-    {
-        let mut res = unsafe { Self::new_empty(handle, len) };
-        res.len = len;
+/// Limits on the memory handed out by an arena.
+///
+/// Allocations taking the arena usage (including alignment padding) beyond the soft limit
+/// invoke a callback with the new usage, allocations that would take it beyond the hard limit
+/// fail with `ArenaError::QuotaExceeded`. The callback is invoked once per crossing of the soft
+/// limit, that is, again only after the arena has been cleared or rewound below the limit.
+#[derive(Clone, Default)]
+pub struct Quota {
+    /// The soft limit
+    soft: Option<usize>,
+
+    /// The callback to invoke when the soft limit is exceeded
+    on_soft: Option<Rc<dyn Fn(usize)>>,
+
+    /// The hard limit
+    hard: Option<usize>,
+}
 
-        for i in 0..len {
-            unsafe {
-                ptr::write(res.ptr.as_ptr().add(i), T::default());
-            }
-        }
+/// The point during the clearing of an arena at which clear observers are invoked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClearPhase {
+    /// The arena is about to be cleared, objects allocated from it are still accessible.
+    Before,
+    /// The arena has been cleared.
+    After,
+}
 
-        res
-    }
+/// A callback invoked when an arena is cleared.
+type ClearObserver = Rc<dyn Fn(ClearPhase)>;
 
-    /// Create a new slice of size `real_len`, but initialize length to `0`.
-    unsafe fn new_empty(handle: H, real_len: usize) -> Self {
-        let ptr: NonNull<T> = if real_len == 0 {
-            NonNull::dangling()
-        } else {
-            handle.allocate(real_len)
-        };
+/// The callbacks registered to be invoked when an arena is cleared.
+#[derive(Default)]
+pub(crate) struct ClearObservers {
+    observers: RefCell<Vec<ClearObserver>>,
+}
 
-        Slice {
-            ptr,
-            len: 0,
-            handle,
+/// A copy of the used part of an arena, which the arena can be restored to.
+///
+/// Snapshots are obtained using the `snapshot` method of an arena (or token), and consumed by
+/// its `restore` method.
+pub struct Snapshot {
+    /// Head of the arena space the snapshot was taken from
+    head: NonNull<u8>,
+
+    /// The contents of the arena up to the position at the time of the snapshot
+    data: Vec<u8>,
+}
+
+/// The memory of an arena, taken out of it using `rc::Arena::into_inner`.
+///
+/// The memory is released according to its backing when this is dropped, unless it has been
+/// taken over using `into_raw_parts`.
+#[derive(Debug)]
+pub struct OwnedBacking {
+    /// Start of the memory
+    ptr: NonNull<u8>,
+
+    /// Size of the memory
+    len: usize,
+
+    /// The kind of backing the memory has been obtained from
+    backing: ArenaBacking,
+}
+
+impl ArenaBacking {
+    /// Return the name of the kind of backing, which is the name of its variant.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            ArenaBacking::MemoryMap => "MemoryMap",
+            ArenaBacking::PopulatedMemoryMap => "PopulatedMemoryMap",
+            ArenaBacking::HugePageMemoryMap => "HugePageMemoryMap",
+            ArenaBacking::LockedMemoryMap => "LockedMemoryMap",
+            ArenaBacking::NumaMemoryMap(_) => "NumaMemoryMap",
+            ArenaBacking::GuardedMemoryMap => "GuardedMemoryMap",
+            ArenaBacking::SystemAllocation => "SystemAllocation",
+            ArenaBacking::SharedMemory(_) => "SharedMemory",
+            ArenaBacking::SealableMemoryMap => "SealableMemoryMap",
+            ArenaBacking::Custom(_) => "Custom",
         }
     }
-}
 
-impl<T: Clone, H: AllocHandle + Clone> Clone for Slice<T, H> {
-    fn clone(&self) -> Self {
-        let ptr: NonNull<T> = self.handle.allocate(self.len);
+    /// Create the memory for an arena of capacity `cap`, or return an error describing the
+    /// failure.
+    pub(crate) fn try_create(&self, cap: usize) -> Result<NonNull<u8>, ArenaError> {
+        self.create(cap).ok_or_else(|| self.allocation_failed(cap))
+    }
 
-        for i in 0..self.len {
-            unsafe {
-                ptr::write(ptr.as_ptr().add(i), (*self.ptr.as_ptr().add(i)).clone());
+    /// Describe the failure to allocate `size` bytes from the backing.
+    ///
+    /// This has to be called right after the failure, so that the error reported by the
+    /// operating system is intact.
+    fn allocation_failed(&self, size: usize) -> ArenaError {
+        match *self {
+            ArenaBacking::SystemAllocation | ArenaBacking::Custom(_) => {
+                ArenaError::AllocationFailed {
+                    size,
+                    backing: self.kind(),
+                    os_error: None,
+                }
             }
+            _ => mapping_failed(self.kind(), size),
         }
+    }
 
-        Slice {
-            ptr,
-            len: self.len,
-            handle: self.handle.clone(),
+    /// Create the memory for an arena of capacity `cap`.
+    pub(crate) fn create(&self, cap: usize) -> Option<NonNull<u8>> {
+        match *self {
+            ArenaBacking::MemoryMap => NonNull::new(create_mapping(cap)),
+            ArenaBacking::PopulatedMemoryMap => NonNull::new(create_mapping_populated(cap)),
+            ArenaBacking::HugePageMemoryMap => NonNull::new(create_mapping_huge(cap)),
+            ArenaBacking::LockedMemoryMap => NonNull::new(create_mapping_locked(cap)),
+            ArenaBacking::NumaMemoryMap(node) => NonNull::new(create_mapping_numa(node, cap)),
+            ArenaBacking::GuardedMemoryMap => NonNull::new(create_mapping_guarded(cap)),
+            ArenaBacking::SystemAllocation => NonNull::new(create_mapping_alloc(cap)),
+            ArenaBacking::SharedMemory(ref name) => NonNull::new(create_mapping_shared(name, cap)),
+            ArenaBacking::SealableMemoryMap => NonNull::new(create_mapping_sealable(cap)),
+            ArenaBacking::Custom(ref source) => source.allocate(cap),
         }
     }
-}
 
-impl<T: fmt::Debug, H> fmt::Debug for Slice<T, H> {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.deref().fmt(fmt)
+    /// Release the memory of an arena of capacity `cap`.
+    pub(crate) fn destroy(&self, head: NonNull<u8>, cap: usize) {
+        match *self {
+            ArenaBacking::MemoryMap
+            | ArenaBacking::PopulatedMemoryMap
+            | ArenaBacking::HugePageMemoryMap
+            | ArenaBacking::LockedMemoryMap
+            | ArenaBacking::NumaMemoryMap(_) => destroy_mapping(head, cap),
+            ArenaBacking::GuardedMemoryMap => destroy_mapping_guarded(head, cap),
+            ArenaBacking::SystemAllocation => destroy_mapping_alloc(head, cap),
+            ArenaBacking::SharedMemory(ref name) => destroy_mapping_shared(name, head, cap),
+            ArenaBacking::SealableMemoryMap => destroy_mapping_sealable(head, cap),
+            ArenaBacking::Custom(ref source) => unsafe { source.release(head, cap) },
+        }
     }
-}
 
-impl<T, H> Deref for Slice<T, H> {
-    type Target = [T];
+    /// Extend the memory of an arena of capacity `cap` to `new_cap` without moving it, and
+    /// return `true` if this was possible.
+    pub(crate) fn grow_in_place(&self, head: NonNull<u8>, cap: usize, new_cap: usize) -> bool {
+        match *self {
+            ArenaBacking::MemoryMap => grow_mapping(head, cap, new_cap),
+            ArenaBacking::PopulatedMemoryMap => {
+                let grown = grow_mapping(head, cap, new_cap);
 
-    fn deref(&self) -> &[T] {
-        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
-    }
-}
+                if grown && new_cap > cap {
+                    let tail = unsafe { NonNull::new_unchecked(head.as_ptr().add(cap)) };
+                    prefault_pages(tail, new_cap - cap);
+                }
 
-impl<T, H> DerefMut for Slice<T, H> {
-    fn deref_mut(&mut self) -> &mut [T] {
-        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
-    }
-}
+                grown
+            }
+            ArenaBacking::HugePageMemoryMap => {
+                let grown = grow_mapping(head, cap, new_cap);
 
-impl<T: Eq, H> Eq for Slice<T, H> {}
+                if grown && new_cap > cap {
+                    advise_huge_pages(head, new_cap);
+                }
 
-impl<T: PartialEq, H> PartialEq for Slice<T, H> {
-    fn eq(&self, other: &Self) -> bool {
-        self.deref().eq(other.deref())
+                grown
+            }
+            ArenaBacking::Custom(ref source) => unsafe { source.grow_in_place(head, cap, new_cap) },
+            _ => false,
+        }
     }
-}
 
-impl<T: PartialOrd, H> PartialOrd for Slice<T, H> {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        self.deref().partial_cmp(other.deref())
+    /// Return `true` if the memory of an arena can be replaced by a larger one.
+    ///
+    /// Other processes may be attached to shared memory, so it can't be replaced.
+    pub(crate) fn is_movable(&self) -> bool {
+        !matches!(*self, ArenaBacking::SharedMemory(_))
     }
-}
 
-impl<'a, T, H> IntoIterator for &'a Slice<T, H> {
-    type Item = &'a T;
-    type IntoIter = slice::Iter<'a, T>;
+    /// Release the physical memory backing the first `len` bytes of an arena at `head`, and
+    /// return `true` if this was possible and the memory is zeroed afterwards.
+    pub(crate) fn decommit(&self, head: NonNull<u8>, len: usize) -> bool {
+        let len = round_to_pages(len);
+
+        match *self {
+            ArenaBacking::MemoryMap
+            | ArenaBacking::PopulatedMemoryMap
+            | ArenaBacking::HugePageMemoryMap
+            | ArenaBacking::NumaMemoryMap(_)
+            | ArenaBacking::GuardedMemoryMap => decommit_pages(head, len),
+            ArenaBacking::Custom(ref source) => unsafe { source.decommit(head, len) },
+            _ => false,
+        }
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.deref().iter()
+    /// Return `true` if fresh arena memory is zeroed.
+    pub(crate) fn is_zeroed(&self) -> bool {
+        match *self {
+            ArenaBacking::Custom(ref source) => source.is_zeroed(),
+            _ => true,
+        }
     }
 }
 
-impl<'a, T, H> IntoIterator for &'a mut Slice<T, H> {
-    type Item = &'a mut T;
-    type IntoIter = slice::IterMut<'a, T>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        self.deref_mut().iter_mut()
+impl Carved {
+    /// Create the backing of a child arena occupying the `len` bytes at `base`.
+    pub(crate) fn backing(base: NonNull<u8>, len: usize) -> ArenaBacking {
+        ArenaBacking::Custom(Arc::new(Carved {
+            base: base.as_ptr() as usize,
+            len,
+            taken: AtomicBool::new(false),
+        }))
     }
 }
 
-#[cfg(feature = "serde")]
-impl<T, H> Serialize for Slice<T, H>
-where
-    T: Serialize,
-{
-    #[inline]
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.collect_seq(self.iter())
+impl Forked {
+    /// Create the backing of a forked arena occupying the private mapping of `len` bytes at
+    /// `base`, which is handed out on creation of the arena unless it has been `taken` already.
+    pub(crate) fn backing(base: NonNull<u8>, len: usize, taken: bool) -> ArenaBacking {
+        ArenaBacking::Custom(Arc::new(Forked {
+            base: base.as_ptr() as usize,
+            len,
+            taken: AtomicBool::new(taken),
+        }))
     }
 }
 
-/* #[cfg(feature = "serde")]
-impl<'de, T, H> Deserialize<'de> for Slice<T, H>
-where
-    T: Deserialize<'de>,
-{
-    #[inline]
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let mut res: Vec<T> = Deserialize::deserialize(deserializer)?;
-        let mut slice = Slice::new(res.len());
-
-        unsafe {
-            let ptr = res.as_mut_ptr();
-            ptr::copy_nonoverlapping(slice.ptr, ptr, slice.len);
-            dealloc(ptr);
+unsafe impl BackingSource for Forked {
+    fn allocate(&self, capacity: usize) -> Option<NonNull<u8>> {
+        // the mapping is handed out once on creation of the forked arena, and can't be replaced
+        // when the arena is moved, as that would copy the arena onto itself.
+        if capacity <= self.len && !self.taken.swap(true, Ordering::Relaxed) {
+            NonNull::new(self.base as *mut u8)
+        } else {
+            None
         }
+    }
 
-        mem::forget(res);
-
-        slice
+    unsafe fn release(&self, base: NonNull<u8>, capacity: usize) {
+        destroy_mapping(base, capacity);
     }
-} */
+}
 
-impl<T, H> Drop for Slice<T, H> {
-    fn drop(&mut self) {
-        unsafe {
-            ptr::drop_in_place(&mut self[..]);
+unsafe impl BackingSource for Carved {
+    fn allocate(&self, capacity: usize) -> Option<NonNull<u8>> {
+        // the carved memory is handed out once on creation of the child arena, and can't be
+        // replaced when the arena is moved, as that would copy the arena onto itself.
+        if capacity <= self.len && !self.taken.swap(true, Ordering::Relaxed) {
+            NonNull::new(self.base as *mut u8)
+        } else {
+            None
         }
     }
+
+    unsafe fn release(&self, _base: NonNull<u8>, _capacity: usize) {}
 }
 
-impl<T, H> SliceVec<T, H> {
-    /// Create an immutable iterator over the elements of the vector.
-    pub fn iter(&self) -> slice::Iter<'_, T> {
-        self.slice.iter()
+impl OwnedBacking {
+    /// Take ownership of `len` bytes of memory at `ptr`, obtained from `backing`.
+    ///
+    /// # Safety
+    ///
+    /// The memory has to have been obtained using `into_raw_parts` with the same parameters, and
+    /// mustn't be owned by anything else.
+    pub unsafe fn from_raw_parts(ptr: NonNull<u8>, len: usize, backing: ArenaBacking) -> Self {
+        OwnedBacking { ptr, len, backing }
     }
 
-    /// Create an mutable iterator over the elements of the vector.
-    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
-        self.slice.iter_mut()
+    /// Return a pointer to the start of the memory.
+    pub fn as_ptr(&self) -> NonNull<u8> {
+        self.ptr
     }
-}
 
-impl<T, H: AllocHandle> SliceVec<T, H> {
-    /// Create a new empty vector of capacity `0` using the given handle.
-    pub fn new(handle: H) -> Self {
-        Self::with_capacity(handle, 0)
+    /// Return the size of the memory.
+    pub fn len(&self) -> usize {
+        self.len
     }
 
-    /// Create a new vector of given capacity using the given handle.
-    pub fn with_capacity(handle: H, capacity: usize) -> Self {
-        SliceVec {
-            slice: unsafe { Slice::new_empty(handle, capacity) },
-            capacity,
-        }
+    /// Return `true` if the memory is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    /// Return the current capacity of the vector.
-    pub fn capacity(&self) -> usize {
-        self.capacity
+    /// Return the kind of backing the memory has been obtained from.
+    pub fn backing(&self) -> &ArenaBacking {
+        &self.backing
     }
 
-    /// Reseve enough space in the vector for at least `size` additional elements.
-    pub fn reserve(&mut self, additional: usize) {
-        let ptr = self.slice.ptr;
-        let size = self.slice.len + additional;
+    /// Give up ownership of the memory, returning its start, size and backing.
+    ///
+    /// Releasing the memory is left to the caller, which can be done by passing the parts back
+    /// to `from_raw_parts` and dropping the result.
+    pub fn into_raw_parts(self) -> (NonNull<u8>, usize, ArenaBacking) {
+        let this = mem::ManuallyDrop::new(self);
 
-        if self.capacity >= size {
-            return;
-        }
+        // the backing is moved out exactly once, and the memory isn't released.
+        (this.ptr, this.len, unsafe { ptr::read(&this.backing) })
+    }
+}
 
-        let mut new_capacity = if self.capacity > 0 { self.capacity } else { 4 };
+impl Drop for OwnedBacking {
+    fn drop(&mut self) {
+        self.backing.destroy(self.ptr, self.len);
+    }
+}
 
-        while new_capacity < size {
-            new_capacity *= 2;
-        }
+impl Snapshot {
+    /// Copy the used part of the arena starting at `head`.
+    pub(crate) fn take(head: NonNull<u8>, pos: usize) -> Self {
+        let data = unsafe { slice::from_raw_parts(head.as_ptr(), pos).to_vec() };
 
-        let new_ptr: NonNull<T> = self.slice.handle.allocate_or_extend(ptr, self.capacity, new_capacity);
+        Snapshot { head, data }
+    }
 
-        if ptr != new_ptr {
-            unsafe {
-                ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), self.slice.len());
-            }
+    /// Copy the snapshot back into the arena starting at `head`, returning the position to reset
+    /// the arena to.
+    ///
+    /// Panics if the snapshot has been taken from another arena.
+    pub(crate) unsafe fn restore(&self, head: NonNull<u8>) -> usize {
+        assert_eq!(
+            self.head, head,
+            "snapshot has been taken from another arena"
+        );
 
-            self.slice.ptr = new_ptr;
-        }
+        ptr::copy_nonoverlapping(self.data.as_ptr(), head.as_ptr(), self.data.len());
 
-        self.capacity = new_capacity;
+        self.data.len()
     }
 
-    // TODO: shrink_to_fit
+    /// Return the number of bytes in use in the arena at the time of the snapshot.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
 
-    // TODO: shrink_to
+    /// Return `true` if the arena was empty at the time of the snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
 
-    /// Shorten the vector, keeping the first `len` elements and dropping the rest.
-    ///
-    /// If `len` is greater than the vector's current length, this has no effect.
-    pub fn truncate(&mut self, len: usize) {
-        let old_len = self.slice.len;
+impl Quota {
+    /// Create a quota without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        if len < old_len {
-            unsafe {
-                ptr::drop_in_place(&mut self.slice[len..old_len]);
-            }
+    /// Set the soft limit, invoking `callback` with the arena usage once it is exceeded.
+    pub fn soft<F>(mut self, limit: usize, callback: F) -> Self
+    where
+        F: Fn(usize) + 'static,
+    {
+        self.soft = Some(limit);
+        self.on_soft = Some(Rc::new(callback));
+        self
+    }
+
+    /// Set the hard limit.
+    pub fn hard(mut self, limit: usize) -> Self {
+        self.hard = Some(limit);
+        self
+    }
+
+    /// Return the soft limit, if any.
+    pub fn soft_limit(&self) -> Option<usize> {
+        self.soft
+    }
+
+    /// Return the hard limit, if any.
+    pub fn hard_limit(&self) -> Option<usize> {
+        self.hard
+    }
+}
+
+impl ClearObservers {
+    /// Register a callback to invoke before and after the arena is cleared.
+    pub(crate) fn add<F>(&self, callback: F)
+    where
+        F: Fn(ClearPhase) + 'static,
+    {
+        self.observers.borrow_mut().push(Rc::new(callback));
+    }
 
-            self.slice.len = len;
+    /// Invoke all callbacks in the order they have been registered.
+    pub(crate) fn notify(&self, phase: ClearPhase) {
+        // callbacks may register further callbacks, which are only invoked on the next clear.
+        let observers = self.observers.borrow().clone();
+
+        for observer in observers {
+            observer(phase);
         }
     }
+}
 
-    /// Remove an element from the vector and return it.
+impl fmt::Debug for ClearObservers {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ClearObservers")
+            .field("len", &self.observers.borrow().len())
+            .finish()
+    }
+}
+
+impl fmt::Debug for Quota {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Quota")
+            .field("soft", &self.soft)
+            .field("hard", &self.hard)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Snapshot")
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// Every arena-allocated object has some form of handle to the arena containing it.
+///
+/// Depending on the type of arena, the actual functionality of the handle can be different,
+/// but it at least allows for allocation and object resizing.
+///
+/// To be useful, handles need to implement `Clone`.
+///
+/// # Safety
+///
+/// The containers in this crate trust the memory handed out by their handles, so implementors
+/// have to uphold the following contract:
+///
+/// * Successful allocations of `count` objects of type `T` return pointers aligned for `T`, to
+///   memory valid for reads and writes of `count * mem::size_of::<T>()` bytes, which doesn't
+///   overlap any other live allocation. The memory has to stay valid as long as the handle or
+///   any of its clones is live. `try_allocate_zeroed` has to return zeroed memory, and
+///   `try_allocate_layout` memory satisfying the size and alignment of the layout.
+/// * `try_allocate_or_extend` either extends the sequence in place, returning `ptr`, or
+///   returns a fresh allocation as above, leaving the old sequence intact.
+/// * `dealloc_last` and `allocate_or_shrink` only return `true` if the memory has been
+///   released. `allocate_or_shrink` keeps the memory of the first `count` objects valid. The
+///   caller gives up the memory passed to `dealloc_last` even if it isn't released, so that it
+///   may be overwritten, for instance with a poison pattern. The same goes for the memory past
+///   the first `count` objects passed to `allocate_or_shrink`, except that it stays valid for
+///   the caller if it isn't released.
+/// * `owns` doesn't return `false` for memory handed out by the handle or any of its clones.
+///
+/// `CheckedHandle` can be used to validate (parts of) the contract in debug builds.
+pub unsafe trait AllocHandle {
+    /// Allocate memory from the arena.
     ///
-    /// The removed element is replaced by the last element of the vector.
-    /// This does not preserve ordering, but is O(1).
-    pub fn swap_remove(&mut self, index: usize) -> T {
-        let hole: *mut T = &mut self[index];
-        self.slice.len -= 1;
+    /// Allocate `count` objects of type `T` from the arena, and panic if this is not possible.
+    fn allocate<T>(&self, count: usize) -> NonNull<T> {
+        self.try_allocate(count).expect("arena overflow")
+    }
+    /// Reallocate memory in the arena.
+    ///
+    /// Resize the object sequence pointed to by `ptr` of `old_count` elements of type `T` to
+    /// `count` objects, or allocate space for a new sequence if no resizing in place is possible,
+    /// and panic if neither is possible. Copying the objects is left to the caller.
+    ///
+    /// `ptr` must point into the arena.
+    fn allocate_or_extend<T>(&self, ptr: NonNull<T>, old_count: usize, count: usize) -> NonNull<T> {
+        self.try_allocate_or_extend(ptr, old_count, count)
+            .expect("arena overflow")
+    }
+    /// Allocate memory from the arena, or return an error if this is not possible.
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError>;
+    /// Reallocate memory in the arena, or return an error if this is not possible.
+    ///
+    /// See `allocate_or_extend` for details.
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError>;
+    /// Allocate zero-initialized memory from the arena.
+    ///
+    /// Allocate `count` objects of type `T` from the arena with all bytes set to `0`, and panic
+    /// if this is not possible.
+    fn allocate_zeroed<T>(&self, count: usize) -> NonNull<T> {
+        self.try_allocate_zeroed(count).expect("arena overflow")
+    }
+    /// Allocate zero-initialized memory from the arena, or return an error if this is not
+    /// possible.
+    ///
+    /// Arenas can override this to avoid zeroing memory that is known to be zeroed already.
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr = self.try_allocate(count)?;
 
         unsafe {
-            let last = ptr::read(self.slice.ptr.as_ptr().add(self.slice.len));
-            ptr::replace(hole, last)
+            ptr::write_bytes(ptr.as_ptr(), 0, count);
+        }
+
+        Ok(ptr)
+    }
+    /// Allocate memory for an arbitrary layout from the arena.
+    ///
+    /// Allocate a block of memory satisfying the size and alignment requirements of `layout`,
+    /// and panic if this is not possible.
+    fn allocate_layout(&self, layout: Layout) -> NonNull<u8> {
+        self.try_allocate_layout(layout).expect("arena overflow")
+    }
+    /// Allocate memory for an arbitrary layout from the arena, or return an error if this is not
+    /// possible.
+    ///
+    /// By default, this over-allocates by up to `layout.align() - 1` bytes to be able to align
+    /// the block, arenas should override it to align the allocation directly.
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        let count = layout
+            .size()
+            .checked_add(layout.align() - 1)
+            .ok_or(ArenaError::CapacityOverflow)?;
+        let ptr: NonNull<u8> = self.try_allocate(count)?;
+        let skip = (ptr.as_ptr() as usize).wrapping_neg() & (layout.align() - 1);
+
+        unsafe { Ok(NonNull::new_unchecked(ptr.as_ptr().add(skip))) }
+    }
+    /// Allocate memory for `count` objects of type `T`, aligned to (at least) `align` bytes.
+    ///
+    /// This is useful for SIMD or DMA buffers, which often require alignment beyond the natural
+    /// alignment of their element type. Panics if `align` is not a power of two or the
+    /// allocation is not possible.
+    fn allocate_aligned<T>(&self, count: usize, align: usize) -> NonNull<T> {
+        self.try_allocate_aligned(count, align)
+            .expect("arena overflow")
+    }
+    /// Allocate memory for `count` objects of type `T`, aligned to (at least) `align` bytes, or
+    /// return an error if this is not possible.
+    fn try_allocate_aligned<T>(
+        &self,
+        count: usize,
+        align: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let layout = Layout::array::<T>(count)
+            .and_then(|layout| layout.align_to(align))
+            .map_err(|_| ArenaError::InvalidLayout)?;
+
+        Ok(self.try_allocate_layout(layout)?.cast())
+    }
+    /// Return the memory of the most recent allocation to the arena.
+    ///
+    /// If the `count` objects of type `T` at `ptr` are the last allocation made from the arena,
+    /// the arena position is moved back to its start, so that the memory can be reused, and
+    /// `true` is returned. Otherwise, nothing happens and `false` is returned.
+    ///
+    /// # Safety
+    ///
+    /// The memory must not be accessed after it has been returned to the arena.
+    unsafe fn dealloc_last<T>(&self, _ptr: NonNull<T>, _count: usize) -> bool {
+        false
+    }
+    /// Shrink an object sequence in the arena.
+    ///
+    /// Resize the object sequence pointed to by `ptr` of `old_count` elements of type `T` to
+    /// `count <= old_count` objects. If the sequence is the last allocation made from the arena,
+    /// the memory past the new end is returned to the arena, and `true` is returned. Otherwise,
+    /// the sequence keeps its size and `false` is returned. Dropping the objects beyond `count`
+    /// is left to the caller.
+    ///
+    /// This is the inverse of `allocate_or_extend`.
+    ///
+    /// # Safety
+    ///
+    /// The memory past the new end of the sequence must not be accessed after it has been
+    /// returned to the arena.
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        _ptr: NonNull<T>,
+        _old_count: usize,
+        _count: usize,
+    ) -> bool {
+        false
+    }
+
+    /// Return `false` if the `size` bytes at `ptr` are known not to belong to the arena.
+    ///
+    /// This is used for sanity checks, handles that can't tell return `true`, which is the
+    /// default.
+    fn owns(&self, _ptr: NonNull<u8>, _size: usize) -> bool {
+        true
+    }
+
+    /// Return an identifier of the arena the handle allocates from, or `None` if the handle
+    /// can't tell, which is the default.
+    ///
+    /// Handles to the same arena return the same identifier, and handles to different arenas
+    /// that are live at the same time return different ones.
+    fn arena_id(&self) -> Option<usize> {
+        None
+    }
+
+    /// Return `true` if the arena has been cleared since the handle has been created, so that
+    /// memory allocated through it may have been reused.
+    ///
+    /// Containers refuse to access their contents through stale handles, and leak them when
+    /// dropped. Handles to arenas that can't be cleared while they are live never become stale,
+    /// which is the default.
+    fn is_stale(&self) -> bool {
+        false
+    }
+}
+
+/// Return `true` if the `size` bytes at `ptr` lie within the `cap` bytes of arena memory at
+/// `head`.
+pub(crate) fn owns_inner(head: NonNull<u8>, cap: usize, ptr: NonNull<u8>, size: usize) -> bool {
+    let offset = (ptr.as_ptr() as usize).wrapping_sub(head.as_ptr() as usize);
+
+    offset.checked_add(size).is_some_and(|end| end <= cap)
+}
+
+/// An arena allocated, fixed-size sequence of objects.
+///
+/// If a slice is the last allocation in its arena when dropped, its memory is returned to the
+/// arena, so short-lived temporaries don't permanently consume arena space.
+pub struct Slice<T, H> {
+    ptr: NonNull<T>,
+    len: usize,
+    handle: H,
+    // `Drop` can't require `H: AllocHandle`, so the constructors store the deallocation routine.
+    release: unsafe fn(&H, NonNull<T>, usize) -> bool,
+    // the same goes for the staleness check guarding accesses to the contents.
+    stale: fn(&H) -> bool,
+}
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub struct SliceVec<T, H> {
+    slice: Slice<T, H>,
+    // owo what's this
+    capacity: usize,
+}
+
+// a slice owns its elements, and the handle is only used for allocation.
+unsafe impl<T: Send, H: Send> Send for Slice<T, H> {}
+unsafe impl<T: Sync, H: Sync> Sync for Slice<T, H> {}
+
+impl<T, H> Slice<T, H> {
+    /// Return the handle the slice has been allocated from.
+    pub fn handle(&self) -> &H {
+        &self.handle
+    }
+
+    /// Panic if the arena has been cleared since the slice has been allocated.
+    fn check_fresh(&self) {
+        assert!(
+            !(self.stale)(&self.handle),
+            "slice used after its arena has been cleared"
+        );
+    }
+
+    /// Release the buffer of the slice without dropping its elements.
+    ///
+    /// # Safety
+    ///
+    /// All elements have to be moved out of the slice beforehand.
+    #[cfg(feature = "rayon")]
+    pub(crate) unsafe fn release_moved(self) {
+        let mut this = mem::ManuallyDrop::new(self);
+
+        if !(this.stale)(&this.handle) {
+            (this.release)(&this.handle, this.ptr, this.len);
+        }
+
+        ptr::drop_in_place(&mut this.handle);
+    }
+}
+
+impl<T, H: AllocHandle> Slice<T, H> {
+    /// Create a new slice of default-initialized objects using the provided handle.
+    pub fn new(handle: H, len: usize) -> Self
+    where
+        T: Default,
+    {
+        let mut res = unsafe { Self::new_empty(handle, len) };
+
+        for _ in 0..len {
+            unsafe { res.push_unchecked(T::default()) };
+        }
+
+        res
+    }
+
+    /// Create a new slice of default-initialized objects using the provided handle, or return
+    /// an error if the arena is exhausted.
+    pub fn try_new(handle: H, len: usize) -> Result<Self, ArenaError>
+    where
+        T: Default,
+    {
+        let mut res = unsafe { Self::try_new_empty(handle, len)? };
+
+        for _ in 0..len {
+            unsafe { res.push_unchecked(T::default()) };
+        }
+
+        Ok(res)
+    }
+
+    /// Create a new slice of zero-initialized objects using the provided handle.
+    ///
+    /// This avoids writing to memory the arena knows to be zeroed already, which makes it a
+    /// cheap way to create large buffers.
+    ///
+    /// # Safety
+    ///
+    /// An all-zero bit pattern has to be a valid value of type `T`.
+    pub unsafe fn new_zeroed(handle: H, len: usize) -> Self {
+        let ptr: NonNull<T> = if len == 0 || mem::size_of::<T>() == 0 {
+            NonNull::dangling()
+        } else {
+            handle.allocate_zeroed(len)
+        };
+
+        Slice {
+            ptr,
+            len,
+            handle,
+            release: H::dealloc_last::<T>,
+            stale: H::is_stale,
+        }
+    }
+
+    /// Create a new slice of size `real_len`, but initialize length to `0`.
+    unsafe fn new_empty(handle: H, real_len: usize) -> Self {
+        let ptr: NonNull<T> = if real_len == 0 || mem::size_of::<T>() == 0 {
+            NonNull::dangling()
+        } else {
+            handle.allocate(real_len)
+        };
+
+        let slice = Slice {
+            ptr,
+            len: 0,
+            handle,
+            release: H::dealloc_last::<T>,
+            stale: H::is_stale,
+        };
+
+        slice.check_owned(real_len);
+
+        slice
+    }
+
+    /// Create a new slice of size `real_len`, but initialize length to `0`, or return an error
+    /// if the arena is exhausted.
+    unsafe fn try_new_empty(handle: H, real_len: usize) -> Result<Self, ArenaError> {
+        let ptr: NonNull<T> = if real_len == 0 || mem::size_of::<T>() == 0 {
+            NonNull::dangling()
+        } else {
+            handle.try_allocate(real_len)?
+        };
+
+        let slice = Slice {
+            ptr,
+            len: 0,
+            handle,
+            release: H::dealloc_last::<T>,
+            stale: H::is_stale,
+        };
+
+        slice.check_owned(real_len);
+
+        Ok(slice)
+    }
+
+    /// Append an element to a slice created with spare room using `new_empty`.
+    ///
+    /// The length is updated right after the element has been written, so if producing the
+    /// next element panics, the elements written so far are dropped along with the slice, and
+    /// the uninitialized ones aren't.
+    ///
+    /// # Safety
+    ///
+    /// The slice has to have room for another element.
+    unsafe fn push_unchecked(&mut self, elem: T) {
+        ptr::write(self.ptr.as_ptr().add(self.len), elem);
+        self.len += 1;
+    }
+
+    /// Check that the buffer of `count` objects at the start of the slice belongs to the arena
+    /// of its handle.
+    ///
+    /// This is only done in debug builds with the `check_ownership` feature enabled, and catches
+    /// buggy handles and buffers that have been mixed up between arenas before memory is
+    /// corrupted.
+    #[cfg_attr(not(feature = "check_ownership"), allow(unused_variables))]
+    fn check_owned(&self, count: usize) {
+        #[cfg(feature = "check_ownership")]
+        {
+            let size = count * mem::size_of::<T>();
+
+            // stale handles refuse to allocate anyway, and can't vouch for their buffers.
+            debug_assert!(
+                size == 0 || (self.stale)(&self.handle) || self.handle.owns(self.ptr.cast(), size),
+                "buffer doesn't belong to the arena of its handle"
+            );
+        }
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for Slice<T, H> {
+    fn clone(&self) -> Self {
+        let mut res = unsafe { Self::new_empty(self.handle.clone(), self.len) };
+
+        for elem in self.iter() {
+            unsafe { res.push_unchecked(elem.clone()) };
         }
+
+        res
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for Slice<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(fmt)
+    }
+}
+
+impl<T, H> Deref for Slice<T, H> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.check_fresh();
+
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T, H> DerefMut for Slice<T, H> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.check_fresh();
+
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T, H> AsRef<[T]> for Slice<T, H> {
+    fn as_ref(&self) -> &[T] {
+        self.deref()
+    }
+}
+
+impl<T, H> AsMut<[T]> for Slice<T, H> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.deref_mut()
+    }
+}
+
+impl<T, H> Borrow<[T]> for Slice<T, H> {
+    fn borrow(&self) -> &[T] {
+        self.deref()
+    }
+}
+
+impl<T: Eq, H> Eq for Slice<T, H> {}
+
+impl<T: PartialEq, H> PartialEq for Slice<T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl<T: PartialOrd, H> PartialOrd for Slice<T, H> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a Slice<T, H> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.deref().iter()
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a mut Slice<T, H> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.deref_mut().iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, H> Serialize for Slice<T, H>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/* #[cfg(feature = "serde")]
+impl<'de, T, H> Deserialize<'de> for Slice<T, H>
+where
+    T: Deserialize<'de>,
+{
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut res: Vec<T> = Deserialize::deserialize(deserializer)?;
+        let mut slice = Slice::new(res.len());
+
+        unsafe {
+            let ptr = res.as_mut_ptr();
+            ptr::copy_nonoverlapping(slice.ptr, ptr, slice.len);
+            dealloc(ptr);
+        }
+
+        mem::forget(res);
+
+        slice
+    }
+} */
+
+impl<T, H> Drop for Slice<T, H> {
+    fn drop(&mut self) {
+        // the contents may have been overwritten already, so they are leaked.
+        if (self.stale)(&self.handle) {
+            return;
+        }
+
+        unsafe {
+            ptr::drop_in_place(&mut self[..]);
+            (self.release)(&self.handle, self.ptr, self.len);
+        }
+    }
+}
+
+impl<T, H> SliceVec<T, H> {
+    /// Create an immutable iterator over the elements of the vector.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.slice.iter()
+    }
+
+    /// Create an mutable iterator over the elements of the vector.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.slice.iter_mut()
+    }
+
+    /// Return the handle the vector allocates from.
+    pub fn handle(&self) -> &H {
+        &self.slice.handle
+    }
+}
+
+impl<T, H: AllocHandle> SliceVec<T, H> {
+    /// Create a new empty vector of capacity `0` using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0)
+    }
+
+    /// Create a new vector of given capacity using the given handle.
+    ///
+    /// Vectors of zero-sized types don't allocate, and have a capacity of `usize::MAX`.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        SliceVec {
+            slice: unsafe { Slice::new_empty(handle, capacity) },
+            capacity: Self::initial_capacity(capacity),
+        }
+    }
+
+    /// Create a new vector of given capacity using the given handle, or return an error if the
+    /// arena is exhausted.
+    pub fn try_with_capacity(handle: H, capacity: usize) -> Result<Self, ArenaError> {
+        Ok(SliceVec {
+            slice: unsafe { Slice::try_new_empty(handle, capacity)? },
+            capacity: Self::initial_capacity(capacity),
+        })
+    }
+
+    /// Return the capacity of a vector created with room for `capacity` elements.
+    fn initial_capacity(capacity: usize) -> usize {
+        if mem::size_of::<T>() == 0 {
+            usize::MAX
+        } else {
+            capacity
+        }
+    }
+
+    /// Return the current capacity of the vector.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the spare capacity of the vector as a slice of uninitialized elements.
+    ///
+    /// The spare capacity can be filled in place, and the elements written committed to the
+    /// vector using `set_len`.
+    pub fn spare_capacity_mut(&mut self) -> &mut [mem::MaybeUninit<T>] {
+        self.slice.check_fresh();
+
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.slice.ptr.as_ptr().add(self.slice.len) as *mut mem::MaybeUninit<T>,
+                self.capacity - self.slice.len,
+            )
+        }
+    }
+
+    /// Set the length of the vector to `len`.
+    ///
+    /// # Safety
+    ///
+    /// `len` must not exceed the capacity of the vector, and the elements up to `len` must be
+    /// initialized.
+    pub unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity);
+
+        self.slice.len = len;
+    }
+
+    /// Reseve enough space in the vector for at least `size` additional elements.
+    ///
+    /// Panics if the new capacity overflows `usize`, or the arena is exhausted.
+    pub fn reserve(&mut self, additional: usize) {
+        match self.try_reserve(additional) {
+            Ok(()) => (),
+            Err(ArenaError::CapacityOverflow) => panic!("capacity overflow"),
+            Err(err) => panic!("arena overflow: {:?}", err),
+        }
+    }
+
+    /// Reseve enough space in the vector for at least `size` additional elements, or return an
+    /// error if the new capacity overflows `usize` or the arena is exhausted.
+    ///
+    /// On error, the vector is left unchanged.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ArenaError> {
+        self.check_owned();
+
+        let ptr = self.slice.ptr;
+        let size = self
+            .slice
+            .len
+            .checked_add(additional)
+            .ok_or(ArenaError::CapacityOverflow)?;
+
+        if self.capacity >= size {
+            return Ok(());
+        }
+
+        let new_capacity =
+            grow_capacity(self.capacity, size).ok_or(ArenaError::CapacityOverflow)?;
+
+        let new_ptr: NonNull<T> =
+            self.slice
+                .handle
+                .try_allocate_or_extend(ptr, self.capacity, new_capacity)?;
+
+        if ptr != new_ptr {
+            unsafe {
+                ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), self.slice.len());
+            }
+
+            self.slice.ptr = new_ptr;
+        }
+
+        self.capacity = new_capacity;
+
+        Ok(())
+    }
+
+    /// Shrink the capacity of the vector as much as possible.
+    ///
+    /// If the vector's buffer is the last allocation in the arena, the excess capacity is
+    /// returned to the arena.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(self.slice.len);
+    }
+
+    /// Shrink the capacity of the vector to the maximum of `min_capacity` and its length.
+    ///
+    /// If the vector's buffer is the last allocation in the arena, the excess capacity is
+    /// returned to the arena. Otherwise, the capacity is left unchanged, as the excess memory
+    /// couldn't be reused anyway.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let capacity = cmp::max(self.slice.len, min_capacity);
+
+        // vectors of zero-sized types keep their capacity, as they don't occupy any memory.
+        if capacity < self.capacity && mem::size_of::<T>() != 0 {
+            self.check_owned();
+
+            let released = unsafe {
+                self.slice
+                    .handle
+                    .allocate_or_shrink(self.slice.ptr, self.capacity, capacity)
+            };
+
+            if released {
+                self.capacity = capacity;
+            }
+        }
+    }
+
+    /// Shorten the vector, keeping the first `len` elements and dropping the rest.
+    ///
+    /// If `len` is greater than the vector's current length, this has no effect. Otherwise, the
+    /// capacity of the vector is shrunk to `len` as well, if the excess memory can be returned to
+    /// the arena (see `shrink_to`).
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.slice.len;
+
+        if len < old_len {
+            unsafe { self.drop_tail(len) };
+            self.shrink_to_fit();
+        }
+    }
+
+    /// Remove an element from the vector and return it.
+    ///
+    /// The removed element is replaced by the last element of the vector.
+    /// This does not preserve ordering, but is O(1).
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let hole: *mut T = &mut self[index];
+        self.slice.len -= 1;
+
+        unsafe {
+            let last = ptr::read(self.slice.ptr.as_ptr().add(self.slice.len));
+            ptr::replace(hole, last)
+        }
+    }
+
+    /// Insert an element at position `index`, shifting all elements after it to the right.
+    ///
+    /// Panics if `index` is greater than the vector's length.
+    pub fn insert(&mut self, index: usize, elem: T) {
+        let len = self.slice.len;
+
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+
+        if len == self.capacity {
+            self.reserve(1);
+        }
+
+        unsafe {
+            let p = self.slice.ptr.as_ptr().add(index);
+            ptr::copy(p, p.add(1), len - index);
+            ptr::write(p, elem);
+        }
+
+        self.slice.len = len + 1;
+    }
+
+    /// Remove and return the element at position `index`, shifting all elements after it to the
+    /// left.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.slice.len;
+
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+
+        unsafe {
+            let p = self.slice.ptr.as_ptr().add(index);
+            let elem = ptr::read(p);
+            ptr::copy(p.add(1), p, len - index - 1);
+            self.slice.len = len - 1;
+
+            elem
+        }
+    }
+
+    // TODO: retain
+
+    // TODO: dedup_by_key
+
+    // TODO: dedup_by
+
+    /// Push an element into the vector.
+    pub fn push(&mut self, elem: T) {
+        if self.slice.len == self.capacity {
+            self.reserve(1);
+        }
+
+        unsafe {
+            ptr::write(self.slice.ptr.as_ptr().add(self.slice.len()), elem);
+        }
+
+        self.slice.len = self.slice.len() + 1;
+    }
+
+    /// Remove the last element from the vector and return it, or `None` if the vector is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        unsafe {
+            self.slice.len -= 1;
+            Some(ptr::read(self.slice.ptr.as_ptr().add(self.slice.len)))
+        }
+    }
+
+    /// Move all elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// `other` may live in a different arena than `self` (see `AllocHandle::arena_id`). The
+    /// elements are moved into `self`'s buffer either way, while `other` keeps its buffer in its
+    /// own arena.
+    pub fn append(&mut self, other: &mut Self) {
+        other.check_owned();
+
+        let count = other.len();
+        self.reserve(count);
+        let len = self.len();
+
+        // moving the elements bitwise is sound regardless of the arenas involved, as the
+        // buffers are distinct, and `other` gives up ownership of the elements.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                other.slice.ptr.as_ptr(),
+                self.slice.ptr.as_ptr().add(len),
+                count);
+        }
+
+        self.slice.len = len + count;
+        other.slice.len = 0;
+    }
+
+    // TODO: drain
+
+    /// Clear the vector.
+    pub fn clear(&mut self) {
+        unsafe { self.drop_tail(0) };
+    }
+
+    /// Return the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.slice.len
+    }
+
+    /// Return `true` if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.slice.len == 0
+    }
+
+    /// Splits the vector into two at the given index.
+    ///
+    /// Retruns a newly allocated `Self`. `self` contains elements `[0, at)`, and the returned
+    /// `Self` contains elements `[at, len)`.
+    ///
+    /// The returned vector is allocated in the same arena as `self`. The capacity of `self`
+    /// remains unchanged. Panics if `at` is greater than the vector's length.
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        H: Clone,
+    {
+        assert!(at <= self.slice.len, "`at` out of bounds");
+
+        let mut ret = Self::with_capacity(self.slice.handle.clone(), self.slice.len - at);
+        ret.slice.len = self.slice.len - at;
+        self.slice.len = at;
+
+        unsafe {
+            ptr::copy_nonoverlapping(
+                self.slice.ptr.as_ptr().add(at),
+                ret.slice.ptr.as_ptr(),
+                ret.len());
+        }
+
+        ret.check_owned();
+
+        ret
+    }
+
+    /// Resize the vector to hold `len` elements, initialized to the return value of `f` if necessary.
+    pub fn resize_with<F>(&mut self, len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        self.resize_with_index(len, |_| f());
+    }
+
+    /// Resize the vector to hold `len` elements, initialized to the return value of `f` if
+    /// necessary, which is passed the index of the element to initialize.
+    ///
+    /// This is useful for filling lookup tables, for instance.
+    pub fn resize_with_index<F>(&mut self, len: usize, mut f: F)
+    where
+        F: FnMut(usize) -> T,
+    {
+        let old_len = self.slice.len;
+
+        if len <= old_len {
+            unsafe { self.drop_tail(len) };
+            return;
+        }
+
+        if self.capacity < len {
+            self.reserve(len - old_len);
+        }
+
+        for index in old_len..len {
+            unsafe { self.slice.push_unchecked(f(index)) };
+        }
+    }
+
+    /// Resize the vector to hold `len` elements, initialized to `value` if necessary.
+    pub fn resize(&mut self, len: usize, value: T)
+    where
+        T: Clone,
+    {
+        let old_len = self.slice.len;
+
+        if len <= old_len {
+            unsafe { self.drop_tail(len) };
+            return;
+        }
+
+        if self.capacity < len {
+            self.reserve(len - old_len);
+        }
+
+        for _ in old_len + 1..len {
+            unsafe { self.slice.push_unchecked(value.clone()) };
+        }
+
+        unsafe { self.slice.push_unchecked(value) };
+    }
+
+    /// Drop the elements from index `len` on.
+    ///
+    /// The length is updated first, so if a destructor panics, the elements aren't dropped
+    /// again along with the vector.
+    ///
+    /// # Safety
+    ///
+    /// `len` mustn't exceed the length of the vector.
+    unsafe fn drop_tail(&mut self, len: usize) {
+        let tail =
+            ptr::slice_from_raw_parts_mut(self.slice.ptr.as_ptr().add(len), self.slice.len - len);
+
+        self.slice.len = len;
+        ptr::drop_in_place(tail);
+    }
+
+    /// Check that the vector's buffer belongs to the arena of its handle.
+    fn check_owned(&self) {
+        self.slice.check_owned(self.capacity);
+    }
+
+    /// Clone and append all elements in a slice to the vector.
+    pub fn extend_from_slice(&mut self, other: &[T])
+        where
+            T: Clone
+    {
+        for e in other {
+            self.push(e.clone());
+        }
+    }
+
+    /// Convert the vector into a fixed-size slice of its elements.
+    ///
+    /// If the vector's buffer is the last allocation in the arena, its excess capacity is
+    /// returned to the arena.
+    pub fn into_slice(mut self) -> Slice<T, H> {
+        self.shrink_to_fit();
+
+        let slice = unsafe { ptr::read(&self.slice) };
+        mem::forget(self);
+
+        slice
+    }
+
+    // TODO: dedup
+
+    // TODO: remove_item
+
+    // TODO: splice
+
+    // TODO: drain_filter
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for SliceVec<T, H> {
+    fn clone(&self) -> Self {
+        let mut vec: SliceVec<T, H> =
+            SliceVec::with_capacity(self.slice.handle.clone(), self.capacity);
+
+        for elem in self.iter() {
+            unsafe { vec.slice.push_unchecked(elem.clone()) };
+        }
+
+        vec
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for SliceVec<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.slice.fmt(fmt)
+    }
+}
+
+impl<T, H> Drop for SliceVec<T, H> {
+    fn drop(&mut self) {
+        // the elements have to be dropped before the memory is returned to the arena, so the
+        // slice is emptied, which turns its own `drop` into a no-op.
+        if (self.slice.stale)(&self.slice.handle) {
+            return;
+        }
+
+        unsafe {
+            ptr::drop_in_place(&mut self.slice[..]);
+            self.slice.len = 0;
+            (self.slice.release)(&self.slice.handle, self.slice.ptr, self.capacity);
+        }
+    }
+}
+
+impl<T, H> Deref for SliceVec<T, H> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.slice.deref()
+    }
+}
+
+impl<T, H> DerefMut for SliceVec<T, H> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.slice.deref_mut()
+    }
+}
+
+impl<T, H> AsRef<[T]> for SliceVec<T, H> {
+    fn as_ref(&self) -> &[T] {
+        self.deref()
+    }
+}
+
+impl<T, H> AsMut<[T]> for SliceVec<T, H> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.deref_mut()
+    }
+}
+
+impl<T, H> Borrow<[T]> for SliceVec<T, H> {
+    fn borrow(&self) -> &[T] {
+        self.deref()
+    }
+}
+
+impl<T: Eq, H> Eq for SliceVec<T, H> { }
+
+impl<T: PartialEq, H> PartialEq for SliceVec<T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl<T: PartialOrd, H> PartialOrd for SliceVec<T, H> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<'a, T: 'a, H> IntoIterator for &'a SliceVec<T, H> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: 'a, H> IntoIterator for &'a mut SliceVec<T, H> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/* impl<T, H> FromIterator<T> for SliceVec<T, H> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item=T>
+    {
+        let iter = iter.into_iter();
+        let (min, max) = iter.size_hint();
+        let cap = if let Some(m) = max { m } else { min };
+
+        let mut res = SliceVec::new(cap);
+
+        for e in iter {
+            res.push(e);
+        }
+
+        res
+    }
+} */
+
+#[cfg(feature = "serde")]
+impl<T, H> Serialize for SliceVec<T, H>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.slice.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, H: AllocHandle> SliceVec<T, H> {
+    /// Deserialize a vector from a sequence, allocating it using the given handle.
+    pub fn deserialize_in<'de, D>(handle: H, deserializer: D) -> Result<Self, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(SliceVecVisitor {
+            handle,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Vectors whose handle type can be created out of thin air, like `ThreadLocalHandle`, are
+/// deserialized into the arena a default handle refers to.
+#[cfg(feature = "serde")]
+impl<'de, T, H> Deserialize<'de> for SliceVec<T, H>
+where
+    T: Deserialize<'de>,
+    H: AllocHandle + Default,
+{
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Self::deserialize_in(H::default(), deserializer)
+    }
+}
+
+/// A visitor collecting a sequence into a vector allocated using a given handle.
+#[cfg(feature = "serde")]
+struct SliceVecVisitor<T, H> {
+    handle: H,
+    _marker: PhantomData<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, H> Visitor<'de> for SliceVecVisitor<T, H>
+where
+    T: Deserialize<'de>,
+    H: AllocHandle,
+{
+    type Value = SliceVec<T, H>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut res = SliceVec::new(self.handle);
+
+        // the size hint is untrusted input, so it is capped to avoid exhausting the arena.
+        res.try_reserve(cmp::min(seq.size_hint().unwrap_or(0), 4096))
+            .map_err(de::Error::custom)?;
+
+        while let Some(elem) = seq.next_element()? {
+            res.try_reserve(1).map_err(de::Error::custom)?;
+            res.push(elem);
+        }
+
+        Ok(res)
+    }
+}
+
+/// Get the page size of the system we are running on.
+#[cfg(unix)]
+pub(crate) fn get_page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Get the page size of the system we are running on.
+#[cfg(windows)]
+pub(crate) fn get_page_size() -> usize {
+    use std::mem;
+    use winapi::um::sysinfoapi::GetSystemInfo;
+
+    unsafe {
+        let mut info = mem::zeroed();
+        GetSystemInfo(&mut info);
+
+        info.dwPageSize as usize
+    }
+}
+
+/// Round an arena capacity up to a multiple of the page size.
+///
+/// Mappings are made in whole pages anyway, so this only makes the rest of the last page
+/// usable. Fails for zero capacities, which would leave the arena without memory to point to.
+pub(crate) fn round_capacity(capacity: usize) -> Result<usize, ArenaError> {
+    let page_size = get_page_size();
+
+    if capacity == 0 {
+        return Err(ArenaError::InvalidCapacity);
+    }
+
+    capacity
+        .checked_add(page_size - 1)
+        .map(|capacity| capacity & !(page_size - 1))
+        .ok_or(ArenaError::InvalidCapacity)
+}
+
+/// Return the capacity of arenas created using `init`, taking `DEFAULT_CAPACITY_VAR` into
+/// account if it is set.
+pub(crate) fn default_capacity() -> Result<usize, ArenaError> {
+    let var = match std::env::var(DEFAULT_CAPACITY_VAR) {
+        Ok(var) => var,
+        Err(std::env::VarError::NotPresent) => return Ok(DEFAULT_CAPACITY),
+        Err(std::env::VarError::NotUnicode(_)) => return Err(ArenaError::InvalidCapacity),
+    };
+    let var = var.trim();
+
+    let (digits, shift) = match var.as_bytes().last() {
+        Some(b'k') | Some(b'K') => (&var[..var.len() - 1], 10),
+        Some(b'm') | Some(b'M') => (&var[..var.len() - 1], 20),
+        Some(b'g') | Some(b'G') => (&var[..var.len() - 1], 30),
+        _ => (var, 0),
+    };
+
+    digits
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|capacity| capacity.checked_mul(1 << shift))
+        .ok_or(ArenaError::InvalidCapacity)
+}
+
+/// Describe the failure to map `size` bytes of memory of the given kind of backing, including
+/// the error reported by the operating system.
+///
+/// This has to be called right after the failure, so that the error is intact.
+pub(crate) fn mapping_failed(backing: &'static str, size: usize) -> ArenaError {
+    ArenaError::AllocationFailed {
+        size,
+        backing,
+        os_error: std::io::Error::last_os_error()
+            .raw_os_error()
+            .filter(|&code| code != 0),
+    }
+}
+
+/// Create a virtual memory mapping of size `capacity`.
+#[cfg(unix)]
+pub(crate) fn create_mapping(capacity: usize) -> *mut u8 {
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            capacity,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_ANON | libc::MAP_PRIVATE,
+            -1,
+            0,
+        )
+    };
+
+    if ptr == libc::MAP_FAILED {
+        ptr::null_mut()
+    } else {
+        ptr as *mut u8
+    }
+}
+
+/// Create a virtual memory mapping of size `capacity`.
+#[cfg(windows)]
+pub(crate) fn create_mapping(capacity: usize) -> *mut u8 {
+    use std::ptr;
+    use winapi::shared::basetsd::SIZE_T;
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::um::memoryapi::VirtualAlloc;
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RESERVE, PAGE_READWRITE};
+
+    let lpAddress: LPVOID = ptr::null_mut();
+    let page_size = get_page_size();
+    let len = if capacity % page_size == 0 {
+        capacity
+    } else {
+        capacity + page_size - (capacity % page_size)
+    };
+    let flAllocationType = MEM_COMMIT | MEM_RESERVE;
+    let flProtect = PAGE_READWRITE;
+
+    let r = unsafe { VirtualAlloc(lpAddress, len as SIZE_T, flAllocationType, flProtect) };
+
+    r as *mut u8
+}
+
+/// Create a virtual memory mapping of size `capacity`, with all pages faulted in.
+#[cfg(target_os = "linux")]
+pub(crate) fn create_mapping_populated(capacity: usize) -> *mut u8 {
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            capacity,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_POPULATE,
+            -1,
+            0,
+        )
+    };
+
+    if ptr == libc::MAP_FAILED {
+        ptr::null_mut()
+    } else {
+        ptr as *mut u8
+    }
+}
+
+/// Create a virtual memory mapping of size `capacity`, with all pages faulted in.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn create_mapping_populated(capacity: usize) -> *mut u8 {
+    let ptr = create_mapping(capacity);
+
+    if let Some(base) = NonNull::new(ptr) {
+        prefault_pages(base, capacity);
+    }
+
+    ptr
+}
+
+/// Create a virtual memory mapping of size `capacity`, marked for backing by transparent huge
+/// pages.
+pub(crate) fn create_mapping_huge(capacity: usize) -> *mut u8 {
+    let ptr = create_mapping(capacity);
+
+    if let Some(base) = NonNull::new(ptr) {
+        advise_huge_pages(base, capacity);
+    }
+
+    ptr
+}
+
+/// Mark the pages covering the `len` bytes at `base` for backing by transparent huge pages.
+///
+/// This is merely a hint, so failure (for instance if transparent huge pages are disabled) is
+/// ignored.
+#[cfg(target_os = "linux")]
+fn advise_huge_pages(base: NonNull<u8>, len: usize) {
+    unsafe {
+        libc::madvise(
+            base.as_ptr() as *mut libc::c_void,
+            round_to_pages(len),
+            libc::MADV_HUGEPAGE,
+        );
+    }
+}
+
+/// Mark the pages covering the `len` bytes at `base` for backing by transparent huge pages.
+///
+/// Transparent huge pages are only supported on Linux, so this does nothing.
+#[cfg(not(target_os = "linux"))]
+fn advise_huge_pages(_base: NonNull<u8>, _len: usize) {}
+
+/// Create a virtual memory mapping of size `capacity`, with all pages locked into physical
+/// memory.
+pub(crate) fn create_mapping_locked(capacity: usize) -> *mut u8 {
+    let ptr = create_mapping(capacity);
+
+    if let Some(base) = NonNull::new(ptr) {
+        if !lock_pages(base, capacity) {
+            destroy_mapping(base, capacity);
+            return ptr::null_mut();
+        }
+    }
+
+    ptr
+}
+
+/// Lock the pages covering the `len` bytes at `base` into physical memory.
+///
+/// The pages are unlocked when they are unmapped.
+#[cfg(unix)]
+fn lock_pages(base: NonNull<u8>, len: usize) -> bool {
+    unsafe { libc::mlock(base.as_ptr() as *const libc::c_void, len) == 0 }
+}
+
+/// Lock the pages covering the `len` bytes at `base` into physical memory.
+///
+/// The pages are unlocked when they are released.
+#[cfg(windows)]
+fn lock_pages(base: NonNull<u8>, len: usize) -> bool {
+    use winapi::shared::basetsd::SIZE_T;
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::um::memoryapi::VirtualLock;
+
+    unsafe { VirtualLock(base.as_ptr() as LPVOID, len as SIZE_T) != 0 }
+}
+
+/// Fault in the pages covering the `len` bytes of zeroed memory at `base` by writing to them.
+fn prefault_pages(base: NonNull<u8>, len: usize) {
+    let page_size = get_page_size();
+    let start = base.as_ptr() as usize;
+    let end = start + len;
+
+    // the first write to a page faults it in, reads would only map the shared zero page.
+    let mut page = start & !(page_size - 1);
+
+    while page < end {
+        let addr = cmp::max(page, start) as *mut u8;
+
+        unsafe { ptr::write_volatile(addr, 0) };
+        page += page_size;
+    }
+}
+
+/// Create a virtual memory mapping of size `capacity`, bound to the NUMA node `node`.
+#[cfg(target_os = "linux")]
+pub(crate) fn create_mapping_numa(node: u32, capacity: usize) -> *mut u8 {
+    const MPOL_BIND: libc::c_int = 2;
+    const BITS: usize = mem::size_of::<libc::c_ulong>() * 8;
+
+    let ptr = create_mapping(capacity);
+
+    if let Some(base) = NonNull::new(ptr) {
+        let node = node as usize;
+        let mut mask = vec![0 as libc::c_ulong; node / BITS + 1];
+        mask[node / BITS] |= 1 << (node % BITS);
+
+        // the kernel ignores the last bit of the mask, so its length is passed plus one.
+        let res = unsafe {
+            libc::syscall(
+                libc::SYS_mbind,
+                ptr as *mut libc::c_void,
+                capacity,
+                MPOL_BIND,
+                mask.as_ptr(),
+                mask.len() * BITS + 1,
+                0,
+            )
+        };
+
+        if res != 0 {
+            destroy_mapping(base, capacity);
+            return ptr::null_mut();
+        }
+    }
+
+    ptr
+}
+
+/// Create a virtual memory mapping of size `capacity`.
+///
+/// NUMA bindings are only supported on Linux, so the node is ignored.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn create_mapping_numa(_node: u32, capacity: usize) -> *mut u8 {
+    create_mapping(capacity)
+}
+
+/// Create a copy-on-write copy of the virtual memory mapping of size `capacity` at `base`, of
+/// which only the byte ranges in `used` need to be preserved.
+///
+/// The used ranges are written to an anonymous file, which both the copy and the original
+/// mapping (replacing it in place) map privately, so that their pages are shared until written
+/// to. The rest of both mappings reads as zeroed afterwards.
+///
+/// Both mappings are readable and writable afterwards, so mappings containing read-only pages
+/// must not be forked. A null pointer is returned if the copy can't be created, or the original
+/// mapping can't be replaced.
+#[cfg(target_os = "linux")]
+pub(crate) fn fork_mapping(base: NonNull<u8>, capacity: usize, used: &[(usize, usize)]) -> *mut u8 {
+    unsafe {
+        let fd = libc::memfd_create(
+            b"arenavec\0".as_ptr() as *const libc::c_char,
+            libc::MFD_CLOEXEC,
+        );
+
+        if fd < 0 {
+            return ptr::null_mut();
+        }
+
+        let mut ok = libc::ftruncate(fd, capacity as libc::off_t) == 0;
+
+        for &(start, end) in used {
+            let mut offset = start;
+
+            while ok && offset < end {
+                let written = libc::pwrite(
+                    fd,
+                    base.as_ptr().add(offset) as *const libc::c_void,
+                    end - offset,
+                    offset as libc::off_t,
+                );
+
+                ok = written > 0;
+                offset += cmp::max(written, 0) as usize;
+            }
+        }
+
+        let ptr = if ok {
+            libc::mmap(
+                ptr::null_mut(),
+                capacity,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE,
+                fd,
+                0,
+            )
+        } else {
+            libc::MAP_FAILED
+        };
+
+        // the original mapping is only replaced once the copy exists, its contents are the same.
+        let ptr = if ptr != libc::MAP_FAILED {
+            let res = libc::mmap(
+                base.as_ptr() as *mut libc::c_void,
+                capacity,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_FIXED,
+                fd,
+                0,
+            );
+
+            // the copy is of no use if the original mapping can't share its pages.
+            if res == libc::MAP_FAILED {
+                libc::munmap(ptr, capacity);
+                libc::MAP_FAILED
+            } else {
+                ptr
+            }
+        } else {
+            ptr
+        };
+
+        // the mappings keep the file alive without the descriptor.
+        libc::close(fd);
+
+        if ptr == libc::MAP_FAILED {
+            ptr::null_mut()
+        } else {
+            ptr as *mut u8
+        }
+    }
+}
+
+/// Create a copy of a virtual memory mapping.
+///
+/// Copy-on-write copies of mappings are only supported on Linux, so this always fails.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn fork_mapping(
+    _base: NonNull<u8>,
+    _capacity: usize,
+    _used: &[(usize, usize)],
+) -> *mut u8 {
+    ptr::null_mut()
+}
+
+/// Release the physical memory backing the `len` bytes of a private memory mapping at `base`,
+/// and return `true` if this was possible.
+///
+/// The memory reads as zeroed afterwards.
+#[cfg(target_os = "linux")]
+pub(crate) fn decommit_pages(base: NonNull<u8>, len: usize) -> bool {
+    unsafe { libc::madvise(base.as_ptr() as *mut libc::c_void, len, libc::MADV_DONTNEED) == 0 }
+}
+
+/// Release the physical memory backing the `len` bytes of a private memory mapping at `base`,
+/// and return `true` if this was possible.
+///
+/// `MADV_DONTNEED` is merely a hint on most systems other than Linux, so the pages are replaced
+/// by fresh ones instead, which read as zeroed.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn decommit_pages(base: NonNull<u8>, len: usize) -> bool {
+    let ptr = unsafe {
+        libc::mmap(
+            base.as_ptr() as *mut libc::c_void,
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED,
+            -1,
+            0,
+        )
+    };
+
+    ptr != libc::MAP_FAILED
+}
+
+/// Release the physical memory backing the `len` bytes of a memory mapping at `base`, and
+/// return `true` if this was possible.
+///
+/// The pages are decommitted and committed again, which makes them read as zeroed.
+#[cfg(windows)]
+pub(crate) fn decommit_pages(base: NonNull<u8>, len: usize) -> bool {
+    use winapi::shared::basetsd::SIZE_T;
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_DECOMMIT, PAGE_READWRITE};
+
+    unsafe {
+        VirtualFree(base.as_ptr() as LPVOID, len as SIZE_T, MEM_DECOMMIT) != 0
+            && !VirtualAlloc(
+                base.as_ptr() as LPVOID,
+                len as SIZE_T,
+                MEM_COMMIT,
+                PAGE_READWRITE,
+            )
+            .is_null()
     }
+}
 
-    // TODO: insert
+/// Round `len` up to a multiple of the page size.
+fn round_to_pages(len: usize) -> usize {
+    let page_size = get_page_size();
 
-    // TODO: remove
+    len.div_ceil(page_size) * page_size
+}
 
-    // TODO: retain
+/// Extend a virtual memory mapping of size `capacity` in place to `new_capacity`.
+///
+/// Returns `false` if the mapping can't be extended without moving it.
+#[cfg(target_os = "linux")]
+pub(crate) fn grow_mapping(base: NonNull<u8>, capacity: usize, new_capacity: usize) -> bool {
+    let len = round_to_pages(capacity);
+    let new_len = round_to_pages(new_capacity);
+
+    if new_len <= len {
+        return true;
+    }
 
-    // TODO: dedup_by_key
+    let ptr = unsafe { libc::mremap(base.as_ptr() as *mut libc::c_void, len, new_len, 0) };
 
-    // TODO: dedup_by
+    ptr != libc::MAP_FAILED
+}
 
-    /// Push an element into the vector.
-    pub fn push(&mut self, elem: T) {
-        if self.slice.len == self.capacity {
-            let new_capacity = if self.capacity == 0 {
-                4
-            } else {
-                self.capacity * 2
-            };
+/// Extend a virtual memory mapping of size `capacity` in place to `new_capacity`.
+///
+/// Returns `false` if the mapping can't be extended without moving it.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn grow_mapping(base: NonNull<u8>, capacity: usize, new_capacity: usize) -> bool {
+    let len = round_to_pages(capacity);
+    let new_len = round_to_pages(new_capacity);
+
+    if new_len <= len {
+        return true;
+    }
 
-            self.reserve(new_capacity - self.capacity);
-        }
+    // map the pages right after the existing mapping, which is only possible if the address
+    // space there is unused (the address is just a hint).
+    let hint = unsafe { base.as_ptr().add(len) };
+    let ptr = unsafe {
+        libc::mmap(
+            hint as *mut libc::c_void,
+            new_len - len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_ANON | libc::MAP_PRIVATE,
+            -1,
+            0,
+        )
+    };
 
+    if ptr == libc::MAP_FAILED {
+        false
+    } else if ptr as *mut u8 != hint {
         unsafe {
-            ptr::write(self.slice.ptr.as_ptr().add(self.slice.len()), elem);
+            libc::munmap(ptr, new_len - len);
         }
 
-        self.slice.len = self.slice.len() + 1;
+        false
+    } else {
+        true
     }
+}
 
-    /// Remove the last element from the vector and return it, or `None` if the vector is empty.
-    pub fn pop(&mut self) -> Option<T> {
-        if self.is_empty() {
-            return None;
-        }
+/// Extend a virtual memory mapping of size `capacity` in place to `new_capacity`.
+///
+/// Mappings are only released as a whole on Windows, so they can only grow into the slack
+/// space of their last page.
+#[cfg(windows)]
+pub(crate) fn grow_mapping(_base: NonNull<u8>, capacity: usize, new_capacity: usize) -> bool {
+    round_to_pages(new_capacity) <= round_to_pages(capacity)
+}
 
-        unsafe {
-            self.slice.len -= 1;
-            Some(ptr::read(self.slice.ptr.as_ptr().add(self.slice.len)))
+/// Create a virtual memory mapping of size `capacity`, surrounded by guard pages.
+#[cfg(unix)]
+pub(crate) fn create_mapping_guarded(capacity: usize) -> *mut u8 {
+    let page_size = get_page_size();
+    let len = round_to_pages(capacity);
+    let total = len + 2 * page_size;
+
+    unsafe {
+        // reserve the whole range inaccessible, and make the arena itself accessible afterwards.
+        let base = libc::mmap(
+            ptr::null_mut(),
+            total,
+            libc::PROT_NONE,
+            libc::MAP_ANON | libc::MAP_PRIVATE,
+            -1,
+            0,
+        );
+
+        if base == libc::MAP_FAILED {
+            return ptr::null_mut();
         }
-    }
 
-    /// Move all elements of `other` into `self`, leaving `other` empty.
-    pub fn append(&mut self, other: &mut Self) {
-        let count = other.len();
-        self.reserve(count);
-        let len = self.len();
+        let head = (base as *mut u8).add(page_size);
 
-        unsafe {
-            ptr::copy_nonoverlapping(
-                other.slice.ptr.as_ptr(),
-                self.slice.ptr.as_ptr().add(len),
-                count);
+        if libc::mprotect(
+            head as *mut libc::c_void,
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+        ) != 0
+        {
+            libc::munmap(base, total);
+            return ptr::null_mut();
         }
 
-        other.slice.len = 0;
+        head
     }
+}
 
-    // TODO: drain
+/// Create a virtual memory mapping of size `capacity`, surrounded by guard pages.
+#[cfg(windows)]
+pub(crate) fn create_mapping_guarded(capacity: usize) -> *mut u8 {
+    use std::ptr;
+    use winapi::shared::basetsd::SIZE_T;
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::um::memoryapi::{VirtualAlloc, VirtualFree};
+    use winapi::um::winnt::{MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_NOACCESS, PAGE_READWRITE};
 
-    /// Clear the vector.
-    pub fn clear(&mut self) {
-        unsafe {
-            ptr::drop_in_place(&mut self.slice[..]);
+    let page_size = get_page_size();
+    let len = round_to_pages(capacity);
+    let total = len + 2 * page_size;
+
+    unsafe {
+        // reserve the whole range inaccessible, and only commit the arena itself.
+        let base = VirtualAlloc(ptr::null_mut(), total as SIZE_T, MEM_RESERVE, PAGE_NOACCESS);
+
+        if base.is_null() {
+            return ptr::null_mut();
         }
 
-        self.slice.len = 0;
-    }
+        let head = (base as *mut u8).add(page_size);
 
-    /// Return the number of elements in the vector.
-    pub fn len(&self) -> usize {
-        self.slice.len
-    }
+        if VirtualAlloc(head as LPVOID, len as SIZE_T, MEM_COMMIT, PAGE_READWRITE).is_null() {
+            VirtualFree(base, 0, MEM_RELEASE);
+            return ptr::null_mut();
+        }
 
-    /// Return `true` if the vector contains no elements.
-    pub fn is_empty(&self) -> bool {
-        self.slice.len == 0
+        head
     }
+}
 
-    /// Splits the vector into two at the given index.
-    ///
-    /// Retruns a newly allocated `Self`. `self` contains elements `[0, at)`, and the returned
-    /// `Self` contains elements `[at, len)`.
-    ///
-    /// The capacity of `self` remains unchanged.
-    pub fn split_off(&mut self, at: usize) -> Self
-    where
-        H: Clone,
-    {
-        let mut ret = Self::with_capacity(self.slice.handle.clone(), self.slice.len - at);
-        ret.slice.len = self.slice.len - at;
+/// Create a named shared memory object of size `capacity` and map it.
+#[cfg(unix)]
+pub(crate) fn create_mapping_shared(name: &str, capacity: usize) -> *mut u8 {
+    let name = match CString::new(name) {
+        Ok(name) => name,
+        Err(_) => return ptr::null_mut(),
+    };
 
-        unsafe {
-            ptr::copy_nonoverlapping(
-                self.slice.ptr.as_ptr().add(at),
-                ret.slice.ptr.as_ptr(),
-                ret.len());
+    unsafe {
+        // the object is created exclusively, so that its contents are known to be zeroed.
+        let fd = libc::shm_open(
+            name.as_ptr(),
+            libc::O_RDWR | libc::O_CREAT | libc::O_EXCL,
+            0o600,
+        );
+
+        if fd < 0 {
+            return ptr::null_mut();
         }
 
-        ret
+        let ptr = if libc::ftruncate(fd, capacity as libc::off_t) == 0 {
+            libc::mmap(
+                ptr::null_mut(),
+                capacity,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        } else {
+            libc::MAP_FAILED
+        };
+
+        // the mapping keeps the object alive without the descriptor.
+        libc::close(fd);
+
+        if ptr == libc::MAP_FAILED {
+            libc::shm_unlink(name.as_ptr());
+            ptr::null_mut()
+        } else {
+            ptr as *mut u8
+        }
     }
+}
 
-    /// Resize the vector to hold `len` elements, initialized to the return value of `f` if necessary.
-    pub fn resize_with<F>(&mut self, len: usize, mut f: F)
-    where
-        F: FnMut() -> T,
-    {
-        let old_len = self.slice.len;
+/// Create a named file mapping of size `capacity` and map it.
+///
+/// The mapping object is only kept alive (and accessible by name) while a handle to it is
+/// open, so the handle is stored in a header page preceding the arena.
+#[cfg(windows)]
+pub(crate) fn create_mapping_shared(name: &str, capacity: usize) -> *mut u8 {
+    use std::ptr;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::shared::winerror::ERROR_ALREADY_EXISTS;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+    use winapi::um::memoryapi::{CreateFileMappingW, MapViewOfFile, FILE_MAP_ALL_ACCESS};
+    use winapi::um::winnt::{HANDLE, PAGE_READWRITE};
+
+    let name = wide_name(name);
+    let page_size = get_page_size();
+    let total = (round_to_pages(capacity) + page_size) as u64;
 
-        if self.capacity < len {
-            self.reserve(len - old_len);
+    unsafe {
+        let handle = CreateFileMappingW(
+            INVALID_HANDLE_VALUE,
+            ptr::null_mut(),
+            PAGE_READWRITE,
+            (total >> 32) as DWORD,
+            total as DWORD,
+            name.as_ptr(),
+        );
+
+        if handle.is_null() {
+            return ptr::null_mut();
         }
 
-        for i in old_len..len.saturating_sub(1) {
-            unsafe { ptr::write(self.slice.ptr.as_ptr().add(i), f()) }
+        // the object is required to be fresh, so that its contents are known to be zeroed.
+        if GetLastError() == ERROR_ALREADY_EXISTS {
+            CloseHandle(handle);
+            return ptr::null_mut();
         }
 
-        if len > old_len {
-            unsafe {
-                ptr::write(self.slice.ptr.as_ptr().add(len - 1), f());
-            }
-        } else if len < old_len {
-            unsafe {
-                ptr::drop_in_place(&mut self.slice[len..old_len]);
-            }
+        let view = MapViewOfFile(handle, FILE_MAP_ALL_ACCESS, 0, 0, 0) as *mut u8;
+
+        if view.is_null() {
+            CloseHandle(handle);
+            return ptr::null_mut();
         }
 
-        self.slice.len = len;
+        ptr::write(view as *mut HANDLE, handle);
+
+        view.add(page_size)
     }
+}
 
-    /// Resize the vector to hold `len` elements, initialized to `value` if necessary.
-    pub fn resize(&mut self, len: usize, value: T)
-    where
-        T: Clone,
-    {
-        let old_len = self.slice.len;
+/// Encode `name` as a nul-terminated UTF-16 string.
+#[cfg(windows)]
+fn wide_name(name: &str) -> Vec<u16> {
+    name.encode_utf16().chain(Some(0)).collect()
+}
 
-        if self.capacity < len {
-            self.reserve(len - old_len);
-        }
+/// Map the shared memory object of the given name, returning the mapping and its size.
+#[cfg(unix)]
+pub(crate) fn open_mapping_shared(name: &str) -> Option<(NonNull<u8>, usize)> {
+    let name = CString::new(name).ok()?;
 
-        for i in old_len..len.saturating_sub(1) {
-            unsafe { ptr::write(self.slice.ptr.as_ptr().add(i), value.clone()) }
-        }
+    unsafe {
+        let fd = libc::shm_open(name.as_ptr(), libc::O_RDWR, 0);
 
-        if len > old_len {
-            unsafe {
-                ptr::write(self.slice.ptr.as_ptr().add(len - 1), value);
-            }
-        } else if len < old_len {
-            unsafe {
-                ptr::drop_in_place(&mut self.slice[len..old_len]);
-            }
+        if fd < 0 {
+            return None;
         }
 
-        self.slice.len = len;
-    }
+        let mut stat: libc::stat = mem::zeroed();
+        let len = if libc::fstat(fd, &mut stat) == 0 {
+            stat.st_size as usize
+        } else {
+            0
+        };
 
-    /// Clone and append all elements in a slice to the vector.
-    pub fn extend_from_slice(&mut self, other: &[T])
-        where
-            T: Clone
-    {
-        for e in other {
-            self.push(e.clone());
+        let ptr = if len > 0 {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        } else {
+            libc::MAP_FAILED
+        };
+
+        libc::close(fd);
+
+        if ptr == libc::MAP_FAILED {
+            None
+        } else {
+            NonNull::new(ptr as *mut u8).map(|ptr| (ptr, len))
         }
     }
+}
 
-    // TODO: dedup
+/// Map the file mapping of the given name, returning the mapping (past the header page) and
+/// its size.
+#[cfg(windows)]
+pub(crate) fn open_mapping_shared(name: &str) -> Option<(NonNull<u8>, usize)> {
+    use winapi::shared::minwindef::FALSE;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::memoryapi::{
+        MapViewOfFile, OpenFileMappingW, VirtualQuery, FILE_MAP_ALL_ACCESS,
+    };
 
-    // TODO: remove_item
+    let name = wide_name(name);
+    let page_size = get_page_size();
 
-    // TODO: splice
+    unsafe {
+        let handle = OpenFileMappingW(FILE_MAP_ALL_ACCESS, FALSE, name.as_ptr());
 
-    // TODO: drain_filter
-}
+        if handle.is_null() {
+            return None;
+        }
 
-impl<T: Clone, H: AllocHandle + Clone> Clone for SliceVec<T, H> {
-    fn clone(&self) -> Self {
-        let mut vec: SliceVec<T, H> =
-            SliceVec::with_capacity(self.slice.handle.clone(), self.capacity);
+        // the view keeps the mapping object alive without the handle.
+        let view = MapViewOfFile(handle, FILE_MAP_ALL_ACCESS, 0, 0, 0) as *mut u8;
+        CloseHandle(handle);
 
-        for i in 0..self.slice.len() {
-            unsafe {
-                ptr::write(
-                    vec.slice.ptr.as_ptr().add(i),
-                    (*self.slice.ptr.as_ptr().add(i)).clone(),
-                );
-            }
+        if view.is_null() {
+            return None;
         }
 
-        vec.slice.len = self.slice.len();
+        let mut info = mem::zeroed();
+        VirtualQuery(view as *const _, &mut info, mem::size_of_val(&info));
 
-        vec
+        NonNull::new(view.add(page_size)).map(|ptr| (ptr, info.RegionSize - page_size))
     }
 }
 
-impl<T: fmt::Debug, H> fmt::Debug for SliceVec<T, H> {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.slice.fmt(fmt)
-    }
+/// Request `capacity` zeroed bytes from the system allocator.
+///
+/// Just like fresh memory mappings, the memory is zeroed, so arenas can skip zeroing memory that
+/// hasn't been handed out yet.
+pub(crate) fn create_mapping_alloc(capacity: usize) -> *mut u8 {
+    unsafe { alloc_zeroed(Layout::from_size_align_unchecked(capacity, get_page_size())) }
 }
 
-impl<T, H> Deref for SliceVec<T, H> {
-    type Target = [T];
+/// Destroy a virtual memory mapping.
+#[cfg(unix)]
+pub(crate) fn destroy_mapping(base: NonNull<u8>, capacity: usize) {
+    let res = unsafe { libc::munmap(base.as_ptr() as *mut libc::c_void, capacity) };
 
-    fn deref(&self) -> &[T] {
-        self.slice.deref()
-    }
+    // TODO: Do something on error
+    debug_assert_eq!(res, 0);
 }
 
-impl<T, H> DerefMut for SliceVec<T, H> {
-    fn deref_mut(&mut self) -> &mut [T] {
-        self.slice.deref_mut()
-    }
+/// Destroy a virtual memory mapping.
+#[cfg(windows)]
+pub(crate) fn destroy_mapping(base: NonNull<u8>, capacity: usize) {
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::um::memoryapi::VirtualFree;
+    use winapi::um::winnt::MEM_RELEASE;
+
+    let res = unsafe { VirtualFree(base.as_ptr() as LPVOID, 0, MEM_RELEASE) };
+
+    // TODO: Do something on error
+    debug_assert_ne!(res, 0);
+}
+
+/// Destroy a virtual memory mapping surrounded by guard pages.
+#[cfg(unix)]
+pub(crate) fn destroy_mapping_guarded(base: NonNull<u8>, capacity: usize) {
+    let page_size = get_page_size();
+    let total = round_to_pages(capacity) + 2 * page_size;
+    let res = unsafe { libc::munmap(base.as_ptr().sub(page_size) as *mut libc::c_void, total) };
+
+    // TODO: Do something on error
+    debug_assert_eq!(res, 0);
+}
+
+/// Destroy a virtual memory mapping surrounded by guard pages.
+#[cfg(windows)]
+pub(crate) fn destroy_mapping_guarded(base: NonNull<u8>, _capacity: usize) {
+    use winapi::shared::minwindef::LPVOID;
+    use winapi::um::memoryapi::VirtualFree;
+    use winapi::um::winnt::MEM_RELEASE;
+
+    let page_size = get_page_size();
+    let res = unsafe { VirtualFree(base.as_ptr().sub(page_size) as LPVOID, 0, MEM_RELEASE) };
+
+    // TODO: Do something on error
+    debug_assert_ne!(res, 0);
 }
 
-impl<T: Eq, H> Eq for SliceVec<T, H> { }
+/// Create a shared mapping of size `capacity` of an anonymous file that can be sealed.
+///
+/// The file descriptor is kept open for sealing, and stored in a header page preceding the
+/// arena, which is part of the file as well.
+#[cfg(target_os = "linux")]
+pub(crate) fn create_mapping_sealable(capacity: usize) -> *mut u8 {
+    let page_size = get_page_size();
+    let len = page_size + capacity;
+
+    unsafe {
+        let fd = libc::memfd_create(
+            b"arenavec\0".as_ptr() as *const libc::c_char,
+            libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+        );
+
+        if fd < 0 {
+            return ptr::null_mut();
+        }
+
+        let ptr = if libc::ftruncate(fd, len as libc::off_t) == 0 {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        } else {
+            libc::MAP_FAILED
+        };
+
+        if ptr == libc::MAP_FAILED {
+            libc::close(fd);
+            return ptr::null_mut();
+        }
+
+        *(ptr as *mut libc::c_int) = fd;
 
-impl<T: PartialEq, H> PartialEq for SliceVec<T, H> {
-    fn eq(&self, other: &Self) -> bool {
-        self.deref().eq(other.deref())
+        (ptr as *mut u8).add(page_size)
     }
 }
 
-impl<T: PartialOrd, H> PartialOrd for SliceVec<T, H> {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        self.deref().partial_cmp(other.deref())
-    }
+/// Create a shared mapping of an anonymous file that can be sealed.
+///
+/// File seals are only supported on Linux, so this always fails.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn create_mapping_sealable(_capacity: usize) -> *mut u8 {
+    ptr::null_mut()
 }
 
-impl<'a, T: 'a, H> IntoIterator for &'a SliceVec<T, H> {
-    type Item = &'a T;
-    type IntoIter = slice::Iter<'a, T>;
+/// Make a mapping created using `create_mapping_sealable` read-only, and seal the file
+/// against modification, returning its file descriptor.
+#[cfg(target_os = "linux")]
+pub(crate) fn seal_mapping(base: NonNull<u8>, capacity: usize) -> Option<libc::c_int> {
+    let page_size = get_page_size();
+    let len = page_size + capacity;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+    unsafe {
+        let header = base.as_ptr().sub(page_size);
+        let fd = *(header as *const libc::c_int);
+
+        // writes can only be sealed once there are no writable shared mappings left, so the
+        // mapping is replaced by one (of the same contents) through a read-only descriptor.
+        let path = CString::new(format!("/proc/self/fd/{}", fd)).ok()?;
+        let read_only = libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC);
+
+        if read_only < 0 {
+            return None;
+        }
+
+        let ptr = libc::mmap(
+            header as *mut libc::c_void,
+            len,
+            libc::PROT_READ,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            read_only,
+            0,
+        );
+
+        libc::close(read_only);
+
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+
+        let seals =
+            libc::F_SEAL_WRITE | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_SEAL;
+
+        if libc::fcntl(fd, libc::F_ADD_SEALS, seals) == 0 {
+            Some(fd)
+        } else {
+            None
+        }
     }
 }
 
-impl<'a, T: 'a, H> IntoIterator for &'a mut SliceVec<T, H> {
-    type Item = &'a mut T;
-    type IntoIter = slice::IterMut<'a, T>;
+/// Destroy a mapping created using `create_mapping_sealable`, closing its file descriptor.
+#[cfg(target_os = "linux")]
+pub(crate) fn destroy_mapping_sealable(base: NonNull<u8>, capacity: usize) {
+    let page_size = get_page_size();
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
+    unsafe {
+        let header = base.as_ptr().sub(page_size);
+
+        libc::close(*(header as *const libc::c_int));
+        destroy_mapping(NonNull::new_unchecked(header), page_size + capacity);
     }
 }
 
-/* impl<T, H> FromIterator<T> for SliceVec<T, H> {
-    fn from_iter<I>(iter: I) -> Self
-    where
-        I: IntoIterator<Item=T>
-    {
-        let iter = iter.into_iter();
-        let (min, max) = iter.size_hint();
-        let cap = if let Some(m) = max { m } else { min };
+/// Destroy a mapping created using `create_mapping_sealable`.
+///
+/// Such mappings can't be created on this system.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn destroy_mapping_sealable(_base: NonNull<u8>, _capacity: usize) {
+    unreachable!("sealable mappings are only supported on Linux")
+}
 
-        let mut res = SliceVec::new(cap);
+/// Map the arena memory in the sealed file with the given descriptor read-only, returning the
+/// mapping (past the header page) and its size.
+///
+/// Fails if the file isn't sealed against writes.
+#[cfg(target_os = "linux")]
+pub(crate) fn open_mapping_sealed(fd: libc::c_int) -> Option<(NonNull<u8>, usize)> {
+    let page_size = get_page_size();
 
-        for e in iter {
-            res.push(e);
+    unsafe {
+        let seals = libc::fcntl(fd, libc::F_GET_SEALS);
+
+        if seals < 0 || seals & libc::F_SEAL_WRITE == 0 {
+            return None;
         }
 
-        res
-    }
-} */
+        let mut stat: libc::stat = mem::zeroed();
 
-#[cfg(feature = "serde")]
-impl<T, H> Serialize for SliceVec<T, H>
-where
-    T: Serialize,
-{
-    #[inline]
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        self.slice.serialize(serializer)
+        if libc::fstat(fd, &mut stat) != 0 || (stat.st_size as usize) <= page_size {
+            return None;
+        }
+
+        let len = stat.st_size as usize - page_size;
+        let ptr = libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_SHARED,
+            fd,
+            page_size as libc::off_t,
+        );
+
+        if ptr == libc::MAP_FAILED {
+            None
+        } else {
+            NonNull::new(ptr as *mut u8).map(|ptr| (ptr, len))
+        }
     }
 }
 
-/// Get the page size of the system we are running on.
+/// Unmap a shared memory object mapped using `open_mapping_shared`.
 #[cfg(unix)]
-pub(crate) fn get_page_size() -> usize {
-    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+pub(crate) fn close_mapping_shared(base: NonNull<u8>, len: usize) {
+    destroy_mapping(base, len);
 }
 
-/// Get the page size of the system we are running on.
+/// Unmap a file mapping mapped using `open_mapping_shared`.
 #[cfg(windows)]
-pub(crate) fn get_page_size() -> usize {
-    use std::mem;
-    use winapi::um::sysinfoapi::GetSystemInfo;
+pub(crate) fn close_mapping_shared(base: NonNull<u8>, _len: usize) {
+    use winapi::um::memoryapi::UnmapViewOfFile;
 
-    unsafe {
-        let mut info = mem::zeroed();
-        GetSystemInfo(&mut info);
+    let page_size = get_page_size();
+    let res = unsafe { UnmapViewOfFile(base.as_ptr().sub(page_size) as *const _) };
 
-        info.dwPageSize as usize
-    }
+    // TODO: Do something on error
+    debug_assert_ne!(res, 0);
 }
 
-/// Create a virtual memory mapping of size `capacity`.
+/// Unmap and remove a named shared memory object.
 #[cfg(unix)]
-pub(crate) fn create_mapping(capacity: usize) -> *mut u8 {
-    let ptr = unsafe {
-        libc::mmap(
-            ptr::null_mut(),
-            capacity,
-            libc::PROT_READ | libc::PROT_WRITE,
-            libc::MAP_ANON | libc::MAP_PRIVATE,
-            -1,
-            0,
-        )
-    };
+pub(crate) fn destroy_mapping_shared(name: &str, base: NonNull<u8>, capacity: usize) {
+    destroy_mapping(base, capacity);
 
-    ptr as *mut u8
+    if let Ok(name) = CString::new(name) {
+        let res = unsafe { libc::shm_unlink(name.as_ptr()) };
+
+        // TODO: Do something on error
+        debug_assert_eq!(res, 0);
+    }
 }
 
-/// Create a virtual memory mapping of size `capacity`.
+/// Unmap a named file mapping and close the handle keeping it alive.
 #[cfg(windows)]
-pub(crate) fn create_mapping(capacity: usize) -> *mut u8 {
-    use std::ptr;
-    use winapi::shared::basetsd::SIZE_T;
-    use winapi::shared::minwindef::LPVOID;
-    use winapi::um::memoryapi::VirtualAlloc;
-    use winapi::um::winnt::{MEM_COMMIT, MEM_RESERVE, PAGE_READWRITE};
+pub(crate) fn destroy_mapping_shared(_name: &str, base: NonNull<u8>, capacity: usize) {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::winnt::HANDLE;
 
-    let lpAddress: LPVOID = ptr::null_mut();
     let page_size = get_page_size();
-    let len = if capacity % page_size == 0 {
-        capacity
-    } else {
-        capacity + page_size - (capacity % page_size)
-    };
-    let flAllocationType = MEM_COMMIT | MEM_RESERVE;
-    let flProtect = PAGE_READWRITE;
 
-    let r = unsafe { VirtualAlloc(lpAddress, len as SIZE_T, flAllocationType, flProtect) };
+    unsafe {
+        let handle = ptr::read(base.as_ptr().sub(page_size) as *const HANDLE);
 
-    r as *mut u8
+        close_mapping_shared(base, capacity);
+        CloseHandle(handle);
+    }
 }
 
-/// Request `capacity` bytes from the system allocator.
-pub(crate) fn create_mapping_alloc(capacity: usize) -> *mut u8 {
-    unsafe { alloc(Layout::from_size_align_unchecked(capacity, get_page_size())) }
+/// Return memory to the system allocator.
+pub(crate) fn destroy_mapping_alloc(base: NonNull<u8>, capacity: usize) {
+    unsafe {
+        let layout = Layout::from_size_align_unchecked(capacity, get_page_size());
+        dealloc(base.as_ptr(), layout);
+    }
 }
 
-/// Destroy a virtual memory mapping.
+/// Make the `len` bytes of arena memory at `base` read-only, or writable again.
+///
+/// `base` has to be page-aligned, and `len` a multiple of the page size.
 #[cfg(unix)]
-pub(crate) fn destroy_mapping(base: NonNull<u8>, capacity: usize) {
-    let res = unsafe { libc::munmap(base.as_ptr() as *mut libc::c_void, capacity) };
+pub(crate) fn protect_pages(base: NonNull<u8>, len: usize, read_only: bool) -> bool {
+    let prot = if read_only {
+        libc::PROT_READ
+    } else {
+        libc::PROT_READ | libc::PROT_WRITE
+    };
 
-    // TODO: Do something on error
-    debug_assert_eq!(res, 0);
+    unsafe { libc::mprotect(base.as_ptr() as *mut libc::c_void, len, prot) == 0 }
 }
 
-/// Destroy a virtual memory mapping.
+/// Make the `len` bytes of arena memory at `base` read-only, or writable again.
+///
+/// `base` has to be page-aligned, and `len` a multiple of the page size.
 #[cfg(windows)]
-pub(crate) fn destroy_mapping(base: NonNull<u8>, capacity: usize) {
+pub(crate) fn protect_pages(base: NonNull<u8>, len: usize, read_only: bool) -> bool {
+    use winapi::shared::basetsd::SIZE_T;
     use winapi::shared::minwindef::LPVOID;
-    use winapi::um::memoryapi::VirtualFree;
-    use winapi::um::winnt::MEM_RELEASE;
+    use winapi::um::memoryapi::VirtualProtect;
+    use winapi::um::winnt::{PAGE_READONLY, PAGE_READWRITE};
 
-    let res = unsafe { VirtualFree(base.as_ptr() as LPVOID, 0, MEM_RELEASE) };
+    let prot = if read_only {
+        PAGE_READONLY
+    } else {
+        PAGE_READWRITE
+    };
+    let mut old = 0;
 
-    // TODO: Do something on error
-    debug_assert_ne!(res, 0);
+    unsafe { VirtualProtect(base.as_ptr() as LPVOID, len as SIZE_T, prot, &mut old) != 0 }
 }
 
-/// Return memory to the system allocator.
-pub(crate) fn destroy_mapping_alloc(base: NonNull<u8>, capacity: usize) {
-    unsafe {
-        let layout = Layout::from_size_align_unchecked(capacity, get_page_size());
-        dealloc(base.as_ptr(), layout);
+/// Return the length of the page-aligned prefix of an arena of capacity `cap` that covers the
+/// first `pos` bytes, or as much of them as possible without exceeding the capacity.
+pub(crate) fn protected_len(pos: usize, cap: usize) -> usize {
+    let page_size = get_page_size();
+
+    cmp::min(round_to_pages(pos), cap / page_size * page_size)
+}
+
+/// Return the capacity a growable sequence of capacity `capacity` is grown to in order to hold
+/// `size` elements, by doubling it (starting from 4 elements), or `None` if it overflows.
+pub(crate) fn grow_capacity(capacity: usize, size: usize) -> Option<usize> {
+    let mut new_capacity = if capacity > 0 { capacity } else { 4 };
+
+    while new_capacity < size {
+        new_capacity = new_capacity.checked_mul(2)?;
     }
+
+    Some(new_capacity)
 }
 
-pub(crate) fn allocate_inner<T>(
+pub(crate) fn try_allocate_inner<T>(
     head: NonNull<u8>,
     position: &Cell<usize>,
     cap: usize,
-    count: usize) -> NonNull<T>
-{
+    count: usize,
+) -> Result<NonNull<T>, ArenaError> {
+    // zero-sized objects don't take up any arena space, so they are placed at a dangling (but
+    // well-aligned) pointer instead.
+    if mem::size_of::<T>() == 0 {
+        return Ok(NonNull::dangling());
+    }
+
     let layout = Layout::new::<T>();
-    let mask = layout.align() - 1;
     let pos = position.get();
 
-    debug_assert!(layout.align() >= (pos & mask));
+    // the padding is computed from the address rather than the offset, so that alignments
+    // beyond that of the arena memory itself (e.g. page-aligned types) are honored as well.
+    let addr = (head.as_ptr() as usize).wrapping_add(pos);
+    let skip = addr.wrapping_neg() & (layout.align() - 1);
 
-    // let align = Ord::max(layout.align(), 64);
-    let mut skip = 64 - (pos & mask);
+    let size = layout
+        .size()
+        .checked_mul(count)
+        .ok_or(ArenaError::CapacityOverflow)?;
+    let additional = size.saturating_add(skip);
 
-    if skip == layout.align() {
-        skip = 0;
+    if pos.checked_add(additional).is_none_or(|end| end > cap) {
+        return Err(capacity_exceeded(size, pos, cap));
     }
 
-    let additional = skip + layout.size() * count;
-
-    assert!(
-        pos + additional <= cap,
-        "arena overflow: {} > {}",
-        pos + additional,
-        cap
-    );
-
     position.set(pos + additional);
 
     let ret = unsafe { head.as_ptr().add(pos + skip) as *mut T };
@@ -725,25 +3028,273 @@ pub(crate) fn allocate_inner<T>(
     assert!((ret as usize) >= head.as_ptr() as usize);
     assert!((ret as usize) < (head.as_ptr() as usize + cap));
 
-    unsafe { NonNull::new_unchecked(ret) }
+    unsafe { Ok(NonNull::new_unchecked(ret)) }
 }
 
-pub(crate) fn allocate_or_extend_inner<T>(
+pub(crate) fn try_allocate_or_extend_inner<T>(
     head: NonNull<u8>,
     position: &Cell<usize>,
     cap: usize,
     ptr: NonNull<T>,
     old_count: usize,
-    count: usize) -> NonNull<T>
-{
+    count: usize,
+) -> Result<NonNull<T>, ArenaError> {
+    if mem::size_of::<T>() == 0 {
+        return Ok(ptr);
+    }
+
     let pos = position.get();
     let next = unsafe { head.as_ptr().add(pos) };
     let end = unsafe { ptr.as_ptr().add(old_count) };
     if next == end as *mut u8 {
-        position.set(pos + (count - old_count) * mem::size_of::<T>());
+        // if the sequence can't be extended in place, there is no room elsewhere either.
+        let additional = count
+            .saturating_sub(old_count)
+            .checked_mul(mem::size_of::<T>())
+            .ok_or(ArenaError::CapacityOverflow)?;
+        let new_pos = pos
+            .checked_add(additional)
+            .filter(|&new_pos| new_pos <= cap)
+            .ok_or_else(|| capacity_exceeded(additional, pos, cap))?;
+
+        position.set(new_pos);
+
+        Ok(ptr)
+    } else {
+        try_allocate_inner(head, position, cap, count)
+    }
+}
+
+/// Return the offset of the allocation at `ptr` of `size` bytes, if it is the last allocation in
+/// the arena.
+pub(crate) fn last_allocation_offset(
+    head: NonNull<u8>,
+    pos: usize,
+    ptr: NonNull<u8>,
+    size: usize,
+) -> Option<usize> {
+    // `ptr` need not point into the memory starting at `head`, in which case the offset wraps
+    // around and can't match the position.
+    let offset = (ptr.as_ptr() as usize).wrapping_sub(head.as_ptr() as usize);
+
+    if size > 0 && pos.checked_sub(size) == Some(offset) {
+        Some(offset)
+    } else {
+        None
+    }
+}
+
+/// Run the allocation `f`, passing it the arena capacity as limited by `quota`.
+///
+/// If the allocation fails because of the hard limit, `ArenaError::QuotaExceeded` is returned,
+/// and if it crosses the soft limit, the callback is invoked.
+pub(crate) fn with_quota<R, F>(
+    quota: &RefCell<Quota>,
+    position: &Cell<usize>,
+    cap: usize,
+    f: F,
+) -> Result<R, ArenaError>
+where
+    F: FnOnce(usize) -> Result<R, ArenaError>,
+{
+    let (limit, soft) = {
+        let quota = quota.borrow();
+
+        (
+            quota.hard.map_or(cap, |hard| cmp::min(hard, cap)),
+            quota.soft,
+        )
+    };
+    let old_pos = position.get();
+
+    match f(limit) {
+        Err(ArenaError::CapacityExceeded {
+            requested,
+            remaining,
+        }) if limit < cap => Err(ArenaError::QuotaExceeded {
+            requested,
+            remaining,
+        }),
+        Ok(res) => {
+            let pos = position.get();
+
+            if soft.is_some_and(|soft| old_pos <= soft && pos > soft) {
+                // the quota is not borrowed while running the callback, so it can be replaced.
+                let callback = quota.borrow().on_soft.clone();
+
+                if let Some(callback) = callback {
+                    callback(pos);
+                }
+            }
+
+            Ok(res)
+        }
+        res => res,
+    }
+}
 
-        ptr
+/// Allocate zeroed memory from the arena, only zeroing the part that may have been written to.
+///
+/// `dirty` is the offset up to which the arena space may have been written to since its
+/// creation (not accounting for the current position).
+pub(crate) fn try_allocate_zeroed_inner<T>(
+    head: NonNull<u8>,
+    position: &Cell<usize>,
+    dirty: &Cell<usize>,
+    cap: usize,
+    count: usize,
+) -> Result<NonNull<T>, ArenaError> {
+    let ptr: NonNull<T> = try_allocate_inner(head, position, cap, count)?;
+
+    if mem::size_of::<T>() == 0 {
+        return Ok(ptr);
+    }
+
+    let start = ptr.as_ptr() as usize - head.as_ptr() as usize;
+    let end = cmp::min(start + count * mem::size_of::<T>(), dirty.get());
+
+    if start < end {
+        unsafe {
+            ptr::write_bytes(ptr.as_ptr() as *mut u8, 0, end - start);
+        }
+    }
+
+    Ok(ptr)
+}
+
+/// Move the arena position back to `pos`, keeping track of the memory written to so far.
+///
+/// With the `sanitize` feature, the released memory is overwritten with `POISON`.
+pub(crate) fn rewind_inner(
+    head: NonNull<u8>,
+    position: &Cell<usize>,
+    dirty: &Cell<usize>,
+    pos: usize,
+) {
+    let old_pos = position.get();
+
+    dirty.set(cmp::max(dirty.get(), old_pos));
+    position.set(pos);
+
+    if pos < old_pos {
+        unsafe {
+            poison(
+                NonNull::new_unchecked(head.as_ptr().add(pos)),
+                old_pos - pos,
+            )
+        };
+    }
+}
+
+/// Overwrite the `len` bytes of released arena memory at `ptr` with `POISON` if the `sanitize`
+/// feature is enabled, and do nothing otherwise.
+///
+/// # Safety
+///
+/// `ptr` has to be valid for writes of `len` bytes.
+#[cfg_attr(not(feature = "sanitize"), allow(unused_variables))]
+pub(crate) unsafe fn poison(ptr: NonNull<u8>, len: usize) {
+    #[cfg(feature = "sanitize")]
+    ptr::write_bytes(ptr.as_ptr(), POISON, len);
+}
+
+/// Release the physical memory backing the memory written to in an arena that has been
+/// rewound to its start, if the backing supports it.
+pub(crate) fn decommit_inner(backing: &ArenaBacking, head: NonNull<u8>, dirty: &Cell<usize>) {
+    let len = dirty.get();
+
+    // the memory reads as zeroed afterwards, so it doesn't need to be zeroed on allocation.
+    if len > 0 && backing.decommit(head, len) {
+        dirty.set(0);
+    }
+}
+
+/// Allocate memory for `layout` from the back end of the arena, which grows downwards from
+/// `back` towards the front end at `pos`, returning `None` if the arena is exhausted.
+pub(crate) fn allocate_back_inner(
+    head: NonNull<u8>,
+    back: &Cell<usize>,
+    pos: usize,
+    layout: Layout,
+) -> Option<NonNull<u8>> {
+    let addr = (head.as_ptr() as usize)
+        .checked_add(back.get())?
+        .checked_sub(layout.size())?;
+    let addr = addr & !(layout.align() - 1);
+    let offset = addr.checked_sub(head.as_ptr() as usize)?;
+
+    if offset < pos {
+        return None;
+    }
+
+    back.set(offset);
+
+    NonNull::new(addr as *mut u8)
+}
+
+/// Allocate memory for `layout` from the arena, returning `None` if the arena is exhausted.
+pub(crate) fn allocate_layout_inner(
+    head: NonNull<u8>,
+    position: &Cell<usize>,
+    cap: usize,
+    layout: Layout,
+) -> Option<NonNull<u8>> {
+    let pos = position.get();
+    let addr = head.as_ptr() as usize + pos;
+    let skip = addr.wrapping_neg() & (layout.align() - 1);
+    let end = pos.checked_add(skip)?.checked_add(layout.size())?;
+
+    if end > cap {
+        return None;
+    }
+
+    position.set(end);
+
+    NonNull::new(unsafe { head.as_ptr().add(pos + skip) })
+}
+
+/// Resize the allocation at `ptr` of `old_size` bytes to fit `layout`.
+///
+/// The allocation is resized in place if it is suitably aligned and either the last allocation
+/// in the arena or being shrunk, and moved otherwise. `None` is returned if the arena is
+/// exhausted.
+pub(crate) fn allocate_or_extend_layout_inner(
+    head: NonNull<u8>,
+    position: &Cell<usize>,
+    cap: usize,
+    ptr: NonNull<u8>,
+    old_size: usize,
+    layout: Layout,
+) -> Option<NonNull<u8>> {
+    let pos = position.get();
+    // `ptr` need not point into the memory starting at `head`, in which case the offset is
+    // meaningless, but doesn't match the position either.
+    let offset = (ptr.as_ptr() as usize).wrapping_sub(head.as_ptr() as usize);
+    let aligned = (ptr.as_ptr() as usize) & (layout.align() - 1) == 0;
+
+    if offset.checked_add(old_size) == Some(pos) && aligned {
+        let end = offset.checked_add(layout.size())?;
+
+        if end > cap {
+            return None;
+        }
+
+        position.set(end);
+
+        Some(ptr)
+    } else if aligned && layout.size() <= old_size {
+        Some(ptr)
     } else {
-        allocate_inner(head, position, cap, count)
+        let new_ptr = allocate_layout_inner(head, position, cap, layout)?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(
+                ptr.as_ptr(),
+                new_ptr.as_ptr(),
+                cmp::min(old_size, layout.size()),
+            );
+        }
+
+        Some(new_ptr)
     }
 }
@@ -0,0 +1,135 @@
+//! This module provides compressed references into arena memory.
+//!
+//! Pointer-heavy structures such as graphs or syntax trees allocated in an arena can refer to
+//! their nodes using `ArenaPtr32`s, which store a 32-bit offset from the start of the arena
+//! memory instead of a full pointer. This halves the size of the references on 64-bit systems,
+//! and keeps the structures valid if the arena memory is mapped at a different address. The
+//! offsets are resolved using a handle to the arena.
+use crate::common::AllocHandle;
+use crate::shm::ArenaOffset;
+
+use std::cmp;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+/// A handle to an arena whose memory is contiguous, so that objects in it can be referred to
+/// by their offset from its start.
+pub trait ArenaBase: AllocHandle {
+    /// Return a pointer to the start of the arena memory.
+    fn base(&self) -> NonNull<u8>;
+}
+
+/// A reference to an object of type `T`, represented by its offset from the start of the arena
+/// memory as a 32-bit integer.
+///
+/// Only objects within the first 4 GiB of an arena can be referred to.
+#[repr(transparent)]
+pub struct ArenaPtr32<T> {
+    offset: u32,
+    marker: PhantomData<*const T>,
+}
+
+impl<T> ArenaPtr32<T> {
+    /// Create a reference from the offset of an object in bytes.
+    pub fn from_offset(offset: u32) -> Self {
+        ArenaPtr32 {
+            offset,
+            marker: PhantomData,
+        }
+    }
+
+    /// Create a reference to the object at `ptr` in the arena of `handle`.
+    ///
+    /// Returns `None` if `ptr` lies before the start of the arena, or too far after it.
+    pub fn new<H: ArenaBase>(handle: &H, ptr: *const T) -> Option<Self> {
+        let offset = (ptr as usize).checked_sub(handle.base().as_ptr() as usize)?;
+
+        u32::try_from(offset).ok().map(Self::from_offset)
+    }
+
+    /// Return the offset of the object in bytes.
+    pub fn offset(self) -> u32 {
+        self.offset
+    }
+
+    /// Turn the reference into a pointer into the arena of `handle`.
+    pub fn to_ptr<H: ArenaBase>(self, handle: &H) -> NonNull<T> {
+        unsafe {
+            NonNull::new_unchecked(handle.base().as_ptr().add(self.offset as usize) as *mut T)
+        }
+    }
+
+    /// Resolve the reference into a reference to the object it refers to.
+    ///
+    /// # Safety
+    ///
+    /// The reference has to refer to an initialized object of type `T` in the arena of
+    /// `handle`, which mustn't be modified while the returned reference is live.
+    pub unsafe fn get<H: ArenaBase>(self, handle: &H) -> &T {
+        &*self.to_ptr(handle).as_ptr()
+    }
+
+    /// Resolve the reference into a mutable reference to the object it refers to.
+    ///
+    /// # Safety
+    ///
+    /// The reference has to refer to an initialized object of type `T` in the arena of
+    /// `handle`, which mustn't be accessed otherwise while the returned reference is live.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_mut<H: ArenaBase>(self, handle: &H) -> &mut T {
+        &mut *self.to_ptr(handle).as_ptr()
+    }
+}
+
+impl<T> From<ArenaPtr32<T>> for ArenaOffset<T> {
+    fn from(ptr: ArenaPtr32<T>) -> Self {
+        ArenaOffset::from_offset(ptr.offset as usize)
+    }
+}
+
+impl<T> Clone for ArenaPtr32<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaPtr32<T> {}
+
+impl<T> fmt::Debug for ArenaPtr32<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("ArenaPtr32").field(&self.offset).finish()
+    }
+}
+
+impl<T> Eq for ArenaPtr32<T> {}
+
+impl<T> PartialEq for ArenaPtr32<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset
+    }
+}
+
+impl<T> Ord for ArenaPtr32<T> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.offset.cmp(&other.offset)
+    }
+}
+
+impl<T> PartialOrd for ArenaPtr32<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Hash for ArenaPtr32<T> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.offset.hash(state);
+    }
+}
+
+// a reference is just a number, the object it refers to is only accessed once it is resolved.
+unsafe impl<T> Send for ArenaPtr32<T> {}
+unsafe impl<T> Sync for ArenaPtr32<T> {}
@@ -0,0 +1,111 @@
+//! This module contains a clone-on-write smart pointer for arena-allocated objects.
+use crate::boxed::ArenaBox;
+use crate::common::AllocHandle;
+
+use std::cmp;
+use std::fmt;
+use std::ops::Deref;
+
+/// A clone-on-write pointer whose owned variant lives in an arena.
+///
+/// This is the arena analogue of `std::borrow::Cow`: APIs can accept either a borrowed value
+/// (such as a shared configuration) or an arena-owned override uniformly, and only clone the
+/// borrowed value into the arena once mutation is required.
+pub enum ArenaCow<'a, T, H> {
+    /// A borrowed object.
+    Borrowed(&'a T),
+    /// An arena-owned object.
+    Owned(ArenaBox<T, H>),
+}
+
+impl<'a, T, H> ArenaCow<'a, T, H> {
+    /// Return `true` if the data is borrowed.
+    pub fn is_borrowed(&self) -> bool {
+        match *self {
+            ArenaCow::Borrowed(_) => true,
+            ArenaCow::Owned(_) => false,
+        }
+    }
+
+    /// Return `true` if the data is owned.
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+}
+
+impl<'a, T: Clone, H: AllocHandle> ArenaCow<'a, T, H> {
+    /// Acquire a mutable reference to the owned form of the data.
+    ///
+    /// If the data is currently borrowed, it is cloned into the arena using the provided handle.
+    /// Otherwise, the handle is not used.
+    pub fn to_mut(&mut self, handle: H) -> &mut T {
+        if let ArenaCow::Borrowed(borrowed) = *self {
+            *self = ArenaCow::Owned(ArenaBox::new(handle, borrowed.clone()));
+        }
+
+        match *self {
+            ArenaCow::Borrowed(_) => unreachable!(),
+            ArenaCow::Owned(ref mut owned) => owned,
+        }
+    }
+
+    /// Extract the owned data, cloning it into the arena using the provided handle if necessary.
+    pub fn into_owned(self, handle: H) -> ArenaBox<T, H> {
+        match self {
+            ArenaCow::Borrowed(borrowed) => ArenaBox::new(handle, borrowed.clone()),
+            ArenaCow::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<'a, T: Clone, H: AllocHandle + Clone> Clone for ArenaCow<'a, T, H> {
+    fn clone(&self) -> Self {
+        match *self {
+            ArenaCow::Borrowed(borrowed) => ArenaCow::Borrowed(borrowed),
+            ArenaCow::Owned(ref owned) => ArenaCow::Owned(owned.clone()),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug, H> fmt::Debug for ArenaCow<'a, T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(fmt)
+    }
+}
+
+impl<'a, T, H> Deref for ArenaCow<'a, T, H> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match *self {
+            ArenaCow::Borrowed(borrowed) => borrowed,
+            ArenaCow::Owned(ref owned) => owned,
+        }
+    }
+}
+
+impl<'a, T, H> From<&'a T> for ArenaCow<'a, T, H> {
+    fn from(borrowed: &'a T) -> Self {
+        ArenaCow::Borrowed(borrowed)
+    }
+}
+
+impl<'a, T, H> From<ArenaBox<T, H>> for ArenaCow<'a, T, H> {
+    fn from(owned: ArenaBox<T, H>) -> Self {
+        ArenaCow::Owned(owned)
+    }
+}
+
+impl<'a, T: Eq, H> Eq for ArenaCow<'a, T, H> {}
+
+impl<'a, T: PartialEq, H> PartialEq for ArenaCow<'a, T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl<'a, T: PartialOrd, H> PartialOrd for ArenaCow<'a, T, H> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
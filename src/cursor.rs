@@ -0,0 +1,143 @@
+//! This module contains a seekable writer backed by a growable arena byte buffer.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::cmp;
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ptr;
+
+/// A cursor over an arena allocated, growable byte buffer.
+///
+/// This is the arena analogue of `std::io::Cursor<Vec<u8>>`: writes overwrite the buffer at the
+/// current position and grow it as needed, and seeking allows encoders to go back and patch
+/// lengths or offsets once they are known. Writing past the end of the buffer fills the gap
+/// with zeroes. Exhausting the arena is reported as an `io::Error` of kind `Other`.
+#[derive(Debug)]
+pub struct ArenaCursor<H> {
+    inner: SliceVec<u8, H>,
+    pos: u64,
+}
+
+impl<H: AllocHandle> ArenaCursor<H> {
+    /// Create a cursor over an empty buffer allocated using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::from_vec(SliceVec::new(handle))
+    }
+}
+
+impl<H> ArenaCursor<H> {
+    /// Create a cursor positioned at the start of `inner`.
+    pub fn from_vec(inner: SliceVec<u8, H>) -> Self {
+        ArenaCursor { inner, pos: 0 }
+    }
+
+    /// Return the current position of the cursor.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Move the cursor to `pos`, which may be past the end of the buffer.
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// Return a reference to the underlying buffer.
+    pub fn get_ref(&self) -> &SliceVec<u8, H> {
+        &self.inner
+    }
+
+    /// Return a mutable reference to the underlying buffer.
+    pub fn get_mut(&mut self) -> &mut SliceVec<u8, H> {
+        &mut self.inner
+    }
+
+    /// Consume the cursor and return the underlying buffer.
+    pub fn into_inner(self) -> SliceVec<u8, H> {
+        self.inner
+    }
+}
+
+impl<H: AllocHandle> Write for ArenaCursor<H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let pos = usize::try_from(self.pos).map_err(|_| invalid_position())?;
+        let end = pos.checked_add(buf.len()).ok_or_else(invalid_position)?;
+        let len = self.inner.len();
+
+        if end > len {
+            self.inner
+                .try_reserve(end - len)
+                .map_err(io::Error::other)?;
+        }
+
+        unsafe {
+            let ptr = self.inner.as_mut_ptr();
+
+            if pos > len {
+                ptr::write_bytes(ptr.add(len), 0, pos - len);
+            }
+
+            ptr::copy_nonoverlapping(buf.as_ptr(), ptr.add(pos), buf.len());
+
+            if end > len {
+                self.inner.set_len(end);
+            }
+        }
+
+        self.pos = end as u64;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<H> Read for ArenaCursor<H> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.len();
+        let start = cmp::min(self.pos, len as u64) as usize;
+        let count = cmp::min(buf.len(), len - start);
+
+        buf[..count].copy_from_slice(&self.inner[start..start + count]);
+        self.pos += count as u64;
+
+        Ok(count)
+    }
+}
+
+impl<H> Seek for ArenaCursor<H> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match style {
+            SeekFrom::Start(pos) => {
+                self.pos = pos;
+                return Ok(pos);
+            }
+            SeekFrom::End(offset) => (self.inner.len() as u64, offset),
+            SeekFrom::Current(offset) => (self.pos, offset),
+        };
+
+        let pos = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+
+        match pos {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(invalid_position()),
+        }
+    }
+}
+
+/// The error returned when seeking to a negative or overflowing position, or writing at a
+/// position that doesn't fit into memory.
+fn invalid_position() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "invalid seek to a negative or overflowing position",
+    )
+}
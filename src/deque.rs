@@ -0,0 +1,352 @@
+//! This module contains a double-ended queue backed by arena memory.
+use crate::common::{self, AllocHandle};
+
+use std::cmp;
+use std::fmt;
+use std::iter::Chain;
+use std::ops::{Index, IndexMut};
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// An arena allocated, growable ring buffer supporting efficient insertion and removal at both
+/// ends.
+///
+/// Just like `SliceVec`, the deque leaks its previous buffer when growing (unless it can be
+/// extended in place), trading memory for speed.
+pub struct ArenaVecDeque<T, H> {
+    ptr: NonNull<T>,
+    head: usize,
+    len: usize,
+    capacity: usize,
+    handle: H,
+}
+
+/// An iterator over the elements of an `ArenaVecDeque`.
+pub type Iter<'a, T> = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+/// A mutable iterator over the elements of an `ArenaVecDeque`.
+pub type IterMut<'a, T> = Chain<slice::IterMut<'a, T>, slice::IterMut<'a, T>>;
+
+impl<T, H> ArenaVecDeque<T, H> {
+    /// Return the current capacity of the deque.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Translate a logical index into an index into the buffer.
+    fn wrap(&self, index: usize) -> usize {
+        let i = self.head + index;
+
+        if i >= self.capacity {
+            i - self.capacity
+        } else {
+            i
+        }
+    }
+
+    /// Return a reference to the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            unsafe { Some(&*self.ptr.as_ptr().add(self.wrap(index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Return a mutable reference to the element at `index`, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            unsafe { Some(&mut *self.ptr.as_ptr().add(self.wrap(index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Return a reference to the first element, or `None` if the deque is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Return a mutable reference to the first element, or `None` if the deque is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Return a reference to the last element, or `None` if the deque is empty.
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Return a mutable reference to the last element, or `None` if the deque is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        match self.len.checked_sub(1) {
+            Some(i) => self.get_mut(i),
+            None => None,
+        }
+    }
+
+    /// Remove the first element from the deque and return it, or `None` if it is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let head = self.head;
+        self.head = self.wrap(1);
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr.as_ptr().add(head))) }
+    }
+
+    /// Remove the last element from the deque and return it, or `None` if it is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr.as_ptr().add(self.wrap(self.len)))) }
+    }
+
+    /// Return the contents of the deque as a pair of slices, in order.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let first = cmp::min(self.len, self.capacity - self.head);
+
+        unsafe {
+            (
+                slice::from_raw_parts(self.ptr.as_ptr().add(self.head), first),
+                slice::from_raw_parts(self.ptr.as_ptr(), self.len - first),
+            )
+        }
+    }
+
+    /// Return the contents of the deque as a pair of mutable slices, in order.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let first = cmp::min(self.len, self.capacity - self.head);
+
+        unsafe {
+            (
+                slice::from_raw_parts_mut(self.ptr.as_ptr().add(self.head), first),
+                slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len - first),
+            )
+        }
+    }
+
+    /// Create an immutable iterator over the elements of the deque, front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b.iter())
+    }
+
+    /// Create a mutable iterator over the elements of the deque, front to back.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (a, b) = self.as_mut_slices();
+        a.iter_mut().chain(b.iter_mut())
+    }
+
+    /// Clear the deque.
+    pub fn clear(&mut self) {
+        let (a, b) = self.as_mut_slices();
+        let (a, b): (*mut [T], *mut [T]) = (a, b);
+
+        self.head = 0;
+        self.len = 0;
+
+        unsafe {
+            ptr::drop_in_place(a);
+            ptr::drop_in_place(b);
+        }
+    }
+}
+
+impl<T, H: AllocHandle> ArenaVecDeque<T, H> {
+    /// Create a new empty deque of capacity `0` using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0)
+    }
+
+    /// Create a new deque of given capacity using the given handle.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        let ptr = if capacity == 0 {
+            NonNull::dangling()
+        } else {
+            handle.allocate(capacity)
+        };
+
+        ArenaVecDeque {
+            ptr,
+            head: 0,
+            len: 0,
+            capacity,
+            handle,
+        }
+    }
+
+    /// Reserve enough space in the deque for at least `additional` more elements.
+    ///
+    /// Panics if the new capacity overflows `usize`, or the arena is exhausted.
+    pub fn reserve(&mut self, additional: usize) {
+        let size = self.len.checked_add(additional).expect("capacity overflow");
+
+        if self.capacity >= size {
+            return;
+        }
+
+        let new_capacity = common::grow_capacity(self.capacity, size).expect("capacity overflow");
+
+        let old_ptr = self.ptr;
+        let new_ptr: NonNull<T> = if self.capacity == 0 {
+            self.handle.allocate(new_capacity)
+        } else {
+            self.handle
+                .allocate_or_extend(old_ptr, self.capacity, new_capacity)
+        };
+
+        let first = cmp::min(self.len, self.capacity - self.head);
+        let second = self.len - first;
+
+        unsafe {
+            if old_ptr != new_ptr {
+                // the buffer has moved, so we can straighten out the contents on the way.
+                ptr::copy_nonoverlapping(old_ptr.as_ptr().add(self.head), new_ptr.as_ptr(), first);
+                ptr::copy_nonoverlapping(old_ptr.as_ptr(), new_ptr.as_ptr().add(first), second);
+
+                self.head = 0;
+            } else {
+                // the buffer has been extended in place, and the capacity at least doubled, so
+                // the wrapped-around part fits right behind the old end of the buffer.
+                ptr::copy_nonoverlapping(
+                    new_ptr.as_ptr(),
+                    new_ptr.as_ptr().add(self.capacity),
+                    second,
+                );
+            }
+        }
+
+        self.ptr = new_ptr;
+        self.capacity = new_capacity;
+    }
+
+    /// Grow the deque if it is full.
+    fn grow_if_full(&mut self) {
+        if self.len == self.capacity {
+            self.reserve(1);
+        }
+    }
+
+    /// Append an element to the back of the deque.
+    pub fn push_back(&mut self, elem: T) {
+        self.grow_if_full();
+
+        unsafe {
+            ptr::write(self.ptr.as_ptr().add(self.wrap(self.len)), elem);
+        }
+
+        self.len += 1;
+    }
+
+    /// Prepend an element to the front of the deque.
+    pub fn push_front(&mut self, elem: T) {
+        self.grow_if_full();
+
+        self.head = if self.head == 0 {
+            self.capacity - 1
+        } else {
+            self.head - 1
+        };
+        self.len += 1;
+
+        unsafe {
+            ptr::write(self.ptr.as_ptr().add(self.head), elem);
+        }
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaVecDeque<T, H> {
+    fn clone(&self) -> Self {
+        let mut deque = ArenaVecDeque::with_capacity(self.handle.clone(), self.capacity);
+
+        for e in self.iter() {
+            deque.push_back(e.clone());
+        }
+
+        deque
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ArenaVecDeque<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, H> Index<usize> for ArenaVecDeque<T, H> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("out of bounds access")
+    }
+}
+
+impl<T, H> IndexMut<usize> for ArenaVecDeque<T, H> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("out of bounds access")
+    }
+}
+
+impl<T: Eq, H> Eq for ArenaVecDeque<T, H> {}
+
+impl<T: PartialEq, H> PartialEq for ArenaVecDeque<T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: PartialOrd, H> PartialOrd for ArenaVecDeque<T, H> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a ArenaVecDeque<T, H> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a mut ArenaVecDeque<T, H> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, H: AllocHandle> Extend<T> for ArenaVecDeque<T, H> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for e in iter {
+            self.push_back(e);
+        }
+    }
+}
+
+impl<T, H> Drop for ArenaVecDeque<T, H> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
@@ -0,0 +1,130 @@
+//! This module provides a C interface to the `rc` arena, enabled by the `ffi` feature.
+//!
+//! C and C++ code embedded in a Rust application can allocate from the same arena as the Rust
+//! side: an arena created on the Rust side is passed to C as `&arena as *const rc::Arena`, and
+//! arenas created from C using `arenavec_rc_create` can be used from Rust by dereferencing the
+//! pointer. C sees the arena as an opaque pointer, and could declare the interface like this:
+//!
+//! ```c
+//! typedef struct arenavec_rc_arena arenavec_rc_arena;
+//!
+//! arenavec_rc_arena *arenavec_rc_create(size_t capacity);
+//! void arenavec_rc_destroy(arenavec_rc_arena *arena);
+//! int arenavec_rc_clear(const arenavec_rc_arena *arena);
+//! void *arenavec_rc_alloc(const arenavec_rc_arena *arena, size_t size, size_t align);
+//! void *arenavec_rc_alloc_zeroed(const arenavec_rc_arena *arena, size_t size, size_t align);
+//! size_t arenavec_rc_used(const arenavec_rc_arena *arena);
+//! size_t arenavec_rc_capacity(const arenavec_rc_arena *arena);
+//! ```
+//!
+//! The arena doesn't track the buffers handed out to C, so they don't block the arena from
+//! being cleared: it's up to the C side not to use them afterwards. Like the arena itself, the
+//! functions must only be called from the thread owning the arena.
+use crate::common::{AllocHandle, ArenaBacking};
+use crate::rc::Arena;
+
+use std::alloc::Layout;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+
+/// Create an arena backed by a memory mapping of `capacity` bytes.
+///
+/// Returns a null pointer if the capacity is zero, or the mapping can't be created. The arena
+/// has to be destroyed using `arenavec_rc_destroy`.
+#[no_mangle]
+pub extern "C" fn arenavec_rc_create(capacity: usize) -> *mut Arena {
+    match Arena::init_capacity(ArenaBacking::MemoryMap, capacity) {
+        Ok(arena) => Box::into_raw(Box::new(arena)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Destroy an arena created using `arenavec_rc_create`, does nothing if `arena` is null.
+///
+/// # Safety
+///
+/// `arena` must have been returned by `arenavec_rc_create`, and must not be used afterwards,
+/// neither may any buffer allocated from it. Objects allocated from the arena on the Rust side
+/// keep its memory alive until they are dropped.
+#[no_mangle]
+pub unsafe extern "C" fn arenavec_rc_destroy(arena: *mut Arena) {
+    if !arena.is_null() {
+        drop(Box::from_raw(arena));
+    }
+}
+
+/// Clear the arena, returning `0` on success, and `-1` if objects allocated from the arena on
+/// the Rust side are still live, or the arena is frozen.
+///
+/// # Safety
+///
+/// `arena` must point to a live arena. Buffers allocated using `arenavec_rc_alloc` must not be
+/// used after the arena has been cleared.
+#[no_mangle]
+pub unsafe extern "C" fn arenavec_rc_clear(arena: *const Arena) -> c_int {
+    match (*arena).clear() {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Allocate `size` bytes aligned to `align` from the arena.
+///
+/// Returns a null pointer if the arena is exhausted, or `align` is not a power of two.
+///
+/// # Safety
+///
+/// `arena` must point to a live arena.
+#[no_mangle]
+pub unsafe extern "C" fn arenavec_rc_alloc(
+    arena: *const Arena,
+    size: usize,
+    align: usize,
+) -> *mut c_void {
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => (*arena)
+            .try_allocate_layout(layout)
+            .map_or(ptr::null_mut(), |ptr| ptr.as_ptr() as *mut c_void),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Allocate `size` zeroed bytes aligned to `align` from the arena, see `arenavec_rc_alloc`.
+///
+/// # Safety
+///
+/// `arena` must point to a live arena.
+#[no_mangle]
+pub unsafe extern "C" fn arenavec_rc_alloc_zeroed(
+    arena: *const Arena,
+    size: usize,
+    align: usize,
+) -> *mut c_void {
+    let ptr = arenavec_rc_alloc(arena, size, align);
+
+    if !ptr.is_null() {
+        ptr::write_bytes(ptr as *mut u8, 0, size);
+    }
+
+    ptr
+}
+
+/// Return the number of bytes allocated from the arena.
+///
+/// # Safety
+///
+/// `arena` must point to a live arena.
+#[no_mangle]
+pub unsafe extern "C" fn arenavec_rc_used(arena: *const Arena) -> usize {
+    (*arena).used()
+}
+
+/// Return the capacity of the arena in bytes.
+///
+/// # Safety
+///
+/// `arena` must point to a live arena.
+#[no_mangle]
+pub unsafe extern "C" fn arenavec_rc_capacity(arena: *const Arena) -> usize {
+    (*arena).capacity()
+}
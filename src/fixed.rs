@@ -0,0 +1,348 @@
+//! This module provides a statically-checked arena over a fixed-size buffer embedded in the arena
+//! itself.
+//!
+//! The arena can be created in a `const` context, so it can be placed in a `static` without any
+//! memory being obtained from the operating system, which suits firmware and other environments
+//! without a heap. Like the arena in the `region` module, it hands out an exclusive generation
+//! token, whose lifetime all objects allocated in the arena are tied to, and which clears the
+//! arena when dropped.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{self, AllocHandle, ArenaError};
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+use crate::tree;
+use crate::vecmap;
+
+use std::alloc::Layout;
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::mem::{self, MaybeUninit};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A statically checked arena over an embedded buffer of `N` bytes.
+///
+/// ```
+/// use arenavec::fixed::{SliceVec, StaticArena};
+///
+/// static ARENA: StaticArena<4096> = StaticArena::new();
+///
+/// let token = ARENA.generation_token().unwrap();
+/// let mut vec = SliceVec::new(token.weak());
+///
+/// vec.push(1);
+/// assert_eq!(&vec[..], &[1]);
+/// ```
+pub struct StaticArena<const N: usize> {
+    /// The arena space
+    data: UnsafeCell<Buffer<N>>,
+
+    /// Whether an exclusive allocation token has been handed out
+    locked: AtomicBool,
+}
+
+/// The arena space, aligned like the memory of the other arenas.
+#[repr(C, align(64))]
+struct Buffer<const N: usize>([MaybeUninit<u8>; N]);
+
+/// A proxy for a static arena that actually allows allocation.
+///
+/// Only one such object referring to an arena instance is allowed to exist at any time. Once it
+/// is dropped, the arena is cleared.
+#[derive(Debug)]
+pub struct StaticToken<'a> {
+    /// Head of the arena space
+    head: NonNull<u8>,
+
+    /// Offset into the arena space
+    pos: Cell<usize>,
+
+    /// Offset up to which the arena space may have been written to
+    dirty: Cell<usize>,
+
+    /// Total capacity of the arena
+    cap: usize,
+
+    /// The lock of the arena, released when the token is dropped
+    locked: &'a AtomicBool,
+}
+
+/// A handle to a static arena for the current generation.
+///
+/// Handles are just shared references to the token, so they can be copied freely.
+#[derive(Debug, Clone, Copy)]
+pub struct StaticHandle<'a>(&'a StaticToken<'a>);
+
+/// An arena allocated, fixed-size sequence of objects
+pub type Slice<'a, T> = common::Slice<T, StaticHandle<'a>>;
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub type SliceVec<'a, T> = common::SliceVec<T, StaticHandle<'a>>;
+
+/// An arena allocated, owned object
+pub type ArenaBox<'a, T> = boxed::ArenaBox<T, StaticHandle<'a>>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'a, T> = cow::ArenaCow<'a, T, StaticHandle<'a>>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<'a, T> = deque::ArenaVecDeque<T, StaticHandle<'a>>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<'a, K, V> = btree::ArenaBTreeMap<K, V, StaticHandle<'a>>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<'a, T> = heap::ArenaBinaryHeap<T, StaticHandle<'a>>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<'a, T> = slab::ArenaSlab<T, StaticHandle<'a>>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<'a, T> = generational::GenArena<T, StaticHandle<'a>>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec<'a> = bitvec::ArenaBitVec<StaticHandle<'a>>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<'a, T> = grid::ArenaGrid<T, StaticHandle<'a>>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<'a, K, V> = vecmap::ArenaVecMap<K, V, StaticHandle<'a>>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<'a, T> = vecmap::ArenaVecSet<T, StaticHandle<'a>>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<'a, K, V> = hash::ArenaHashMap<K, V, StaticHandle<'a>>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<'a, T> = hash::ArenaHashSet<T, StaticHandle<'a>>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<'a, T> = chunkvec::ChunkedVec<T, StaticHandle<'a>>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<'a, T> = ring::ArenaRingBuffer<T, StaticHandle<'a>>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<'a, N, E> = graph::ArenaGraph<N, E, StaticHandle<'a>>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<'a, T> = tree::NodeArena<T, StaticHandle<'a>>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor<'a> = cursor::ArenaCursor<StaticHandle<'a>>;
+
+impl<const N: usize> StaticArena<N> {
+    /// Create an empty arena.
+    pub const fn new() -> Self {
+        StaticArena {
+            data: UnsafeCell::new(Buffer([MaybeUninit::uninit(); N])),
+            locked: AtomicBool::new(false),
+        }
+    }
+
+    /// Return the total capacity of the arena.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Return a fresh generation token for the arena.
+    ///
+    /// If a generation of objects is currently live, an error is returned instead.
+    pub fn generation_token(&self) -> Result<StaticToken<'_>, ArenaError> {
+        if self
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(ArenaError::AlreadyLocked);
+        }
+
+        Ok(StaticToken {
+            head: NonNull::new(self.data.get() as *mut u8).unwrap(),
+            pos: Cell::new(0),
+            // the memory may have been written to in earlier generations.
+            dirty: Cell::new(N),
+            cap: N,
+            locked: &self.locked,
+        })
+    }
+
+    /// Run `f` with a fresh generation token, clearing the arena once it returns.
+    ///
+    /// The token (and hence all objects allocated using it) can't escape the closure. Panics if
+    /// a generation of objects is currently live.
+    pub fn scope<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&StaticToken<'_>) -> R,
+    {
+        let token = self
+            .generation_token()
+            .expect("arena is locked by another generation token");
+
+        f(&token)
+    }
+}
+
+impl<const N: usize> Default for StaticArena<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for StaticArena<N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("StaticArena")
+            .field("capacity", &N)
+            .field("locked", &self.locked.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+// the arena space is only ever accessed through the generation token, which is handed out to one
+// thread at a time, and can't be sent to other threads.
+unsafe impl<const N: usize> Sync for StaticArena<N> {}
+
+impl<'a> StaticToken<'a> {
+    /// Create an arena handle using the current generation's token.
+    pub fn weak(&'a self) -> StaticHandle<'a> {
+        StaticHandle(self)
+    }
+
+    /// Return the number of bytes allocated from the arena, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.pos.get()
+    }
+}
+
+unsafe impl<'a> AllocHandle for StaticToken<'a> {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        common::try_allocate_inner(self.head, &self.pos, self.cap, count)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        common::try_allocate_or_extend_inner(self.head, &self.pos, self.cap, ptr, old_count, count)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        common::try_allocate_zeroed_inner(self.head, &self.pos, &self.dirty, self.cap, count)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        common::allocate_layout_inner(self.head, &self.pos, self.cap, layout)
+            .ok_or_else(|| common::capacity_exceeded(layout.size(), self.pos.get(), self.cap))
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let size = count * mem::size_of::<T>();
+
+        match common::last_allocation_offset(self.head, self.pos.get(), ptr.cast(), size) {
+            Some(offset) => {
+                common::rewind_inner(self.head, &self.pos, &self.dirty, offset);
+                true
+            }
+            None => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let size = mem::size_of::<T>();
+
+        if let Some(offset) =
+            common::last_allocation_offset(self.head, self.pos.get(), ptr.cast(), old_count * size)
+        {
+            common::rewind_inner(self.head, &self.pos, &self.dirty, offset + count * size);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        common::owns_inner(self.head, self.cap, ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(self.head.as_ptr() as usize)
+    }
+}
+
+unsafe impl<'a> AllocHandle for StaticHandle<'a> {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.0.try_allocate(count)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        self.0.try_allocate_or_extend(ptr, old_count, count)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.0.try_allocate_zeroed(count)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.0.try_allocate_layout(layout)
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        self.0.dealloc_last(ptr, count)
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        self.0.allocate_or_shrink(ptr, old_count, count)
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.0.owns(ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        self.0.arena_id()
+    }
+}
+
+impl<'a> Drop for StaticToken<'a> {
+    fn drop(&mut self) {
+        common::rewind_inner(self.head, &self.pos, &self.dirty, 0);
+        self.locked.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,99 @@
+//! This module provides support for generating arena containers from fuzzer input using the
+//! `arbitrary` crate, enabled by the `arbitrary` feature.
+//!
+//! Fuzz targets can build containers in any arena using `SliceVec::arbitrary_in` and
+//! `Slice::arbitrary_in`, or through the `Arbitrary` trait if the handle type implements
+//! `Default`. Exhausting the arena is reported as `arbitrary::Error::IncorrectFormat`, so that
+//! fuzz targets can reject such inputs like any other malformed input.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+impl<T, H: AllocHandle> SliceVec<T, H> {
+    /// Generate an arbitrary vector from fuzzer input, allocating it using the given handle.
+    pub fn arbitrary_in<'a>(u: &mut Unstructured<'a>, handle: H) -> Result<Self>
+    where
+        T: Arbitrary<'a>,
+    {
+        let len = u.arbitrary_len::<T>()?;
+        let mut res =
+            SliceVec::try_with_capacity(handle, len).map_err(|_| Error::IncorrectFormat)?;
+
+        for _ in 0..len {
+            res.push(T::arbitrary(u)?);
+        }
+
+        Ok(res)
+    }
+
+    /// Generate an arbitrary vector from all of the remaining fuzzer input, allocating it using
+    /// the given handle.
+    pub fn arbitrary_take_rest_in<'a>(u: Unstructured<'a>, handle: H) -> Result<Self>
+    where
+        T: Arbitrary<'a>,
+    {
+        let mut res = SliceVec::new(handle);
+
+        for elem in u.arbitrary_take_rest_iter()? {
+            res.try_reserve(1).map_err(|_| Error::IncorrectFormat)?;
+            res.push(elem?);
+        }
+
+        Ok(res)
+    }
+}
+
+impl<T, H: AllocHandle> Slice<T, H> {
+    /// Generate an arbitrary slice from fuzzer input, allocating it using the given handle.
+    pub fn arbitrary_in<'a>(u: &mut Unstructured<'a>, handle: H) -> Result<Self>
+    where
+        T: Arbitrary<'a>,
+    {
+        SliceVec::arbitrary_in(u, handle).map(SliceVec::into_slice)
+    }
+
+    /// Generate an arbitrary slice from all of the remaining fuzzer input, allocating it using
+    /// the given handle.
+    pub fn arbitrary_take_rest_in<'a>(u: Unstructured<'a>, handle: H) -> Result<Self>
+    where
+        T: Arbitrary<'a>,
+    {
+        SliceVec::arbitrary_take_rest_in(u, handle).map(SliceVec::into_slice)
+    }
+}
+
+impl<'a, T, H> Arbitrary<'a> for SliceVec<T, H>
+where
+    T: Arbitrary<'a>,
+    H: AllocHandle + Default,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_in(u, H::default())
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_take_rest_in(u, H::default())
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<'a, T, H> Arbitrary<'a> for Slice<T, H>
+where
+    T: Arbitrary<'a>,
+    H: AllocHandle + Default,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_in(u, H::default())
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        Self::arbitrary_take_rest_in(u, H::default())
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
@@ -0,0 +1,228 @@
+//! This module contains a generational arena container, whose keys detect reuse of their slot.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::fmt;
+use std::mem;
+use std::ops;
+
+/// An arena allocated container of objects addressed by generational indices.
+///
+/// Like `ArenaSlab`, removed slots are reused by later insertions. Every slot additionally
+/// carries a generation counter that is bumped on removal, and every `Index` records the
+/// generation of the object it has been handed out for. This way, stale indices referring to
+/// removed objects are detected, even if their slot has been reused in the meantime.
+pub struct GenArena<T, H> {
+    entries: SliceVec<Entry<T>, H>,
+    len: usize,
+    next: Option<usize>,
+}
+
+/// A key referring to an object in a `GenArena`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Index {
+    index: usize,
+    generation: u64,
+}
+
+/// A slot in the container.
+#[derive(Clone)]
+enum Entry<T> {
+    /// An occupied slot
+    Occupied { generation: u64, value: T },
+    /// A free slot, pointing to the next free slot
+    Vacant {
+        generation: u64,
+        next: Option<usize>,
+    },
+}
+
+impl Index {
+    /// Create an index from its raw parts.
+    pub fn from_raw_parts(index: usize, generation: u64) -> Self {
+        Index { index, generation }
+    }
+
+    /// Return the raw parts of the index.
+    pub fn into_raw_parts(self) -> (usize, u64) {
+        (self.index, self.generation)
+    }
+}
+
+impl<T, H> GenArena<T, H> {
+    /// Return the number of objects in the container.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the container holds no objects.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return a reference to the object referred to by `index`, if it is still live.
+    pub fn get(&self, index: Index) -> Option<&T> {
+        match self.entries.get(index.index) {
+            Some(Entry::Occupied { generation, value }) if *generation == index.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Return a mutable reference to the object referred to by `index`, if it is still live.
+    pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        match self.entries.get_mut(index.index) {
+            Some(Entry::Occupied { generation, value }) if *generation == index.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Return `true` if the object referred to by `index` is still live.
+    pub fn contains(&self, index: Index) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Remove the object referred to by `index` and return it.
+    ///
+    /// If the index is stale, `None` is returned instead.
+    pub fn remove(&mut self, index: Index) -> Option<T> {
+        if !self.contains(index) {
+            return None;
+        }
+
+        let vacant = Entry::Vacant {
+            generation: index.generation.wrapping_add(1),
+            next: self.next,
+        };
+
+        match mem::replace(&mut self.entries[index.index], vacant) {
+            Entry::Occupied { value, .. } => {
+                self.len -= 1;
+                self.next = Some(index.index);
+
+                Some(value)
+            }
+            Entry::Vacant { .. } => unreachable!(),
+        }
+    }
+
+    /// Remove all objects from the container, invalidating all indices handed out so far.
+    ///
+    /// The slots are kept around for later insertions.
+    pub fn clear(&mut self) {
+        let mut next = None;
+
+        for (i, entry) in self.entries.iter_mut().enumerate().rev() {
+            let generation = match *entry {
+                Entry::Occupied { generation, .. } => generation.wrapping_add(1),
+                Entry::Vacant { generation, .. } => generation,
+            };
+
+            *entry = Entry::Vacant { generation, next };
+            next = Some(i);
+        }
+
+        self.len = 0;
+        self.next = next;
+    }
+
+    /// Create an iterator over the indices and objects in the container, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (Index, &T)> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match *entry {
+                Entry::Occupied {
+                    generation,
+                    ref value,
+                } => Some((Index { index, generation }, value)),
+                Entry::Vacant { .. } => None,
+            })
+    }
+}
+
+impl<T, H: AllocHandle> GenArena<T, H> {
+    /// Create a new empty container using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0)
+    }
+
+    /// Create a new empty container with space for `capacity` objects using the given handle.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        GenArena {
+            entries: SliceVec::with_capacity(handle, capacity),
+            len: 0,
+            next: None,
+        }
+    }
+
+    /// Return the number of objects the container can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Insert an object into the container and return its index.
+    pub fn insert(&mut self, value: T) -> Index {
+        self.len += 1;
+
+        match self.next {
+            Some(index) => {
+                let generation = match self.entries[index] {
+                    Entry::Vacant { generation, next } => {
+                        self.next = next;
+                        generation
+                    }
+                    Entry::Occupied { .. } => unreachable!(),
+                };
+
+                self.entries[index] = Entry::Occupied { generation, value };
+
+                Index { index, generation }
+            }
+            None => {
+                let index = self.entries.len();
+                self.entries.push(Entry::Occupied {
+                    generation: 0,
+                    value,
+                });
+
+                Index {
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for GenArena<T, H> {
+    fn clone(&self) -> Self {
+        GenArena {
+            entries: self.entries.clone(),
+            len: self.len,
+            next: self.next,
+        }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for GenArena<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, H> ops::Index<Index> for GenArena<T, H> {
+    type Output = T;
+
+    fn index(&self, index: Index) -> &T {
+        self.get(index).expect("stale or invalid index")
+    }
+}
+
+impl<T, H> ops::IndexMut<Index> for GenArena<T, H> {
+    fn index_mut(&mut self, index: Index) -> &mut T {
+        self.get_mut(index).expect("stale or invalid index")
+    }
+}
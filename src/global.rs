@@ -0,0 +1,163 @@
+//! This module provides an arena that can be installed as the global allocator.
+//!
+//! This is primarily meant for short-lived programs, such as command line tools and
+//! benchmarks, that allocate a bounded amount of memory and never need to reclaim it
+//! piecemeal: freeing memory is a no-op, which makes allocation very cheap.
+use crate::common;
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// A thread-safe arena implementing `GlobalAlloc`.
+///
+/// The backing memory mapping is created lazily on the first allocation (so the arena can be
+/// constructed in a `static`), and never released. Deallocation is a no-op, and once the
+/// capacity of the arena is exhausted, allocation fails.
+///
+/// ```no_run
+/// use arenavec::ArenaGlobalAlloc;
+///
+/// #[global_allocator]
+/// static ALLOC: ArenaGlobalAlloc = ArenaGlobalAlloc::new(1 << 30);
+///
+/// fn main() {
+///     let v = vec![1, 2, 3];
+///     assert_eq!(v.len(), 3);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ArenaGlobalAlloc {
+    /// Head of the arena space, null until the first allocation
+    head: AtomicPtr<u8>,
+
+    /// Offset into the arena space
+    pos: AtomicUsize,
+
+    /// Total capacity of the arena
+    cap: usize,
+}
+
+impl ArenaGlobalAlloc {
+    /// Create an arena with the specified capacity.
+    ///
+    /// No memory is reserved until the first allocation.
+    pub const fn new(cap: usize) -> Self {
+        ArenaGlobalAlloc {
+            head: AtomicPtr::new(ptr::null_mut()),
+            pos: AtomicUsize::new(0),
+            cap,
+        }
+    }
+
+    /// Return the capacity of the arena.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Return the number of bytes allocated from the arena, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.pos.load(Ordering::Relaxed)
+    }
+
+    /// Reset the arena, making all of its memory available for allocation again.
+    ///
+    /// # Safety
+    ///
+    /// All memory allocated from the arena becomes invalid, the caller has to ensure that no
+    /// such allocation (including those made by the standard library on the caller's behalf) is
+    /// accessed or deallocated afterwards.
+    pub unsafe fn reset(&self) {
+        self.pos.store(0, Ordering::Release);
+    }
+
+    /// Return the head of the arena space, creating it if necessary.
+    fn head(&self) -> *mut u8 {
+        let head = self.head.load(Ordering::Acquire);
+
+        if !head.is_null() {
+            return head;
+        }
+
+        let new = common::create_mapping(self.cap);
+
+        if new.is_null() {
+            return new;
+        }
+
+        match self
+            .head
+            .compare_exchange(ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => new,
+            Err(head) => {
+                // another thread has been faster.
+                common::destroy_mapping(unsafe { ptr::NonNull::new_unchecked(new) }, self.cap);
+
+                head
+            }
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for ArenaGlobalAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let head = self.head();
+
+        if head.is_null() {
+            return head;
+        }
+
+        let mut pos = self.pos.load(Ordering::Relaxed);
+
+        loop {
+            let skip = (head as usize + pos).wrapping_neg() & (layout.align() - 1);
+            let end = match pos
+                .checked_add(skip)
+                .and_then(|p| p.checked_add(layout.size()))
+            {
+                Some(end) if end <= self.cap => end,
+                _ => return ptr::null_mut(),
+            };
+
+            match self
+                .pos
+                .compare_exchange_weak(pos, end, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => return head.add(pos + skip),
+                Err(p) => pos = p,
+            }
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let head = self.head.load(Ordering::Acquire);
+        let offset = ptr as usize - head as usize;
+        let end = offset + layout.size();
+
+        if new_size <= layout.size() {
+            return ptr;
+        }
+
+        // extend the allocation in place if it is the last one.
+        if offset + new_size <= self.cap
+            && self
+                .pos
+                .compare_exchange(end, offset + new_size, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        {
+            return ptr;
+        }
+
+        let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+        let new_ptr = self.alloc(new_layout);
+
+        if !new_ptr.is_null() {
+            ptr::copy_nonoverlapping(ptr, new_ptr, layout.size());
+        }
+
+        new_ptr
+    }
+}
@@ -0,0 +1,287 @@
+//! This module contains a directed graph whose nodes, edges and adjacency lists live in an arena.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+/// An arena allocated, directed graph with node weights `N` and edge weights `E`.
+///
+/// Nodes and edges are stored in arena-backed vectors and addressed by typed indices, and each
+/// node keeps its outgoing edges in a vector of its own. Nodes and edges can't be removed,
+/// which matches the build-then-traverse usage graphs typically see, as well as the arena's
+/// allocation model.
+pub struct ArenaGraph<N, E, H> {
+    nodes: SliceVec<Node<N, H>, H>,
+    edges: SliceVec<Edge<E>, H>,
+    handle: H,
+}
+
+/// The index of a node in an `ArenaGraph`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+/// The index of an edge in an `ArenaGraph`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeId(usize);
+
+/// A node of the graph.
+struct Node<N, H> {
+    /// The weight of the node
+    weight: N,
+
+    /// The outgoing edges of the node
+    outgoing: SliceVec<EdgeId, H>,
+}
+
+/// An edge of the graph.
+struct Edge<E> {
+    /// The weight of the edge
+    weight: E,
+
+    /// The source of the edge
+    source: NodeId,
+
+    /// The target of the edge
+    target: NodeId,
+}
+
+/// An iterator over the outgoing edges of a node.
+pub type Edges<'a> = std::iter::Cloned<slice::Iter<'a, EdgeId>>;
+
+/// An iterator over the successors of a node.
+#[derive(Clone)]
+pub struct Neighbors<'a, E> {
+    edges: slice::Iter<'a, EdgeId>,
+    data: &'a [Edge<E>],
+}
+
+impl NodeId {
+    /// Create a node index from a raw index.
+    pub fn new(index: usize) -> Self {
+        NodeId(index)
+    }
+
+    /// Return the raw index of the node.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl EdgeId {
+    /// Create an edge index from a raw index.
+    pub fn new(index: usize) -> Self {
+        EdgeId(index)
+    }
+
+    /// Return the raw index of the edge.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl<N, E, H> ArenaGraph<N, E, H> {
+    /// Return the number of nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return the number of edges in the graph.
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Return a reference to the weight of `node`, if it exists.
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.nodes.get(node.0).map(|n| &n.weight)
+    }
+
+    /// Return a mutable reference to the weight of `node`, if it exists.
+    pub fn node_weight_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(node.0).map(|n| &mut n.weight)
+    }
+
+    /// Return a reference to the weight of `edge`, if it exists.
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.edges.get(edge.0).map(|e| &e.weight)
+    }
+
+    /// Return a mutable reference to the weight of `edge`, if it exists.
+    pub fn edge_weight_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.edges.get_mut(edge.0).map(|e| &mut e.weight)
+    }
+
+    /// Return the source and target of `edge`, if it exists.
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edges.get(edge.0).map(|e| (e.source, e.target))
+    }
+
+    /// Create an iterator over the indices of all nodes in the graph.
+    pub fn node_ids(&self) -> impl ExactSizeIterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    /// Create an iterator over the indices of all edges in the graph.
+    pub fn edge_ids(&self) -> impl ExactSizeIterator<Item = EdgeId> {
+        (0..self.edges.len()).map(EdgeId)
+    }
+
+    /// Create an iterator over the outgoing edges of `node`, in insertion order.
+    ///
+    /// Panics if `node` does not exist.
+    pub fn edges(&self, node: NodeId) -> Edges<'_> {
+        self.nodes[node.0].outgoing.iter().cloned()
+    }
+
+    /// Create an iterator over the successors of `node`, in the order the connecting edges have
+    /// been added.
+    ///
+    /// Panics if `node` does not exist.
+    pub fn neighbors(&self, node: NodeId) -> Neighbors<'_, E> {
+        Neighbors {
+            edges: self.nodes[node.0].outgoing.iter(),
+            data: &self.edges,
+        }
+    }
+
+    /// Return the index of an edge from `source` to `target`, if any.
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        self.nodes
+            .get(source.0)?
+            .outgoing
+            .iter()
+            .cloned()
+            .find(|e| self.edges[e.0].target == target)
+    }
+}
+
+impl<N, E, H: AllocHandle + Clone> ArenaGraph<N, E, H> {
+    /// Create a new empty graph using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0, 0)
+    }
+
+    /// Create a new empty graph with room for the given number of nodes and edges.
+    pub fn with_capacity(handle: H, nodes: usize, edges: usize) -> Self {
+        ArenaGraph {
+            nodes: SliceVec::with_capacity(handle.clone(), nodes),
+            edges: SliceVec::with_capacity(handle.clone(), edges),
+            handle,
+        }
+    }
+
+    /// Add a node with the given weight to the graph and return its index.
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        let id = NodeId(self.nodes.len());
+
+        self.nodes.push(Node {
+            weight,
+            outgoing: SliceVec::new(self.handle.clone()),
+        });
+
+        id
+    }
+
+    /// Add an edge from `source` to `target` with the given weight and return its index.
+    ///
+    /// Panics if either node does not exist.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        assert!(target.0 < self.nodes.len(), "target node does not exist");
+
+        let id = EdgeId(self.edges.len());
+
+        self.nodes[source.0].outgoing.push(id);
+        self.edges.push(Edge {
+            weight,
+            source,
+            target,
+        });
+
+        id
+    }
+
+    /// Remove all nodes and edges from the graph.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+    }
+}
+
+impl<N: fmt::Debug, E: fmt::Debug, H> fmt::Debug for ArenaGraph<N, E, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("ArenaGraph")
+            .field("nodes", &DebugNodes(&self.nodes))
+            .field("edges", &DebugEdges(&self.edges))
+            .finish()
+    }
+}
+
+/// A helper to format the nodes of a graph.
+struct DebugNodes<'a, N, H>(&'a [Node<N, H>]);
+
+/// A helper to format the edges of a graph.
+struct DebugEdges<'a, E>(&'a [Edge<E>]);
+
+impl<'a, N: fmt::Debug, H> fmt::Debug for DebugNodes<'a, N, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list()
+            .entries(self.0.iter().map(|n| &n.weight))
+            .finish()
+    }
+}
+
+impl<'a, E: fmt::Debug> fmt::Debug for DebugEdges<'a, E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list()
+            .entries(self.0.iter().map(|e| (e.source.0, e.target.0, &e.weight)))
+            .finish()
+    }
+}
+
+impl<N, E, H> Index<NodeId> for ArenaGraph<N, E, H> {
+    type Output = N;
+
+    fn index(&self, node: NodeId) -> &N {
+        self.node_weight(node).expect("out of bounds access")
+    }
+}
+
+impl<N, E, H> IndexMut<NodeId> for ArenaGraph<N, E, H> {
+    fn index_mut(&mut self, node: NodeId) -> &mut N {
+        self.node_weight_mut(node).expect("out of bounds access")
+    }
+}
+
+impl<N, E, H> Index<EdgeId> for ArenaGraph<N, E, H> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeId) -> &E {
+        self.edge_weight(edge).expect("out of bounds access")
+    }
+}
+
+impl<N, E, H> IndexMut<EdgeId> for ArenaGraph<N, E, H> {
+    fn index_mut(&mut self, edge: EdgeId) -> &mut E {
+        self.edge_weight_mut(edge).expect("out of bounds access")
+    }
+}
+
+impl<'a, E> Iterator for Neighbors<'a, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        self.edges.next().map(|e| self.data[e.0].target)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.edges.size_hint()
+    }
+}
+
+impl<'a, E> ExactSizeIterator for Neighbors<'a, E> {}
+
+impl<'a, E> fmt::Debug for Neighbors<'a, E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Neighbors").finish()
+    }
+}
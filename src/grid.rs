@@ -0,0 +1,204 @@
+//! This module contains a two-dimensional array backed by arena memory.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+/// An arena allocated, fixed-size two-dimensional array of objects, stored in row-major order.
+///
+/// Objects are addressed by `(x, y)` coordinates, where `x` is the column and `y` the row.
+pub struct ArenaGrid<T, H> {
+    cells: Slice<T, H>,
+    width: usize,
+    height: usize,
+}
+
+impl<T, H> ArenaGrid<T, H> {
+    /// Return the number of columns in the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the number of rows in the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return a reference to the object at `(x, y)`, or `None` if it is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Return a mutable reference to the object at `(x, y)`, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Return row `y` of the grid.
+    ///
+    /// Panics if `y` is out of bounds.
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(
+            y < self.height,
+            "row out of bounds: {} >= {}",
+            y,
+            self.height
+        );
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Return row `y` of the grid mutably.
+    ///
+    /// Panics if `y` is out of bounds.
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(
+            y < self.height,
+            "row out of bounds: {} >= {}",
+            y,
+            self.height
+        );
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Create an iterator over the rows of the grid, top to bottom.
+    pub fn rows(&self) -> slice::ChunksExact<'_, T> {
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    /// Create a mutable iterator over the rows of the grid, top to bottom.
+    pub fn rows_mut(&mut self) -> slice::ChunksExactMut<'_, T> {
+        self.cells.chunks_exact_mut(self.width.max(1))
+    }
+
+    /// Create an iterator over the objects in a column of the grid, top to bottom.
+    ///
+    /// Panics if `x` is out of bounds.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(
+            x < self.width,
+            "column out of bounds: {} >= {}",
+            x,
+            self.width
+        );
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// Return the objects in the grid as a single row-major slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Return the objects in the grid as a single mutable row-major slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Create an iterator over the coordinates and objects in the grid, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let width = self.width;
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, e)| ((i % width, i / width), e))
+    }
+}
+
+impl<T, H: AllocHandle> ArenaGrid<T, H> {
+    /// Create a new grid of default-initialized objects using the provided handle.
+    pub fn new(handle: H, width: usize, height: usize) -> Self
+    where
+        T: Default,
+    {
+        ArenaGrid {
+            cells: Slice::new(handle, width * height),
+            width,
+            height,
+        }
+    }
+
+    /// Create a new grid with all objects initialized to `value` using the provided handle.
+    pub fn from_elem(handle: H, width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let mut cells = SliceVec::with_capacity(handle, width * height);
+        cells.resize(width * height, value);
+
+        ArenaGrid {
+            cells: cells.into_slice(),
+            width,
+            height,
+        }
+    }
+
+    /// Create a new grid using the provided handle, initializing the object at `(x, y)` to the
+    /// return value of `f(x, y)`.
+    ///
+    /// Objects are initialized in row-major order.
+    pub fn from_fn<F>(handle: H, width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let mut cells = SliceVec::with_capacity(handle, width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(x, y));
+            }
+        }
+
+        ArenaGrid {
+            cells: cells.into_slice(),
+            width,
+            height,
+        }
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaGrid<T, H> {
+    fn clone(&self) -> Self {
+        ArenaGrid {
+            cells: self.cells.clone(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ArenaGrid<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.rows()).finish()
+    }
+}
+
+impl<T, H> Index<(usize, usize)> for ArenaGrid<T, H> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("out of bounds access")
+    }
+}
+
+impl<T, H> IndexMut<(usize, usize)> for ArenaGrid<T, H> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("out of bounds access")
+    }
+}
+
+impl<T: PartialEq, H> PartialEq for ArenaGrid<T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.cells == other.cells
+    }
+}
+
+impl<T: Eq, H> Eq for ArenaGrid<T, H> {}
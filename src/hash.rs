@@ -0,0 +1,60 @@
+//! This module provides hash maps and sets allocating from arenas, built on the `hashbrown`
+//! crate and enabled by the `hashbrown` feature.
+//!
+//! The containers are plain `hashbrown` containers using a `HandleAllocator`, so their whole
+//! API is available. Since tables are reallocated when they grow, the memory of previous tables
+//! is only reused if they are the most recent allocation in the arena. Reserving the expected
+//! capacity up front avoids that waste.
+use crate::allocator::HandleAllocator;
+use crate::common::AllocHandle;
+
+use hashbrown::{DefaultHashBuilder, HashMap, HashSet};
+
+/// An arena allocated hash map
+pub type ArenaHashMap<K, V, H> = HashMap<K, V, DefaultHashBuilder, HandleAllocator<H>>;
+
+/// An arena allocated hash set
+pub type ArenaHashSet<T, H> = HashSet<T, DefaultHashBuilder, HandleAllocator<H>>;
+
+/// Create an empty hash map allocating from the arena of the given handle.
+///
+/// The map doesn't allocate until elements are inserted.
+pub fn map_in<K, V, H: AllocHandle>(handle: H) -> ArenaHashMap<K, V, H> {
+    HashMap::with_hasher_in(DefaultHashBuilder::default(), HandleAllocator(handle))
+}
+
+/// Create an empty hash map with room for at least `capacity` elements, allocating from the
+/// arena of the given handle.
+///
+/// If the arena is exhausted, `std::alloc::handle_alloc_error` is called, use `try_reserve` on
+/// an empty container to handle this gracefully.
+pub fn map_with_capacity_in<K, V, H: AllocHandle>(
+    handle: H,
+    capacity: usize,
+) -> ArenaHashMap<K, V, H> {
+    HashMap::with_capacity_and_hasher_in(
+        capacity,
+        DefaultHashBuilder::default(),
+        HandleAllocator(handle),
+    )
+}
+
+/// Create an empty hash set allocating from the arena of the given handle.
+///
+/// The set doesn't allocate until elements are inserted.
+pub fn set_in<T, H: AllocHandle>(handle: H) -> ArenaHashSet<T, H> {
+    HashSet::with_hasher_in(DefaultHashBuilder::default(), HandleAllocator(handle))
+}
+
+/// Create an empty hash set with room for at least `capacity` elements, allocating from the
+/// arena of the given handle.
+///
+/// If the arena is exhausted, `std::alloc::handle_alloc_error` is called, use `try_reserve` on
+/// an empty container to handle this gracefully.
+pub fn set_with_capacity_in<T, H: AllocHandle>(handle: H, capacity: usize) -> ArenaHashSet<T, H> {
+    HashSet::with_capacity_and_hasher_in(
+        capacity,
+        DefaultHashBuilder::default(),
+        HandleAllocator(handle),
+    )
+}
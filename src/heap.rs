@@ -0,0 +1,182 @@
+//! This module contains a priority queue backed by arena memory.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use std::fmt;
+use std::slice;
+
+/// An arena allocated priority queue implemented as a binary max-heap on top of `SliceVec`.
+pub struct ArenaBinaryHeap<T, H> {
+    data: SliceVec<T, H>,
+}
+
+impl<T, H> ArenaBinaryHeap<T, H> {
+    /// Return the greatest element in the heap, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    /// Return the number of elements in the heap.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Return `true` if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Create an iterator over the elements of the heap, in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Return the underlying vector, in arbitrary order.
+    pub fn into_vec(self) -> SliceVec<T, H> {
+        self.data
+    }
+}
+
+impl<T: Ord, H: AllocHandle> ArenaBinaryHeap<T, H> {
+    /// Create a new empty heap using the given handle.
+    pub fn new(handle: H) -> Self {
+        ArenaBinaryHeap {
+            data: SliceVec::new(handle),
+        }
+    }
+
+    /// Create a new empty heap of given capacity using the given handle.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        ArenaBinaryHeap {
+            data: SliceVec::with_capacity(handle, capacity),
+        }
+    }
+
+    /// Return the current capacity of the heap.
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    /// Reserve enough space in the heap for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+    }
+
+    /// Push an element onto the heap.
+    pub fn push(&mut self, elem: T) {
+        self.data.push(elem);
+
+        let mut pos = self.data.len() - 1;
+
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+
+            if self.data[pos] <= self.data[parent] {
+                break;
+            }
+
+            self.data.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    /// Remove the greatest element from the heap and return it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        let len = self.data.len();
+
+        if len == 0 {
+            return None;
+        }
+
+        self.data.swap(0, len - 1);
+        let elem = self.data.pop();
+        self.sift_down(0, len - 1);
+
+        elem
+    }
+
+    /// Restore the heap property for the subtree rooted at `pos`, considering the first `end`
+    /// elements only.
+    fn sift_down(&mut self, mut pos: usize, end: usize) {
+        loop {
+            let mut child = 2 * pos + 1;
+
+            if child >= end {
+                break;
+            }
+
+            if child + 1 < end && self.data[child + 1] > self.data[child] {
+                child += 1;
+            }
+
+            if self.data[pos] >= self.data[child] {
+                break;
+            }
+
+            self.data.swap(pos, child);
+            pos = child;
+        }
+    }
+
+    /// Remove all elements from the heap.
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Consume the heap and return its elements as a slice, in ascending order.
+    pub fn into_sorted_slice(mut self) -> Slice<T, H> {
+        let mut end = self.data.len();
+
+        while end > 1 {
+            end -= 1;
+            self.data.swap(0, end);
+            self.sift_down(0, end);
+        }
+
+        self.data.into_slice()
+    }
+}
+
+impl<T: Ord, H: AllocHandle> From<SliceVec<T, H>> for ArenaBinaryHeap<T, H> {
+    /// Turn a vector into a heap, in linear time.
+    fn from(data: SliceVec<T, H>) -> Self {
+        let mut heap = ArenaBinaryHeap { data };
+        let len = heap.data.len();
+
+        for pos in (0..len / 2).rev() {
+            heap.sift_down(pos, len);
+        }
+
+        heap
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaBinaryHeap<T, H> {
+    fn clone(&self) -> Self {
+        ArenaBinaryHeap {
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ArenaBinaryHeap<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.data.fmt(fmt)
+    }
+}
+
+impl<T: Ord, H: AllocHandle> Extend<T> for ArenaBinaryHeap<T, H> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for e in iter {
+            self.push(e);
+        }
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a ArenaBinaryHeap<T, H> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
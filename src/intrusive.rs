@@ -0,0 +1,366 @@
+//! This module contains an intrusive doubly-linked list for arena-allocated elements.
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+/// The links of an element in an `IntrusiveList`, embedded in the element itself.
+pub struct Link<T> {
+    prev: Cell<Option<NonNull<T>>>,
+    next: Cell<Option<NonNull<T>>>,
+    linked: Cell<bool>,
+}
+
+/// Types that can be placed in an `IntrusiveList`.
+///
+/// # Safety
+///
+/// `link` has to return the same `Link`, embedded in `self`, every time it is called.
+pub unsafe trait Linked: Sized {
+    /// Return the links embedded in the element.
+    fn link(&self) -> &Link<Self>;
+}
+
+/// A doubly-linked list threaded through its elements.
+///
+/// Since objects allocated in an arena keep their address until the arena is cleared, they
+/// can be linked together without any per-node allocation: the list only borrows its elements
+/// for `'a`, the links are stored in the elements themselves. An element can be part of at
+/// most one list at a time, and pushing an element that is already linked panics.
+pub struct IntrusiveList<'a, T: Linked> {
+    head: Option<NonNull<T>>,
+    tail: Option<NonNull<T>>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+/// An iterator over the elements of an `IntrusiveList`, front to back.
+pub struct Iter<'l, 'a, T: Linked> {
+    next: Option<NonNull<T>>,
+    len: usize,
+    _marker: PhantomData<&'l IntrusiveList<'a, T>>,
+}
+
+/// A cursor over an `IntrusiveList` that can insert and remove elements.
+///
+/// The cursor points either at an element of the list or at a "ghost" position between the
+/// back and the front of the list.
+pub struct CursorMut<'l, 'a, T: Linked> {
+    current: Option<NonNull<T>>,
+    list: &'l mut IntrusiveList<'a, T>,
+}
+
+impl<T> Link<T> {
+    /// Create a new, unlinked link.
+    pub fn new() -> Self {
+        Link {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            linked: Cell::new(false),
+        }
+    }
+
+    /// Return `true` if the element owning the link is part of a list.
+    pub fn is_linked(&self) -> bool {
+        self.linked.get()
+    }
+}
+
+impl<T> Default for Link<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Link<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Link")
+            .field("linked", &self.linked.get())
+            .finish()
+    }
+}
+
+/// Access the links of the element behind `ptr`.
+unsafe fn link<'a, T: Linked>(ptr: NonNull<T>) -> &'a Link<T> {
+    (*ptr.as_ptr()).link()
+}
+
+impl<'a, T: Linked> IntrusiveList<'a, T> {
+    /// Create a new empty list.
+    pub fn new() -> Self {
+        IntrusiveList {
+            head: None,
+            tail: None,
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the first element, or `None` if the list is empty.
+    pub fn front(&self) -> Option<&'a T> {
+        self.head.map(|p| unsafe { &*p.as_ptr() })
+    }
+
+    /// Return the last element, or `None` if the list is empty.
+    pub fn back(&self) -> Option<&'a T> {
+        self.tail.map(|p| unsafe { &*p.as_ptr() })
+    }
+
+    /// Link `elem` into the list between `prev` and `next`, which have to be adjacent.
+    fn link_between(&mut self, elem: &'a T, prev: Option<NonNull<T>>, next: Option<NonNull<T>>) {
+        let link = elem.link();
+
+        assert!(!link.is_linked(), "element is already linked into a list");
+
+        let ptr = NonNull::from(elem);
+
+        link.prev.set(prev);
+        link.next.set(next);
+        link.linked.set(true);
+
+        unsafe {
+            match prev {
+                Some(p) => self::link(p).next.set(Some(ptr)),
+                None => self.head = Some(ptr),
+            }
+
+            match next {
+                Some(n) => self::link(n).prev.set(Some(ptr)),
+                None => self.tail = Some(ptr),
+            }
+        }
+
+        self.len += 1;
+    }
+
+    /// Unlink the element behind `ptr`, which has to be part of the list.
+    fn unlink(&mut self, ptr: NonNull<T>) -> &'a T {
+        unsafe {
+            let link = self::link(ptr);
+            let (prev, next) = (link.prev.take(), link.next.take());
+
+            match prev {
+                Some(p) => self::link(p).next.set(next),
+                None => self.head = next,
+            }
+
+            match next {
+                Some(n) => self::link(n).prev.set(prev),
+                None => self.tail = prev,
+            }
+
+            link.linked.set(false);
+            self.len -= 1;
+
+            &*ptr.as_ptr()
+        }
+    }
+
+    /// Prepend an element to the list.
+    pub fn push_front(&mut self, elem: &'a T) {
+        let head = self.head;
+        self.link_between(elem, None, head);
+    }
+
+    /// Append an element to the list.
+    pub fn push_back(&mut self, elem: &'a T) {
+        let tail = self.tail;
+        self.link_between(elem, tail, None);
+    }
+
+    /// Remove the first element from the list and return it, or `None` if it is empty.
+    pub fn pop_front(&mut self) -> Option<&'a T> {
+        self.head.map(|p| self.unlink(p))
+    }
+
+    /// Remove the last element from the list and return it, or `None` if it is empty.
+    pub fn pop_back(&mut self) -> Option<&'a T> {
+        self.tail.map(|p| self.unlink(p))
+    }
+
+    /// Create an iterator over the elements of the list, front to back.
+    pub fn iter(&self) -> Iter<'_, 'a, T> {
+        Iter {
+            next: self.head,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Create a cursor pointing at the first element of the list.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, 'a, T> {
+        CursorMut {
+            current: self.head,
+            list: self,
+        }
+    }
+
+    /// Create a cursor pointing at the last element of the list.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, 'a, T> {
+        CursorMut {
+            current: self.tail,
+            list: self,
+        }
+    }
+
+    /// Unlink all elements from the list.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<'a, T: Linked> Default for IntrusiveList<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: Linked + fmt::Debug> fmt::Debug for IntrusiveList<'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'l, 'a, T: Linked> IntoIterator for &'l IntrusiveList<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'l, 'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: Linked> Extend<&'a T> for IntrusiveList<'a, T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for e in iter {
+            self.push_back(e);
+        }
+    }
+}
+
+impl<'a, T: Linked> Drop for IntrusiveList<'a, T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<'l, 'a, T: Linked> Iterator for Iter<'l, 'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let ptr = self.next?;
+
+        unsafe {
+            self.next = link(ptr).next.get();
+            self.len -= 1;
+
+            Some(&*ptr.as_ptr())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'l, 'a, T: Linked> ExactSizeIterator for Iter<'l, 'a, T> {}
+
+impl<'l, 'a, T: Linked> Clone for Iter<'l, 'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            next: self.next,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'l, 'a, T: Linked> fmt::Debug for Iter<'l, 'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter").field("len", &self.len).finish()
+    }
+}
+
+impl<'l, 'a, T: Linked> CursorMut<'l, 'a, T> {
+    /// Return the element the cursor points at, or `None` at the ghost position.
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|p| unsafe { &*p.as_ptr() })
+    }
+
+    /// Return the element following the cursor's position, if any.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        self.next().map(|p| unsafe { &*p.as_ptr() })
+    }
+
+    /// Return the element preceding the cursor's position, if any.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        self.prev().map(|p| unsafe { &*p.as_ptr() })
+    }
+
+    fn next(&self) -> Option<NonNull<T>> {
+        match self.current {
+            Some(p) => unsafe { link(p).next.get() },
+            None => self.list.head,
+        }
+    }
+
+    fn prev(&self) -> Option<NonNull<T>> {
+        match self.current {
+            Some(p) => unsafe { link(p).prev.get() },
+            None => self.list.tail,
+        }
+    }
+
+    /// Move the cursor to the next element, wrapping around through the ghost position.
+    pub fn move_next(&mut self) {
+        self.current = self.next();
+    }
+
+    /// Move the cursor to the previous element, wrapping around through the ghost position.
+    pub fn move_prev(&mut self) {
+        self.current = self.prev();
+    }
+
+    /// Insert an element after the cursor's position.
+    ///
+    /// At the ghost position, the element is inserted at the front of the list.
+    pub fn insert_after(&mut self, elem: &'a T) {
+        let next = self.next();
+        self.list.link_between(elem, self.current, next);
+    }
+
+    /// Insert an element before the cursor's position.
+    ///
+    /// At the ghost position, the element is inserted at the back of the list.
+    pub fn insert_before(&mut self, elem: &'a T) {
+        let prev = self.prev();
+        self.list.link_between(elem, prev, self.current);
+    }
+
+    /// Remove the current element from the list and return it, moving the cursor to the next
+    /// element.
+    ///
+    /// At the ghost position, nothing is removed and `None` is returned.
+    pub fn remove_current(&mut self) -> Option<&'a T> {
+        let current = self.current?;
+        self.current = self.next();
+
+        Some(self.list.unlink(current))
+    }
+}
+
+impl<'l, 'a, T: Linked + fmt::Debug> fmt::Debug for CursorMut<'l, 'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("CursorMut").field(&self.current()).finish()
+    }
+}
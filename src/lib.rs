@@ -1,13 +1,102 @@
 //! This crate exposes a number of arena allocator implementations tailored to slightly different
-//! usecases. Currently, all of them are non-MT-safe, and hence intended to be used locally per
-//! thread, for instance being placed in a thread-local variable, or nested in user types.
+//! usecases. Except for the arenas in the `arc`, `sync`, `shared` and `global` modules, all of
+//! them are non-MT-safe, and hence intended to be used locally per thread, for instance being
+//! placed in a thread-local variable, or nested in user types.
 //!
 //! In addition to the allocator types, the library provides a set of data structures that are
 //! allocator-agnostic (as in, compatible with all allocators provided in this crate).
 #![deny(missing_debug_implementations, warnings, rust_2018_idioms)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
+#[cfg(feature = "allocator-api2")]
+pub mod allocator;
+pub mod arc;
+#[cfg(feature = "rkyv")]
+pub mod archive;
+pub mod bitvec;
+pub mod boxed;
+pub mod btree;
+#[cfg(feature = "bytes")]
+pub mod buf;
+pub mod bump;
+#[cfg(feature = "zerocopy")]
+pub mod cast;
+pub mod checked;
+pub mod chunked;
+pub mod chunkvec;
+#[cfg(any(feature = "bincode", feature = "borsh"))]
+pub mod codec;
 pub mod common;
+pub mod compact;
+pub mod cow;
+pub mod cursor;
+pub mod deque;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixed;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+pub mod generational;
+pub mod global;
+pub mod graph;
+pub mod grid;
+#[cfg(feature = "hashbrown")]
+pub mod hash;
+pub mod heap;
+pub mod intrusive;
+pub mod local;
+#[cfg(feature = "memmap2")]
+pub mod memmap;
+#[cfg(feature = "rayon")]
+pub mod par;
 pub mod rc;
 pub mod region;
+pub mod registry;
+pub mod ring;
+pub mod shared;
+pub mod shm;
+pub mod slab;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod sync;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod tree;
+pub mod typed;
+pub mod vecmap;
 
+#[cfg(feature = "allocator-api2")]
+pub use crate::allocator::HandleAllocator;
+pub use crate::bitvec::*;
+pub use crate::boxed::*;
+pub use crate::btree::ArenaBTreeMap;
+pub use crate::bump::BumpAlloc;
+pub use crate::checked::CheckedHandle;
+pub use crate::chunkvec::*;
 pub use crate::common::*;
+pub use crate::compact::{ArenaBase, ArenaPtr32};
+pub use crate::cow::*;
+pub use crate::deque::ArenaVecDeque;
+pub use crate::generational::GenArena;
+pub use crate::global::ArenaGlobalAlloc;
+pub use crate::graph::{ArenaGraph, EdgeId, NodeId};
+pub use crate::grid::*;
+pub use crate::heap::*;
+pub use crate::intrusive::{IntrusiveList, Link, Linked};
+pub use crate::local::ThreadLocalHandle;
+#[cfg(feature = "memmap2")]
+pub use crate::memmap::MemmapSource;
+pub use crate::registry::{ArenaRegistry, ArenaUsage};
+pub use crate::ring::{ArenaRingBuffer, RingMode};
+pub use crate::shm::{ArenaOffset, SharedMapping};
+pub use crate::slab::ArenaSlab;
+#[cfg(feature = "stats")]
+pub use crate::stats::{StatsReport, TypeStats};
+#[cfg(feature = "trace")]
+pub use crate::trace::TraceEvent;
+pub use crate::tree::{Id, NodeArena};
+pub use crate::vecmap::*;
@@ -0,0 +1,285 @@
+//! This module provides a per-thread default arena and a zero-sized handle referring to it.
+//!
+//! Library code can use arena-backed containers without having a handle passed in, as long as
+//! the thread's arena has been set up using `init_thread_local` beforehand. Allocating from a
+//! thread without an arena panics.
+//!
+//! Since the handle doesn't keep the arena alive, or even track which objects live in it, the
+//! arena can only be cleared using the unsafe `clear_thread_local`. Objects allocated in the
+//! arena may be placed in other thread-local variables, which can be destroyed after the arena,
+//! in unspecified order. Thus, an arena that isn't empty is leaked when the thread exits, and
+//! allocating from it afterwards fails with `ArenaError::StaleHandle`.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{self, AllocHandle, ArenaBacking, ArenaError};
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::rc;
+use crate::ring;
+use crate::slab;
+use crate::tree;
+use crate::vecmap;
+
+use std::alloc::Layout;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+thread_local! {
+    /// The arena of the current thread, if any.
+    static ARENA: ThreadArena = const { ThreadArena(RefCell::new(None)) };
+}
+
+/// The arena of a thread, which is leaked when the thread exits if it isn't empty.
+struct ThreadArena(RefCell<Option<rc::Arena>>);
+
+impl Drop for ThreadArena {
+    fn drop(&mut self) {
+        if let Some(arena) = self.0.get_mut().take() {
+            // objects in the arena may still be reachable from other thread-local variables.
+            if arena.used() > 0 {
+                mem::forget(arena);
+            }
+        }
+    }
+}
+
+/// A handle to the current thread's arena.
+///
+/// The handle is zero-sized, and can't be sent to other threads.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadLocalHandle {
+    _marker: PhantomData<*const ()>,
+}
+
+/// An arena allocated, fixed-size sequence of objects
+pub type Slice<T> = common::Slice<T, ThreadLocalHandle>;
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub type SliceVec<T> = common::SliceVec<T, ThreadLocalHandle>;
+
+/// An arena allocated, owned object
+pub type ArenaBox<T> = boxed::ArenaBox<T, ThreadLocalHandle>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'b, T> = cow::ArenaCow<'b, T, ThreadLocalHandle>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<T> = deque::ArenaVecDeque<T, ThreadLocalHandle>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<K, V> = btree::ArenaBTreeMap<K, V, ThreadLocalHandle>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<T> = heap::ArenaBinaryHeap<T, ThreadLocalHandle>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<T> = slab::ArenaSlab<T, ThreadLocalHandle>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<T> = generational::GenArena<T, ThreadLocalHandle>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec = bitvec::ArenaBitVec<ThreadLocalHandle>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<T> = grid::ArenaGrid<T, ThreadLocalHandle>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<K, V> = vecmap::ArenaVecMap<K, V, ThreadLocalHandle>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<T> = vecmap::ArenaVecSet<T, ThreadLocalHandle>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<K, V> = hash::ArenaHashMap<K, V, ThreadLocalHandle>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<T> = hash::ArenaHashSet<T, ThreadLocalHandle>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<T> = chunkvec::ChunkedVec<T, ThreadLocalHandle>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<T> = ring::ArenaRingBuffer<T, ThreadLocalHandle>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<N, E> = graph::ArenaGraph<N, E, ThreadLocalHandle>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<T> = tree::NodeArena<T, ThreadLocalHandle>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor = cursor::ArenaCursor<ThreadLocalHandle>;
+
+/// Set up the arena of the current thread with specified capacity.
+///
+/// The capacity is rounded up to a multiple of the page size, and must not be zero. If the thread
+/// already has an arena, an error is returned.
+pub fn init_thread_local(backing: ArenaBacking, cap: usize) -> Result<(), ArenaError> {
+    ARENA.with(|arena| {
+        let mut arena = arena.0.borrow_mut();
+
+        if arena.is_some() {
+            return Err(ArenaError::AlreadyLocked);
+        }
+
+        *arena = Some(rc::Arena::init_capacity(backing, cap)?);
+
+        Ok(())
+    })
+}
+
+/// Return `true` if the current thread has an arena.
+pub fn has_thread_local() -> bool {
+    ARENA.with(|arena| arena.0.borrow().is_some())
+}
+
+/// Clear the arena of the current thread, if any.
+///
+/// # Safety
+///
+/// No object allocated in the arena may be accessed afterwards.
+pub unsafe fn clear_thread_local() {
+    ARENA.with(|arena| {
+        if let Some(ref arena) = *arena.0.borrow() {
+            // no other references to the arena are ever handed out.
+            arena.clear().unwrap();
+        }
+    })
+}
+
+impl ThreadLocalHandle {
+    /// Create a handle to the current thread's arena.
+    pub fn new() -> Self {
+        ThreadLocalHandle {
+            _marker: PhantomData,
+        }
+    }
+
+    /// Run `f` on the current thread's arena.
+    ///
+    /// Fails if the arena has been destroyed already, which can happen when called from the
+    /// destructor of another thread-local variable.
+    fn with<R, F>(f: F) -> Result<R, ArenaError>
+    where
+        F: FnOnce(&rc::InnerRef) -> Result<R, ArenaError>,
+    {
+        ARENA
+            .try_with(|arena| match *arena.0.borrow() {
+                Some(ref arena) => f(arena),
+                None => panic!("no thread-local arena has been initialized"),
+            })
+            .unwrap_or(Err(ArenaError::StaleHandle))
+    }
+}
+
+unsafe impl AllocHandle for ThreadLocalHandle {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        Self::with(|arena| arena.try_allocate(count))
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        Self::with(|arena| arena.try_allocate_or_extend(ptr, old_count, count))
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        Self::with(|arena| arena.try_allocate_zeroed(count))
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        Self::with(|arena| arena.try_allocate_layout(layout))
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        // this is called from destructors, so it mustn't panic if the arena is gone.
+        ARENA
+            .try_with(|arena| match *arena.0.borrow() {
+                Some(ref arena) => arena.dealloc_last(ptr, count),
+                None => false,
+            })
+            .unwrap_or(false)
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        // like `dealloc_last`, this is called from destructors. If the arena is gone, the memory
+        // has been leaked along with it.
+        ARENA
+            .try_with(|arena| match *arena.0.borrow() {
+                Some(ref arena) => arena.allocate_or_shrink(ptr, old_count, count),
+                None => false,
+            })
+            .unwrap_or(false)
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        // if the arena is gone, the memory it handed out can't be told apart anymore.
+        ARENA
+            .try_with(|arena| match *arena.0.borrow() {
+                Some(ref arena) => arena.owns(ptr, size),
+                None => false,
+            })
+            .unwrap_or(true)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        ARENA
+            .try_with(|arena| arena.0.borrow().as_ref().and_then(|arena| arena.arena_id()))
+            .unwrap_or(None)
+    }
+}
+
+impl<T> common::Slice<T, ThreadLocalHandle> {
+    /// Create a new slice of default-initialized objects in the current thread's arena.
+    pub fn new_thread_local(len: usize) -> Self
+    where
+        T: Default,
+    {
+        Self::new(ThreadLocalHandle::new(), len)
+    }
+}
+
+impl<T> common::SliceVec<T, ThreadLocalHandle> {
+    /// Create a new empty vector in the current thread's arena.
+    pub fn new_thread_local() -> Self {
+        Self::new(ThreadLocalHandle::new())
+    }
+
+    /// Create a new vector of given capacity in the current thread's arena.
+    pub fn with_capacity_thread_local(capacity: usize) -> Self {
+        Self::with_capacity(ThreadLocalHandle::new(), capacity)
+    }
+}
+
+impl<T> boxed::ArenaBox<T, ThreadLocalHandle> {
+    /// Move `value` into the current thread's arena.
+    pub fn new_thread_local(value: T) -> Self {
+        Self::new(ThreadLocalHandle::new(), value)
+    }
+}
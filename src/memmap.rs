@@ -0,0 +1,74 @@
+//! This module provides an arena backing built on the `memmap2` crate, enabled by the `memmap2`
+//! feature.
+//!
+//! The backing creates anonymous memory mappings using `memmap2` instead of the system calls
+//! used by `ArenaBacking::MemoryMap`, so it is available on all platforms `memmap2` supports.
+//! It doesn't support growing arenas in place, or releasing their physical memory on clear.
+use crate::common::{ArenaBacking, BackingSource};
+
+use memmap2::MmapMut;
+
+use std::fmt;
+use std::ptr::{self, NonNull};
+use std::sync::{Arc, Mutex};
+
+/// A source of arena memory creating anonymous memory mappings using `memmap2`.
+///
+/// Use `MemmapSource::backing` to create an arena from it.
+#[derive(Default)]
+pub struct MemmapSource {
+    /// The mappings handed out, which are unmapped when released
+    mappings: Mutex<Vec<MmapMut>>,
+}
+
+impl MemmapSource {
+    /// Create a new source.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Return an arena backing obtaining its memory from a new source.
+    pub fn backing() -> ArenaBacking {
+        ArenaBacking::Custom(Arc::new(MemmapSource::new()))
+    }
+}
+
+impl fmt::Debug for MemmapSource {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mappings = self.mappings.lock().unwrap_or_else(|err| err.into_inner());
+
+        fmt.debug_struct("MemmapSource")
+            .field("mappings", &mappings.len())
+            .finish()
+    }
+}
+
+unsafe impl BackingSource for MemmapSource {
+    fn allocate(&self, capacity: usize) -> Option<NonNull<u8>> {
+        let mut mapping = MmapMut::map_anon(capacity).ok()?;
+        let ptr = NonNull::new(mapping.as_mut_ptr())?;
+
+        // the mapping doesn't move when the handle is moved into the list.
+        self.mappings
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(mapping);
+
+        Some(ptr)
+    }
+
+    unsafe fn release(&self, base: NonNull<u8>, _capacity: usize) {
+        let mut mappings = self.mappings.lock().unwrap_or_else(|err| err.into_inner());
+
+        if let Some(index) = mappings
+            .iter()
+            .position(|m| ptr::eq(m.as_ptr(), base.as_ptr()))
+        {
+            mappings.swap_remove(index);
+        }
+    }
+
+    fn is_zeroed(&self) -> bool {
+        true
+    }
+}
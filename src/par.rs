@@ -0,0 +1,215 @@
+//! This module provides parallel iterators over arena containers built on the `rayon` crate,
+//! enabled by the `rayon` feature.
+//!
+//! Iterating over borrowed `Slice`s and `SliceVec`s simply iterates over the slices they
+//! dereference to. Consuming a container moves its elements out of the arena buffer from
+//! multiple threads, while the buffer itself, and the handle it has been allocated from, stay on
+//! the thread the iteration has been started from, where the buffer is released afterwards.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use rayon::slice::{Iter, IterMut, ParallelSlice, ParallelSliceMut};
+
+use std::mem;
+use std::ptr;
+use std::slice;
+
+/// A parallel iterator moving the elements out of a `Slice` or `SliceVec`.
+#[derive(Debug)]
+pub struct IntoParIter<T, H> {
+    slice: Slice<T, H>,
+}
+
+/// A producer moving the elements out of a part of an arena buffer.
+///
+/// Elements that haven't been moved out when the producer is dropped are dropped in place.
+struct DrainProducer<'a, T> {
+    slice: &'a mut [T],
+}
+
+/// A sequential iterator moving the elements out of a part of an arena buffer.
+struct DrainIter<'a, T> {
+    iter: slice::IterMut<'a, T>,
+}
+
+impl<T: Send, H: Send> ParallelIterator for IntoParIter<T, H> {
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.slice.len())
+    }
+}
+
+impl<T: Send, H: Send> IndexedParallelIterator for IntoParIter<T, H> {
+    fn drive<C>(self, consumer: C) -> C::Result
+    where
+        C: Consumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn len(&self) -> usize {
+        self.slice.len()
+    }
+
+    fn with_producer<CB>(mut self, callback: CB) -> CB::Output
+    where
+        CB: ProducerCallback<Self::Item>,
+    {
+        let len = self.slice.len();
+        let ptr = self.slice.as_mut_ptr();
+
+        // the producer takes over the elements, if the callback panics, the buffer is leaked.
+        let res = callback.callback(DrainProducer {
+            slice: unsafe { slice::from_raw_parts_mut(ptr, len) },
+        });
+
+        unsafe { self.slice.release_moved() };
+
+        res
+    }
+}
+
+impl<'a, T: Send> Producer for DrainProducer<'a, T> {
+    type Item = T;
+    type IntoIter = DrainIter<'a, T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        DrainIter {
+            iter: mem::take(&mut self.slice).iter_mut(),
+        }
+    }
+
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let (left, right) = mem::take(&mut self.slice).split_at_mut(index);
+
+        (
+            DrainProducer { slice: left },
+            DrainProducer { slice: right },
+        )
+    }
+}
+
+impl<'a, T> Drop for DrainProducer<'a, T> {
+    fn drop(&mut self) {
+        let rest: *mut [T] = mem::take::<&mut [T]>(&mut self.slice);
+
+        unsafe { ptr::drop_in_place(rest) };
+    }
+}
+
+impl<'a, T> Iterator for DrainIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.iter.next().map(|elem| unsafe { ptr::read(elem) })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for DrainIter<'a, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back().map(|elem| unsafe { ptr::read(elem) })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for DrainIter<'a, T> {}
+
+impl<'a, T> Drop for DrainIter<'a, T> {
+    fn drop(&mut self) {
+        let rest: *mut [T] = mem::replace(&mut self.iter, [].iter_mut()).into_slice();
+
+        unsafe { ptr::drop_in_place(rest) };
+    }
+}
+
+impl<T: Send, H: Send> IntoParallelIterator for Slice<T, H> {
+    type Item = T;
+    type Iter = IntoParIter<T, H>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter { slice: self }
+    }
+}
+
+impl<T: Send, H: AllocHandle + Send> IntoParallelIterator for SliceVec<T, H> {
+    type Item = T;
+    type Iter = IntoParIter<T, H>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter {
+            slice: self.into_slice(),
+        }
+    }
+}
+
+impl<'a, T: Sync, H> IntoParallelIterator for &'a Slice<T, H> {
+    type Item = &'a T;
+    type Iter = Iter<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        <&[T]>::into_par_iter(self)
+    }
+}
+
+impl<'a, T: Send, H> IntoParallelIterator for &'a mut Slice<T, H> {
+    type Item = &'a mut T;
+    type Iter = IterMut<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        <&mut [T]>::into_par_iter(self)
+    }
+}
+
+impl<'a, T: Sync, H> IntoParallelIterator for &'a SliceVec<T, H> {
+    type Item = &'a T;
+    type Iter = Iter<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        <&[T]>::into_par_iter(self)
+    }
+}
+
+impl<'a, T: Send, H> IntoParallelIterator for &'a mut SliceVec<T, H> {
+    type Item = &'a mut T;
+    type Iter = IterMut<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        <&mut [T]>::into_par_iter(self)
+    }
+}
+
+impl<T: Sync, H> ParallelSlice<T> for Slice<T, H> {
+    fn as_parallel_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: Send, H> ParallelSliceMut<T> for Slice<T, H> {
+    fn as_parallel_slice_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T: Sync, H> ParallelSlice<T> for SliceVec<T, H> {
+    fn as_parallel_slice(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: Send, H> ParallelSliceMut<T> for SliceVec<T, H> {
+    fn as_parallel_slice_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
@@ -8,14 +8,53 @@
 //! already invalidated.
 //!
 //! If you are not sure what arena to use, it's strongly suggested you try the `region` module
-//! first.
-use crate::common::{self, AllocHandle, ArenaBacking, ArenaError};
+//! first. Arenas which have to move between threads can use the `arc` module, which provides a
+//! variant of this arena whose handles are `Send` and `Sync`, or the simpler `sync` module, which
+//! only supports allocation and clearing.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{
+    self, AllocHandle, ArenaBacking, ArenaError, ClearObservers, ClearPhase, Marker, OwnedBacking,
+    Quota, Snapshot, SERIALIZED_MAGIC, SERIALIZED_VERSION,
+};
+use crate::compact::ArenaBase;
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+#[cfg(feature = "stats")]
+use crate::stats::{self, StatsReport};
+#[cfg(feature = "trace")]
+use crate::trace::{self, Tracer};
+use crate::tree;
+use crate::vecmap;
 
-use std::cell::Cell;
+use std::alloc::Layout;
+#[cfg(feature = "allocator_api")]
+use std::alloc::{AllocError, Allocator};
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::mem;
 use std::ops::Deref;
-use std::ptr::NonNull;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::RawFd;
+use std::ptr::{self, NonNull};
 use std::rc::Rc;
 
+#[cfg(feature = "serde")]
+use serde::de::{self, Deserialize, Deserializer};
+
 /// A reference-counting arena (non-MT-safe).
 ///
 /// This is the only object that can be used to clear the arena. All other objects referring to
@@ -32,19 +71,119 @@ pub struct Arena(InnerRef, ArenaBacking);
 #[derive(Clone, Debug)]
 pub struct InnerRef {
     inner: Rc<Inner>,
+
+    /// Generation of the arena the reference has been created in
+    generation: Cell<u64>,
+}
+
+/// A non-owning object referring to the arena, which allocates from its back end.
+///
+/// Allocations from the back grow downwards from the end of the arena, towards the
+/// allocations made from the front. Keeping temporary objects at one end and results at the
+/// other allows for either end to be cleared independently, see `Arena::clear_front` and
+/// `Arena::clear_back`. Allocations from the back aren't subject to the arena's quota.
+#[derive(Debug)]
+pub struct BackRef {
+    inner: Rc<Inner>,
+
+    /// Generation of the arena the reference has been created in
+    generation: u64,
+}
+
+/// A reference to the arena which can only allocate up to a budget of bytes.
+///
+/// Obtained using `InnerRef::child`, this allows for handing a component a share of an arena
+/// shared with other components, without letting it exhaust the arena. Clones of the reference
+/// share its budget. Allocations are charged with their size (excluding alignment padding), and
+/// memory returned to the arena by dropping (or shrinking) the last allocation is credited back.
+/// Allocations failing for lack of budget fail with `ArenaError::QuotaExceeded`.
+#[derive(Clone, Debug)]
+pub struct ChildRef {
+    inner: InnerRef,
+
+    /// Number of bytes the reference can still allocate
+    budget: Rc<Cell<usize>>,
+}
+
+/// A child arena occupying a contiguous range of memory carved out of another arena.
+///
+/// Obtained using `Arena::carve`, the child arena has its own position, quota and handles, so
+/// that a subsystem allocating from it can exhaust it (or clear it) without affecting the
+/// parent arena or its siblings. The parent arena can't be cleared while the child arena is
+/// live.
+#[derive(Debug)]
+pub struct SubArena {
+    /// The child arena
+    arena: Arena,
+
+    /// The parent arena, kept from being cleared while the child arena is live
+    _parent: InnerRef,
 }
 
 /// An arena's guts
 #[derive(Debug)]
 struct Inner {
     /// Head of the arena space
-    head: NonNull<u8>,
+    head: Cell<NonNull<u8>>,
 
     /// Offset into the last region
     pos: Cell<usize>,
 
+    /// Offset up to which the arena space may have been written to before the last rewind
+    dirty: Cell<usize>,
+
+    /// Offset of the lowest allocation from the back end of the arena space
+    back: Cell<usize>,
+
+    /// Number of live handles allocating from the back end of the arena space
+    back_refs: Cell<usize>,
+
+    /// Limits on the memory handed out by the arena
+    quota: RefCell<Quota>,
+
+    /// Length of the write-protected prefix of the arena space, if frozen
+    frozen: Cell<Option<usize>>,
+
+    /// Whether to release the physical memory backing the arena when it is cleared
+    decommit: Cell<bool>,
+
+    /// Whether the arena memory is a private mapping created by forking an arena
+    forked: Cell<bool>,
+
+    /// Whether the arena memory has been sealed read-only for good
+    sealed: Cell<bool>,
+
+    /// Number of clears, references from earlier generations can't be used anymore
+    generation: Cell<u64>,
+
+    /// Callbacks to invoke when the arena is cleared
+    observers: ClearObservers,
+
+    /// Allocation statistics since the last clear
+    #[cfg(feature = "stats")]
+    stats: stats::Recorder,
+
+    /// Allocation tracing state
+    #[cfg(feature = "trace")]
+    tracer: Tracer,
+
     /// Total capacity of the arena
-    cap: usize,
+    cap: Cell<usize>,
+}
+
+#[cfg(feature = "serde")]
+thread_local! {
+    /// The arena vectors are deserialized into on the current thread, if any.
+    static DESERIALIZE_ARENA: RefCell<Option<InnerRef>> = const { RefCell::new(None) };
+}
+
+/// A guard keeping the memory allocated from an arena read-only while it is live.
+///
+/// Obtained using `Arena::freeze`, the memory is made writable again when the guard is dropped,
+/// or using `Frozen::unfreeze`, which reports failure to do so.
+#[derive(Debug)]
+pub struct Frozen<'a> {
+    arena: &'a Arena,
 }
 
 /// An arena allocated, fixed-size sequence of objects
@@ -57,21 +196,144 @@ pub type Slice<T> = common::Slice<T, InnerRef>;
 /// for speed.
 pub type SliceVec<T> = common::SliceVec<T, InnerRef>;
 
+/// An arena allocated, owned object
+pub type ArenaBox<T> = boxed::ArenaBox<T, InnerRef>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'b, T> = cow::ArenaCow<'b, T, InnerRef>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<T> = deque::ArenaVecDeque<T, InnerRef>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<K, V> = btree::ArenaBTreeMap<K, V, InnerRef>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<T> = heap::ArenaBinaryHeap<T, InnerRef>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<T> = slab::ArenaSlab<T, InnerRef>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<T> = generational::GenArena<T, InnerRef>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec = bitvec::ArenaBitVec<InnerRef>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<T> = grid::ArenaGrid<T, InnerRef>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<K, V> = vecmap::ArenaVecMap<K, V, InnerRef>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<T> = vecmap::ArenaVecSet<T, InnerRef>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<K, V> = hash::ArenaHashMap<K, V, InnerRef>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<T> = hash::ArenaHashSet<T, InnerRef>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<T> = chunkvec::ChunkedVec<T, InnerRef>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<T> = ring::ArenaRingBuffer<T, InnerRef>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<N, E> = graph::ArenaGraph<N, E, InnerRef>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<T> = tree::NodeArena<T, InnerRef>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor = cursor::ArenaCursor<InnerRef>;
+
+/// Run `f` with `arena` set up as the arena `SliceVec`s are deserialized into on the current
+/// thread.
+///
+/// This allows for `Deserialize` to be derived for types containing vectors, which then are
+/// allocated from `arena` when deserialized by `f`. Calls can be nested, the previously set
+/// arena is restored when `f` returns. Deserializing a vector on a thread without such an arena
+/// fails with an error.
+#[cfg(feature = "serde")]
+pub fn with_deserialize_arena<R, F: FnOnce() -> R>(arena: &InnerRef, f: F) -> R {
+    /// Restores the previous arena, even if `f` panics.
+    struct Restore(Option<InnerRef>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            DESERIALIZE_ARENA.with(|cell| *cell.borrow_mut() = prev);
+        }
+    }
+
+    let prev = DESERIALIZE_ARENA.with(|cell| cell.replace(Some(arena.clone())));
+    let _restore = Restore(prev);
+
+    f()
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SliceVec<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match DESERIALIZE_ARENA.with(|cell| cell.borrow().clone()) {
+            Some(arena) => Self::deserialize_in(arena, deserializer),
+            None => Err(de::Error::custom(
+                "no arena has been set up using `with_deserialize_arena`",
+            )),
+        }
+    }
+}
+
 impl Arena {
+    /// Create an `Arena` backed by a memory mapping of the default capacity.
+    ///
+    /// The capacity is `DEFAULT_CAPACITY`, unless overridden by the `ARENAVEC_DEFAULT_CAPACITY`
+    /// environment variable, so that it can be tuned without recompiling. Only address space is
+    /// reserved up front, the memory is committed on first use.
+    pub fn init() -> Result<Self, ArenaError> {
+        Self::init_capacity(ArenaBacking::MemoryMap, common::default_capacity()?)
+    }
+
     /// Create an `Arena` with specified capacity.
     ///
-    /// Capacity must be a power of 2. The capacity cannot be grown after the fact.
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. It can be
+    /// grown after the fact using `grow`, if the arena is backed by a memory mapping.
     pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
-        let head = NonNull::new(match backing {
-            ArenaBacking::MemoryMap => common::create_mapping(cap),
-            ArenaBacking::SystemAllocation => common::create_mapping_alloc(cap),
-        })
-        .ok_or(ArenaError::AllocationFailed)?;
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
         let pos = Cell::new(0);
+        let dirty = Cell::new(if backing.is_zeroed() { 0 } else { cap });
 
         Ok(Arena(
             InnerRef {
-                inner: Rc::new(Inner { head, pos, cap }),
+                inner: Rc::new(Inner {
+                    head: Cell::new(head),
+                    pos,
+                    dirty,
+                    back: Cell::new(cap),
+                    back_refs: Cell::new(0),
+                    quota: RefCell::new(Quota::new()),
+                    frozen: Cell::new(None),
+                    decommit: Cell::new(false),
+                    forked: Cell::new(false),
+                    sealed: Cell::new(false),
+                    generation: Cell::new(0),
+                    observers: Default::default(),
+                    #[cfg(feature = "stats")]
+                    stats: Default::default(),
+                    #[cfg(feature = "trace")]
+                    tracer: Default::default(),
+                    cap: Cell::new(cap),
+                }),
+                generation: Cell::new(0),
             },
             backing,
         ))
@@ -82,19 +344,642 @@ impl Arena {
         self.0.clone()
     }
 
+    /// Create a reference to the arena allocating from its back end.
+    pub fn back(&self) -> BackRef {
+        self.inner.back_refs.set(self.inner.back_refs.get() + 1);
+
+        BackRef {
+            inner: self.inner.clone(),
+            generation: self.inner.generation.get(),
+        }
+    }
+
+    /// Reserve `bytes` bytes of the arena and return a child arena allocating from them.
+    ///
+    /// The reserved memory is aligned to the page size and rounded up to a multiple of it, so that
+    /// the child arena can be frozen.
+    /// It can't be grown, and is given back to the parent arena only when it is cleared.
+    pub fn carve(&self, bytes: usize) -> Result<SubArena, ArenaError> {
+        let bytes = common::round_capacity(bytes)?;
+        let layout = Layout::from_size_align(bytes, common::get_page_size())
+            .map_err(|_| ArenaError::InvalidLayout)?;
+        let base = self.try_allocate_layout(layout)?;
+        Ok(SubArena {
+            arena: Arena::init_capacity(common::Carved::backing(base, bytes), bytes)?,
+            _parent: self.inner(),
+        })
+    }
+
+    /// Create a copy of the arena sharing its memory copy-on-write.
+    ///
+    /// The allocated part of the arena is written to an anonymous file once, which both arenas
+    /// map privately afterwards, so that their pages are only copied when either arena writes to
+    /// them. This allows for speculative computations to branch off cheaply, and for the copy to
+    /// be thrown away. Objects in the copy are located at different addresses, so only their
+    /// contents can be accessed through the copy, for instance by offset from its `base`.
+    ///
+    /// Only arenas backed by private memory mappings (or forked themselves) on Linux can be
+    /// forked, and neither arena can be grown afterwards. Frozen arenas can't be forked.
+    pub fn fork(&mut self) -> Result<Arena, ArenaError> {
+        let forkable = matches!(
+            self.1,
+            ArenaBacking::MemoryMap | ArenaBacking::PopulatedMemoryMap
+        ) || self.inner.forked.get();
+
+        if !forkable || self.inner.frozen.get().is_some() {
+            return Err(ArenaError::CannotFork);
+        }
+
+        let head = self.inner.head.get();
+        let cap = self.inner.cap.get();
+        let pos = self.inner.pos.get();
+        let back = self.inner.back.get();
+
+        let copy = NonNull::new(common::fork_mapping(head, cap, &[(0, pos), (back, cap)]))
+            .ok_or(ArenaError::CannotFork)?;
+
+        // the original mapping has been replaced, and has to be released as such.
+        self.1 = common::Forked::backing(head, cap, true);
+        self.inner.forked.set(true);
+
+        let arena = Arena::init_capacity(common::Forked::backing(copy, cap, false), cap)?;
+
+        // the memory between the allocations from either end reads as zeroed in both arenas.
+        for inner in &[&self.inner, &arena.inner] {
+            inner.pos.set(pos);
+            inner.back.set(back);
+            inner.dirty.set(if back < cap { cap } else { pos });
+        }
+
+        arena.inner.forked.set(true);
+
+        Ok(arena)
+    }
+
+    /// Return the number of live references to the arena, not counting the arena itself.
+    ///
+    /// These include references held by containers allocated from the arena, and references
+    /// allocating from its back end. The arena can only be cleared if there are none.
+    pub fn live_handles(&self) -> usize {
+        Rc::strong_count(&self.inner) - 1
+    }
+
+    /// Take the memory out of the arena without releasing it.
+    ///
+    /// This is only possible if no other reference to the arena is live, otherwise the arena is
+    /// handed back. The memory can then be repurposed, for instance by another allocator, and is
+    /// released when the returned object is dropped. A frozen arena is made writable first, and
+    /// handed back if that fails. Sealed arenas are always handed back.
+    pub fn into_inner(self) -> Result<OwnedBacking, Self> {
+        if Rc::strong_count(&self.inner) != 1 || self.inner.sealed.get() {
+            return Err(self);
+        }
+
+        if self.unfreeze().is_err() {
+            return Err(self);
+        }
+
+        let head = self.inner.head.get();
+        let cap = self.inner.cap.get();
+        let mut arena = mem::ManuallyDrop::new(self);
+
+        // the backing is moved out, and the last reference to the arena's guts dropped, without
+        // running the arena's `drop`, which would release the memory.
+        unsafe {
+            let backing = ptr::read(&arena.1);
+            ptr::drop_in_place(&mut arena.0);
+
+            Ok(OwnedBacking::from_raw_parts(head, cap, backing))
+        }
+    }
+
     /// Clear the arena.
     ///
     /// This only requires an immutable reference, as it (a) perfors a check that
     /// no arena-allocated object is still alive (weak reason), and because all mutable
     /// state is neatly contained in a `Cell` (slightly stronger reason).
+    ///
+    /// A frozen arena can't be cleared either.
     pub fn clear(&self) -> Result<(), ArenaError> {
-        if Rc::strong_count(&self.inner) == 1 {
-            self.inner.pos.set(0);
+        if Rc::strong_count(&self.inner) == 1 && self.inner.frozen.get().is_none() {
+            self.inner.observers.notify(ClearPhase::Before);
+
+            #[cfg(feature = "trace")]
+            self.inner.tracer.clear(self.used());
+
+            common::rewind_inner(self.inner.head.get(), &self.inner.pos, &self.inner.dirty, 0);
+            self.inner.rewind_back();
+
+            if self.inner.decommit.get() {
+                common::decommit_inner(&self.1, self.inner.head.get(), &self.inner.dirty);
+            }
+
+            #[cfg(feature = "stats")]
+            self.inner.stats.clear();
+
+            self.next_generation();
+            self.inner.observers.notify(ClearPhase::After);
 
             Ok(())
         } else {
-            Err(ArenaError::CannotClear)
+            Err(ArenaError::CannotClear {
+                live_handles: self.live_handles(),
+            })
+        }
+    }
+
+    /// Clear the arena, even if references to it are still live.
+    ///
+    /// This starts a new generation of the arena: references (and thus containers) created
+    /// before refuse to allocate with `ArenaError::StaleHandle`, and report themselves as stale
+    /// using `is_stale`. Slices and vectors from earlier generations panic when their contents
+    /// are accessed, and leak them when dropped. A frozen arena can't be cleared.
+    ///
+    /// # Safety
+    ///
+    /// The memory of the arena is reused, so no object allocated from it before may be accessed
+    /// afterwards, except through the checked containers mentioned above. Other containers from
+    /// earlier generations have to be forgotten using `mem::forget`, unless their elements don't
+    /// need to be dropped. Child arenas carved out of the arena are invalidated as well.
+    pub unsafe fn clear_force(&self) -> Result<(), ArenaError> {
+        if self.inner.frozen.get().is_some() {
+            return Err(ArenaError::CannotClear { live_handles: 0 });
+        }
+
+        self.inner.observers.notify(ClearPhase::Before);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.clear(self.used());
+
+        common::rewind_inner(self.inner.head.get(), &self.inner.pos, &self.inner.dirty, 0);
+        self.inner.rewind_back();
+
+        if self.inner.decommit.get() {
+            common::decommit_inner(&self.1, self.inner.head.get(), &self.inner.dirty);
+        }
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.clear();
+
+        self.next_generation();
+        self.inner.observers.notify(ClearPhase::After);
+
+        Ok(())
+    }
+
+    /// Register a callback to invoke just before and after the arena is cleared.
+    ///
+    /// This allows caches keyed by the contents of the arena to invalidate themselves. The
+    /// callbacks are invoked on successful calls to `clear` and `clear_force`, in the order
+    /// they have been registered, and stay registered for the lifetime of the arena.
+    pub fn on_clear<F>(&self, callback: F)
+    where
+        F: Fn(ClearPhase) + 'static,
+    {
+        self.inner.observers.add(callback);
+    }
+
+    /// Start a new generation of the arena after clearing it, making older references stale.
+    fn next_generation(&self) {
+        let generation = self.inner.generation.get() + 1;
+
+        self.inner.generation.set(generation);
+        self.0.generation.set(generation);
+    }
+
+    /// Clear the front end of the arena, keeping the allocations made from the back.
+    ///
+    /// This fails if any reference to the arena allocating from the front is still live, or the
+    /// arena is frozen.
+    pub fn clear_front(&self) -> Result<(), ArenaError> {
+        let front_refs = Rc::strong_count(&self.inner) - self.inner.back_refs.get();
+
+        if front_refs == 1 && self.inner.frozen.get().is_none() {
+            #[cfg(feature = "trace")]
+            self.inner.tracer.clear(self.inner.pos.get());
+
+            common::rewind_inner(self.inner.head.get(), &self.inner.pos, &self.inner.dirty, 0);
+
+            Ok(())
+        } else {
+            Err(ArenaError::CannotClear {
+                live_handles: front_refs - 1,
+            })
+        }
+    }
+
+    /// Clear the back end of the arena, keeping the allocations made from the front.
+    ///
+    /// This fails if any reference to the arena allocating from the back is still live, or the
+    /// arena is sealed.
+    pub fn clear_back(&self) -> Result<(), ArenaError> {
+        if self.inner.back_refs.get() == 0 && !self.inner.sealed.get() {
+            #[cfg(feature = "trace")]
+            self.inner
+                .tracer
+                .clear(self.inner.cap.get() - self.inner.back.get());
+
+            self.inner.rewind_back();
+
+            Ok(())
+        } else {
+            Err(ArenaError::CannotClear {
+                live_handles: self.inner.back_refs.get(),
+            })
+        }
+    }
+
+    /// Return a report of the allocations made from the arena since its creation or the last
+    /// clear, grouped by type.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> StatsReport {
+        self.inner.stats.report()
+    }
+
+    /// Return the id identifying the arena in tracing events.
+    #[cfg(feature = "trace")]
+    pub fn id(&self) -> u64 {
+        self.inner.tracer.id()
+    }
+
+    /// Grow the capacity of the arena by `additional` bytes.
+    ///
+    /// If the arena is backed by a memory mapping and the address space following it is unused,
+    /// the mapping is extended in place. Otherwise, the arena has to be moved to a larger
+    /// mapping (or allocation), which is only possible if no objects allocated from the arena are
+    /// live and the arena isn't frozen, and clears the arena. Shared memory arenas can't be grown.
+    ///
+    /// Since allocations from the back are placed at the end of the arena, the arena is only
+    /// grown in place if there are none.
+    pub fn grow(&self, additional: usize) -> Result<(), ArenaError> {
+        let head = self.inner.head.get();
+        let cap = self.inner.cap.get();
+        let new_cap = cap
+            .checked_add(additional)
+            .ok_or(ArenaError::CapacityOverflow)?;
+
+        if self.inner.back.get() == cap && self.1.grow_in_place(head, cap, new_cap) {
+            self.inner.cap.set(new_cap);
+            self.inner.back.set(new_cap);
+            return Ok(());
+        }
+
+        if Rc::strong_count(&self.inner) != 1
+            || self.inner.frozen.get().is_some()
+            || !self.1.is_movable()
+        {
+            return Err(ArenaError::CannotGrow);
+        }
+
+        let new_head = self.1.try_create(new_cap)?;
+
+        self.1.destroy(head, cap);
+
+        // no object has been allocated from the new memory yet.
+        self.inner.head.set(new_head);
+        self.inner.cap.set(new_cap);
+        self.inner.back.set(new_cap);
+        self.inner.pos.set(0);
+        self.inner
+            .dirty
+            .set(if self.1.is_zeroed() { 0 } else { new_cap });
+
+        Ok(())
+    }
+
+    /// Change the capacity of the arena to `new_cap` bytes, keeping its contents.
+    ///
+    /// The new capacity is rounded up to a multiple of the page size, and has to fit all
+    /// allocations made from either end of the arena. Growing the arena extends its memory in
+    /// place if possible (see `grow`), otherwise the arena is moved to new memory of the
+    /// requested size, and its contents are copied over, with allocations from the back moving
+    /// to the new end of the arena. This is only possible if no references to the arena are
+    /// live and the arena isn't frozen, since objects allocated from it change their address.
+    pub fn resize(&self, new_cap: usize) -> Result<(), ArenaError> {
+        let new_cap = common::round_capacity(new_cap)?;
+        let head = self.inner.head.get();
+        let cap = self.inner.cap.get();
+        let pos = self.inner.pos.get();
+        let back_len = cap - self.inner.back.get();
+
+        if new_cap == cap {
+            return Ok(());
+        }
+
+        if Rc::strong_count(&self.inner) != 1
+            || self.inner.frozen.get().is_some()
+            || !self.1.is_movable()
+        {
+            return Err(ArenaError::CannotGrow);
+        }
+
+        if pos + back_len > new_cap {
+            return Err(common::capacity_exceeded(pos + back_len, 0, new_cap));
+        }
+
+        if new_cap > cap && back_len == 0 && self.1.grow_in_place(head, cap, new_cap) {
+            self.inner.cap.set(new_cap);
+            self.inner.back.set(new_cap);
+            return Ok(());
+        }
+
+        let new_head = self.1.try_create(new_cap)?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(head.as_ptr(), new_head.as_ptr(), pos);
+            ptr::copy_nonoverlapping(
+                head.as_ptr().add(cap - back_len),
+                new_head.as_ptr().add(new_cap - back_len),
+                back_len,
+            );
+        }
+
+        self.1.destroy(head, cap);
+
+        self.inner.head.set(new_head);
+        self.inner.cap.set(new_cap);
+        self.inner.back.set(new_cap - back_len);
+        self.inner
+            .dirty
+            .set(if self.1.is_zeroed() && back_len == 0 {
+                pos
+            } else {
+                new_cap
+            });
+
+        Ok(())
+    }
+
+    /// Make the memory allocated from the arena so far read-only, until the returned guard is
+    /// dropped.
+    ///
+    /// Writes to the frozen memory fault, which makes sure that data structures built in the
+    /// arena are no longer modified, for instance through interior mutability. Since memory is
+    /// protected in whole pages, the arena position is advanced to the next page boundary, and
+    /// allocations made while the arena is frozen land in writable memory after the frozen part.
+    /// The frozen memory can't be released while the arena is frozen, so the arena can't be
+    /// cleared, and memory released by dropping (or shrinking) objects in it isn't reused.
+    /// Note that destructors modifying the objects they drop can't run on frozen objects either.
+    ///
+    /// Returns `ArenaError::AlreadyLocked` if the arena is frozen already, and
+    /// `ArenaError::CannotProtect` if the memory protection can't be changed.
+    pub fn freeze(&self) -> Result<Frozen<'_>, ArenaError> {
+        if self.inner.frozen.get().is_some() {
+            return Err(ArenaError::AlreadyLocked);
+        }
+
+        let pos = self.inner.pos.get();
+        let len = common::protected_len(pos, self.inner.back.get());
+
+        if len > 0 && !common::protect_pages(self.inner.head.get(), len, true) {
+            return Err(ArenaError::CannotProtect);
+        }
+
+        self.inner.pos.set(cmp::max(pos, len));
+        self.inner.frozen.set(Some(len));
+
+        Ok(Frozen { arena: self })
+    }
+
+    /// Return `true` if the arena is frozen.
+    pub fn is_frozen(&self) -> bool {
+        self.inner.frozen.get().is_some()
+    }
+
+    /// Make the arena memory read-only for good, and seal the file backing it against
+    /// modification, returning its file descriptor.
+    ///
+    /// This is only possible for arenas created with `ArenaBacking::SealableMemoryMap`. The
+    /// descriptor stays valid while the arena is live, and can be passed to other processes,
+    /// which can map the arena memory using `SharedMapping::from_sealed_fd`. Sealing the arena
+    /// freezes all of it (see `freeze`), so it can neither be allocated from nor cleared
+    /// afterwards, and writes to it fault.
+    ///
+    /// Returns `ArenaError::AlreadyLocked` if the arena is frozen already, and
+    /// `ArenaError::CannotProtect` if the arena isn't sealable, or sealing fails.
+    #[cfg(target_os = "linux")]
+    pub fn seal(&self) -> Result<RawFd, ArenaError> {
+        if self.inner.frozen.get().is_some() {
+            return Err(ArenaError::AlreadyLocked);
+        }
+
+        if !matches!(self.1, ArenaBacking::SealableMemoryMap) {
+            return Err(ArenaError::CannotProtect);
+        }
+
+        let cap = self.inner.cap.get();
+        let fd =
+            common::seal_mapping(self.inner.head.get(), cap).ok_or(ArenaError::CannotProtect)?;
+
+        // the position is moved to the back end, so that nothing can be allocated anymore.
+        self.inner.pos.set(self.inner.back.get());
+        self.inner.frozen.set(Some(cap));
+        self.inner.sealed.set(true);
+
+        Ok(fd)
+    }
+
+    /// Make the frozen part of the arena writable again.
+    ///
+    /// Sealed arenas stay read-only. Returns `ArenaError::CannotProtect` if the memory protection
+    /// can't be changed, in which case the arena stays frozen.
+    fn unfreeze(&self) -> Result<(), ArenaError> {
+        if self.inner.sealed.get() {
+            return Ok(());
+        }
+
+        if let Some(len) = self.inner.frozen.take() {
+            if len > 0 && !common::protect_pages(self.inner.head.get(), len, false) {
+                self.inner.frozen.set(Some(len));
+                return Err(ArenaError::CannotProtect);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Return the total capacity of the arena.
+    pub fn capacity(&self) -> usize {
+        self.inner.cap.get()
+    }
+
+    /// Return the number of bytes allocated from both ends of the arena, including alignment
+    /// padding.
+    pub fn used(&self) -> usize {
+        self.inner.pos.get() + (self.inner.cap.get() - self.inner.back.get())
+    }
+
+    /// Set whether the physical memory backing the arena is returned to the operating system when
+    /// the arena is cleared.
+    ///
+    /// This keeps bursts of usage from staying resident for the lifetime of the arena, at the
+    /// cost of page faults when the memory is used again. Only arenas backed by private memory
+    /// mappings (or custom backings supporting it) release their memory, for other backings
+    /// this has no effect.
+    pub fn set_decommit_on_clear(&self, decommit: bool) {
+        self.inner.decommit.set(decommit);
+    }
+
+    /// Set the quota limiting the memory handed out by the arena, replacing the previous one.
+    pub fn set_quota(&self, quota: Quota) {
+        *self.inner.quota.borrow_mut() = quota;
+    }
+
+    /// Return a marker for the current position in the arena.
+    pub fn mark(&self) -> Marker {
+        Marker {
+            pos: self.inner.pos.get(),
+        }
+    }
+
+    /// Rewind the arena to a previously obtained marker, releasing all memory allocated since.
+    ///
+    /// Panics if the arena has been cleared or rewound past the marker in the meantime, or if
+    /// the marker lies in the frozen part of the arena.
+    ///
+    /// # Safety
+    ///
+    /// No memory allocated after the marker has been obtained may be accessed afterwards. This
+    /// includes memory that objects allocated before have grown into, and implies that all
+    /// objects allocated since have to be dropped or forgotten.
+    pub unsafe fn reset_to(&self, marker: Marker) {
+        assert!(
+            marker.pos <= self.inner.pos.get(),
+            "marker lies beyond the arena position"
+        );
+        assert!(
+            marker.pos >= self.inner.frozen_len(),
+            "marker lies in the frozen part of the arena"
+        );
+
+        common::rewind_inner(
+            self.inner.head.get(),
+            &self.inner.pos,
+            &self.inner.dirty,
+            marker.pos,
+        );
+    }
+
+    /// Write the memory allocated from the arena, along with its position and capacity, to
+    /// `writer`.
+    ///
+    /// The arena can be recreated from the output using `load_from`. Since the memory is written
+    /// byte by byte, objects in the arena can only be used after loading if they don't contain
+    /// pointers (including those into the arena). Structures meant to be serialized should refer
+    /// to each other using `ArenaOffset`s or `ArenaPtr32`s instead.
+    pub fn serialize_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let head = self.inner.head.get();
+        let cap = self.inner.cap.get();
+        let pos = self.inner.pos.get();
+        let back = self.inner.back.get();
+
+        writer.write_all(SERIALIZED_MAGIC)?;
+
+        for &field in &[SERIALIZED_VERSION, cap as u64, pos as u64, back as u64] {
+            writer.write_all(&field.to_le_bytes())?;
+        }
+
+        unsafe {
+            writer.write_all(std::slice::from_raw_parts(head.as_ptr(), pos))?;
+            writer.write_all(std::slice::from_raw_parts(
+                head.as_ptr().add(back),
+                cap - back,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Create an arena from the output of `serialize_to` read from `reader`.
+    ///
+    /// The arena is created with the given backing and the capacity of the serialized arena,
+    /// and all objects allocated from the serialized arena are located at the same offsets from
+    /// its start. Fails with `io::ErrorKind::InvalidData` if the input isn't a serialized arena.
+    pub fn load_from<R: Read>(backing: ArenaBacking, mut reader: R) -> io::Result<Self> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != SERIALIZED_MAGIC {
+            return Err(invalid("not a serialized arena"));
+        }
+
+        let mut fields = [0; 4];
+
+        for field in fields.iter_mut() {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+            *field = u64::from_le_bytes(bytes);
+        }
+
+        let [version, cap, pos, back] = fields;
+
+        if version != SERIALIZED_VERSION {
+            return Err(invalid("unsupported serialized arena version"));
+        }
+
+        let cap = usize::try_from(cap).map_err(|_| invalid("arena capacity out of range"))?;
+        let pos = usize::try_from(pos).map_err(|_| invalid("arena position out of range"))?;
+        let back = usize::try_from(back).map_err(|_| invalid("arena position out of range"))?;
+
+        if pos > back || back > cap {
+            return Err(invalid("arena position out of range"));
         }
+
+        let arena = Arena::init_capacity(backing, cap)
+            .map_err(|err| io::Error::other(format!("{:?}", err)))?;
+        let head = arena.inner.head.get();
+
+        unsafe {
+            reader.read_exact(std::slice::from_raw_parts_mut(head.as_ptr(), pos))?;
+            reader.read_exact(std::slice::from_raw_parts_mut(
+                head.as_ptr().add(back),
+                cap - back,
+            ))?;
+        }
+
+        arena.inner.pos.set(pos);
+        arena.inner.back.set(back);
+        arena
+            .inner
+            .dirty
+            .set(cmp::max(arena.inner.dirty.get(), pos));
+
+        Ok(arena)
+    }
+
+    /// Take a snapshot of the contents of the arena and its position.
+    ///
+    /// This copies all memory allocated from the arena so far, so that speculative computations
+    /// can be undone using `restore`.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::take(self.inner.head.get(), self.inner.pos.get())
+    }
+
+    /// Restore the contents of the arena and its position from a snapshot.
+    ///
+    /// Panics if the snapshot has been taken from another arena, or if the arena is frozen.
+    ///
+    /// # Safety
+    ///
+    /// The contents of the arena are restored byte by byte, which is only sound if all objects
+    /// live afterwards have been live (and located in the same memory) when the snapshot was
+    /// taken, and their state outside of the arena is consistent with the restored contents.
+    /// Notably, this does not hold for most containers, which keep their length outside of the
+    /// arena, or for objects owning resources outside of the arena. Memory allocated after the
+    /// snapshot has been taken may not be accessed afterwards.
+    pub unsafe fn restore(&self, snapshot: &Snapshot) {
+        assert!(!self.is_frozen(), "can't restore a frozen arena");
+
+        let pos = snapshot.restore(self.inner.head.get());
+
+        common::rewind_inner(
+            self.inner.head.get(),
+            &self.inner.pos,
+            &self.inner.dirty,
+            pos,
+        );
     }
 }
 
@@ -108,29 +993,594 @@ impl Deref for Arena {
 
 impl Drop for Arena {
     fn drop(&mut self) {
-        match self.1 {
-            ArenaBacking::MemoryMap => {
-                common::destroy_mapping(self.inner.head, self.inner.cap);
+        // memory handed back to the system allocator has to be writable, so it is leaked if it
+        // can't be made writable again.
+        if self.unfreeze().is_ok() {
+            self.1.destroy(self.inner.head.get(), self.inner.cap.get());
+        }
+    }
+}
+
+impl Deref for SubArena {
+    type Target = Arena;
+
+    fn deref(&self) -> &Self::Target {
+        &self.arena
+    }
+}
+
+impl InnerRef {
+    /// Return a pointer to the start of the arena memory, which `ArenaOffset`s are relative to.
+    pub fn base(&self) -> NonNull<u8> {
+        self.inner.head.get()
+    }
+
+    /// Return `true` if the arena has been force-cleared since the reference has been created.
+    pub fn is_stale(&self) -> bool {
+        self.generation.get() != self.inner.generation.get()
+    }
+
+    /// Create a reference to the arena which can allocate at most `budget` bytes.
+    pub fn child(&self, budget: usize) -> ChildRef {
+        ChildRef {
+            inner: self.clone(),
+            budget: Rc::new(Cell::new(budget)),
+        }
+    }
+
+    /// Refuse to allocate through a stale reference.
+    fn check_generation(&self) -> Result<(), ArenaError> {
+        if self.is_stale() {
+            Err(ArenaError::StaleHandle)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl ChildRef {
+    /// Return the number of bytes the reference can still allocate.
+    pub fn remaining(&self) -> usize {
+        self.budget.get()
+    }
+
+    /// Take `size` bytes from the budget, if possible.
+    fn charge(&self, size: usize) -> Result<(), ArenaError> {
+        let budget = self.budget.get();
+        let remaining = budget.checked_sub(size).ok_or(ArenaError::QuotaExceeded {
+            requested: size,
+            remaining: budget,
+        })?;
+
+        self.budget.set(remaining);
+
+        Ok(())
+    }
+
+    /// Give `size` bytes back to the budget.
+    fn credit(&self, size: usize) {
+        self.budget.set(self.budget.get() + size);
+    }
+
+    /// Charge the budget with `size` bytes for the allocation made by `allocate`, which is
+    /// credited back if the allocation fails.
+    fn charged<R, F>(&self, size: usize, allocate: F) -> Result<R, ArenaError>
+    where
+        F: FnOnce() -> Result<R, ArenaError>,
+    {
+        self.charge(size)?;
+
+        allocate().inspect_err(|_| self.credit(size))
+    }
+}
+
+impl BackRef {
+    /// Return `true` if the arena has been force-cleared since the reference has been created.
+    pub fn is_stale(&self) -> bool {
+        self.generation != self.inner.generation.get()
+    }
+
+    /// Refuse to allocate through a stale reference.
+    fn check_generation(&self) -> Result<(), ArenaError> {
+        if self.is_stale() {
+            Err(ArenaError::StaleHandle)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'a> Frozen<'a> {
+    /// Keep the arena frozen for the rest of its lifetime.
+    pub fn leak(self) {
+        mem::forget(self);
+    }
+
+    /// Make the arena writable again, like dropping the guard does.
+    ///
+    /// Returns `ArenaError::CannotProtect` if the memory protection can't be changed, in which
+    /// case the arena stays frozen.
+    pub fn unfreeze(self) -> Result<(), ArenaError> {
+        let arena = self.arena;
+        mem::forget(self);
+
+        arena.unfreeze()
+    }
+}
+
+impl<'a> Drop for Frozen<'a> {
+    fn drop(&mut self) {
+        // the arena stays frozen if its memory can't be made writable, see `unfreeze`.
+        let _ = self.arena.unfreeze();
+    }
+}
+
+impl Inner {
+    /// Release all allocations made from the back end of the arena space.
+    fn rewind_back(&self) {
+        let back = self.back.get();
+        let cap = self.cap.get();
+
+        if back < cap {
+            // the dirty range only extends from the front, so it has to cover the back end now.
+            self.dirty.set(cap);
+            self.back.set(cap);
+
+            #[cfg(feature = "sanitize")]
+            unsafe {
+                let ptr = self.head.get().as_ptr().add(back);
+                std::ptr::write_bytes(ptr, common::POISON, cap - back);
             }
-            ArenaBacking::SystemAllocation => {
-                common::destroy_mapping_alloc(self.inner.head, self.inner.cap);
+        }
+    }
+
+    /// Return the length of the write-protected prefix of the arena space.
+    fn frozen_len(&self) -> usize {
+        self.frozen.get().unwrap_or(0)
+    }
+}
+
+unsafe impl AllocHandle for InnerRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+
+        let ptr = common::with_quota(
+            &self.inner.quota,
+            &self.inner.pos,
+            self.inner.back.get(),
+            |cap| common::try_allocate_inner(self.inner.head.get(), &self.inner.pos, cap, count),
+        )?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(count);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.allocate::<T>(count);
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+
+        let new_ptr = common::with_quota(
+            &self.inner.quota,
+            &self.inner.pos,
+            self.inner.back.get(),
+            |cap| {
+                common::try_allocate_or_extend_inner(
+                    self.inner.head.get(),
+                    &self.inner.pos,
+                    cap,
+                    ptr,
+                    old_count,
+                    count,
+                )
+            },
+        )?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(if new_ptr == ptr {
+            count.saturating_sub(old_count)
+        } else {
+            count
+        });
+
+        #[cfg(feature = "trace")]
+        self.inner
+            .tracer
+            .extend::<T>(old_count, count, new_ptr == ptr);
+
+        Ok(new_ptr)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+
+        let ptr = common::with_quota(
+            &self.inner.quota,
+            &self.inner.pos,
+            self.inner.back.get(),
+            |cap| {
+                common::try_allocate_zeroed_inner(
+                    self.inner.head.get(),
+                    &self.inner.pos,
+                    &self.inner.dirty,
+                    cap,
+                    count,
+                )
+            },
+        )?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(count);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.allocate::<T>(count);
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.check_generation()?;
+
+        let ptr = common::with_quota(
+            &self.inner.quota,
+            &self.inner.pos,
+            self.inner.back.get(),
+            |cap| {
+                common::allocate_layout_inner(self.inner.head.get(), &self.inner.pos, cap, layout)
+                    .ok_or_else(|| {
+                        common::capacity_exceeded(layout.size(), self.inner.pos.get(), cap)
+                    })
+            },
+        )?;
+
+        #[cfg(feature = "stats")]
+        self.inner
+            .stats
+            .record_bytes(stats::LAYOUT_ALLOCATIONS, layout.size());
+
+        #[cfg(feature = "trace")]
+        self.inner
+            .tracer
+            .allocate_bytes(trace::LAYOUT_ALLOCATIONS, layout.size());
+
+        Ok(ptr)
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        // the allocation has been released by a forced clear already.
+        if self.is_stale() {
+            return false;
+        }
+
+        let size = count * mem::size_of::<T>();
+
+        match common::last_allocation_offset(
+            self.inner.head.get(),
+            self.inner.pos.get(),
+            ptr.cast(),
+            size,
+        ) {
+            Some(offset) if offset >= self.inner.frozen_len() => {
+                common::rewind_inner(
+                    self.inner.head.get(),
+                    &self.inner.pos,
+                    &self.inner.dirty,
+                    offset,
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        if self.is_stale() {
+            return false;
+        }
+
+        let size = mem::size_of::<T>();
+        let head = self.inner.head.get();
+
+        if let Some(offset) =
+            common::last_allocation_offset(head, self.inner.pos.get(), ptr.cast(), old_count * size)
+        {
+            let end = offset + count * size;
+
+            if end >= self.inner.frozen_len() {
+                common::rewind_inner(
+                    self.inner.head.get(),
+                    &self.inner.pos,
+                    &self.inner.dirty,
+                    end,
+                );
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        common::owns_inner(self.inner.head.get(), self.inner.cap.get(), ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(Rc::as_ptr(&self.inner) as usize)
+    }
+
+    fn is_stale(&self) -> bool {
+        Self::is_stale(self)
+    }
+}
+
+impl Clone for BackRef {
+    fn clone(&self) -> Self {
+        self.inner.back_refs.set(self.inner.back_refs.get() + 1);
+
+        BackRef {
+            inner: self.inner.clone(),
+            generation: self.generation,
+        }
+    }
+}
+
+impl Drop for BackRef {
+    fn drop(&mut self) {
+        self.inner.back_refs.set(self.inner.back_refs.get() - 1);
+    }
+}
+
+unsafe impl AllocHandle for BackRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+
+        let layout = Layout::array::<T>(count).map_err(|_| ArenaError::CapacityOverflow)?;
+        let ptr = common::allocate_back_inner(
+            self.inner.head.get(),
+            &self.inner.back,
+            self.inner.pos.get(),
+            layout,
+        )
+        .ok_or_else(|| {
+            common::capacity_exceeded(layout.size(), self.inner.pos.get(), self.inner.back.get())
+        })?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(count);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.allocate::<T>(count);
+
+        Ok(ptr.cast())
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        _ptr: NonNull<T>,
+        _old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        // allocations from the back grow downwards, so they can't be extended in place.
+        self.try_allocate(count)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.check_generation()?;
+
+        let ptr = common::allocate_back_inner(
+            self.inner.head.get(),
+            &self.inner.back,
+            self.inner.pos.get(),
+            layout,
+        )
+        .ok_or_else(|| {
+            common::capacity_exceeded(layout.size(), self.inner.pos.get(), self.inner.back.get())
+        })?;
+
+        #[cfg(feature = "stats")]
+        self.inner
+            .stats
+            .record_bytes(stats::LAYOUT_ALLOCATIONS, layout.size());
+
+        #[cfg(feature = "trace")]
+        self.inner
+            .tracer
+            .allocate_bytes(trace::LAYOUT_ALLOCATIONS, layout.size());
+
+        Ok(ptr)
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let offset = (ptr.as_ptr() as usize).wrapping_sub(self.inner.head.get().as_ptr() as usize);
+        let size = count * mem::size_of::<T>();
+
+        if size > 0 && !self.is_stale() && offset == self.inner.back.get() {
+            self.inner.dirty.set(self.inner.cap.get());
+            self.inner.back.set(offset + size);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        common::owns_inner(self.inner.head.get(), self.inner.cap.get(), ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(Rc::as_ptr(&self.inner) as usize)
+    }
+
+    fn is_stale(&self) -> bool {
+        Self::is_stale(self)
+    }
+}
+
+impl ArenaBase for InnerRef {
+    fn base(&self) -> NonNull<u8> {
+        self.inner.head.get()
+    }
+}
+
+unsafe impl AllocHandle for ChildRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let size = Layout::array::<T>(count)
+            .map_err(|_| ArenaError::CapacityOverflow)?
+            .size();
+
+        self.charged(size, || self.inner.try_allocate(count))
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let size = Layout::array::<T>(count)
+            .map_err(|_| ArenaError::CapacityOverflow)?
+            .size();
+        let old_size = old_count * mem::size_of::<T>();
+        let extra = size.saturating_sub(old_size);
+
+        // an allocation extended in place has been charged with its old size already.
+        let new_ptr = self.charged(extra, || {
+            self.inner.try_allocate_or_extend(ptr, old_count, count)
+        })?;
+
+        if new_ptr != ptr {
+            if let Err(err) = self.charge(size - extra) {
+                // the moved allocation is the last one, so it can be handed back.
+                unsafe { self.inner.dealloc_last(new_ptr, count) };
+                self.credit(extra);
+
+                return Err(err);
             }
         }
+
+        Ok(new_ptr)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let size = Layout::array::<T>(count)
+            .map_err(|_| ArenaError::CapacityOverflow)?
+            .size();
+
+        self.charged(size, || self.inner.try_allocate_zeroed(count))
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.charged(layout.size(), || self.inner.try_allocate_layout(layout))
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let released = self.inner.dealloc_last(ptr, count);
+
+        if released {
+            self.credit(count * mem::size_of::<T>());
+        }
+
+        released
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let released = self.inner.allocate_or_shrink(ptr, old_count, count);
+
+        if released {
+            self.credit((old_count - count) * mem::size_of::<T>());
+        }
+
+        released
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.inner.owns(ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        self.inner.arena_id()
+    }
+
+    fn is_stale(&self) -> bool {
+        self.inner.is_stale()
+    }
+}
+
+impl ArenaBase for ChildRef {
+    fn base(&self) -> NonNull<u8> {
+        self.inner.base()
+    }
+}
+
+impl ArenaBase for BackRef {
+    fn base(&self) -> NonNull<u8> {
+        self.inner.head.get()
     }
 }
 
-impl AllocHandle for InnerRef {
-    fn allocate<T>(&self, count: usize) -> NonNull<T> {
-        common::allocate_inner(self.inner.head, &self.inner.pos, self.inner.cap, count)
+#[cfg(feature = "allocator_api")]
+unsafe impl Allocator for InnerRef {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.try_allocate_layout(layout).map_err(|_| AllocError)?;
+
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
     }
 
-    fn allocate_or_extend<T>(&self, ptr: NonNull<T>, old_count: usize, count: usize) -> NonNull<T> {
-        common::allocate_or_extend_inner(
-            self.inner.head,
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = common::with_quota(
+            &self.inner.quota,
             &self.inner.pos,
-            self.inner.cap,
-            ptr,
-            old_count,
-            count)
+            self.inner.back.get(),
+            |cap| {
+                common::allocate_or_extend_layout_inner(
+                    self.inner.head.get(),
+                    &self.inner.pos,
+                    cap,
+                    ptr,
+                    old_layout.size(),
+                    new_layout,
+                )
+                .ok_or_else(|| {
+                    common::capacity_exceeded(new_layout.size(), self.inner.pos.get(), cap)
+                })
+            },
+        )
+        .map_err(|_| AllocError)?;
+
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.grow(ptr, old_layout, new_layout)
     }
 }
@@ -9,10 +9,49 @@
 //! lifetimes, for instance if the arena-allocated objects have dynamic lifetimes depending on user
 //! input or other factors only known at runtime. In such cases the reference-counted arena found
 //! in the `rc` module might be a better fit.
-use crate::common::{self, AllocHandle, ArenaBacking, ArenaError};
+//!
+//! Objects moved into the arena using `ArenaToken::alloc` are recorded in a drop list if their
+//! type needs to be dropped, and their destructors are run in reverse allocation order when the
+//! generation ends (or the arena is rewound past them). Since a destructor could otherwise
+//! observe objects dropped before it, such objects have to be `'static`, and can't borrow from
+//! the arena.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{
+    self, AllocHandle, ArenaBacking, ArenaError, ClearObservers, ClearPhase, Marker, Quota,
+    Snapshot,
+};
+use crate::compact::ArenaBase;
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+#[cfg(feature = "stats")]
+use crate::stats::{self, StatsReport};
+#[cfg(feature = "trace")]
+use crate::trace::{self, Tracer};
+use crate::tree;
+use crate::vecmap;
 
-use std::cell::Cell;
-use std::ptr::NonNull;
+use std::alloc::Layout;
+#[cfg(feature = "allocator_api")]
+use std::alloc::{AllocError, Allocator};
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::str;
 
 /// A statically checked arena (non-MT-safe).
 ///
@@ -26,19 +65,102 @@ use std::ptr::NonNull;
 #[derive(Debug)]
 pub struct Arena {
     /// Head of the arena space
-    head: NonNull<u8>,
+    head: Cell<NonNull<u8>>,
 
     /// Offset into the last region
     pos: Cell<usize>,
 
+    /// Offset up to which the arena space may have been written to before the last rewind
+    dirty: Cell<usize>,
+
+    /// Limits on the memory handed out by the arena
+    quota: RefCell<Quota>,
+
+    /// Allocation statistics since the last clear
+    #[cfg(feature = "stats")]
+    stats: stats::Recorder,
+
+    /// Allocation tracing state
+    #[cfg(feature = "trace")]
+    tracer: Tracer,
+
     /// Total capacity of the arena
-    cap: usize,
+    cap: Cell<usize>,
 
     /// The type of backing storage used in the arena
     backing: ArenaBacking,
 
     /// Whether an exclusive allocation token has been handed out
     locked: Cell<bool>,
+
+    /// Whether to release the physical memory backing the arena when a generation ends
+    decommit: Cell<bool>,
+
+    /// The last object allocated using `ArenaToken::alloc` that has to be dropped
+    drops: Cell<Option<NonNull<DropEntry>>>,
+
+    /// Callbacks to invoke when a generation ends
+    observers: ClearObservers,
+
+    /// Memory usage of the current (or last) generation
+    usage: Cell<GenerationUsage>,
+
+    /// Whether to allocate additional chunks once the current one is exhausted
+    growable: Cell<bool>,
+
+    /// The number of generations that have ended
+    generation: Cell<u64>,
+
+    /// Chunks exhausted during the current generation, the oldest first
+    retired: RefCell<Vec<Chunk>>,
+
+    /// Position of the current chunk in the generation, the total capacity of the retired chunks
+    offset: Cell<usize>,
+}
+
+/// A chunk of memory exhausted during the current generation of a growable arena.
+#[derive(Debug)]
+struct Chunk {
+    /// Head of the chunk
+    head: NonNull<u8>,
+
+    /// Offset into the chunk when it has been exhausted
+    pos: usize,
+
+    /// Offset up to which the chunk may have been written to
+    dirty: usize,
+
+    /// Capacity of the chunk
+    cap: usize,
+}
+
+/// The memory usage of a generation of objects in a region arena.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GenerationUsage {
+    /// The number of bytes allocated during the generation, including alignment padding.
+    ///
+    /// Memory released by rewinding the arena and allocated again is counted every time.
+    pub allocated: usize,
+    /// The highest number of bytes in use at any point during the generation.
+    pub peak: usize,
+}
+
+/// An entry in the list of objects to drop when a generation ends, placed in front of the
+/// object in the arena.
+#[derive(Debug)]
+struct DropEntry {
+    /// Run the destructor of the object following the entry
+    drop: unsafe fn(NonNull<DropEntry>),
+
+    /// The entry of the object registered before
+    prev: Option<NonNull<DropEntry>>,
+}
+
+/// An object allocated using `ArenaToken::alloc`, along with its drop list entry.
+#[repr(C)]
+struct Registered<T> {
+    entry: DropEntry,
+    value: T,
 }
 
 /// A proxy for an arena that actually allows allocation.
@@ -49,13 +171,44 @@ pub struct Arena {
 #[derive(Debug)]
 pub struct ArenaToken<'a> {
     inner: &'a Arena,
+
+    /// The position to rewind the arena to when dropped, if this is a child token
+    start: Option<usize>,
+}
+
+/// A region arena split into disjoint partitions, each of which is an arena in its own right.
+///
+/// Obtained using `Arena::partition`, the partitions dereference to a slice of arenas, so that
+/// independent subsystems can each take a generation token of their own partition at the same
+/// time. The parent arena is locked while the partitions are live, and cleared once they are
+/// dropped.
+#[derive(Debug)]
+pub struct Partitions<'a> {
+    /// The partitions, dropped before the parent generation ends
+    parts: Vec<Arena>,
+
+    /// The generation token of the parent arena, holding on to the partitioned memory
+    _token: ArenaToken<'a>,
 }
 
 /// A handle to the arena for the current generation.
 ///
 /// Allows for allocation, but doesn't cause the generation of objects to die when dropped.
-#[derive(Debug, Clone)]
-pub struct ArenaHandle<'a>(&'a ArenaToken<'a>);
+/// Handles are just shared references to the token, so they can be copied freely. In debug
+/// builds, they also record the generation they have been created in, so that containers
+/// smuggled out of it using unsafe code panic on access instead of reading reused memory.
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaHandle<'a> {
+    token: &'a ArenaToken<'a>,
+
+    /// The arena, which outlives the token
+    #[cfg(debug_assertions)]
+    arena: &'a Arena,
+
+    /// The id of the generation the handle has been created in
+    #[cfg(debug_assertions)]
+    generation: u64,
+}
 
 /// An arena allocated, fixed-size sequence of objects
 pub type Slice<'a, T> = common::Slice<T, ArenaHandle<'a>>;
@@ -67,25 +220,103 @@ pub type Slice<'a, T> = common::Slice<T, ArenaHandle<'a>>;
 /// for speed.
 pub type SliceVec<'a, T> = common::SliceVec<T, ArenaHandle<'a>>;
 
+/// An arena allocated, owned object
+pub type ArenaBox<'a, T> = boxed::ArenaBox<T, ArenaHandle<'a>>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'a, T> = cow::ArenaCow<'a, T, ArenaHandle<'a>>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<'a, T> = deque::ArenaVecDeque<T, ArenaHandle<'a>>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<'a, K, V> = btree::ArenaBTreeMap<K, V, ArenaHandle<'a>>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<'a, T> = heap::ArenaBinaryHeap<T, ArenaHandle<'a>>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<'a, T> = slab::ArenaSlab<T, ArenaHandle<'a>>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<'a, T> = generational::GenArena<T, ArenaHandle<'a>>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec<'a> = bitvec::ArenaBitVec<ArenaHandle<'a>>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<'a, T> = grid::ArenaGrid<T, ArenaHandle<'a>>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<'a, K, V> = vecmap::ArenaVecMap<K, V, ArenaHandle<'a>>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<'a, T> = vecmap::ArenaVecSet<T, ArenaHandle<'a>>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<'a, K, V> = hash::ArenaHashMap<K, V, ArenaHandle<'a>>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<'a, T> = hash::ArenaHashSet<T, ArenaHandle<'a>>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<'a, T> = chunkvec::ChunkedVec<T, ArenaHandle<'a>>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<'a, T> = ring::ArenaRingBuffer<T, ArenaHandle<'a>>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<'a, N, E> = graph::ArenaGraph<N, E, ArenaHandle<'a>>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<'a, T> = tree::NodeArena<T, ArenaHandle<'a>>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor<'a> = cursor::ArenaCursor<ArenaHandle<'a>>;
+
 impl Arena {
+    /// Create an `Arena` backed by a memory mapping of the default capacity.
+    ///
+    /// The capacity is `DEFAULT_CAPACITY`, unless overridden by the `ARENAVEC_DEFAULT_CAPACITY`
+    /// environment variable, so that it can be tuned without recompiling. Only address space is
+    /// reserved up front, the memory is committed on first use.
+    pub fn init() -> Result<Self, ArenaError> {
+        Self::init_capacity(ArenaBacking::MemoryMap, common::default_capacity()?)
+    }
+
     /// Create an `Arena` with specified capacity.
     ///
-    /// Capacity must be a power of 2. The capacity cannot be grown after the fact.
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. It can be
+    /// grown after the fact using `grow`, if the arena is backed by a memory mapping.
     pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
-        let head = NonNull::new(match backing {
-            ArenaBacking::MemoryMap => common::create_mapping(cap),
-            ArenaBacking::SystemAllocation => common::create_mapping_alloc(cap),
-        })
-        .ok_or(ArenaError::AllocationFailed)?;
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
         let pos = Cell::new(0);
+        let dirty = Cell::new(if backing.is_zeroed() { 0 } else { cap });
         let locked = Cell::new(false);
 
         Ok(Arena {
-            head,
+            head: Cell::new(head),
             pos,
-            cap,
+            dirty,
+            quota: RefCell::new(Quota::new()),
+            #[cfg(feature = "stats")]
+            stats: Default::default(),
+            #[cfg(feature = "trace")]
+            tracer: Default::default(),
+            cap: Cell::new(cap),
             backing,
             locked,
+            decommit: Cell::new(false),
+            drops: Cell::new(None),
+            observers: Default::default(),
+            usage: Default::default(),
+            growable: Cell::new(false),
+            generation: Cell::new(0),
+            retired: RefCell::new(Vec::new()),
+            offset: Cell::new(0),
         })
     }
 
@@ -97,60 +328,952 @@ impl Arena {
             Err(ArenaError::AlreadyLocked)
         } else {
             self.locked.set(true);
-            Ok(ArenaToken { inner: self })
+            self.usage.set(Default::default());
+
+            #[cfg(feature = "stats")]
+            self.stats.clear();
+
+            Ok(ArenaToken {
+                inner: self,
+                start: None,
+            })
         }
     }
-}
 
-impl Drop for Arena {
-    fn drop(&mut self) {
-        match self.backing {
-            ArenaBacking::MemoryMap => {
-                common::destroy_mapping(self.head, self.cap);
+    /// Split the arena into `n` partitions of equal size, each of which hands out generation
+    /// tokens independently.
+    ///
+    /// The partitions are aligned to the page size, and their size is rounded down to a multiple
+    /// of it, so the arena must provide at least one page per partition. The partitions can't be
+    /// grown, and their memory is given back to the arena when they are dropped. If a generation
+    /// of objects is currently live, an error is returned instead.
+    pub fn partition(&self, n: usize) -> Result<Partitions<'_>, ArenaError> {
+        if n == 0 {
+            return Err(ArenaError::InvalidCapacity);
+        }
+
+        let token = self.generation_token()?;
+        let page_size = common::get_page_size();
+        let bytes = self.cap.get() / n / page_size * page_size;
+
+        if bytes == 0 {
+            return Err(common::capacity_exceeded(
+                n.saturating_mul(page_size),
+                0,
+                self.cap.get(),
+            ));
+        }
+
+        let layout =
+            Layout::from_size_align(bytes, page_size).map_err(|_| ArenaError::InvalidLayout)?;
+        let mut parts = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            let base = token.try_allocate_layout(layout)?;
+
+            parts.push(Arena::init_capacity(
+                common::Carved::backing(base, bytes),
+                bytes,
+            )?);
+        }
+
+        Ok(Partitions {
+            parts,
+            _token: token,
+        })
+    }
+
+    /// Return a report of the allocations made from the arena in the current (or last)
+    /// generation, grouped by type.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> StatsReport {
+        self.stats.report()
+    }
+
+    /// Return the id of the current (or next) generation, which is the number of generations
+    /// that have ended before it.
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    /// Return the memory usage of the current (or last) generation.
+    ///
+    /// Once a generation has ended, this reports its totals until the next generation token is
+    /// handed out, so per-generation consumption can be logged after the fact.
+    pub fn usage(&self) -> GenerationUsage {
+        self.usage.get()
+    }
+
+    /// Return the id identifying the arena in tracing events.
+    #[cfg(feature = "trace")]
+    pub fn id(&self) -> u64 {
+        self.tracer.id()
+    }
+
+    /// Grow the capacity of the arena by `additional` bytes.
+    ///
+    /// If the arena is backed by a memory mapping and the address space following it is unused,
+    /// the mapping is extended in place. Otherwise, the arena has to be moved to a larger
+    /// mapping (or allocation), which is only possible if no generation token is live, and clears
+    /// the arena. Shared memory arenas can't be grown.
+    pub fn grow(&self, additional: usize) -> Result<(), ArenaError> {
+        let head = self.head.get();
+        let cap = self.cap.get();
+        let new_cap = cap
+            .checked_add(additional)
+            .ok_or(ArenaError::CapacityOverflow)?;
+
+        if self.backing.grow_in_place(head, cap, new_cap) {
+            self.cap.set(new_cap);
+            return Ok(());
+        }
+
+        if self.locked.get() || !self.backing.is_movable() {
+            return Err(ArenaError::CannotGrow);
+        }
+
+        let new_head = self.backing.try_create(new_cap)?;
+
+        self.backing.destroy(head, cap);
+
+        // no object has been allocated from the new memory yet.
+        self.head.set(new_head);
+        self.cap.set(new_cap);
+        self.pos.set(0);
+        self.dirty
+            .set(if self.backing.is_zeroed() { 0 } else { new_cap });
+
+        Ok(())
+    }
+
+    /// Return the total capacity of the arena, including the chunks exhausted during the
+    /// current generation.
+    pub fn capacity(&self) -> usize {
+        self.offset.get() + self.cap.get()
+    }
+
+    /// Return the number of chunks the arena currently consists of.
+    pub fn chunk_count(&self) -> usize {
+        self.retired.borrow().len() + 1
+    }
+
+    /// Return a pointer to the start of the arena memory, which `ArenaOffset`s are relative to.
+    ///
+    /// If the arena has grown into additional chunks during the current generation, this is the
+    /// start of the first one.
+    pub fn base(&self) -> NonNull<u8> {
+        self.retired
+            .borrow()
+            .first()
+            .map_or(self.head.get(), |chunk| chunk.head)
+    }
+
+    /// Set whether the arena allocates an additional chunk of memory once it is exhausted,
+    /// instead of failing with `ArenaError::CapacityExceeded`.
+    ///
+    /// Each chunk is at least twice the size of the previous one, and allocations are contiguous
+    /// within a chunk only. The exhausted chunks are released when the generation ends, keeping
+    /// the last (and largest) one, so an arena filled to a similar extent every generation
+    /// settles on a single chunk. Quotas are enforced within each chunk. Objects allocated in
+    /// later chunks don't lie within the memory starting at `base`, so offset-based references
+    /// such as `ArenaPtr32` can't be used for them. Shared memory arenas can't grow this way.
+    pub fn set_growable(&self, growable: bool) {
+        self.growable.set(growable);
+    }
+
+    /// Set whether the physical memory backing the arena is returned to the operating system when
+    /// a generation ends.
+    ///
+    /// This keeps bursts of usage from staying resident for the lifetime of the arena, at the
+    /// cost of page faults when the memory is used again. Only arenas backed by private memory
+    /// mappings (or custom backings supporting it) release their memory, for other backings
+    /// this has no effect.
+    pub fn set_decommit_on_clear(&self, decommit: bool) {
+        self.decommit.set(decommit);
+    }
+
+    /// Register a callback to invoke just before and after a generation ends.
+    ///
+    /// The callbacks are invoked when an allocation token is dropped, before the objects
+    /// registered using `ArenaToken::alloc` are dropped and after the arena has been cleared, in
+    /// the order they have been registered. They stay registered for the lifetime of the arena.
+    pub fn on_clear<F>(&self, callback: F)
+    where
+        F: Fn(ClearPhase) + 'static,
+    {
+        self.observers.add(callback);
+    }
+
+    /// Set the quota limiting the memory handed out by the arena, replacing the previous one.
+    pub fn set_quota(&self, quota: Quota) {
+        *self.quota.borrow_mut() = quota;
+    }
+
+    /// Run `f` with a fresh generation token, clearing the arena once it returns.
+    ///
+    /// The token (and hence all objects allocated using it) can't escape the closure. If a
+    /// generation of objects is currently live, an error is returned instead.
+    pub fn try_scope<R, F>(&self, f: F) -> Result<R, ArenaError>
+    where
+        F: FnOnce(&ArenaToken<'_>) -> R,
+    {
+        let token = self.generation_token()?;
+
+        Ok(f(&token))
+    }
+
+    /// Run `f` with a fresh generation token, clearing the arena once it returns.
+    ///
+    /// The token (and hence all objects allocated using it) can't escape the closure. Panics if
+    /// a generation of objects is currently live.
+    pub fn scope<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&ArenaToken<'_>) -> R,
+    {
+        self.try_scope(f)
+            .expect("arena is locked by another generation token")
+    }
+
+    /// Run `f` with a fresh generation token and the handle `dest`, clearing the arena once it
+    /// returns.
+    ///
+    /// This formalizes the pattern of computing in scratch memory and extracting the result:
+    /// temporaries are allocated using the token, while the result is built using `dest`, which
+    /// refers to a longer-lived arena (or any other allocator implementing `AllocHandle`), and
+    /// moved out before the generation ends. Since the result can't borrow from the generation,
+    /// it can also be copied into a standard collection instead. Panics if a generation of
+    /// objects is currently live.
+    pub fn scope_map<H, R, F>(&self, dest: H, f: F) -> R
+    where
+        H: AllocHandle,
+        F: FnOnce(&ArenaToken<'_>, H) -> R,
+    {
+        self.scope(|token| f(token, dest))
+    }
+
+    /// Return the position in the current generation, across all chunks.
+    fn position(&self) -> usize {
+        self.offset.get() + self.pos.get()
+    }
+
+    /// Run the allocation `f` of at most `needed` bytes (including alignment padding) within the
+    /// quota, recording the memory it takes up in the usage of the current generation.
+    ///
+    /// If the arena is growable and exhausted, `f` is run again in a fresh chunk.
+    fn with_quota<R, F>(&self, needed: usize, f: F) -> Result<R, ArenaError>
+    where
+        F: Fn(usize) -> Result<R, ArenaError>,
+    {
+        let mut start = self.position();
+        let res = match common::with_quota(&self.quota, &self.pos, self.cap.get(), &f) {
+            Err(ArenaError::CapacityExceeded { .. })
+                if self.growable.get() && self.backing.is_movable() =>
+            {
+                self.add_chunk(needed)?;
+                start = self.offset.get();
+
+                common::with_quota(&self.quota, &self.pos, self.cap.get(), &f)?
             }
-            ArenaBacking::SystemAllocation => {
-                common::destroy_mapping_alloc(self.head, self.cap);
+            res => res?,
+        };
+        let pos = self.position();
+        let usage = self.usage.get();
+
+        self.usage.set(GenerationUsage {
+            allocated: usage.allocated + pos.saturating_sub(start),
+            peak: usage.peak.max(pos),
+        });
+
+        Ok(res)
+    }
+
+    /// Retire the current chunk and continue in a fresh one of at least `needed` bytes.
+    fn add_chunk(&self, needed: usize) -> Result<(), ArenaError> {
+        let needed = needed
+            .checked_next_power_of_two()
+            .ok_or(ArenaError::CapacityOverflow)?;
+        let cap = common::round_capacity(cmp::max(self.cap.get().saturating_mul(2), needed))?;
+        let head = self.backing.try_create(cap)?;
+
+        self.retired.borrow_mut().push(Chunk {
+            head: self.head.get(),
+            pos: self.pos.get(),
+            dirty: self.dirty.get(),
+            cap: self.cap.get(),
+        });
+        self.offset.set(self.offset.get() + self.cap.get());
+        self.head.set(head);
+        self.pos.set(0);
+        self.dirty
+            .set(if self.backing.is_zeroed() { 0 } else { cap });
+        self.cap.set(cap);
+
+        Ok(())
+    }
+
+    /// Return the position of the memory at `ptr` in the current generation, across all chunks.
+    fn position_of(&self, ptr: NonNull<u8>) -> usize {
+        if common::owns_inner(self.head.get(), self.cap.get(), ptr, 0) {
+            return self.offset.get() + (ptr.as_ptr() as usize - self.head.get().as_ptr() as usize);
+        }
+
+        let mut offset = 0;
+
+        for chunk in self.retired.borrow().iter() {
+            if common::owns_inner(chunk.head, chunk.cap, ptr, 0) {
+                return offset + (ptr.as_ptr() as usize - chunk.head.as_ptr() as usize);
+            }
+
+            offset += chunk.cap;
+        }
+
+        unreachable!("pointer lies outside of the arena")
+    }
+
+    /// Rewind the arena to the position `pos` in the current generation, running the
+    /// destructors of the objects allocated using `ArenaToken::alloc` beyond it, and returning
+    /// to the chunk it lies in.
+    fn rewind(&self, pos: usize) {
+        self.run_drops(pos);
+
+        while pos < self.offset.get() {
+            let chunk = self.retired.borrow_mut().pop().unwrap();
+
+            common::rewind_inner(self.head.get(), &self.pos, &self.dirty, 0);
+            self.backing.destroy(self.head.get(), self.cap.get());
+            self.offset.set(self.offset.get() - chunk.cap);
+            self.head.set(chunk.head);
+            self.pos.set(chunk.pos);
+            self.dirty.set(chunk.dirty);
+            self.cap.set(chunk.cap);
+        }
+
+        common::rewind_inner(
+            self.head.get(),
+            &self.pos,
+            &self.dirty,
+            pos - self.offset.get(),
+        );
+    }
+
+    /// Release the chunks exhausted during the current generation, keeping the current one as
+    /// the only chunk of the arena.
+    fn release_chunks(&self) {
+        for chunk in self.retired.borrow_mut().drain(..) {
+            self.backing.destroy(chunk.head, chunk.cap);
+        }
+
+        self.offset.set(0);
+    }
+
+    /// Run the destructors of the objects allocated using `ArenaToken::alloc` at or beyond the
+    /// offset `pos`, in reverse order of allocation.
+    fn run_drops(&self, pos: usize) {
+        while let Some(entry) = self.drops.get() {
+            if self.position_of(entry.cast()) < pos {
+                break;
+            }
+
+            // the entry is unlinked first, so that a panicking destructor isn't run again.
+            unsafe {
+                self.drops.set(entry.as_ref().prev);
+                (entry.as_ref().drop)(entry);
             }
         }
     }
 }
 
+/// Drop the object following the drop list entry `entry`.
+unsafe fn drop_registered<T>(entry: NonNull<DropEntry>) {
+    ptr::drop_in_place(&mut (*entry.cast::<Registered<T>>().as_ptr()).value);
+}
+
+/// Return the number of bytes an allocation of `count` objects of type `T` takes up at most,
+/// including alignment padding.
+fn needed<T>(count: usize) -> usize {
+    count
+        .saturating_mul(mem::size_of::<T>())
+        .saturating_add(mem::align_of::<T>())
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        self.release_chunks();
+        self.backing.destroy(self.head.get(), self.cap.get());
+    }
+}
+
+impl<'a> Partitions<'a> {
+    /// Return a fresh generation token for every partition, in order.
+    ///
+    /// If a generation of objects is currently live in any partition, an error is returned
+    /// instead.
+    pub fn tokens(&self) -> Result<Vec<ArenaToken<'_>>, ArenaError> {
+        self.parts.iter().map(Arena::generation_token).collect()
+    }
+}
+
+impl<'a> Deref for Partitions<'a> {
+    type Target = [Arena];
+
+    fn deref(&self) -> &Self::Target {
+        &self.parts
+    }
+}
+
 impl<'a> ArenaToken<'a> {
     /// Create an arena handle using the current generation's token.
     pub fn weak(&'a self) -> ArenaHandle<'a> {
-        ArenaHandle(self)
+        ArenaHandle {
+            token: self,
+            #[cfg(debug_assertions)]
+            arena: self.inner,
+            #[cfg(debug_assertions)]
+            generation: self.inner.generation.get(),
+        }
     }
-}
 
-impl<'a> AllocHandle for ArenaToken<'a> {
-    fn allocate<T>(&self, count: usize) -> NonNull<T> {
-        common::allocate_inner(self.inner.head, &self.inner.pos, self.inner.cap, count)
+    /// Return a marker for the current position in the arena.
+    pub fn mark(&self) -> Marker {
+        Marker {
+            pos: self.inner.position(),
+        }
+    }
+
+    /// Return the memory usage of the generation so far.
+    pub fn usage(&self) -> GenerationUsage {
+        self.inner.usage()
+    }
+
+    /// Create a child token, which rewinds the arena to its current position when dropped.
+    ///
+    /// This is the guard-based counterpart of `rewind_scope`, allowing for recursive algorithms
+    /// to release their temporaries in a stack-like fashion within one generation. The child
+    /// token borrows the token exclusively, so no objects allocated before can be live while
+    /// it is, and the objects allocated using the child token can't outlive it. Child tokens
+    /// can be nested, and dropping them doesn't end the generation.
+    pub fn subscope(&mut self) -> ArenaToken<'_> {
+        ArenaToken {
+            inner: self.inner,
+            start: Some(self.inner.position()),
+        }
+    }
+
+    /// Move `value` into the arena, and run its destructor when the generation ends.
+    ///
+    /// Unlike objects owned by containers such as `ArenaBox`, the object is dropped by the
+    /// arena, so that `Drop` types can be allocated directly. Destructors run in reverse
+    /// allocation order, so an object borrowing from one allocated after it could observe it
+    /// after it has been dropped. The object is thus required to be `'static`, which rules out
+    /// borrowing from the arena altogether. Objects borrowing from the arena can be allocated
+    /// using `BumpAlloc::alloc` instead, which never runs destructors. Panics if the arena is
+    /// exhausted.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: 'static>(&self, value: T) -> &mut T {
+        match self.try_alloc(value) {
+            Ok(r) => r,
+            Err(_) => panic!("arena is exhausted"),
+        }
+    }
+
+    /// Move `value` into the arena, or return it if the arena is exhausted, see `alloc`.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc<T: 'static>(&self, value: T) -> Result<&mut T, T> {
+        if !mem::needs_drop::<T>() {
+            let ptr = match self.try_allocate::<T>(1) {
+                Ok(ptr) => ptr,
+                Err(_) => return Err(value),
+            };
+
+            unsafe {
+                ptr::write(ptr.as_ptr(), value);
+                return Ok(&mut *ptr.as_ptr());
+            }
+        }
+
+        let ptr = match self.try_allocate::<Registered<T>>(1) {
+            Ok(ptr) => ptr,
+            Err(_) => return Err(value),
+        };
+
+        unsafe {
+            ptr::write(
+                ptr.as_ptr(),
+                Registered {
+                    entry: DropEntry {
+                        drop: drop_registered::<T>,
+                        prev: self.inner.drops.get(),
+                    },
+                    value,
+                },
+            );
+
+            self.inner.drops.set(Some(ptr.cast()));
+
+            Ok(&mut (*ptr.as_ptr()).value)
+        }
+    }
+
+    /// Copy `values` into the arena.
+    ///
+    /// Panics if the arena is exhausted.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> &mut [T] {
+        let ptr = match self.try_allocate::<T>(values.len()) {
+            Ok(ptr) => ptr,
+            Err(_) => panic!("arena is exhausted"),
+        };
+
+        unsafe {
+            ptr::copy_nonoverlapping(values.as_ptr(), ptr.as_ptr(), values.len());
+            slice::from_raw_parts_mut(ptr.as_ptr(), values.len())
+        }
+    }
+
+    /// Copy the string `s` into the arena.
+    ///
+    /// Panics if the arena is exhausted.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, s: &str) -> &mut str {
+        unsafe { str::from_utf8_unchecked_mut(self.alloc_slice_copy(s.as_bytes())) }
     }
 
-    fn allocate_or_extend<T>(&self, ptr: NonNull<T>, old_count: usize, count: usize) -> NonNull<T> {
-        common::allocate_or_extend_inner(
-            self.inner.head,
+    /// Rewind the arena to a previously obtained marker, releasing all memory allocated since.
+    ///
+    /// Panics if the arena has been rewound past the marker in the meantime. The destructors of
+    /// objects allocated using `alloc` since the marker has been obtained are run first.
+    ///
+    /// # Safety
+    ///
+    /// No memory allocated after the marker has been obtained may be accessed afterwards. This
+    /// includes memory that objects allocated before have grown into, and implies that all
+    /// objects allocated since have to be dropped or forgotten.
+    pub unsafe fn reset_to(&self, marker: Marker) {
+        assert!(
+            marker.pos <= self.inner.position(),
+            "marker lies beyond the arena position"
+        );
+
+        self.inner.rewind(marker.pos);
+    }
+
+    /// Take a snapshot of the contents of the arena and its position.
+    ///
+    /// This copies all memory allocated from the arena so far, so that speculative computations
+    /// can be undone using `restore`. Panics if the arena has grown into additional chunks during
+    /// the current generation.
+    pub fn snapshot(&self) -> Snapshot {
+        assert_eq!(
+            self.inner.chunk_count(),
+            1,
+            "can't snapshot an arena spanning multiple chunks"
+        );
+
+        Snapshot::take(self.inner.head.get(), self.inner.pos.get())
+    }
+
+    /// Restore the contents of the arena and its position from a snapshot.
+    ///
+    /// Panics if the snapshot has been taken from another arena. The destructors of objects
+    /// allocated using `alloc` since the snapshot has been taken are run first.
+    ///
+    /// # Safety
+    ///
+    /// The contents of the arena are restored byte by byte, which is only sound if all objects
+    /// live afterwards have been live (and located in the same memory) when the snapshot was
+    /// taken, and their state outside of the arena is consistent with the restored contents.
+    /// Notably, this does not hold for most containers, which keep their length outside of the
+    /// arena, or for objects owning resources outside of the arena. Memory allocated after the
+    /// snapshot has been taken may not be accessed afterwards.
+    pub unsafe fn restore(&self, snapshot: &Snapshot) {
+        // the snapshot has been taken in the first chunk, so this returns to it.
+        self.inner
+            .rewind(cmp::min(snapshot.len(), self.inner.position()));
+
+        let pos = snapshot.restore(self.inner.head.get());
+
+        common::rewind_inner(
+            self.inner.head.get(),
             &self.inner.pos,
-            self.inner.cap,
-            ptr,
-            old_count,
-            count)
+            &self.inner.dirty,
+            pos,
+        );
+    }
+
+    /// Run `f` with the token, rewinding the arena to its current position once `f` returns.
+    ///
+    /// This requires exclusive access to the token, so no objects allocated before (which could
+    /// grow into memory allocated by `f`) can be live, and since handles created inside `f`
+    /// can't escape it, neither can the objects allocated there. This allows for the classic
+    /// obstack pattern of nested phases, each releasing its temporaries when finished.
+    pub fn rewind_scope<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&ArenaToken<'_>) -> R,
+    {
+        let marker = self.mark();
+        let res = f(self);
+
+        unsafe {
+            self.reset_to(marker);
+        }
+
+        res
+    }
+}
+
+unsafe impl<'a> AllocHandle for ArenaToken<'a> {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr = self.inner.with_quota(needed::<T>(count), |cap| {
+            common::try_allocate_inner(self.inner.head.get(), &self.inner.pos, cap, count)
+        })?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(count);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.allocate::<T>(count);
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let new_ptr = self.inner.with_quota(needed::<T>(count), |cap| {
+            common::try_allocate_or_extend_inner(
+                self.inner.head.get(),
+                &self.inner.pos,
+                cap,
+                ptr,
+                old_count,
+                count,
+            )
+        })?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(if new_ptr == ptr {
+            count.saturating_sub(old_count)
+        } else {
+            count
+        });
+
+        #[cfg(feature = "trace")]
+        self.inner
+            .tracer
+            .extend::<T>(old_count, count, new_ptr == ptr);
+
+        Ok(new_ptr)
+    }
+
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr = self.inner.with_quota(needed::<T>(count), |cap| {
+            common::try_allocate_zeroed_inner(
+                self.inner.head.get(),
+                &self.inner.pos,
+                &self.inner.dirty,
+                cap,
+                count,
+            )
+        })?;
+
+        #[cfg(feature = "stats")]
+        self.inner.stats.record::<T>(count);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.allocate::<T>(count);
+
+        Ok(ptr)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        let ptr = self
+            .inner
+            .with_quota(layout.size().saturating_add(layout.align()), |cap| {
+                common::allocate_layout_inner(self.inner.head.get(), &self.inner.pos, cap, layout)
+                    .ok_or_else(|| {
+                        common::capacity_exceeded(layout.size(), self.inner.pos.get(), cap)
+                    })
+            })?;
+
+        #[cfg(feature = "stats")]
+        self.inner
+            .stats
+            .record_bytes(stats::LAYOUT_ALLOCATIONS, layout.size());
+
+        #[cfg(feature = "trace")]
+        self.inner
+            .tracer
+            .allocate_bytes(trace::LAYOUT_ALLOCATIONS, layout.size());
+
+        Ok(ptr)
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let size = count * mem::size_of::<T>();
+
+        match common::last_allocation_offset(
+            self.inner.head.get(),
+            self.inner.pos.get(),
+            ptr.cast(),
+            size,
+        ) {
+            Some(offset) => {
+                common::rewind_inner(
+                    self.inner.head.get(),
+                    &self.inner.pos,
+                    &self.inner.dirty,
+                    offset,
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let size = mem::size_of::<T>();
+        let head = self.inner.head.get();
+
+        if let Some(offset) =
+            common::last_allocation_offset(head, self.inner.pos.get(), ptr.cast(), old_count * size)
+        {
+            common::rewind_inner(
+                self.inner.head.get(),
+                &self.inner.pos,
+                &self.inner.dirty,
+                offset + count * size,
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        common::owns_inner(self.inner.head.get(), self.inner.cap.get(), ptr, size)
+            || self
+                .inner
+                .retired
+                .borrow()
+                .iter()
+                .any(|chunk| common::owns_inner(chunk.head, chunk.cap, ptr, size))
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(self.inner as *const Arena as usize)
+    }
+}
+
+impl<'a> ArenaHandle<'a> {
+    /// Return `true` if the generation the handle has been created in has ended.
+    ///
+    /// This can only happen if the handle has been smuggled out of its generation using unsafe
+    /// code, and is only detected in debug builds.
+    pub fn is_stale(&self) -> bool {
+        #[cfg(debug_assertions)]
+        {
+            self.generation != self.arena.generation.get()
+        }
+
+        #[cfg(not(debug_assertions))]
+        {
+            false
+        }
+    }
+
+    /// Move `value` into the arena, and run its destructor when the generation ends.
+    ///
+    /// Unlike `ArenaToken::alloc`, the object can live as long as the handle's generation,
+    /// instead of being tied to the borrow of the handle. Panics if the arena is exhausted.
+    pub fn alloc<T: 'static>(&self, value: T) -> &'a mut T {
+        self.fresh_token().alloc(value)
+    }
+
+    /// Copy `values` into the arena, see `alloc`.
+    pub fn alloc_slice_copy<T: Copy>(&self, values: &[T]) -> &'a mut [T] {
+        self.fresh_token().alloc_slice_copy(values)
+    }
+
+    /// Copy the string `s` into the arena, see `alloc`.
+    pub fn alloc_str(&self, s: &str) -> &'a mut str {
+        self.fresh_token().alloc_str(s)
+    }
+
+    /// Return the token of the handle, panicking if its generation has ended.
+    fn fresh_token(&self) -> &'a ArenaToken<'a> {
+        assert!(
+            !self.is_stale(),
+            "handle used after its generation has ended"
+        );
+
+        self.token
+    }
+
+    /// Refuse to allocate through a stale handle, whose token is gone.
+    fn check_generation(&self) -> Result<(), ArenaError> {
+        if self.is_stale() {
+            Err(ArenaError::StaleHandle)
+        } else {
+            Ok(())
+        }
     }
 }
 
-impl<'a> AllocHandle for ArenaHandle<'a> {
-    fn allocate<T>(&self, count: usize) -> NonNull<T> {
-        self.0.allocate(count)
+unsafe impl<'a> AllocHandle for ArenaHandle<'a> {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+        self.token.try_allocate(count)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+        self.token.try_allocate_or_extend(ptr, old_count, count)
     }
 
-    fn allocate_or_extend<T>(&self, ptr: NonNull<T>, old_count: usize, count: usize) -> NonNull<T> {
-        self.0.allocate_or_extend(ptr, old_count, count)
+    fn try_allocate_zeroed<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.check_generation()?;
+        self.token.try_allocate_zeroed(count)
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        self.check_generation()?;
+        self.token.try_allocate_layout(layout)
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        !self.is_stale() && self.token.dealloc_last(ptr, count)
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        if self.is_stale() {
+            return false;
+        }
+
+        self.token.allocate_or_shrink(ptr, old_count, count)
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        !self.is_stale() && self.token.owns(ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        self.token.arena_id()
+    }
+
+    fn is_stale(&self) -> bool {
+        Self::is_stale(self)
+    }
+}
+
+impl<'a> ArenaBase for ArenaToken<'a> {
+    fn base(&self) -> NonNull<u8> {
+        self.inner.base()
+    }
+}
+
+impl<'a> ArenaBase for ArenaHandle<'a> {
+    fn base(&self) -> NonNull<u8> {
+        self.token.base()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+unsafe impl<'a> Allocator for ArenaHandle<'a> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self
+            .token
+            .try_allocate_layout(layout)
+            .map_err(|_| AllocError)?;
+
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let needed = new_layout.size().saturating_add(new_layout.align());
+        let ptr = self
+            .token
+            .inner
+            .with_quota(needed, |cap| {
+                common::allocate_or_extend_layout_inner(
+                    self.token.inner.head.get(),
+                    &self.token.inner.pos,
+                    cap,
+                    ptr,
+                    old_layout.size(),
+                    new_layout,
+                )
+                .ok_or_else(|| {
+                    common::capacity_exceeded(new_layout.size(), self.token.inner.pos.get(), cap)
+                })
+            })
+            .map_err(|_| AllocError)?;
+
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.grow(ptr, old_layout, new_layout)
     }
 }
 
 impl<'a> Drop for ArenaToken<'a> {
     fn drop(&mut self) {
-        self.inner.pos.set(0);
+        if let Some(start) = self.start {
+            // the child token may have been rewound below `start` using an older marker.
+            self.inner.rewind(start.min(self.inner.position()));
+
+            return;
+        }
+
+        self.inner.observers.notify(ClearPhase::Before);
+        self.inner.run_drops(0);
+
+        #[cfg(feature = "trace")]
+        self.inner.tracer.clear(self.inner.position());
+
+        self.inner.release_chunks();
+        common::rewind_inner(self.inner.head.get(), &self.inner.pos, &self.inner.dirty, 0);
+
+        if self.inner.decommit.get() {
+            common::decommit_inner(
+                &self.inner.backing,
+                self.inner.head.get(),
+                &self.inner.dirty,
+            );
+        }
+
+        self.inner.generation.set(self.inner.generation.get() + 1);
         self.inner.locked.set(false);
+        self.inner.observers.notify(ClearPhase::After);
     }
 }
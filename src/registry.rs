@@ -0,0 +1,181 @@
+//! This module provides a registry owning a set of named reference-counted arenas.
+//!
+//! Applications using one arena per subsystem can keep all of them in a registry, look them up
+//! by name, and clear or inspect them together, for instance to report the memory usage of each
+//! subsystem in one place.
+use crate::common::{ArenaBacking, ArenaError};
+use crate::rc::Arena;
+#[cfg(feature = "stats")]
+use crate::stats::StatsReport;
+
+use std::collections::btree_map::{self, BTreeMap};
+use std::fmt;
+
+/// A set of named `rc` arenas.
+#[derive(Debug, Default)]
+pub struct ArenaRegistry {
+    arenas: BTreeMap<String, Arena>,
+}
+
+/// The memory usage of an arena (or a set of arenas).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArenaUsage {
+    /// The number of bytes allocated, including alignment padding.
+    pub used: usize,
+    /// The total capacity in bytes.
+    pub capacity: usize,
+}
+
+impl ArenaRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create an arena with specified capacity and add it to the registry under `name`.
+    ///
+    /// Fails with `ArenaError::AlreadyLocked` if an arena with the same name is registered
+    /// already.
+    pub fn create(
+        &mut self,
+        name: &str,
+        backing: ArenaBacking,
+        cap: usize,
+    ) -> Result<&Arena, ArenaError> {
+        match self.arenas.entry(name.to_owned()) {
+            btree_map::Entry::Occupied(_) => Err(ArenaError::AlreadyLocked),
+            btree_map::Entry::Vacant(entry) => {
+                Ok(entry.insert(Arena::init_capacity(backing, cap)?))
+            }
+        }
+    }
+
+    /// Add `arena` to the registry under `name`, returning the arena previously registered
+    /// under that name, if any.
+    pub fn insert(&mut self, name: &str, arena: Arena) -> Option<Arena> {
+        self.arenas.insert(name.to_owned(), arena)
+    }
+
+    /// Remove the arena registered under `name` from the registry and return it.
+    pub fn remove(&mut self, name: &str) -> Option<Arena> {
+        self.arenas.remove(name)
+    }
+
+    /// Return the arena registered under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&Arena> {
+        self.arenas.get(name)
+    }
+
+    /// Create an iterator over the names and arenas in the registry, ordered by name.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.arenas.iter(),
+        }
+    }
+
+    /// Return the number of arenas in the registry.
+    pub fn len(&self) -> usize {
+        self.arenas.len()
+    }
+
+    /// Return `true` if the registry doesn't hold any arenas.
+    pub fn is_empty(&self) -> bool {
+        self.arenas.is_empty()
+    }
+
+    /// Clear all arenas in the registry.
+    ///
+    /// Arenas that can be cleared are cleared even if others can't, in which case
+    /// `ArenaError::CannotClear` is returned.
+    pub fn clear(&self) -> Result<(), ArenaError> {
+        let mut res = Ok(());
+
+        for arena in self.arenas.values() {
+            if let Err(err) = arena.clear() {
+                res = Err(err);
+            }
+        }
+
+        res
+    }
+
+    /// Return the memory usage of the arena registered under `name`, if any.
+    pub fn usage(&self, name: &str) -> Option<ArenaUsage> {
+        self.arenas.get(name).map(ArenaUsage::of)
+    }
+
+    /// Return the memory usage summed up over all arenas in the registry.
+    pub fn total_usage(&self) -> ArenaUsage {
+        self.arenas
+            .values()
+            .map(ArenaUsage::of)
+            .fold(ArenaUsage::default(), |acc, usage| ArenaUsage {
+                used: acc.used + usage.used,
+                capacity: acc.capacity + usage.capacity,
+            })
+    }
+
+    /// Return a report of the allocations made from all arenas in the registry since their
+    /// creation or last clear, grouped by type.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> StatsReport {
+        let reports: Vec<_> = self.arenas.values().map(Arena::stats).collect();
+
+        StatsReport::merge(&reports)
+    }
+}
+
+impl fmt::Display for ArenaRegistry {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, arena) in self.iter() {
+            let usage = ArenaUsage::of(arena);
+
+            writeln!(
+                fmt,
+                "{:>12} bytes of {:>12}  {}",
+                usage.used, usage.capacity, name
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a ArenaRegistry {
+    type Item = (&'a str, &'a Arena);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the names and arenas in a registry.
+#[derive(Debug)]
+pub struct Iter<'a> {
+    inner: btree_map::Iter<'a, String, Arena>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a Arena);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(name, arena)| (name.as_str(), arena))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl ArenaUsage {
+    /// Return the memory usage of `arena`.
+    fn of(arena: &Arena) -> Self {
+        ArenaUsage {
+            used: arena.used(),
+            capacity: arena.capacity(),
+        }
+    }
+}
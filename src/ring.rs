@@ -0,0 +1,219 @@
+//! This module contains a fixed-capacity ring buffer backed by arena memory.
+use crate::common::AllocHandle;
+
+use std::cmp;
+use std::fmt;
+use std::iter::Chain;
+use std::ops::Index;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// The behaviour of a ring buffer when pushing into it while it is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RingMode {
+    /// Evict the oldest element to make room for the new one.
+    OverwriteOldest,
+    /// Reject the new element.
+    FailWhenFull,
+}
+
+/// An arena allocated ring buffer with a capacity fixed at construction.
+///
+/// Since the buffer never grows, it is a good fit for long-lived arenas, such as telemetry or
+/// history buffers in `rc` arenas: it consumes a fixed amount of arena memory.
+pub struct ArenaRingBuffer<T, H> {
+    ptr: NonNull<T>,
+    head: usize,
+    len: usize,
+    capacity: usize,
+    mode: RingMode,
+    _handle: H,
+}
+
+/// An iterator over the elements of an `ArenaRingBuffer`, oldest to newest.
+pub type Iter<'a, T> = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+impl<T, H> ArenaRingBuffer<T, H> {
+    /// Return the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the buffer contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return `true` if the buffer is filled to capacity.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Return the behaviour of the buffer when pushing into it while it is full.
+    pub fn mode(&self) -> RingMode {
+        self.mode
+    }
+
+    /// Translate a logical index into an index into the buffer.
+    fn wrap(&self, index: usize) -> usize {
+        (self.head + index) % self.capacity
+    }
+
+    /// Push an element into the buffer.
+    ///
+    /// If the buffer is full, the buffer's mode decides which element is dropped from the
+    /// buffer, and that element is returned: the oldest element for
+    /// `RingMode::OverwriteOldest`, and `elem` itself for `RingMode::FailWhenFull`.
+    pub fn push(&mut self, elem: T) -> Option<T> {
+        match self.mode {
+            RingMode::OverwriteOldest => self.push_overwrite(elem),
+            RingMode::FailWhenFull => self.try_push(elem).err(),
+        }
+    }
+
+    /// Push an element into the buffer, evicting and returning the oldest element if the buffer
+    /// is full.
+    ///
+    /// If the buffer has capacity `0`, `elem` is returned.
+    pub fn push_overwrite(&mut self, elem: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(elem);
+        }
+
+        let evicted = if self.is_full() { self.pop() } else { None };
+
+        unsafe {
+            ptr::write(self.ptr.as_ptr().add(self.wrap(self.len)), elem);
+        }
+
+        self.len += 1;
+
+        evicted
+    }
+
+    /// Push an element into the buffer, or return it if the buffer is full.
+    pub fn try_push(&mut self, elem: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(elem);
+        }
+
+        unsafe {
+            ptr::write(self.ptr.as_ptr().add(self.wrap(self.len)), elem);
+        }
+
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Remove the oldest element from the buffer and return it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let head = self.head;
+        self.head = self.wrap(1);
+        self.len -= 1;
+
+        unsafe { Some(ptr::read(self.ptr.as_ptr().add(head))) }
+    }
+
+    /// Return a reference to the element at `index`, counting from the oldest element, or
+    /// `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            unsafe { Some(&*self.ptr.as_ptr().add(self.wrap(index))) }
+        } else {
+            None
+        }
+    }
+
+    /// Return the oldest element in the buffer, if any.
+    pub fn oldest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Return the newest element in the buffer, if any.
+    pub fn newest(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// Return the contents of the buffer as a pair of slices, oldest to newest.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let first = cmp::min(self.len, self.capacity - self.head);
+
+        unsafe {
+            (
+                slice::from_raw_parts(self.ptr.as_ptr().add(self.head), first),
+                slice::from_raw_parts(self.ptr.as_ptr(), self.len - first),
+            )
+        }
+    }
+
+    /// Create an iterator over the elements of the buffer, oldest to newest.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b.iter())
+    }
+
+    /// Clear the buffer.
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+        self.head = 0;
+    }
+}
+
+impl<T, H: AllocHandle> ArenaRingBuffer<T, H> {
+    /// Create a new empty buffer of given capacity and mode using the given handle.
+    pub fn new(handle: H, capacity: usize, mode: RingMode) -> Self {
+        let ptr = if capacity == 0 {
+            NonNull::dangling()
+        } else {
+            handle.allocate(capacity)
+        };
+
+        ArenaRingBuffer {
+            ptr,
+            head: 0,
+            len: 0,
+            capacity,
+            mode,
+            _handle: handle,
+        }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ArenaRingBuffer<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, H> Index<usize> for ArenaRingBuffer<T, H> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("out of bounds access")
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a ArenaRingBuffer<T, H> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, H> Drop for ArenaRingBuffer<T, H> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
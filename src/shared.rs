@@ -0,0 +1,275 @@
+//! This module provides a reference-counted arena whose handles can be sent across threads.
+//!
+//! Allocation is serialized by a lock, which makes it slower than in the arena provided by the
+//! `sync` module, but it makes all operations, including extending allocations in place,
+//! behave exactly as they do in the single-threaded `rc` arena.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{self, AllocHandle, ArenaBacking, ArenaError};
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+use crate::tree;
+use crate::vecmap;
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A reference-counting arena with lock-protected allocation.
+///
+/// This is the only object that can be used to clear the arena. All other objects referring to
+/// the arena merely allow for allocation, and are present to avoid arena clearing while they are
+/// live.
+#[derive(Debug)]
+pub struct SharedArena(SharedHandle, ArenaBacking);
+
+/// A non-owning object referring to the arena, which can be sent across threads.
+///
+/// A reference to the arena that allows its holder to allocate memory from the arena. While
+/// it is live, the arena cannot be cleared (as it is associated with an arena-allocated
+/// object).
+#[derive(Clone, Debug)]
+pub struct SharedHandle {
+    inner: Arc<Mutex<Inner>>,
+}
+
+/// An arena's guts
+#[derive(Debug)]
+struct Inner {
+    /// Head of the arena space
+    head: NonNull<u8>,
+
+    /// Offset into the last region
+    pos: Cell<usize>,
+
+    /// Total capacity of the arena
+    cap: usize,
+}
+
+// all accesses to the arena's state go through the lock.
+unsafe impl Send for Inner {}
+
+/// An arena allocated, fixed-size sequence of objects
+pub type Slice<T> = common::Slice<T, SharedHandle>;
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub type SliceVec<T> = common::SliceVec<T, SharedHandle>;
+
+/// An arena allocated, owned object
+pub type ArenaBox<T> = boxed::ArenaBox<T, SharedHandle>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'b, T> = cow::ArenaCow<'b, T, SharedHandle>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<T> = deque::ArenaVecDeque<T, SharedHandle>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<K, V> = btree::ArenaBTreeMap<K, V, SharedHandle>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<T> = heap::ArenaBinaryHeap<T, SharedHandle>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<T> = slab::ArenaSlab<T, SharedHandle>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<T> = generational::GenArena<T, SharedHandle>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec = bitvec::ArenaBitVec<SharedHandle>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<T> = grid::ArenaGrid<T, SharedHandle>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<K, V> = vecmap::ArenaVecMap<K, V, SharedHandle>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<T> = vecmap::ArenaVecSet<T, SharedHandle>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<K, V> = hash::ArenaHashMap<K, V, SharedHandle>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<T> = hash::ArenaHashSet<T, SharedHandle>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<T> = chunkvec::ChunkedVec<T, SharedHandle>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<T> = ring::ArenaRingBuffer<T, SharedHandle>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<N, E> = graph::ArenaGraph<N, E, SharedHandle>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<T> = tree::NodeArena<T, SharedHandle>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor = cursor::ArenaCursor<SharedHandle>;
+
+impl SharedArena {
+    /// Create a `SharedArena` with specified capacity.
+    ///
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. It cannot
+    /// be grown after the fact.
+    pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
+        let pos = Cell::new(0);
+
+        Ok(SharedArena(
+            SharedHandle {
+                inner: Arc::new(Mutex::new(Inner { head, pos, cap })),
+            },
+            backing,
+        ))
+    }
+
+    /// Create another reference to the arena.
+    pub fn inner(&self) -> SharedHandle {
+        self.0.clone()
+    }
+
+    /// Clear the arena.
+    ///
+    /// This fails if any other reference to the arena is still live, in any thread.
+    pub fn clear(&self) -> Result<(), ArenaError> {
+        if Arc::strong_count(&self.0.inner) == 1 {
+            let inner = self.0.lock();
+            let pos = inner.pos.replace(0);
+
+            unsafe { common::poison(inner.head, pos) };
+
+            Ok(())
+        } else {
+            Err(ArenaError::CannotClear {
+                live_handles: Arc::strong_count(&self.0.inner) - 1,
+            })
+        }
+    }
+}
+
+impl Deref for SharedArena {
+    type Target = SharedHandle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for SharedArena {
+    fn drop(&mut self) {
+        let inner = self.0.lock();
+
+        self.1.destroy(inner.head, inner.cap);
+    }
+}
+
+impl SharedHandle {
+    /// Acquire the lock protecting the arena's state.
+    ///
+    /// A panic during allocation leaves the state untouched, so poisoning is ignored.
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+unsafe impl AllocHandle for SharedHandle {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let inner = self.lock();
+
+        common::try_allocate_inner(inner.head, &inner.pos, inner.cap, count)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let inner = self.lock();
+
+        common::try_allocate_or_extend_inner(
+            inner.head, &inner.pos, inner.cap, ptr, old_count, count,
+        )
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        let inner = self.lock();
+
+        common::allocate_layout_inner(inner.head, &inner.pos, inner.cap, layout)
+            .ok_or_else(|| common::capacity_exceeded(layout.size(), inner.pos.get(), inner.cap))
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let inner = self.lock();
+        let size = count * mem::size_of::<T>();
+
+        match common::last_allocation_offset(inner.head, inner.pos.get(), ptr.cast(), size) {
+            Some(offset) => {
+                inner.pos.set(offset);
+                common::poison(ptr.cast(), size);
+                true
+            }
+            None => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let inner = self.lock();
+        let size = mem::size_of::<T>();
+
+        if let Some(offset) = common::last_allocation_offset(
+            inner.head,
+            inner.pos.get(),
+            ptr.cast(),
+            old_count * size,
+        ) {
+            inner.pos.set(offset + count * size);
+            common::poison(
+                NonNull::new_unchecked(ptr.as_ptr().add(count)).cast(),
+                (old_count - count) * size,
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        let inner = self.lock();
+
+        common::owns_inner(inner.head, inner.cap, ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(Arc::as_ptr(&self.inner) as usize)
+    }
+}
@@ -0,0 +1,197 @@
+//! This module contains support for sharing arena memory between processes.
+//!
+//! An arena created with `ArenaBacking::SharedMemory` can be attached to by other processes
+//! using `SharedMapping::open`. Since the memory is mapped at different addresses in each
+//! process, arena-resident structures meant to be shared refer to each other using
+//! `ArenaOffset`s, which are relative to the start of the arena memory. On Linux, arenas created
+//! with `ArenaBacking::SealableMemoryMap` can be sealed read-only, and mapped by other processes
+//! using `SharedMapping::from_sealed_fd`.
+use crate::common::{self, ArenaError};
+
+use std::cmp;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::RawFd;
+use std::ptr::NonNull;
+
+/// A reference to an object of type `T`, represented by its offset from the start of the arena
+/// memory.
+///
+/// Offsets stay meaningful when the arena memory is mapped at a different address, so they can
+/// be stored in shared arena memory (as opposed to pointers) and resolved in every process
+/// attached to it.
+#[repr(transparent)]
+pub struct ArenaOffset<T> {
+    offset: usize,
+    marker: PhantomData<*const T>,
+}
+
+/// A mapping of the memory of an arena created in another process with
+/// `ArenaBacking::SharedMemory`, or of a sealed arena.
+///
+/// The memory is unmapped when the mapping is dropped.
+pub struct SharedMapping {
+    /// Start of the arena memory
+    base: NonNull<u8>,
+
+    /// Size of the arena memory
+    len: usize,
+}
+
+impl<T> ArenaOffset<T> {
+    /// Create an offset from the offset of an object in bytes.
+    pub fn from_offset(offset: usize) -> Self {
+        ArenaOffset {
+            offset,
+            marker: PhantomData,
+        }
+    }
+
+    /// Create an offset from a pointer to an object in the arena memory starting at `base`.
+    ///
+    /// Panics if `ptr` lies before `base`.
+    pub fn new(base: NonNull<u8>, ptr: *const T) -> Self {
+        let offset = (ptr as usize)
+            .checked_sub(base.as_ptr() as usize)
+            .expect("pointer lies before the arena");
+
+        Self::from_offset(offset)
+    }
+
+    /// Return the offset of the object in bytes.
+    pub fn offset(self) -> usize {
+        self.offset
+    }
+
+    /// Turn the offset into a pointer into the arena memory starting at `base`.
+    pub fn to_ptr(self, base: NonNull<u8>) -> NonNull<T> {
+        unsafe { NonNull::new_unchecked(base.as_ptr().add(self.offset) as *mut T) }
+    }
+}
+
+impl SharedMapping {
+    /// Map the memory of the arena created with `ArenaBacking::SharedMemory` using the given
+    /// name.
+    pub fn open(name: &str) -> Result<Self, ArenaError> {
+        let (base, len) = common::open_mapping_shared(name)
+            .ok_or_else(|| common::mapping_failed("SharedMemory", 0))?;
+
+        Ok(SharedMapping { base, len })
+    }
+
+    /// Map the memory of the arena sealed using `rc::Arena::seal` with the given file
+    /// descriptor, read-only.
+    ///
+    /// Fails if the file isn't sealed against writes, so that its contents can be relied on.
+    /// The descriptor can be closed afterwards.
+    #[cfg(target_os = "linux")]
+    pub fn from_sealed_fd(fd: RawFd) -> Result<Self, ArenaError> {
+        let (base, len) = common::open_mapping_sealed(fd)
+            .ok_or_else(|| common::mapping_failed("SealableMemoryMap", 0))?;
+
+        Ok(SharedMapping { base, len })
+    }
+
+    /// Return a pointer to the start of the arena memory, which `ArenaOffset`s are relative to.
+    pub fn base(&self) -> NonNull<u8> {
+        self.base
+    }
+
+    /// Return the size of the arena memory.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the arena memory is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Resolve an offset into a reference to the object it refers to.
+    ///
+    /// Panics if the object doesn't lie within the arena memory, or is misaligned.
+    ///
+    /// # Safety
+    ///
+    /// The offset has to refer to an initialized object of type `T`, which mustn't be modified
+    /// while the reference is live, including by other processes.
+    pub unsafe fn get<T>(&self, offset: ArenaOffset<T>) -> &T {
+        let end = offset.offset.checked_add(mem::size_of::<T>());
+
+        assert!(
+            end.is_some_and(|end| end <= self.len),
+            "offset lies beyond the arena"
+        );
+
+        let ptr = offset.to_ptr(self.base);
+
+        assert_eq!(
+            ptr.as_ptr() as usize % mem::align_of::<T>(),
+            0,
+            "offset is misaligned"
+        );
+
+        &*ptr.as_ptr()
+    }
+}
+
+impl<T> Clone for ArenaOffset<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaOffset<T> {}
+
+impl<T> fmt::Debug for ArenaOffset<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("ArenaOffset").field(&self.offset).finish()
+    }
+}
+
+impl<T> Eq for ArenaOffset<T> {}
+
+impl<T> PartialEq for ArenaOffset<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset
+    }
+}
+
+impl<T> Ord for ArenaOffset<T> {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.offset.cmp(&other.offset)
+    }
+}
+
+impl<T> PartialOrd for ArenaOffset<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Hash for ArenaOffset<T> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.offset.hash(state);
+    }
+}
+
+// an offset is just a number, the object it refers to is only accessed once it is resolved.
+unsafe impl<T> Send for ArenaOffset<T> {}
+unsafe impl<T> Sync for ArenaOffset<T> {}
+
+impl fmt::Debug for SharedMapping {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SharedMapping")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl Drop for SharedMapping {
+    fn drop(&mut self) {
+        common::close_mapping_shared(self.base, self.len);
+    }
+}
@@ -0,0 +1,208 @@
+//! This module contains a slab allocator for objects of a single type, backed by arena memory.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/// An arena allocated slab of objects addressed by stable `usize` keys.
+///
+/// Removing an object puts its slot on an internal free list, so that the slot can be reused by
+/// later insertions. This reuses slots, not arena memory: the slab's storage only grows, and its
+/// memory is reclaimed once the arena is cleared.
+pub struct ArenaSlab<T, H> {
+    entries: SliceVec<Entry<T>, H>,
+    len: usize,
+    next: usize,
+}
+
+/// A slot in the slab.
+#[derive(Clone)]
+enum Entry<T> {
+    /// An occupied slot
+    Occupied(T),
+    /// A free slot, pointing to the next free slot
+    Vacant(usize),
+}
+
+/// An iterator over the keys and objects in an `ArenaSlab`.
+pub struct Iter<'a, T> {
+    inner: std::iter::Enumerate<std::slice::Iter<'a, Entry<T>>>,
+}
+
+impl<T, H> ArenaSlab<T, H> {
+    /// Return the number of objects in the slab.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the slab contains no objects.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return a reference to the object stored under `key`, if any.
+    pub fn get(&self, key: usize) -> Option<&T> {
+        match self.entries.get(key) {
+            Some(Entry::Occupied(elem)) => Some(elem),
+            _ => None,
+        }
+    }
+
+    /// Return a mutable reference to the object stored under `key`, if any.
+    pub fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        match self.entries.get_mut(key) {
+            Some(Entry::Occupied(elem)) => Some(elem),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if an object is stored under `key`.
+    pub fn contains(&self, key: usize) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Return the key the next inserted object will be stored under.
+    pub fn vacant_key(&self) -> usize {
+        self.next
+    }
+
+    /// Remove the object stored under `key` and return it, or `None` if the slot is vacant.
+    pub fn try_remove(&mut self, key: usize) -> Option<T> {
+        let entry = self.entries.get_mut(key)?;
+
+        if let Entry::Vacant(_) = *entry {
+            return None;
+        }
+
+        match mem::replace(entry, Entry::Vacant(self.next)) {
+            Entry::Occupied(elem) => {
+                self.len -= 1;
+                self.next = key;
+
+                Some(elem)
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+    }
+
+    /// Remove the object stored under `key` and return it.
+    ///
+    /// Panics if the slot is vacant.
+    pub fn remove(&mut self, key: usize) -> T {
+        self.try_remove(key).expect("invalid slab key")
+    }
+
+    /// Create an iterator over the keys and objects in the slab, in key order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.entries.iter().enumerate(),
+        }
+    }
+}
+
+impl<T, H: AllocHandle> ArenaSlab<T, H> {
+    /// Create a new empty slab using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0)
+    }
+
+    /// Create a new empty slab with space for `capacity` objects using the given handle.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        ArenaSlab {
+            entries: SliceVec::with_capacity(handle, capacity),
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Return the number of objects the slab can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Insert an object into the slab and return its key.
+    pub fn insert(&mut self, elem: T) -> usize {
+        let key = self.next;
+
+        if key == self.entries.len() {
+            self.entries.push(Entry::Occupied(elem));
+            self.next = key + 1;
+        } else {
+            match mem::replace(&mut self.entries[key], Entry::Occupied(elem)) {
+                Entry::Vacant(next) => self.next = next,
+                Entry::Occupied(_) => unreachable!(),
+            }
+        }
+
+        self.len += 1;
+
+        key
+    }
+
+    /// Remove all objects from the slab.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.len = 0;
+        self.next = 0;
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaSlab<T, H> {
+    fn clone(&self) -> Self {
+        ArenaSlab {
+            entries: self.entries.clone(),
+            len: self.len,
+            next: self.next,
+        }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ArenaSlab<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, H> Index<usize> for ArenaSlab<T, H> {
+    type Output = T;
+
+    fn index(&self, key: usize) -> &T {
+        self.get(key).expect("invalid slab key")
+    }
+}
+
+impl<T, H> IndexMut<usize> for ArenaSlab<T, H> {
+    fn index_mut(&mut self, key: usize) -> &mut T {
+        self.get_mut(key).expect("invalid slab key")
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a ArenaSlab<T, H> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (key, entry) in &mut self.inner {
+            if let Entry::Occupied(ref elem) = *entry {
+                return Some((key, elem));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, T> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Iter").finish()
+    }
+}
@@ -0,0 +1,156 @@
+//! This module contains per-type allocation accounting for arenas, enabled by the `stats`
+//! feature.
+//!
+//! The `rc` and `region` arenas record the number of allocations and bytes requested for each
+//! type (as named by `std::any::type_name`), which can be inspected using their `stats` method
+//! to find out which data structures take up the most arena space.
+use std::any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::slice;
+
+/// The name allocations made for a raw `Layout` (as opposed to a type) are recorded under.
+pub const LAYOUT_ALLOCATIONS: &str = "<layout>";
+
+/// Allocation statistics for a single type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TypeStats {
+    /// The number of allocations made for the type, including reallocations.
+    pub allocations: usize,
+    /// The number of bytes allocated for the type, excluding alignment padding.
+    pub bytes: usize,
+}
+
+/// A report of the allocations made from an arena, grouped by type.
+///
+/// The types are ordered by the number of bytes allocated for them, largest first.
+#[derive(Clone, Debug, Default)]
+pub struct StatsReport {
+    types: Vec<(&'static str, TypeStats)>,
+}
+
+/// The allocation statistics kept by an arena.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    types: RefCell<HashMap<&'static str, TypeStats>>,
+}
+
+impl StatsReport {
+    /// Return the statistics recorded for type `T`, if any.
+    pub fn get<T: ?Sized>(&self) -> Option<TypeStats> {
+        self.get_by_name(any::type_name::<T>())
+    }
+
+    /// Return the statistics recorded under the given type name, if any.
+    pub fn get_by_name(&self, name: &str) -> Option<TypeStats> {
+        self.types
+            .iter()
+            .find(|&&(n, _)| n == name)
+            .map(|&(_, stats)| stats)
+    }
+
+    /// Return the statistics summed up over all types.
+    pub fn total(&self) -> TypeStats {
+        self.types
+            .iter()
+            .fold(TypeStats::default(), |acc, &(_, stats)| TypeStats {
+                allocations: acc.allocations + stats.allocations,
+                bytes: acc.bytes + stats.bytes,
+            })
+    }
+
+    /// Create an iterator over the type names and their statistics, largest first.
+    pub fn iter(&self) -> slice::Iter<'_, (&'static str, TypeStats)> {
+        self.types.iter()
+    }
+
+    /// Return the number of types in the report.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Return `true` if no allocations have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+impl StatsReport {
+    /// Combine several reports into one, summing up the statistics recorded for each type.
+    pub(crate) fn merge(reports: &[StatsReport]) -> Self {
+        let recorder = Recorder::default();
+
+        {
+            let mut types = recorder.types.borrow_mut();
+
+            for &(name, stats) in reports.iter().flat_map(StatsReport::iter) {
+                let total = types.entry(name).or_default();
+
+                total.allocations += stats.allocations;
+                total.bytes += stats.bytes;
+            }
+        }
+
+        recorder.report()
+    }
+}
+
+impl fmt::Display for StatsReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &(name, stats) in &self.types {
+            writeln!(
+                fmt,
+                "{:>12} bytes {:>8} allocations  {}",
+                stats.bytes, stats.allocations, name
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a StatsReport {
+    type Item = &'a (&'static str, TypeStats);
+    type IntoIter = slice::Iter<'a, (&'static str, TypeStats)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Recorder {
+    /// Record an allocation of `count` objects of type `T`.
+    pub(crate) fn record<T>(&self, count: usize) {
+        self.record_bytes(any::type_name::<T>(), count * mem::size_of::<T>());
+    }
+
+    /// Record an allocation of `bytes` bytes under the given name.
+    pub(crate) fn record_bytes(&self, name: &'static str, bytes: usize) {
+        let mut types = self.types.borrow_mut();
+        let stats = types.entry(name).or_default();
+
+        stats.allocations += 1;
+        stats.bytes += bytes;
+    }
+
+    /// Create a report of the statistics recorded so far.
+    pub(crate) fn report(&self) -> StatsReport {
+        let mut types: Vec<_> = self
+            .types
+            .borrow()
+            .iter()
+            .map(|(&name, &stats)| (name, stats))
+            .collect();
+
+        types.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then(a.0.cmp(b.0)));
+
+        StatsReport { types }
+    }
+
+    /// Forget all statistics recorded so far.
+    pub(crate) fn clear(&self) {
+        self.types.borrow_mut().clear();
+    }
+}
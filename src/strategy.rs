@@ -0,0 +1,51 @@
+//! This module provides `proptest` strategies generating arena-allocated containers, enabled by
+//! the `proptest` feature.
+//!
+//! The strategies place the generated contents into the arena of a caller-provided handle, so
+//! that code using arena containers can be property-tested the same way this crate's own tests
+//! are. Shrinking a generated value allocates the simplified contents anew, so the arena should
+//! have some room to spare beyond the largest value generated.
+use crate::common::{AllocHandle, Slice, SliceVec};
+
+use proptest::collection::{self, SizeRange};
+use proptest::strategy::Strategy;
+
+use std::fmt::Debug;
+
+/// Return a strategy generating vectors with elements drawn from `element`, and a length within
+/// `size`, allocated in the arena of `handle`.
+pub fn slice_vec<T, H, S>(
+    handle: H,
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = SliceVec<T, H>>
+where
+    T: Debug,
+    H: AllocHandle + Clone + Debug,
+    S: Strategy<Value = T>,
+{
+    collection::vec(element, size).prop_map(move |elems| {
+        let mut vec = SliceVec::with_capacity(handle.clone(), elems.len());
+
+        for elem in elems {
+            vec.push(elem);
+        }
+
+        vec
+    })
+}
+
+/// Return a strategy generating slices with elements drawn from `element`, and a length within
+/// `size`, allocated in the arena of `handle`.
+pub fn slice<T, H, S>(
+    handle: H,
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = Slice<T, H>>
+where
+    T: Debug,
+    H: AllocHandle + Clone + Debug,
+    S: Strategy<Value = T>,
+{
+    slice_vec(handle, element, size).prop_map(SliceVec::into_slice)
+}
@@ -0,0 +1,324 @@
+//! This module provides a thread-safe, reference-counted arena.
+//!
+//! It works just like the arena in the `rc` module, but its allocation position is updated
+//! atomically and its handles are reference-counted using `Arc`. Hence, handles are `Send` and
+//! `Sync`, and multiple threads can bump-allocate from the same arena concurrently. Extending
+//! an allocation in place only succeeds if no other thread has allocated in the meantime,
+//! otherwise the object is moved.
+use crate::bitvec;
+use crate::boxed;
+use crate::btree;
+use crate::chunkvec;
+use crate::common::{self, AllocHandle, ArenaBacking, ArenaError};
+use crate::cow;
+use crate::cursor;
+use crate::deque;
+use crate::generational;
+use crate::graph;
+use crate::grid;
+#[cfg(feature = "hashbrown")]
+use crate::hash;
+use crate::heap;
+use crate::ring;
+use crate::slab;
+use crate::tree;
+use crate::vecmap;
+
+use std::alloc::Layout;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A reference-counting, thread-safe arena.
+///
+/// This is the only object that can be used to clear the arena. All other objects referring to
+/// the arena merely allow for allocation, and are present to avoid arena clearing while they are
+/// live.
+#[derive(Debug)]
+pub struct Arena(InnerRef, ArenaBacking);
+
+/// A non-owning object referring to the arena.
+///
+/// A reference to the arena that allows its holder to allocate memory from the arena. While
+/// it is live, the arena cannot be cleared (as it is associated with an arena-allocated
+/// object).
+#[derive(Clone, Debug)]
+pub struct InnerRef {
+    inner: Arc<Inner>,
+}
+
+/// An arena's guts
+#[derive(Debug)]
+struct Inner {
+    /// Head of the arena space
+    head: NonNull<u8>,
+
+    /// Offset into the last region
+    pos: AtomicUsize,
+
+    /// Total capacity of the arena
+    cap: usize,
+}
+
+// the arena space is only ever handed out in disjoint pieces, the position being updated
+// atomically.
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
+
+/// An arena allocated, fixed-size sequence of objects
+pub type Slice<T> = common::Slice<T, InnerRef>;
+
+/// An arena allocated, sequential, resizable vector
+///
+/// Since the arena does not support resizing, or freeing memory, this implementation just
+/// creates new slices as necessary and leaks the previous arena allocation, trading memory
+/// for speed.
+pub type SliceVec<T> = common::SliceVec<T, InnerRef>;
+
+/// An arena allocated, owned object
+pub type ArenaBox<T> = boxed::ArenaBox<T, InnerRef>;
+
+/// A clone-on-write pointer whose owned variant lives in the arena
+pub type ArenaCow<'b, T> = cow::ArenaCow<'b, T, InnerRef>;
+
+/// An arena allocated double-ended queue
+pub type ArenaVecDeque<T> = deque::ArenaVecDeque<T, InnerRef>;
+
+/// An arena allocated ordered map
+pub type ArenaBTreeMap<K, V> = btree::ArenaBTreeMap<K, V, InnerRef>;
+
+/// An arena allocated priority queue
+pub type ArenaBinaryHeap<T> = heap::ArenaBinaryHeap<T, InnerRef>;
+
+/// An arena allocated slab with stable keys
+pub type ArenaSlab<T> = slab::ArenaSlab<T, InnerRef>;
+
+/// An arena allocated container with generational indices
+pub type GenArena<T> = generational::GenArena<T, InnerRef>;
+
+/// An arena allocated bit vector
+pub type ArenaBitVec = bitvec::ArenaBitVec<InnerRef>;
+
+/// An arena allocated two-dimensional array
+pub type ArenaGrid<T> = grid::ArenaGrid<T, InnerRef>;
+
+/// An arena allocated map implemented as a sorted vector
+pub type ArenaVecMap<K, V> = vecmap::ArenaVecMap<K, V, InnerRef>;
+
+/// An arena allocated set implemented as a sorted vector
+pub type ArenaVecSet<T> = vecmap::ArenaVecSet<T, InnerRef>;
+
+/// An arena allocated hash map
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashMap<K, V> = hash::ArenaHashMap<K, V, InnerRef>;
+
+/// An arena allocated hash set
+#[cfg(feature = "hashbrown")]
+pub type ArenaHashSet<T> = hash::ArenaHashSet<T, InnerRef>;
+
+/// An arena allocated vector with stable element addresses
+pub type ChunkedVec<T> = chunkvec::ChunkedVec<T, InnerRef>;
+
+/// An arena allocated, fixed-capacity ring buffer
+pub type ArenaRingBuffer<T> = ring::ArenaRingBuffer<T, InnerRef>;
+
+/// An arena allocated, directed graph
+pub type ArenaGraph<N, E> = graph::ArenaGraph<N, E, InnerRef>;
+
+/// An arena allocated container of tree nodes
+pub type NodeArena<T> = tree::NodeArena<T, InnerRef>;
+
+/// A seekable writer over an arena allocated byte buffer
+pub type ArenaCursor = cursor::ArenaCursor<InnerRef>;
+
+impl Arena {
+    /// Create an `Arena` with specified capacity.
+    ///
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. It cannot
+    /// be grown after the fact.
+    pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
+        let pos = AtomicUsize::new(0);
+
+        Ok(Arena(
+            InnerRef {
+                inner: Arc::new(Inner { head, pos, cap }),
+            },
+            backing,
+        ))
+    }
+
+    /// Create another reference to the arena.
+    pub fn inner(&self) -> InnerRef {
+        self.0.clone()
+    }
+
+    /// Clear the arena.
+    ///
+    /// This fails if any other reference to the arena is still live, in any thread. The arena
+    /// is borrowed exclusively, so that no other thread can create new references while it is
+    /// being cleared.
+    pub fn clear(&mut self) -> Result<(), ArenaError> {
+        if Arc::strong_count(&self.inner) == 1 {
+            // synchronize with the drops of references in other threads, so that their accesses
+            // to the arena happen before it is reused, like `Arc::get_mut` does.
+            atomic::fence(Ordering::Acquire);
+
+            let pos = self.inner.pos.swap(0, Ordering::Relaxed);
+
+            unsafe { common::poison(self.inner.head, pos) };
+
+            Ok(())
+        } else {
+            Err(ArenaError::CannotClear {
+                live_handles: Arc::strong_count(&self.inner) - 1,
+            })
+        }
+    }
+}
+
+impl Deref for Arena {
+    type Target = InnerRef;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Arena {
+    fn drop(&mut self) {
+        self.1.destroy(self.inner.head, self.inner.cap);
+    }
+}
+
+unsafe impl AllocHandle for InnerRef {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let layout = Layout::array::<T>(count).map_err(|_| ArenaError::CapacityOverflow)?;
+
+        Ok(self.try_allocate_layout(layout)?.cast())
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        let size = mem::size_of::<T>();
+        // `ptr` may be dangling if nothing has been allocated yet, in which case the offset is
+        // meaningless, but doesn't match the position either.
+        let offset = (ptr.as_ptr() as usize).wrapping_sub(self.inner.head.as_ptr() as usize);
+        let old_end = offset.wrapping_add(old_count * size);
+        let new_end = count
+            .checked_mul(size)
+            .and_then(|new_size| offset.checked_add(new_size));
+
+        match new_end {
+            Some(new_end)
+                if offset < self.inner.cap
+                    && new_end <= self.inner.cap
+                    && self
+                        .inner
+                        .pos
+                        .compare_exchange(old_end, new_end, Ordering::Acquire, Ordering::Relaxed)
+                        .is_ok() =>
+            {
+                Ok(ptr)
+            }
+            _ => self.try_allocate(count),
+        }
+    }
+
+    fn try_allocate_layout(&self, layout: Layout) -> Result<NonNull<u8>, ArenaError> {
+        let align = layout.align();
+        let size = layout.size();
+        let head = self.inner.head.as_ptr();
+        let mut pos = self.inner.pos.load(Ordering::Relaxed);
+
+        loop {
+            let skip = (head as usize + pos).wrapping_neg() & (align - 1);
+            let end = match (pos + skip).checked_add(size) {
+                Some(end) if end <= self.inner.cap => end,
+                _ => return Err(common::capacity_exceeded(size, pos, self.inner.cap)),
+            };
+
+            // synchronize with the thread that released the memory last, see `dealloc_last`.
+            match self.inner.pos.compare_exchange_weak(
+                pos,
+                end,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return unsafe { Ok(NonNull::new_unchecked(head.add(pos + skip))) },
+                Err(p) => pos = p,
+            }
+        }
+    }
+
+    unsafe fn dealloc_last<T>(&self, ptr: NonNull<T>, count: usize) -> bool {
+        let size = count * mem::size_of::<T>();
+        let pos = self.inner.pos.load(Ordering::Relaxed);
+
+        match common::last_allocation_offset(self.inner.head, pos, ptr.cast(), size) {
+            // another thread might have allocated in the meantime, in which case the allocation
+            // is no longer the last one. The memory is poisoned up front, as another thread may
+            // allocate and write to it as soon as it is released, and poisoning it afterwards
+            // would race with that. The caller gives up the memory either way, so poisoning it
+            // if the release fails is harmless. Releasing the memory synchronizes with the
+            // thread allocating it next, so that our accesses to it happen before theirs.
+            Some(offset) => {
+                common::poison(ptr.cast(), size);
+
+                self.inner
+                    .pos
+                    .compare_exchange(pos, offset, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            }
+            None => false,
+        }
+    }
+
+    unsafe fn allocate_or_shrink<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> bool {
+        let size = mem::size_of::<T>();
+        let pos = self.inner.pos.load(Ordering::Relaxed);
+
+        match common::last_allocation_offset(self.inner.head, pos, ptr.cast(), old_count * size) {
+            Some(offset) => {
+                // the tail is poisoned before it is released, see `dealloc_last`.
+                common::poison(
+                    NonNull::new_unchecked(ptr.as_ptr().add(count)).cast(),
+                    (old_count - count) * size,
+                );
+
+                // if another thread has allocated in the meantime, the memory is simply not
+                // released.
+                self.inner
+                    .pos
+                    .compare_exchange(
+                        pos,
+                        offset + count * size,
+                        Ordering::AcqRel,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+            }
+            None => false,
+        }
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        common::owns_inner(self.inner.head, self.inner.cap, ptr, size)
+    }
+
+    fn arena_id(&self) -> Option<usize> {
+        Some(Arc::as_ptr(&self.inner) as usize)
+    }
+}
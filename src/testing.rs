@@ -0,0 +1,190 @@
+//! This module provides a harness for differential testing of vector types against `Vec`,
+//! enabled by the `testing` feature.
+//!
+//! A sequence of `SliceVecOp`s is applied to a set of containers under test and to a set of
+//! `Vec`s alike, and after every operation, the contents of both are compared. This is the model
+//! this crate's own randomized tests are built on. Containers other than `SliceVec`, such as
+//! wrappers around it, can be tested by implementing `VecModel` for them.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::fmt;
+
+/// An operation applied to one of the containers in a `Differential` harness.
+///
+/// Operations other than `Delete` and `Clone` create the container if it doesn't exist.
+#[derive(Clone, Debug)]
+pub enum SliceVecOp<T> {
+    /// Push an element.
+    Push(T),
+    /// Pop the last element, if any.
+    Pop,
+    /// Resize the container to the given length, filling it up with the given value.
+    Resize(usize, T),
+    /// Reserve space for the given number of additional elements.
+    Reserve(usize),
+    /// Shorten the container to the given length.
+    Truncate(usize),
+    /// Drop the container.
+    Delete,
+    /// Replace the container by a clone of the container at the given index.
+    Clone(usize),
+}
+
+/// A vector type that can be tested against `Vec` using a `Differential` harness.
+pub trait VecModel<T>: Clone {
+    /// Append an element.
+    fn push(&mut self, elem: T);
+    /// Remove the last element and return it, or `None` if the container is empty.
+    fn pop(&mut self) -> Option<T>;
+    /// Resize the container to hold `len` elements, initialized to `value` if necessary.
+    fn resize(&mut self, len: usize, value: T);
+    /// Reserve space for at least `additional` more elements.
+    fn reserve(&mut self, additional: usize);
+    /// Shorten the container to `len` elements.
+    fn truncate(&mut self, len: usize);
+    /// Return the contents of the container.
+    fn as_slice(&self) -> &[T];
+}
+
+impl<T: Clone, H: AllocHandle + Clone> VecModel<T> for SliceVec<T, H> {
+    fn push(&mut self, elem: T) {
+        SliceVec::push(self, elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        SliceVec::pop(self)
+    }
+
+    fn resize(&mut self, len: usize, value: T) {
+        SliceVec::resize(self, len, value)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        SliceVec::reserve(self, additional)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        SliceVec::truncate(self, len)
+    }
+
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+}
+
+/// A harness applying operations to a number of containers of type `V` and to as many `Vec`s,
+/// and checking that their contents stay the same.
+///
+/// New containers are created using the closure `F`, for instance creating an empty `SliceVec`
+/// using a handle to the arena to be tested.
+pub struct Differential<T, V, F> {
+    /// The reference containers
+    vecs: Vec<Option<Vec<T>>>,
+    /// The containers under test
+    models: Vec<Option<V>>,
+    /// The constructor for new containers under test
+    new: F,
+}
+
+impl<T, V, F> Differential<T, V, F>
+where
+    T: Clone + fmt::Debug + PartialEq,
+    V: VecModel<T>,
+    F: FnMut() -> V,
+{
+    /// Create a new harness for `count` containers, none of which exist initially.
+    pub fn new(count: usize, new: F) -> Self {
+        Differential {
+            vecs: vec![None; count],
+            models: (0..count).map(|_| None).collect(),
+            new,
+        }
+    }
+
+    /// Return the number of containers in the harness.
+    pub fn len(&self) -> usize {
+        self.vecs.len()
+    }
+
+    /// Return `true` if the harness has no containers.
+    pub fn is_empty(&self) -> bool {
+        self.vecs.is_empty()
+    }
+
+    /// Apply `op` to the containers at `index`, and check all containers afterwards.
+    ///
+    /// Panics if `index` (or the index of the container to clone) is out of bounds, or the
+    /// contents of any container under test differ from the reference.
+    pub fn apply(&mut self, index: usize, op: SliceVecOp<T>) {
+        match op {
+            SliceVecOp::Delete => {
+                self.vecs[index] = None;
+                self.models[index] = None;
+            }
+            SliceVecOp::Clone(other) => {
+                self.vecs[index] = self.vecs[other].clone();
+                self.models[index] = self.models[other].clone();
+            }
+            op => {
+                let new = &mut self.new;
+                let vec = self.vecs[index].get_or_insert_with(Vec::new);
+                let model = self.models[index].get_or_insert_with(new);
+
+                match op {
+                    SliceVecOp::Push(elem) => {
+                        vec.push(elem.clone());
+                        model.push(elem);
+                    }
+                    SliceVecOp::Pop => assert_eq!(vec.pop(), model.pop(), "pop on {}", index),
+                    SliceVecOp::Resize(len, value) => {
+                        vec.resize(len, value.clone());
+                        model.resize(len, value);
+                    }
+                    SliceVecOp::Reserve(additional) => {
+                        vec.reserve(additional);
+                        model.reserve(additional);
+                    }
+                    SliceVecOp::Truncate(len) => {
+                        vec.truncate(len);
+                        model.truncate(len);
+                    }
+                    SliceVecOp::Delete | SliceVecOp::Clone(_) => unreachable!(),
+                }
+            }
+        }
+
+        self.check();
+    }
+
+    /// Apply a sequence of operations, each paired with the index of the containers it applies
+    /// to.
+    pub fn run<I>(&mut self, ops: I)
+    where
+        I: IntoIterator<Item = (usize, SliceVecOp<T>)>,
+    {
+        for (index, op) in ops {
+            self.apply(index, op);
+        }
+    }
+
+    /// Check that the contents of all containers under test match their reference.
+    pub fn check(&self) {
+        for (index, (vec, model)) in self.vecs.iter().zip(&self.models).enumerate() {
+            match (vec, model) {
+                (Some(vec), Some(model)) => {
+                    assert_eq!(&vec[..], model.as_slice(), "contents of {} differ", index)
+                }
+                (None, None) => (),
+                _ => panic!("container {} is missing", index),
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, V, F> fmt::Debug for Differential<T, V, F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Differential")
+            .field("vecs", &self.vecs)
+            .finish()
+    }
+}
@@ -0,0 +1,137 @@
+//! This module contains allocation tracing hooks for arenas, enabled by the `trace` feature.
+//!
+//! The `rc` and `region` arenas report every allocation, extension and clear to a hook
+//! registered using `set_hook`, tagged with an id identifying the arena. This allows to find
+//! out which code causes bursts of allocations in production, by logging the events or
+//! forwarding them to a tracing framework.
+use std::any;
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// The name allocations made for a raw `Layout` (as opposed to a type) are reported under.
+pub const LAYOUT_ALLOCATIONS: &str = "<layout>";
+
+/// An event reported to the tracing hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// Memory for objects of a type has been allocated.
+    Allocate {
+        /// The id of the arena.
+        arena: u64,
+        /// The name of the type allocated.
+        type_name: &'static str,
+        /// The number of bytes allocated, excluding alignment padding.
+        bytes: usize,
+    },
+    /// A sequence of objects has been extended, either in place or by moving it.
+    Extend {
+        /// The id of the arena.
+        arena: u64,
+        /// The name of the type allocated.
+        type_name: &'static str,
+        /// The number of bytes of the sequence before it has been extended.
+        old_bytes: usize,
+        /// The number of bytes of the sequence after it has been extended.
+        bytes: usize,
+        /// Whether the sequence has been extended in place.
+        in_place: bool,
+    },
+    /// The arena has been cleared (or a generation has ended).
+    Clear {
+        /// The id of the arena.
+        arena: u64,
+        /// The number of bytes allocated before the arena has been cleared.
+        used: usize,
+    },
+}
+
+/// The hook events are reported to.
+type Hook = Arc<dyn Fn(&TraceEvent) + Send + Sync>;
+
+/// The registered hook, if any.
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// The id assigned to the next arena created.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The tracing state kept by an arena.
+#[derive(Debug)]
+pub(crate) struct Tracer {
+    id: u64,
+}
+
+/// Register `hook` to be called on every event reported by any arena, replacing the previous
+/// hook.
+///
+/// The hook is called on the thread the event occurs on, while the arena is in use, so it may
+/// not allocate from the arena reporting the event.
+pub fn set_hook<F>(hook: F)
+where
+    F: Fn(&TraceEvent) + Send + Sync + 'static,
+{
+    *HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Unregister the hook, if any.
+pub fn clear_hook() {
+    *HOOK.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+/// Report `event` to the hook, if any.
+fn emit(event: TraceEvent) {
+    // the hook isn't locked while running, so it can be replaced from within.
+    let hook = HOOK.read().unwrap_or_else(|err| err.into_inner()).clone();
+
+    if let Some(hook) = hook {
+        hook(&event);
+    }
+}
+
+impl Default for Tracer {
+    fn default() -> Self {
+        Tracer {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl Tracer {
+    /// Return the id of the arena.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Report an allocation of `count` objects of type `T`.
+    pub(crate) fn allocate<T>(&self, count: usize) {
+        self.allocate_bytes(any::type_name::<T>(), count * mem::size_of::<T>());
+    }
+
+    /// Report an allocation of `bytes` bytes under the given name.
+    pub(crate) fn allocate_bytes(&self, type_name: &'static str, bytes: usize) {
+        emit(TraceEvent::Allocate {
+            arena: self.id,
+            type_name,
+            bytes,
+        });
+    }
+
+    /// Report the extension of a sequence of `old_count` objects of type `T` to `count` objects.
+    pub(crate) fn extend<T>(&self, old_count: usize, count: usize, in_place: bool) {
+        emit(TraceEvent::Extend {
+            arena: self.id,
+            type_name: any::type_name::<T>(),
+            old_bytes: old_count * mem::size_of::<T>(),
+            bytes: count * mem::size_of::<T>(),
+            in_place,
+        });
+    }
+
+    /// Report a clear of the arena with `used` bytes allocated.
+    pub(crate) fn clear(&self, used: usize) {
+        emit(TraceEvent::Clear {
+            arena: self.id,
+            used,
+        });
+    }
+}
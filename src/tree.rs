@@ -0,0 +1,309 @@
+//! This module contains an arena of tree nodes addressed by typed ids.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// An arena allocated container of tree nodes, such as the nodes of an abstract syntax tree.
+///
+/// Allocating a node yields a lightweight `Id<T>`, which can be used to access the node
+/// through the container. In addition to the node values, the container keeps track of the
+/// tree structure: every node can be given a parent, and the children of a node can be
+/// iterated in the order they have been attached.
+pub struct NodeArena<T, H> {
+    nodes: SliceVec<Node<T>, H>,
+}
+
+/// The id of a node in a `NodeArena<T, _>`.
+///
+/// Ids are 32 bits wide and don't record which container they belong to.
+pub struct Id<T> {
+    index: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// A node stored in the container.
+struct Node<T> {
+    /// The value of the node
+    value: T,
+
+    /// The parent of the node, if any
+    parent: Option<Id<T>>,
+
+    /// The first child of the node, if any
+    first_child: Option<Id<T>>,
+
+    /// The last child of the node, if any
+    last_child: Option<Id<T>>,
+
+    /// The next sibling of the node, if any
+    next_sibling: Option<Id<T>>,
+}
+
+/// An iterator over the children of a node.
+pub struct Children<'a, T, H> {
+    arena: &'a NodeArena<T, H>,
+    next: Option<Id<T>>,
+}
+
+impl<T> Id<T> {
+    /// Create an id from a raw index.
+    pub fn from_raw(index: u32) -> Self {
+        Id {
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the raw index of the id.
+    pub fn into_raw(self) -> u32 {
+        self.index
+    }
+
+    /// Return the index of the node in its container.
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> fmt::Debug for Id<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("Id").field(&self.index).finish()
+    }
+}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> PartialOrd for Id<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Id<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> Hash for Id<T> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        self.index.hash(state);
+    }
+}
+
+impl<T, H> NodeArena<T, H> {
+    /// Return the number of nodes in the container.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return `true` if the container holds no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Return a reference to the value of node `id`, if it exists.
+    pub fn get(&self, id: Id<T>) -> Option<&T> {
+        self.nodes.get(id.index()).map(|n| &n.value)
+    }
+
+    /// Return a mutable reference to the value of node `id`, if it exists.
+    pub fn get_mut(&mut self, id: Id<T>) -> Option<&mut T> {
+        self.nodes.get_mut(id.index()).map(|n| &mut n.value)
+    }
+
+    /// Return the parent of node `id`, if any.
+    ///
+    /// Panics if the node does not exist.
+    pub fn parent(&self, id: Id<T>) -> Option<Id<T>> {
+        self.nodes[id.index()].parent
+    }
+
+    /// Return the first child of node `id`, if any.
+    ///
+    /// Panics if the node does not exist.
+    pub fn first_child(&self, id: Id<T>) -> Option<Id<T>> {
+        self.nodes[id.index()].first_child
+    }
+
+    /// Return the sibling following node `id`, if any.
+    ///
+    /// Panics if the node does not exist.
+    pub fn next_sibling(&self, id: Id<T>) -> Option<Id<T>> {
+        self.nodes[id.index()].next_sibling
+    }
+
+    /// Create an iterator over the children of node `id`, in the order they have been attached.
+    ///
+    /// Panics if the node does not exist.
+    pub fn children(&self, id: Id<T>) -> Children<'_, T, H> {
+        Children {
+            arena: self,
+            next: self.first_child(id),
+        }
+    }
+
+    /// Create an iterator over the ancestors of node `id`, starting with its parent.
+    ///
+    /// Panics if the node does not exist.
+    pub fn ancestors(&self, id: Id<T>) -> impl Iterator<Item = Id<T>> + '_ {
+        let mut next = self.parent(id);
+
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = self.parent(current);
+            Some(current)
+        })
+    }
+
+    /// Create an iterator over the ids and values of all nodes, in allocation order.
+    pub fn iter(&self) -> impl Iterator<Item = (Id<T>, &T)> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (Id::from_raw(i as u32), &n.value))
+    }
+
+    /// Attach node `child` as the last child of node `parent`.
+    ///
+    /// Panics if either node does not exist, if `child` already has a parent, or if `child`
+    /// is `parent` or one of its ancestors.
+    pub fn append_child(&mut self, parent: Id<T>, child: Id<T>) {
+        assert!(parent.index() < self.len(), "parent node does not exist");
+        assert!(
+            self.nodes[child.index()].parent.is_none(),
+            "node already has a parent"
+        );
+        assert!(
+            child != parent && self.ancestors(parent).all(|a| a != child),
+            "attaching node would create a cycle"
+        );
+
+        match self.nodes[parent.index()].last_child {
+            Some(last) => self.nodes[last.index()].next_sibling = Some(child),
+            None => self.nodes[parent.index()].first_child = Some(child),
+        }
+
+        self.nodes[parent.index()].last_child = Some(child);
+        self.nodes[child.index()].parent = Some(parent);
+    }
+}
+
+impl<T, H: AllocHandle> NodeArena<T, H> {
+    /// Create a new empty container using the given handle.
+    pub fn new(handle: H) -> Self {
+        NodeArena {
+            nodes: SliceVec::new(handle),
+        }
+    }
+
+    /// Create a new empty container with room for `capacity` nodes.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        NodeArena {
+            nodes: SliceVec::with_capacity(handle, capacity),
+        }
+    }
+
+    /// Allocate a new, detached node holding `value` and return its id.
+    ///
+    /// Panics if the container already holds `u32::MAX + 1` nodes.
+    pub fn alloc(&mut self, value: T) -> Id<T> {
+        let index = u32::try_from(self.nodes.len()).expect("too many nodes");
+
+        self.nodes.push(Node {
+            value,
+            parent: None,
+            first_child: None,
+            last_child: None,
+            next_sibling: None,
+        });
+
+        Id::from_raw(index)
+    }
+
+    /// Allocate a new node holding `value` as the last child of node `parent` and return its
+    /// id.
+    ///
+    /// Panics if `parent` does not exist.
+    pub fn alloc_child(&mut self, parent: Id<T>, value: T) -> Id<T> {
+        assert!(parent.index() < self.len(), "parent node does not exist");
+
+        let child = self.alloc(value);
+        self.append_child(parent, child);
+
+        child
+    }
+
+    /// Remove all nodes from the container.
+    ///
+    /// Ids handed out before refer to new nodes once these are allocated.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for NodeArena<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T, H> Index<Id<T>> for NodeArena<T, H> {
+    type Output = T;
+
+    fn index(&self, id: Id<T>) -> &T {
+        self.get(id).expect("out of bounds access")
+    }
+}
+
+impl<T, H> IndexMut<Id<T>> for NodeArena<T, H> {
+    fn index_mut(&mut self, id: Id<T>) -> &mut T {
+        self.get_mut(id).expect("out of bounds access")
+    }
+}
+
+impl<'a, T, H> Iterator for Children<'a, T, H> {
+    type Item = Id<T>;
+
+    fn next(&mut self) -> Option<Id<T>> {
+        let current = self.next?;
+        self.next = self.arena.next_sibling(current);
+        Some(current)
+    }
+}
+
+impl<'a, T, H> Clone for Children<'a, T, H> {
+    fn clone(&self) -> Self {
+        Children {
+            arena: self.arena,
+            next: self.next,
+        }
+    }
+}
+
+impl<'a, T, H> fmt::Debug for Children<'a, T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Children")
+            .field("next", &self.next)
+            .finish()
+    }
+}
@@ -0,0 +1,148 @@
+//! This module provides an arena holding objects of a single type.
+//!
+//! As opposed to the other arenas in this crate, this arena knows the type of every object
+//! allocated in it, and hence can run their destructors when it is cleared or dropped. In turn,
+//! it can only hold objects of one type, and hands out plain references to them: the arena is
+//! borrowed for as long as any object in it is accessed, and clearing it requires exclusive
+//! access, so the borrow-checker statically verifies that no object outlives its generation.
+use crate::common::{self, ArenaBacking, ArenaError};
+
+use std::cell::Cell;
+use std::fmt;
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::slice;
+
+/// An arena holding objects of type `T` (non-MT-safe), dropping them when cleared.
+///
+/// Since the objects are dropped together with the arena, they can't hold references to each
+/// other, use the `region` arena for cyclic structures instead.
+pub struct Arena<T> {
+    /// Head of the arena space
+    head: NonNull<T>,
+
+    /// Number of objects allocated
+    len: Cell<usize>,
+
+    /// Number of objects that fit into the arena
+    cap: usize,
+
+    /// Size of the arena space in bytes
+    size: usize,
+
+    /// The type of backing storage used in the arena
+    backing: ArenaBacking,
+}
+
+impl<T> Arena<T> {
+    /// Create an `Arena` with specified capacity in bytes.
+    ///
+    /// The capacity is rounded up to a multiple of the page size, and must not be zero. It cannot
+    /// be grown after the fact.
+    pub fn init_capacity(backing: ArenaBacking, cap: usize) -> Result<Self, ArenaError> {
+        assert!(
+            mem::align_of::<T>() <= common::get_page_size(),
+            "alignment exceeds page size"
+        );
+
+        let cap = common::round_capacity(cap)?;
+        let head = backing.try_create(cap)?;
+
+        Ok(Arena {
+            head: head.cast(),
+            len: Cell::new(0),
+            cap: cap.checked_div(mem::size_of::<T>()).unwrap_or(usize::MAX),
+            size: cap,
+            backing,
+        })
+    }
+
+    /// Return the number of objects allocated in the arena.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Return `true` if no objects have been allocated in the arena.
+    pub fn is_empty(&self) -> bool {
+        self.len.get() == 0
+    }
+
+    /// Return the number of objects the arena can hold.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Move `value` into the arena, or return it if the arena is full.
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_alloc(&self, value: T) -> Result<&mut T, T> {
+        let len = self.len.get();
+
+        if len == self.cap {
+            return Err(value);
+        }
+
+        self.len.set(len + 1);
+
+        unsafe {
+            let ptr = self.head.as_ptr().add(len);
+            ptr::write(ptr, value);
+
+            Ok(&mut *ptr)
+        }
+    }
+
+    /// Move `value` into the arena, and panic if the arena is full.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        match self.try_alloc(value) {
+            Ok(r) => r,
+            Err(_) => panic!("arena overflow: {} objects", self.cap),
+        }
+    }
+
+    /// Move the elements of an iterator into the arena, contiguously.
+    ///
+    /// Panics if the arena is full before the iterator is exhausted.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend<I: IntoIterator<Item = T>>(&self, iter: I) -> &mut [T] {
+        let start = self.len.get();
+
+        for value in iter {
+            self.alloc(value);
+        }
+
+        unsafe { slice::from_raw_parts_mut(self.head.as_ptr().add(start), self.len.get() - start) }
+    }
+
+    /// Create an iterator over all objects in the arena, in allocation order.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        unsafe { slice::from_raw_parts_mut(self.head.as_ptr(), self.len.get()).iter_mut() }
+    }
+
+    /// Clear the arena, dropping all objects allocated in it.
+    pub fn clear(&mut self) {
+        let len = self.len.replace(0);
+
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.head.as_ptr(), len));
+        }
+    }
+}
+
+impl<T> fmt::Debug for Arena<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Arena")
+            .field("len", &self.len.get())
+            .field("cap", &self.cap)
+            .field("backing", &self.backing)
+            .finish()
+    }
+}
+
+impl<T> Drop for Arena<T> {
+    fn drop(&mut self) {
+        self.clear();
+
+        self.backing.destroy(self.head.cast(), self.size);
+    }
+}
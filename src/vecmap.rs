@@ -0,0 +1,301 @@
+//! This module contains ordered maps and sets implemented as sorted vectors in arena memory.
+use crate::common::{AllocHandle, SliceVec};
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::mem;
+use std::slice;
+
+/// An arena allocated, ordered map implemented as a sorted `SliceVec` of entries.
+///
+/// Lookups use binary search, and insertions and removals shift the entries behind the
+/// affected position. This makes the map a cache-friendly option for small maps and maps that
+/// are mostly built in key order, which fits the append-heavy allocation pattern of arenas.
+pub struct ArenaVecMap<K, V, H> {
+    entries: SliceVec<(K, V), H>,
+}
+
+/// An arena allocated, ordered set implemented as a sorted `SliceVec`.
+///
+/// See `ArenaVecMap` for the performance characteristics.
+pub struct ArenaVecSet<T, H> {
+    elems: SliceVec<T, H>,
+}
+
+impl<K, V, H> ArenaVecMap<K, V, H> {
+    /// Return the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the position of the entry for `key`, or the position to insert it at.
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    /// Return a reference to the value corresponding to `key`, if any.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).ok().map(|i| &self.entries[i].1)
+    }
+
+    /// Return a mutable reference to the value corresponding to `key`, if any.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.search(key) {
+            Ok(i) => Some(&mut self.entries[i].1),
+            Err(_) => None,
+        }
+    }
+
+    /// Return `true` if the map contains an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).is_ok()
+    }
+
+    /// Create an iterator over the entries of the map, in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Create an iterator over the entries of the map with mutable values, in key order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> + '_ {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    /// Create an iterator over the keys of the map, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Create an iterator over the values of the map, in key order.
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Return the entries of the map as a slice, in key order.
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.entries
+    }
+}
+
+impl<K: Ord, V, H: AllocHandle> ArenaVecMap<K, V, H> {
+    /// Create a new empty map using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0)
+    }
+
+    /// Create a new empty map with space for `capacity` entries using the given handle.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        ArenaVecMap {
+            entries: SliceVec::with_capacity(handle, capacity),
+        }
+    }
+
+    /// Return the number of entries the map can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Insert an entry into the map.
+    ///
+    /// If the map already contained an entry for `key`, its value is replaced and the old
+    /// value returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.search(&key) {
+            Ok(i) => Some(mem::replace(&mut self.entries[i].1, value)),
+            Err(i) => {
+                self.entries.insert(i, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove the entry for `key` from the map and return its value, if any.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.search(key) {
+            Ok(i) => Some(self.entries.remove(i).1),
+            Err(_) => None,
+        }
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<K: Clone, V: Clone, H: AllocHandle + Clone> Clone for ArenaVecMap<K, V, H> {
+    fn clone(&self) -> Self {
+        ArenaVecMap {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, H> fmt::Debug for ArenaVecMap<K, V, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, H> PartialEq for ArenaVecMap<K, V, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K: Eq, V: Eq, H> Eq for ArenaVecMap<K, V, H> {}
+
+impl<K: Ord, V, H: AllocHandle> Extend<(K, V)> for ArenaVecMap<K, V, H> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<T, H> ArenaVecSet<T, H> {
+    /// Return the number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    /// Return `true` if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+
+    /// Return `true` if the set contains `elem`.
+    pub fn contains<Q>(&self, elem: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.elems
+            .binary_search_by(|e| e.borrow().cmp(elem))
+            .is_ok()
+    }
+
+    /// Create an iterator over the elements of the set, in order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.elems.iter()
+    }
+
+    /// Return the elements of the set as a slice, in order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.elems
+    }
+}
+
+impl<T: Ord, H: AllocHandle> ArenaVecSet<T, H> {
+    /// Create a new empty set using the given handle.
+    pub fn new(handle: H) -> Self {
+        Self::with_capacity(handle, 0)
+    }
+
+    /// Create a new empty set with space for `capacity` elements using the given handle.
+    pub fn with_capacity(handle: H, capacity: usize) -> Self {
+        ArenaVecSet {
+            elems: SliceVec::with_capacity(handle, capacity),
+        }
+    }
+
+    /// Return the number of elements the set can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.elems.capacity()
+    }
+
+    /// Insert an element into the set, returning `true` if it wasn't present before.
+    pub fn insert(&mut self, elem: T) -> bool {
+        match self.elems.binary_search(&elem) {
+            Ok(_) => false,
+            Err(i) => {
+                self.elems.insert(i, elem);
+                true
+            }
+        }
+    }
+
+    /// Remove an element from the set, returning `true` if it was present.
+    pub fn remove<Q>(&mut self, elem: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.elems.binary_search_by(|e| e.borrow().cmp(elem)) {
+            Ok(i) => {
+                self.elems.remove(i);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Remove all elements from the set.
+    pub fn clear(&mut self) {
+        self.elems.clear();
+    }
+}
+
+impl<T: Clone, H: AllocHandle + Clone> Clone for ArenaVecSet<T, H> {
+    fn clone(&self) -> Self {
+        ArenaVecSet {
+            elems: self.elems.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, H> fmt::Debug for ArenaVecSet<T, H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, H> PartialEq for ArenaVecSet<T, H> {
+    fn eq(&self, other: &Self) -> bool {
+        self.elems == other.elems
+    }
+}
+
+impl<T: Eq, H> Eq for ArenaVecSet<T, H> {}
+
+impl<'a, T, H> IntoIterator for &'a ArenaVecSet<T, H> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord, H: AllocHandle> Extend<T> for ArenaVecSet<T, H> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for e in iter {
+            self.insert(e);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "allocator_api")]
+#![feature(allocator_api)]
+
+use std::collections::VecDeque;
+
+use arenavec::rc::Arena;
+use arenavec::region;
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn std_collections_rc() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = Vec::new_in(arena.inner());
+    vec.extend(0..1000);
+    assert!(vec.iter().cloned().eq(0..1000));
+
+    let boxed = Box::new_in([1u8; 32], arena.inner());
+    assert_eq!(*boxed, [1; 32]);
+
+    let mut deque = VecDeque::new_in(arena.inner());
+    deque.push_back(1);
+    deque.push_front(0);
+    assert!(deque.iter().cloned().eq(0..2));
+
+    vec.shrink_to_fit();
+    assert_eq!(vec.len(), 1000);
+    assert!(arena.clear().is_err());
+
+    drop((vec, boxed, deque));
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn std_collections_region() {
+    let arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let mut vec: Vec<u64, _> = Vec::new_in(token.weak());
+    vec.extend(0..1000);
+    assert!(vec.iter().cloned().eq(0..1000));
+
+    #[repr(align(4096))]
+    struct Page(u8);
+
+    let page = Box::new_in(Page(3), token.weak());
+    assert_eq!(&*page as *const Page as usize % 4096, 0);
+    assert_eq!(page.0, 3);
+}
+
+#[test]
+fn exhaustion() {
+    use std::alloc::{Allocator, Layout};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let handle = arena.inner();
+
+    assert!(handle.allocate(Layout::new::<[u8; 4096]>()).is_ok());
+    assert!(handle.allocate(Layout::new::<u8>()).is_err());
+}
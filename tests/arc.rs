@@ -0,0 +1,464 @@
+use arenavec::arc::{Arena, ArenaBox, Quota, SliceVec};
+use arenavec::ArenaBacking;
+
+use std::thread;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn concurrent_allocation() {
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let threads: Vec<_> = (0..8)
+        .map(|t| {
+            let handle = arena.inner();
+
+            thread::spawn(move || {
+                let mut vec = SliceVec::new(handle.clone());
+                let mut boxes = Vec::new();
+
+                for i in 0..1000 {
+                    vec.push(t * 1000 + i);
+                    boxes.push(ArenaBox::new(handle.clone(), i));
+                }
+
+                (vec, boxes)
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+
+    for (t, (vec, boxes)) in results.iter().enumerate() {
+        assert!(vec.iter().cloned().eq(t * 1000..(t + 1) * 1000));
+        assert!(boxes.iter().map(|b| **b).eq(0..1000));
+    }
+
+    assert!(arena.clear().is_err());
+    std::mem::drop(results);
+    assert!(arena.clear().is_ok());
+    assert_eq!(arena.used(), 0);
+}
+
+#[test]
+fn moved_between_threads() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    // the arena is built on one thread, its contents consumed and the arena cleared on another
+    let (mut arena, vec) = thread::spawn(move || {
+        let mut vec = SliceVec::new(arena.inner());
+        for i in 0..100usize {
+            vec.push(i);
+        }
+
+        (arena, vec)
+    })
+    .join()
+    .unwrap();
+
+    thread::spawn(move || {
+        assert_eq!(vec.iter().sum::<usize>(), 4950);
+        assert!(arena.clear().is_err());
+
+        std::mem::drop(vec);
+        assert!(arena.clear().is_ok());
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn reclaim_last_allocation() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 8);
+    vec.extend_from_slice(&[1u64, 2]);
+    let used = arena.used();
+
+    // growing and shrinking the last allocation happens in place
+    vec.reserve(8);
+    assert_eq!(arena.used(), used + 64);
+    vec.shrink_to_fit();
+    assert_eq!(arena.used(), used - 48);
+
+    std::mem::drop(vec);
+    assert_eq!(arena.used(), 0);
+}
+
+#[test]
+fn clear_force() {
+    use arenavec::common::ArenaError;
+
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 4);
+    vec.push(1u32);
+
+    assert!(matches!(
+        arena.clear(),
+        Err(ArenaError::CannotClear { live_handles: 1 })
+    ));
+    unsafe { arena.clear_force() };
+    assert_eq!(arena.used(), 0);
+
+    // the old references can't allocate anymore, but the arena itself can
+    assert!(vec.handle().is_stale());
+    assert!(matches!(
+        SliceVec::<u8>::try_with_capacity(vec.handle().clone(), 1),
+        Err(ArenaError::StaleHandle)
+    ));
+    assert!(!arena.is_stale());
+    assert!(!arena.inner().is_stale());
+
+    let fresh = SliceVec::<u32>::with_capacity(arena.inner(), 4);
+    assert_eq!(fresh.capacity(), 4);
+
+    // stale vectors leak their contents when dropped
+    std::mem::drop(vec);
+    std::mem::drop(fresh);
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn quota() {
+    use arenavec::ArenaError;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let warnings = Arc::new(AtomicUsize::new(0));
+    let w = warnings.clone();
+
+    arena.set_quota(
+        Quota::new()
+            .soft(1024, move |_| {
+                w.fetch_add(1, Ordering::Relaxed);
+            })
+            .hard(4096),
+    );
+
+    {
+        let handle = arena.inner();
+
+        // the soft limit is checked on the thread allocating from the arena
+        let mut vec = thread::spawn(move || {
+            let mut vec = SliceVec::new(handle);
+
+            for i in 0..400u32 {
+                vec.push(i);
+            }
+
+            vec
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(warnings.load(Ordering::Relaxed), 1);
+
+        match vec.try_reserve(1024) {
+            Err(ArenaError::QuotaExceeded { .. }) => (),
+            _ => panic!("hard quota not enforced"),
+        }
+
+        // allocations within the quota still succeed
+        vec.push(0);
+        assert_eq!(warnings.load(Ordering::Relaxed), 1);
+    }
+
+    arena.clear().unwrap();
+
+    let _big: SliceVec<u8> = SliceVec::with_capacity(arena.inner(), 2048);
+    assert_eq!(warnings.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn child_budget() {
+    use arenavec::common::{ArenaError, SliceVec as GenericSliceVec};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let child = arena.inner().child(64);
+
+    let mut vec = GenericSliceVec::with_capacity(child.clone(), 4);
+    vec.extend_from_slice(&[1u64, 2, 3, 4]);
+    assert_eq!(child.remaining(), 32);
+
+    // clones of the reference share the budget, also across threads
+    let other = child.clone();
+    thread::spawn(move || {
+        let vec = GenericSliceVec::<u64, _>::with_capacity(other.clone(), 4);
+        assert!(matches!(
+            GenericSliceVec::<u8, _>::try_with_capacity(other, 1),
+            Err(ArenaError::QuotaExceeded {
+                requested: 1,
+                remaining: 0
+            })
+        ));
+
+        std::mem::drop(vec);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(child.remaining(), 32);
+
+    // shrinking and dropping the last allocation credits its size back
+    vec.truncate(2);
+    assert_eq!(child.remaining(), 48);
+    std::mem::drop(vec);
+    assert_eq!(child.remaining(), 64);
+}
+
+#[test]
+fn mark_reset() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut persistent = SliceVec::with_capacity(arena.inner(), 16);
+    persistent.push(1);
+
+    let marker = arena.mark();
+
+    {
+        let mut temp = SliceVec::new(arena.inner());
+        temp.resize(1000, 0u64);
+
+        // leak the vector, as dropping the last allocation returns its memory to the arena
+        std::mem::forget(temp);
+    }
+
+    assert!(arena.mark() > marker);
+
+    unsafe {
+        arena.reset_to(marker);
+    }
+
+    assert_eq!(arena.mark(), marker);
+
+    // reusing the rewound memory doesn't affect objects allocated before the marker
+    let mut temp = SliceVec::new(arena.inner());
+    temp.resize(1000, 0xffu8);
+    assert_eq!(persistent[0], 1);
+}
+
+#[test]
+fn snapshot_restore() {
+    use arenavec::common::AllocHandle;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+    let board: std::ptr::NonNull<u32> = handle.allocate(64);
+
+    let read = |i| unsafe { *board.as_ptr().add(i) };
+    let write = |i, v| unsafe { *board.as_ptr().add(i) = v };
+
+    for i in 0..64 {
+        write(i, i as u32);
+    }
+
+    let marker = arena.mark();
+    let snapshot = unsafe { arena.snapshot() };
+
+    // a speculative computation, which is undone afterwards
+    for i in 0..64 {
+        write(i, 0);
+    }
+
+    let _scratch: std::ptr::NonNull<u64> = handle.allocate(128);
+
+    unsafe {
+        arena.restore(&snapshot);
+    }
+
+    assert_eq!(arena.mark(), marker);
+    assert!((0..64).all(|i| read(i) == i as u32));
+}
+
+#[test]
+fn clear_observers() {
+    use arenavec::ClearPhase;
+    use std::sync::{Arc, Mutex};
+
+    let phases = Arc::new(Mutex::new(Vec::new()));
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let observed = phases.clone();
+    arena.on_clear(move |phase| observed.lock().unwrap().push(phase));
+
+    // failed clears aren't observed
+    let vec = SliceVec::<u8>::with_capacity(arena.inner(), 8);
+    assert!(arena.clear().is_err());
+    assert!(phases.lock().unwrap().is_empty());
+
+    // the arena is cleared on another thread
+    std::mem::drop(vec);
+    thread::spawn(move || arena.clear().unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(
+        *phases.lock().unwrap(),
+        [ClearPhase::Before, ClearPhase::After]
+    );
+}
+
+#[test]
+fn carve() {
+    use arenavec::common::ArenaError;
+
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096 * 4).unwrap();
+
+    let mut first = arena.carve(4096).unwrap();
+    let second = arena.carve(4096).unwrap();
+
+    {
+        let mut vec = SliceVec::with_capacity(first.inner(), 1024);
+        vec.resize(1024, 1u8);
+
+        // exhausting one child arena leaves the other one and the parent untouched
+        assert!(matches!(
+            vec.try_reserve(4096),
+            Err(ArenaError::CapacityExceeded { .. })
+        ));
+
+        let mut other = SliceVec::with_capacity(second.inner(), 1024);
+        other.resize(1024, 2u8);
+
+        let mut own = SliceVec::with_capacity(arena.inner(), 1024);
+        own.resize(1024, 3u8);
+
+        assert!(vec.iter().all(|&b| b == 1));
+        assert!(other.iter().all(|&b| b == 2));
+        assert!(own.iter().all(|&b| b == 3));
+
+        assert!(matches!(
+            first.arena_mut().clear(),
+            Err(ArenaError::CannotClear { .. })
+        ));
+    }
+
+    first.arena_mut().clear().unwrap();
+    assert!(matches!(
+        arena.carve(4096 * 3),
+        Err(ArenaError::CapacityExceeded { .. })
+    ));
+    assert!(matches!(arena.clear(), Err(ArenaError::CannotClear { .. })));
+
+    std::mem::drop((first, second));
+    arena.clear().unwrap();
+}
+
+#[test]
+fn grow_and_resize() {
+    use arenavec::common::{AllocHandle, ArenaError};
+
+    for backing in [ArenaBacking::MemoryMap, ArenaBacking::SystemAllocation] {
+        if cfg!(miri) {
+            continue;
+        }
+
+        let mut arena = Arena::init_capacity(backing, 4096).unwrap();
+
+        // allocations through the arena itself don't keep it from moving
+        let ptr = arena.allocate::<u64>(1);
+        let offset = ptr.as_ptr() as usize - arena.base().as_ptr() as usize;
+        unsafe { *ptr.as_ptr() = 7 };
+
+        let vec = SliceVec::<u8>::with_capacity(arena.inner(), 1);
+        assert!(matches!(
+            arena.resize(4096 * 2),
+            Err(ArenaError::CannotGrow)
+        ));
+        std::mem::drop(vec);
+
+        arena.resize(4096 * 4).unwrap();
+        assert_eq!(arena.capacity(), 4096 * 4);
+        assert_eq!(
+            unsafe { *(arena.base().as_ptr().add(offset) as *const u64) },
+            7
+        );
+
+        arena.grow(4096 * 4).unwrap();
+        assert!(arena.capacity() >= 4096 * 8);
+
+        let mut vec = SliceVec::with_capacity(arena.inner(), 4096);
+
+        for i in 0..4096u64 {
+            vec.push(i);
+        }
+
+        assert!(vec.iter().cloned().eq(0..4096));
+    }
+}
+
+#[test]
+fn serialize_roundtrip() {
+    use arenavec::common::AllocHandle;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let ptr = arena.allocate::<u64>(4);
+    unsafe { std::ptr::copy_nonoverlapping([1u64, 2, 3, 4].as_ptr(), ptr.as_ptr(), 4) };
+    let offset = ptr.as_ptr() as usize - arena.base().as_ptr() as usize;
+
+    let mut bytes = Vec::new();
+    unsafe { arena.serialize_to(&mut bytes) }.unwrap();
+
+    // the format is shared with the `rc` arena
+    let loaded = Arena::load_from(ArenaBacking::SystemAllocation, &bytes[..]).unwrap();
+    let rc = arenavec::rc::Arena::load_from(ArenaBacking::SystemAllocation, &bytes[..]).unwrap();
+    assert_eq!(loaded.used(), arena.used());
+    assert_eq!(rc.used(), arena.used());
+
+    for base in [loaded.base(), rc.base()] {
+        let copy =
+            unsafe { std::slice::from_raw_parts(base.as_ptr().add(offset) as *const u64, 4) };
+        assert_eq!(copy, &[1, 2, 3, 4]);
+    }
+
+    // arenas with allocations from the back can't be loaded
+    let rc = arenavec::rc::Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    rc.back().allocate::<u64>(1);
+    let mut bytes = Vec::new();
+    rc.serialize_to(&mut bytes).unwrap();
+
+    let err = Arena::load_from(ArenaBacking::SystemAllocation, &bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn fork() {
+    use arenavec::common::ArenaError;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let mut system = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    assert!(matches!(
+        unsafe { system.fork() },
+        Err(ArenaError::CannotFork)
+    ));
+
+    if cfg!(not(target_os = "linux")) {
+        return;
+    }
+
+    let mut arena = Arena::init_capacity(ArenaBacking::MemoryMap, 4096 * 4).unwrap();
+    let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+    vec.extend_from_slice(&[1u64, 2, 3, 4]);
+
+    let offset = vec.as_ptr() as usize - arena.base().as_ptr() as usize;
+    let forked = unsafe { arena.fork() }.unwrap();
+
+    // the forked arena can be handed to another thread
+    let copy = thread::spawn(move || {
+        let copy = unsafe {
+            std::slice::from_raw_parts_mut(forked.base().as_ptr().add(offset) as *mut u64, 4)
+        };
+        copy[0] = 10;
+        copy.to_vec()
+    })
+    .join()
+    .unwrap();
+
+    // writes to either arena are private to it
+    vec[1] = 20;
+    assert_eq!(&vec[..], &[1, 20, 3, 4]);
+    assert_eq!(copy, &[10, 2, 3, 4]);
+}
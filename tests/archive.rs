@@ -0,0 +1,91 @@
+#![cfg(feature = "rkyv")]
+
+use arenavec::common::AllocHandle;
+use arenavec::rc::{Arena, Slice, SliceVec};
+use arenavec::{local, ArenaBacking};
+
+use rkyv::de::Pool;
+use rkyv::rancor::{Error, Strategy};
+use rkyv::vec::ArchivedVec;
+use rkyv::{Archive, Archived};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[derive(Archive, rkyv::Serialize)]
+struct Level {
+    name: String,
+    tiles: SliceVec<u16>,
+}
+
+#[test]
+fn roundtrip() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let target = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::new(arena.inner());
+    vec.extend_from_slice(&[String::from("a"), String::from("bc")]);
+
+    let bytes = rkyv::to_bytes::<Error>(&vec).unwrap();
+    let archived = rkyv::access::<ArchivedVec<Archived<String>>, Error>(&bytes).unwrap();
+    assert_eq!(archived.len(), 2);
+    assert_eq!(archived[1], "bc");
+
+    let mut pool = Pool::new();
+    let res: SliceVec<String> = SliceVec::deserialize_archived_in(
+        target.inner(),
+        archived,
+        Strategy::<_, Error>::wrap(&mut pool),
+    )
+    .unwrap();
+
+    assert_eq!(res, vec);
+    assert_eq!(res.handle().arena_id(), target.inner().arena_id());
+
+    // slices are archived like vectors
+    let slice: Slice<u32> = Slice::new(arena.inner(), 3);
+    let bytes = rkyv::to_bytes::<Error>(&slice).unwrap();
+    let archived = rkyv::access::<ArchivedVec<Archived<u32>>, Error>(&bytes).unwrap();
+    assert_eq!(archived.as_slice(), &[0, 0, 0]);
+}
+
+#[test]
+fn nested() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut tiles = SliceVec::new(arena.inner());
+    tiles.extend_from_slice(&[1, 2, 3]);
+    let level = Level {
+        name: String::from("first"),
+        tiles,
+    };
+
+    let bytes = rkyv::to_bytes::<Error>(&level).unwrap();
+    let archived = rkyv::access::<ArchivedLevel, Error>(&bytes).unwrap();
+
+    assert_eq!(archived.name, "first");
+    assert_eq!(archived.tiles.as_slice(), &[1, 2, 3]);
+}
+
+#[test]
+fn exhausted() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    let big = vec![0u64; 1024];
+    let bytes = rkyv::to_bytes::<Error>(&big).unwrap();
+    let archived = rkyv::access::<ArchivedVec<Archived<u64>>, Error>(&bytes).unwrap();
+
+    let mut pool = Pool::new();
+    let res: Result<SliceVec<u64>, Error> =
+        SliceVec::deserialize_archived_in(arena.inner(), archived, Strategy::wrap(&mut pool));
+    assert!(res.is_err());
+}
+
+#[test]
+fn default_handle() {
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let bytes = rkyv::to_bytes::<Error>(&vec![1u8, 2, 3]).unwrap();
+    let vec: local::SliceVec<u8> = rkyv::from_bytes::<local::SliceVec<u8>, Error>(&bytes).unwrap();
+
+    assert_eq!(&*vec, &[1, 2, 3]);
+}
@@ -0,0 +1,75 @@
+use proptest::collection;
+use proptest::prelude::*;
+
+use arenavec::rc::{Arena, ArenaBitVec};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[cfg(not(miri))]
+mod prop {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn bitwise_ops(bits in collection::vec((any::<bool>(), any::<bool>()), 0..300)) {
+            let arena =
+                Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+            let mut a = ArenaBitVec::new(arena.inner());
+            let mut b = ArenaBitVec::new(arena.inner());
+
+            for &(x, y) in &bits {
+                a.push(x);
+                b.push(y);
+            }
+
+            let mut and = a.clone();
+            and.and(&b);
+            let mut or = a.clone();
+            or.or(&b);
+            let mut xor = a.clone();
+            xor.xor(&b);
+            let mut not = a.clone();
+            not.negate();
+
+            for (i, &(x, y)) in bits.iter().enumerate() {
+                assert_eq!(and.get(i), Some(x && y));
+                assert_eq!(or.get(i), Some(x || y));
+                assert_eq!(xor.get(i), Some(x ^ y));
+                assert_eq!(not.get(i), Some(!x));
+                assert_eq!(a.rank(i), bits[..i].iter().filter(|b| b.0).count());
+            }
+
+            assert_eq!(a.count_ones(), bits.iter().filter(|b| b.0).count());
+            assert_eq!(not.count_ones(), a.count_zeros());
+            assert!(a.iter_ones().eq((0..bits.len()).filter(|&i| bits[i].0)));
+        }
+    }
+}
+
+#[test]
+fn push_set_pop() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut bits = ArenaBitVec::from_elem(arena.inner(), 70, true);
+
+    assert_eq!(bits.len(), 70);
+    assert_eq!(bits.count_ones(), 70);
+    assert!(bits.all());
+
+    bits.set(3, false);
+    bits.push(false);
+
+    assert_eq!(bits.get(3), Some(false));
+    assert_eq!(bits.get(70), Some(false));
+    assert_eq!(bits.get(71), None);
+    assert_eq!(bits.rank(71), 69);
+
+    assert_eq!(bits.pop(), Some(false));
+    assert_eq!(bits.pop(), Some(true));
+    assert_eq!(bits.len(), 69);
+
+    bits.fill(false);
+
+    assert!(!bits.any());
+    assert_eq!(format!("{:?}", bits).len(), 69);
+}
@@ -0,0 +1,207 @@
+use std::future::Future;
+use std::marker::PhantomPinned;
+use std::ptr;
+use std::rc::Rc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn rc_box() {
+    use arenavec::rc::{Arena, ArenaBox};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut b = ArenaBox::new(arena.inner(), 42);
+    *b += 1;
+
+    assert_eq!(*b, 43);
+    assert_eq!(b.clone(), b);
+    assert_eq!(ArenaBox::into_inner(b), 43);
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn region_box_drop() {
+    use arenavec::region::{Arena, ArenaBox};
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    {
+        let b = ArenaBox::new(token.weak(), rc.clone());
+        let _c = b.clone();
+
+        assert_eq!(Rc::strong_count(&rc), 3);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+struct SelfRef {
+    value: usize,
+    ptr: *const usize,
+    _pin: PhantomPinned,
+}
+
+#[test]
+fn pinned_self_reference() {
+    use arenavec::region::{Arena, ArenaBox};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let mut b = unsafe {
+        ArenaBox::pin_in(
+            token.weak(),
+            SelfRef {
+                value: 5,
+                ptr: ptr::null(),
+                _pin: PhantomPinned,
+            },
+        )
+    };
+
+    unsafe {
+        let inner = b.as_mut().get_unchecked_mut();
+        inner.ptr = &inner.value;
+    }
+
+    assert_eq!(unsafe { *b.ptr }, 5);
+    assert_eq!(b.ptr, &b.value as *const usize);
+}
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+}
+
+#[test]
+fn pinned_future() {
+    use arenavec::rc::{Arena, ArenaBox};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut fut = unsafe {
+        ArenaBox::pin_in(arena.inner(), async {
+            let x = [1, 2, 3];
+            let r = &x;
+            async {}.await;
+            r.iter().sum::<usize>()
+        })
+    };
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(6));
+}
+
+#[test]
+fn boxed_slice() {
+    use arenavec::region::{Arena, ArenaBox};
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    {
+        let b: ArenaBox<[Rc<()>]> = ArenaBox::from_iter(token.weak(), (0..7).map(|_| rc.clone()));
+        let c = b.clone();
+
+        assert_eq!(b.len(), 7);
+        assert_eq!(c.len(), 7);
+        assert_eq!(Rc::strong_count(&rc), 15);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+
+    let mut bytes: ArenaBox<[u8]> = ArenaBox::from_slice(token.weak(), b"abc");
+    bytes[0] = b'x';
+
+    assert_eq!(&*bytes, b"xbc");
+    // the length lives in the arena, so the box is a single pointer plus the handle
+    assert_eq!(
+        std::mem::size_of_val(&bytes),
+        std::mem::size_of::<usize>() + std::mem::size_of_val(&token.weak())
+    );
+
+    let empty: ArenaBox<[u64]> = ArenaBox::from_slice(token.weak(), &[]);
+
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn boxed_slice_alignment() {
+    use arenavec::rc::{Arena, ArenaBox};
+
+    #[derive(Clone, Debug, PartialEq)]
+    #[repr(align(32))]
+    struct Aligned(u8);
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let _pad: ArenaBox<[u8]> = ArenaBox::from_slice(arena.inner(), b"x");
+    let b: ArenaBox<[Aligned]> = ArenaBox::from_slice(arena.inner(), &[Aligned(1), Aligned(2)]);
+
+    assert_eq!(&*b, &[Aligned(1), Aligned(2)]);
+    assert_eq!(b.as_ptr() as usize % 32, 0);
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn boxed_slice_overflow() {
+    use arenavec::rc::{Arena, ArenaBox};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    // the reported length overflows the size of the slice
+    let _b: ArenaBox<[u64]> = ArenaBox::from_iter(arena.inner(), (0..usize::MAX).map(|_| 0));
+}
+
+#[test]
+fn downcast() {
+    use arenavec::rc::{Arena, ArenaBox};
+    use std::any::Any;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let rc = Rc::new(());
+        let mut registry: Vec<ArenaBox<dyn Any>> = vec![
+            ArenaBox::into_any(ArenaBox::new(arena.inner(), 7u32)),
+            ArenaBox::into_any(ArenaBox::new(arena.inner(), String::from("plugin"))),
+            ArenaBox::into_any(ArenaBox::new(arena.inner(), rc.clone())),
+        ];
+
+        assert!(registry[0].is::<u32>());
+        assert_eq!(registry[1].downcast_ref::<String>().unwrap(), "plugin");
+        assert_eq!(Rc::strong_count(&rc), 2);
+
+        let last = registry.pop().unwrap();
+        let last = last.downcast::<u32>().unwrap_err();
+        let last = last.downcast::<Rc<()>>().unwrap();
+
+        assert_eq!(Rc::strong_count(&last), 2);
+
+        let first = registry.remove(0).downcast::<u32>().unwrap();
+
+        assert_eq!(*first, 7);
+
+        registry.clear();
+        std::mem::drop(last);
+
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    assert!(arena.clear().is_ok());
+}
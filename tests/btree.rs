@@ -0,0 +1,96 @@
+use proptest::collection;
+use proptest::prelude::*;
+
+use std::collections::BTreeMap;
+
+use arenavec::region::{Arena, ArenaBTreeMap};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+fn check(entries: Vec<(u16, usize)>, ranges: Vec<(u16, u16)>) {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let mut map = BTreeMap::new();
+    let mut arena_map = ArenaBTreeMap::new(token.weak());
+
+    for (k, v) in entries {
+        assert_eq!(map.insert(k, v), arena_map.insert(k, v));
+        assert_eq!(map.len(), arena_map.len());
+    }
+
+    assert!(map.iter().eq(arena_map.iter()));
+    assert_eq!(map.first_key_value(), arena_map.first_key_value());
+    assert_eq!(map.last_key_value(), arena_map.last_key_value());
+
+    for (a, b) in ranges {
+        let (a, b) = (a.min(b), a.max(b));
+
+        assert!(map.range(a..b).eq(arena_map.range(a..b)));
+        assert!(map.range(a..=b).eq(arena_map.range(a..=b)));
+        assert!(map.range(a..).eq(arena_map.range(a..)));
+        assert!(map.range(..b).eq(arena_map.range(..b)));
+        assert_eq!(map.get(&a), arena_map.get(&a));
+    }
+}
+
+#[cfg(not(miri))]
+mod prop {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn rand_inserts(entries in collection::vec((0..2000u16, 0..usize::MAX), 0..1000),
+                        ranges in collection::vec((0..2000u16, 0..2000u16), 20)) {
+            check(entries, ranges);
+        }
+    }
+}
+
+#[test]
+fn ordered_inserts() {
+    check(
+        (0..500).map(|i| (i, i as usize)).collect(),
+        vec![(10, 400), (0, 0), (499, 600)],
+    );
+    check(
+        (0..500).rev().map(|i| (i, i as usize)).collect(),
+        vec![(10, 400), (0, 0), (499, 600)],
+    );
+}
+
+#[test]
+fn get_mut_and_drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    {
+        let mut map = ArenaBTreeMap::new(token.weak());
+
+        for i in 0..100 {
+            map.insert(format!("{:03}", i), rc.clone());
+        }
+
+        let clone = map.clone();
+
+        assert_eq!(Rc::strong_count(&rc), 201);
+        assert_eq!(clone, map);
+
+        *map.get_mut("042").unwrap() = Rc::new(());
+
+        assert_eq!(Rc::strong_count(&rc), 200);
+        assert!(map.contains_key("099"));
+        assert!(!map.contains_key("100"));
+
+        map.clear();
+
+        assert!(map.is_empty());
+        assert_eq!(Rc::strong_count(&rc), 101);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
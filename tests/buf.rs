@@ -0,0 +1,69 @@
+#![cfg(feature = "bytes")]
+
+use arenavec::buf::ByteCursor;
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::ArenaBacking;
+
+use bytes::{Buf, BufMut};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn write_frame() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut frame = SliceVec::new(arena.inner());
+    frame.put_u8(1);
+    frame.put_u32(0xdead_beef);
+    frame.put_slice(b"payload");
+    frame.put_bytes(0, 3);
+    frame.put_u16_le(0x1234);
+
+    assert_eq!(frame.len(), 1 + 4 + 7 + 3 + 2);
+    assert_eq!(&frame[..5], &[1, 0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(&frame[5..12], b"payload");
+    assert_eq!(&frame[15..], &[0x34, 0x12]);
+
+    // a large write grows the vector as needed
+    let mut big = SliceVec::new(arena.inner());
+    for i in 0..10_000u32 {
+        big.put_u32(i);
+    }
+    assert_eq!(big.len(), 40_000);
+    assert_eq!(&big[39_996..], &9_999u32.to_be_bytes());
+}
+
+#[test]
+fn read_frame() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut frame = SliceVec::new(arena.inner());
+    frame.put_u16(7);
+    frame.put_slice(b"abcdefg");
+    frame.put_i64_le(-1);
+
+    let mut cursor = ByteCursor::new(frame.into_slice());
+    assert_eq!(cursor.remaining(), 17);
+
+    let len = cursor.get_u16() as usize;
+    let mut payload = vec![0; len];
+    cursor.copy_to_slice(&mut payload);
+    assert_eq!(&payload, b"abcdefg");
+    assert_eq!(cursor.get_i64_le(), -1);
+    assert!(!cursor.has_remaining());
+
+    cursor.set_position(2);
+    assert_eq!(cursor.chunk()[0], b'a');
+    assert_eq!(cursor.into_inner().len(), 17);
+}
+
+#[test]
+#[should_panic(expected = "cannot advance")]
+fn advance_past_end() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut frame = SliceVec::new(arena.inner());
+    frame.put_u8(0);
+
+    ByteCursor::new(frame.into_slice()).advance(2);
+}
@@ -0,0 +1,69 @@
+use arenavec::rc::Arena;
+use arenavec::{local, region, ArenaBacking, BumpAlloc, ThreadLocalHandle};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn rc_handle() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+
+    let a = handle.alloc(1u64);
+    let b = handle.alloc_slice_copy(&[1u8, 2, 3]);
+    let c = handle.alloc_slice_clone(&[String::from("x"), String::from("yz")]);
+    let d = handle.alloc_slice_fill_with(4, |i| i * i);
+    let e = handle.alloc_str("hello");
+
+    *a += 1;
+    b[0] = 7;
+    e.make_ascii_uppercase();
+
+    assert_eq!(*a, 2);
+    assert_eq!(b, &[7, 2, 3]);
+    assert_eq!(c, &["x", "yz"]);
+    assert_eq!(d, &[0, 1, 4, 9]);
+    assert_eq!(e, "HELLO");
+
+    // empty and zero-sized allocations don't consume arena space
+    let used = arena.used();
+    assert!(handle.alloc_slice_copy::<u32>(&[]).is_empty());
+    assert_eq!(handle.alloc_slice_fill_with(16, |_| ()).len(), 16);
+    assert_eq!(arena.used(), used);
+}
+
+#[test]
+fn thread_local_handle() {
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let handle = ThreadLocalHandle::new();
+    let values = handle.alloc_slice_fill_with(3, |i| i as u8);
+
+    assert_eq!(values, &[0, 1, 2]);
+}
+
+#[test]
+fn region_token() {
+    let arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    arena.scope(|token| {
+        // the inherent methods of tokens take precedence
+        let s = token.alloc(String::from("owned"));
+        let clones = token.alloc_slice_clone(&[1, 2]);
+
+        assert_eq!(s, "owned");
+        assert_eq!(clones, &[1, 2]);
+
+        // objects borrowing from the arena are never dropped
+        let borrowed = BumpAlloc::alloc(token, (&*s, clones.len()));
+        assert_eq!(borrowed.0, "owned");
+    });
+}
+
+#[test]
+#[should_panic(expected = "arena is exhausted")]
+fn exhausted() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    arena.inner().alloc_slice_fill_with(1 << 20, |i| i);
+}
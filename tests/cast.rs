@@ -0,0 +1,40 @@
+#![cfg(feature = "zerocopy")]
+
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn cast_records() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    // the first allocation starts at the (aligned) beginning of the arena
+    let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+    for i in 0u32..4 {
+        vec.extend_from_slice(&i.to_ne_bytes());
+    }
+
+    assert_eq!(vec.as_slice_of::<u32>(), Some(&[0, 1, 2, 3][..]));
+    assert_eq!(vec.as_slice_of::<[u8; 8]>().map(|r| r.len()), Some(2));
+    vec.as_mut_slice_of::<u32>().unwrap()[2] = 7;
+
+    let mut slice = vec.into_slice();
+    assert_eq!(slice.cast::<u32>(), Some(&[0, 1, 7, 3][..]));
+    slice.cast_mut::<u16>().unwrap()[0] = 1;
+    assert_eq!(slice.cast::<u16>().unwrap()[..2], [1, 0]);
+
+    // the length is not a multiple of the record size
+    let mut vec = SliceVec::with_capacity(arena.inner(), 8);
+    vec.extend_from_slice(&[0u8; 6]);
+    assert!(vec.as_slice_of::<u32>().is_none());
+    assert!(vec.as_mut_slice_of::<u32>().is_none());
+
+    // the buffer is misaligned
+    let _pad = SliceVec::<u8>::with_capacity(arena.inner(), 1);
+    let mut vec = SliceVec::with_capacity(arena.inner(), 4);
+    vec.extend_from_slice(&[0u8; 4]);
+    assert_ne!(vec.as_ptr() as usize % 4, 0);
+    assert!(vec.as_slice_of::<u32>().is_none());
+    assert!(vec.into_slice().cast::<u32>().is_none());
+}
@@ -0,0 +1,64 @@
+#![cfg(all(feature = "check_ownership", debug_assertions))]
+
+use std::ptr::NonNull;
+
+use arenavec::common::{self, AllocHandle};
+use arenavec::rc::{Arena, InnerRef, SliceVec};
+use arenavec::{ArenaBacking, ArenaError};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+/// A broken handle, which allocates from a different arena than the one it claims to own.
+#[derive(Clone, Debug)]
+struct Misdirected {
+    alloc: InnerRef,
+    owner: InnerRef,
+}
+
+unsafe impl AllocHandle for Misdirected {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        self.alloc.try_allocate(count)
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        ptr: NonNull<T>,
+        old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        self.alloc.try_allocate_or_extend(ptr, old_count, count)
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.owner.owns(ptr, size)
+    }
+}
+
+#[test]
+fn owned_buffers() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::new(arena.inner());
+    let mut other = SliceVec::new(arena.inner());
+
+    vec.extend_from_slice(&[1, 2, 3]);
+    other.extend_from_slice(&[4, 5]);
+    vec.append(&mut other);
+
+    let tail = vec.split_off(3);
+
+    assert_eq!(&vec[..], &[1, 2, 3]);
+    assert_eq!(&tail[..], &[4, 5]);
+}
+
+#[test]
+#[should_panic(expected = "doesn't belong to the arena")]
+fn foreign_buffer() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let other = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = Misdirected {
+        alloc: other.inner(),
+        owner: arena.inner(),
+    };
+
+    let _vec: common::SliceVec<u32, _> = common::SliceVec::with_capacity(handle, 4);
+}
@@ -0,0 +1,66 @@
+use std::ptr::NonNull;
+
+use arenavec::common::{AllocHandle, SliceVec};
+use arenavec::rc::{Arena, InnerRef};
+use arenavec::{ArenaBacking, ArenaError, CheckedHandle};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+/// A broken handle, which returns pointers one byte past the actual allocation.
+#[derive(Clone, Debug)]
+struct OffByOne(InnerRef);
+
+unsafe impl AllocHandle for OffByOne {
+    fn try_allocate<T>(&self, count: usize) -> Result<NonNull<T>, ArenaError> {
+        let ptr: NonNull<u8> = self.0.try_allocate(count * std::mem::size_of::<T>() + 1)?;
+
+        unsafe { Ok(NonNull::new_unchecked(ptr.as_ptr().add(1)).cast()) }
+    }
+
+    fn try_allocate_or_extend<T>(
+        &self,
+        _ptr: NonNull<T>,
+        _old_count: usize,
+        count: usize,
+    ) -> Result<NonNull<T>, ArenaError> {
+        self.try_allocate(count)
+    }
+
+    fn owns(&self, ptr: NonNull<u8>, size: usize) -> bool {
+        self.0.owns(ptr, size)
+    }
+}
+
+#[test]
+fn checked_handle() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::new(CheckedHandle::new(arena.inner()));
+
+    for i in 0..1000u64 {
+        vec.push(i);
+    }
+
+    vec.shrink_to_fit();
+
+    assert!(vec.iter().cloned().eq(0..1000));
+}
+
+#[test]
+fn foreign_memory() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let other = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let ptr: NonNull<u64> = other.inner().allocate(4);
+
+    assert!(other.inner().owns(ptr.cast(), 32));
+    assert!(!arena.inner().owns(ptr.cast(), 32));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "misaligned")]
+fn misaligned_pointer() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = CheckedHandle::new(OffByOne(arena.inner()));
+
+    let _: NonNull<u64> = handle.allocate(4);
+}
@@ -0,0 +1,62 @@
+use arenavec::chunked::{Arena, SliceVec};
+use arenavec::common::AllocHandle;
+use arenavec::ArenaBacking;
+
+#[test]
+fn growth() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    {
+        let mut vec = SliceVec::new(arena.inner());
+
+        for i in 0..10000usize {
+            vec.push(i);
+        }
+
+        let mut other = SliceVec::new(arena.inner());
+        other.extend_from_slice(&vec);
+
+        assert!(vec.iter().cloned().eq(0..10000));
+        assert!(other.iter().cloned().eq(0..10000));
+        assert!(arena.chunk_count() > 1);
+        assert!(arena.capacity() >= 2 * 10000 * std::mem::size_of::<usize>());
+        assert!(arena.clear().is_err());
+    }
+
+    let cap = arena.capacity();
+
+    assert!(arena.clear().is_ok());
+    assert_eq!(arena.chunk_count(), 1);
+    assert!(arena.capacity() < cap);
+}
+
+#[test]
+fn large_allocation() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let handle = arena.inner();
+
+    let small: std::ptr::NonNull<u8> = handle.allocate(100);
+    let large: std::ptr::NonNull<u64> = handle.allocate(1 << 16);
+
+    unsafe {
+        std::ptr::write_bytes(small.as_ptr(), 1, 100);
+        std::ptr::write_bytes(large.as_ptr(), 2, 1 << 16);
+    }
+
+    assert_eq!(arena.chunk_count(), 2);
+    assert!(arena.capacity() >= 4096 + (8 << 16));
+    assert_eq!(large.as_ptr() as usize % 8, 0);
+}
+
+#[test]
+fn memory_map() {
+    if cfg!(not(miri)) {
+        let arena = Arena::init_capacity(ArenaBacking::MemoryMap, 4096).unwrap();
+        let mut vec = SliceVec::new(arena.inner());
+
+        vec.extend_from_slice(&[0u8; 10000]);
+
+        assert_eq!(vec.len(), 10000);
+        assert_eq!(arena.chunk_count(), 2);
+    }
+}
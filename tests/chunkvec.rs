@@ -0,0 +1,52 @@
+use arenavec::rc::{Arena, ChunkedVec};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn stable_addresses() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let vec = ChunkedVec::with_chunk_size(arena.inner(), 4);
+
+    let first = vec.push(String::from("first"));
+    let refs: Vec<&String> = (0..20).map(|i| vec.push(i.to_string())).collect();
+
+    assert_eq!(first, "first");
+    assert_eq!(vec.len(), 21);
+    assert_eq!(vec.capacity(), 24);
+
+    for (i, r) in refs.iter().enumerate() {
+        assert_eq!(**r, i.to_string());
+        assert!(std::ptr::eq(*r, &vec[i + 1]));
+    }
+}
+
+#[test]
+fn mutation_and_drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut vec = ChunkedVec::with_chunk_size(arena.inner(), 3);
+        vec.extend((0..10).map(|i| (i, rc.clone())));
+
+        for (i, _) in vec.iter_mut() {
+            *i *= 2;
+        }
+
+        assert!(vec.iter().map(|(i, _)| *i).eq((0..10).map(|i| i * 2)));
+        assert_eq!(Rc::strong_count(&rc), 11);
+
+        vec.clear();
+
+        assert!(vec.is_empty());
+        assert_eq!(vec.capacity(), 12);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        vec.push((0, rc.clone()));
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
@@ -0,0 +1,106 @@
+#![cfg(any(feature = "bincode", feature = "borsh"))]
+
+use arenavec::common::AllocHandle;
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::{local, ArenaBacking};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[cfg(feature = "borsh")]
+#[test]
+fn borsh_roundtrip() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let target = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::new(arena.inner());
+    vec.extend_from_slice(&[String::from("a"), String::from("bc")]);
+
+    // the encoding is the same as the one of a standard vector
+    let bytes = borsh::to_vec(&vec).unwrap();
+    assert_eq!(
+        bytes,
+        borsh::to_vec(&vec![String::from("a"), String::from("bc")]).unwrap()
+    );
+
+    let res: SliceVec<String> =
+        SliceVec::deserialize_borsh_in(target.inner(), &mut &bytes[..]).unwrap();
+    assert_eq!(res, vec);
+    assert_eq!(res.handle().arena_id(), target.inner().arena_id());
+
+    // the announced length doesn't fit into the arena
+    let small = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let bytes = borsh::to_vec(&vec![0u64; 1024]).unwrap();
+    assert!(SliceVec::<u64>::deserialize_borsh_in(small.inner(), &mut &bytes[..]).is_err());
+}
+
+#[cfg(feature = "borsh")]
+#[test]
+fn borsh_default_handle() {
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let bytes = borsh::to_vec(&vec![1u32, 2, 3]).unwrap();
+    let vec: local::SliceVec<u32> = borsh::from_slice(&bytes).unwrap();
+    let slice: local::Slice<u32> = borsh::from_slice(&bytes).unwrap();
+
+    assert_eq!(&*vec, &[1, 2, 3]);
+    assert_eq!(&*slice, &[1, 2, 3]);
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn bincode_roundtrip() {
+    use bincode::config;
+    use bincode::de::read::SliceReader;
+    use bincode::de::DecoderImpl;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let target = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::new(arena.inner());
+    vec.extend_from_slice(&[String::from("a"), String::from("bc")]);
+
+    let bytes = bincode::encode_to_vec(&vec, config::standard()).unwrap();
+    assert_eq!(
+        bytes,
+        bincode::encode_to_vec(
+            vec![String::from("a"), String::from("bc")],
+            config::standard()
+        )
+        .unwrap()
+    );
+
+    let mut decoder = DecoderImpl::new(SliceReader::new(&bytes), config::standard(), ());
+    let res: SliceVec<String> = SliceVec::decode_in(target.inner(), &mut decoder).unwrap();
+    assert_eq!(res, vec);
+    assert_eq!(res.handle().arena_id(), target.inner().arena_id());
+
+    // the announced length exceeds the decoder's limit
+    let bytes = bincode::encode_to_vec(vec![0u64; 1024], config::standard()).unwrap();
+    let limited = config::standard().with_limit::<1024>();
+    let mut decoder = DecoderImpl::new(SliceReader::new(&bytes), limited, ());
+    assert!(SliceVec::<u64>::decode_in(arena.inner(), &mut decoder).is_err());
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn bincode_derive() {
+    use bincode::{config, Decode, Encode};
+
+    #[derive(Debug, Encode, Decode, PartialEq)]
+    struct Message {
+        id: u32,
+        payload: local::SliceVec<u8>,
+    }
+
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut payload = local::SliceVec::new_thread_local();
+    payload.extend_from_slice(&[1, 2, 3]);
+    let msg = Message { id: 7, payload };
+
+    let bytes = bincode::encode_to_vec(&msg, config::standard()).unwrap();
+    let (res, len): (Message, _) = bincode::decode_from_slice(&bytes, config::standard()).unwrap();
+
+    assert_eq!(len, bytes.len());
+    assert_eq!(res, msg);
+}
@@ -0,0 +1,118 @@
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::region;
+use arenavec::{ArenaBacking, ArenaOffset, ArenaPtr32};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+/// A binary expression tree node, referring to its children compactly.
+struct Expr {
+    value: i64,
+    children: Option<(ArenaPtr32<Expr>, ArenaPtr32<Expr>)>,
+}
+
+fn eval<H: arenavec::ArenaBase>(handle: &H, expr: ArenaPtr32<Expr>) -> i64 {
+    let expr = unsafe { expr.get(handle) };
+
+    match expr.children {
+        Some((lhs, rhs)) => eval(handle, lhs) + eval(handle, rhs),
+        None => expr.value,
+    }
+}
+
+#[test]
+fn compact_references() {
+    assert_eq!(std::mem::size_of::<ArenaPtr32<Expr>>(), 4);
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+    let mut nodes = SliceVec::with_capacity(handle.clone(), 3);
+
+    nodes.push(Expr {
+        value: 2,
+        children: None,
+    });
+    nodes.push(Expr {
+        value: 3,
+        children: None,
+    });
+
+    let lhs = ArenaPtr32::new(&handle, &nodes[0]).unwrap();
+    let rhs = ArenaPtr32::new(&handle, &nodes[1]).unwrap();
+    nodes.push(Expr {
+        value: 0,
+        children: Some((lhs, rhs)),
+    });
+
+    let root = ArenaPtr32::new(&handle, &nodes[2]).unwrap();
+    assert_eq!(eval(&handle, root), 5);
+
+    unsafe { root.get_mut(&handle) }.children = None;
+    assert_eq!(eval(&handle, root), 0);
+
+    let offset: ArenaOffset<Expr> = root.into();
+    assert_eq!(offset.offset(), root.offset() as usize);
+
+    // pointers before the arena can't be referred to
+    let before = (handle.base().as_ptr() as usize - 8) as *const Expr;
+    assert!(ArenaPtr32::new(&handle, before).is_none());
+}
+
+#[test]
+fn compact_region() {
+    let arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    arena.scope(|token| {
+        let handle = token.weak();
+        let mut nodes = region::SliceVec::with_capacity(handle, 1);
+        nodes.push(Expr {
+            value: 7,
+            children: None,
+        });
+
+        let ptr = ArenaPtr32::new(token, &nodes[0]).unwrap();
+        assert_eq!(eval(&handle, ptr), 7);
+    });
+}
+
+#[test]
+fn serialized_arena() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let handle = arena.inner();
+    let mut nodes = SliceVec::with_capacity(handle.clone(), 3);
+
+    nodes.push(Expr {
+        value: 4,
+        children: None,
+    });
+    nodes.push(Expr {
+        value: 6,
+        children: None,
+    });
+
+    let lhs = ArenaPtr32::new(&handle, &nodes[0]).unwrap();
+    let rhs = ArenaPtr32::new(&handle, &nodes[1]).unwrap();
+    nodes.push(Expr {
+        value: 0,
+        children: Some((lhs, rhs)),
+    });
+
+    let root = ArenaPtr32::new(&handle, &nodes[2]).unwrap();
+
+    let mut bytes = Vec::new();
+    arena.serialize_to(&mut bytes).unwrap();
+    assert!(bytes.len() < 4096);
+
+    let loaded = Arena::load_from(ArenaBacking::SystemAllocation, &bytes[..]).unwrap();
+    assert_eq!(loaded.capacity(), 4096);
+    assert_ne!(loaded.base(), arena.base());
+    assert_eq!(eval(&loaded.inner(), root), 10);
+
+    // allocations continue after the loaded objects
+    let vec = SliceVec::<u64>::with_capacity(loaded.inner(), 1);
+    assert!(vec.as_ptr() as usize - loaded.base().as_ptr() as usize > root.offset() as usize);
+
+    let err = Arena::load_from(ArenaBacking::SystemAllocation, &bytes[1..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(Arena::load_from(ArenaBacking::SystemAllocation, &bytes[..40]).is_err());
+}
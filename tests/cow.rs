@@ -0,0 +1,59 @@
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Config {
+    verbose: bool,
+    level: usize,
+}
+
+#[test]
+fn borrowed_to_owned() {
+    use arenavec::region::{Arena, ArenaCow};
+
+    let default = Config {
+        verbose: false,
+        level: 1,
+    };
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let mut cow: ArenaCow<'_, Config> = ArenaCow::from(&default);
+
+    assert!(cow.is_borrowed());
+    assert_eq!(*cow, default);
+
+    cow.to_mut(token.weak()).level = 3;
+
+    assert!(cow.is_owned());
+    assert_eq!(cow.level, 3);
+    assert_eq!(default.level, 1);
+
+    cow.to_mut(token.weak()).verbose = true;
+
+    assert_eq!(
+        *cow.into_owned(token.weak()),
+        Config {
+            verbose: true,
+            level: 3
+        }
+    );
+}
+
+#[test]
+fn owned() {
+    use arenavec::rc::{Arena, ArenaBox, ArenaCow};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let cow: ArenaCow<'_, usize> = ArenaCow::Owned(ArenaBox::new(arena.inner(), 5));
+        let clone = cow.clone();
+
+        assert!(clone.is_owned());
+        assert_eq!(cow, clone);
+    }
+
+    assert!(arena.clear().is_ok());
+}
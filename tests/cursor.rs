@@ -0,0 +1,79 @@
+use arenavec::rc::{Arena, ArenaCursor};
+use arenavec::ArenaBacking;
+
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn back_patch_length() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut cursor = ArenaCursor::new(arena.inner());
+
+    cursor.write_all(&[0; 4]).unwrap();
+    cursor.write_all(b"payload").unwrap();
+
+    let end = cursor.stream_position().unwrap();
+    cursor.seek(SeekFrom::Start(0)).unwrap();
+    cursor.write_all(&((end - 4) as u32).to_le_bytes()).unwrap();
+    assert_eq!(cursor.position(), 4);
+    cursor.seek(SeekFrom::End(0)).unwrap();
+    cursor.write_all(b"!").unwrap();
+
+    let vec = cursor.into_inner();
+    assert_eq!(&*vec, b"\x07\x00\x00\x00payload!");
+}
+
+#[test]
+fn write_past_end() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut cursor = ArenaCursor::new(arena.inner());
+
+    cursor.write_all(b"ab").unwrap();
+    assert_eq!(cursor.seek(SeekFrom::Current(2)).unwrap(), 4);
+    assert_eq!(cursor.get_ref().len(), 2);
+    cursor.write_all(b"cd").unwrap();
+    assert_eq!(&**cursor.get_ref(), b"ab\0\0cd");
+
+    // overwriting across the end of the buffer grows it
+    cursor.seek(SeekFrom::End(-1)).unwrap();
+    cursor.write_all(b"ef").unwrap();
+    assert_eq!(&**cursor.get_ref(), b"ab\0\0cef");
+
+    assert_eq!(
+        cursor.seek(SeekFrom::Current(-8)).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert_eq!(cursor.position(), 7);
+}
+
+#[test]
+fn read_back() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut cursor = ArenaCursor::new(arena.inner());
+
+    cursor.write_all(b"hello world").unwrap();
+    cursor.set_position(6);
+
+    let mut res = String::new();
+    cursor.read_to_string(&mut res).unwrap();
+    assert_eq!(res, "world");
+
+    // reading past the end yields nothing
+    cursor.set_position(20);
+    assert_eq!(cursor.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn exhausted_arena() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let mut cursor = ArenaCursor::new(arena.inner());
+
+    cursor.write_all(&[1; 1024]).unwrap();
+    cursor.set_position(8192);
+
+    let err = cursor.write(&[1]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    assert_eq!(cursor.get_ref().len(), 1024);
+    assert_eq!(cursor.position(), 8192);
+}
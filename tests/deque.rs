@@ -0,0 +1,127 @@
+use proptest::collection;
+use proptest::prelude::*;
+
+use std::collections::VecDeque;
+
+use arenavec::rc::{Arena, ArenaVecDeque};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[derive(Clone, Debug)]
+enum DequeOp {
+    PushBack(usize),
+    PushFront(usize),
+    PopBack,
+    PopFront,
+    Reserve(usize),
+}
+
+prop_compose! {
+    fn arb_op() (id in 0..5, size in 0..100, val in 0..usize::MAX) -> DequeOp {
+        match id {
+             0 => DequeOp::PushBack(val),
+             1 => DequeOp::PushFront(val),
+             2 => DequeOp::PopBack,
+             3 => DequeOp::PopFront,
+             4 => DequeOp::Reserve(size as usize),
+             _ => unreachable!(),
+        }
+    }
+}
+
+fn rand_op_seq_inner(seq: Vec<DequeOp>) {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut deque = VecDeque::new();
+    let mut arena_deque = ArenaVecDeque::new(arena.inner());
+    // keep a second deque around so that in-place extension is not always possible
+    let mut other = ArenaVecDeque::new(arena.inner());
+
+    for op in seq {
+        match op {
+            DequeOp::PushBack(e) => {
+                deque.push_back(e);
+                arena_deque.push_back(e);
+                other.push_back(e);
+            }
+            DequeOp::PushFront(e) => {
+                deque.push_front(e);
+                arena_deque.push_front(e);
+            }
+            DequeOp::PopBack => assert_eq!(deque.pop_back(), arena_deque.pop_back()),
+            DequeOp::PopFront => assert_eq!(deque.pop_front(), arena_deque.pop_front()),
+            DequeOp::Reserve(l) => {
+                deque.reserve(l);
+                arena_deque.reserve(l);
+            }
+        }
+
+        assert_eq!(deque.len(), arena_deque.len());
+        assert!(deque.iter().eq(arena_deque.iter()));
+        assert_eq!(deque.front(), arena_deque.front());
+        assert_eq!(deque.back(), arena_deque.back());
+    }
+}
+
+#[cfg(not(miri))]
+mod prop {
+    use super::*;
+    const NUM_OPS: usize = 400;
+
+    proptest! {
+        #[test]
+        fn rand_op_seq(seq in collection::vec(arb_op(), NUM_OPS)) {
+            rand_op_seq_inner(seq);
+        }
+    }
+}
+
+#[test]
+fn wrap_around_growth() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut deque = ArenaVecDeque::with_capacity(arena.inner(), 4);
+
+    deque.push_back(2);
+    deque.push_back(3);
+    deque.push_front(1);
+    deque.push_front(0);
+
+    assert_eq!(deque.capacity(), 4);
+    assert_eq!(deque.as_slices(), (&[0, 1][..], &[2, 3][..]));
+
+    deque.push_back(4);
+
+    assert_eq!(deque.capacity(), 8);
+    assert!(deque.iter().cloned().eq(0..5));
+
+    for i in 0..5 {
+        assert_eq!(deque[i], i);
+    }
+}
+
+#[test]
+fn drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut deque = ArenaVecDeque::new(arena.inner());
+
+        for i in 0..20 {
+            if i % 2 == 0 {
+                deque.push_back(rc.clone());
+            } else {
+                deque.push_front(rc.clone());
+            }
+        }
+
+        deque.pop_front();
+        let _clone = deque.clone();
+
+        assert_eq!(Rc::strong_count(&rc), 39);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
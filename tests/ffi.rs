@@ -0,0 +1,60 @@
+#![cfg(feature = "ffi")]
+
+use arenavec::ffi::*;
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::ArenaBacking;
+
+use std::ptr;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn create_alloc_clear() {
+    unsafe {
+        let arena = arenavec_rc_create(DEFAULT_CAPACITY);
+        assert!(!arena.is_null());
+        assert!(arenavec_rc_capacity(arena) >= DEFAULT_CAPACITY);
+
+        let buf = arenavec_rc_alloc(arena, 100, 64) as *mut u8;
+        assert!(!buf.is_null());
+        assert_eq!(buf as usize % 64, 0);
+        ptr::write_bytes(buf, 0xff, 100);
+
+        let zeroed = arenavec_rc_alloc_zeroed(arena, 16, 8) as *const u8;
+        assert!((0..16).all(|i| *zeroed.add(i) == 0));
+        assert!(arenavec_rc_used(arena) >= 116);
+
+        // invalid alignments and exhaustion are reported as null pointers
+        assert!(arenavec_rc_alloc(arena, 8, 3).is_null());
+        assert!(arenavec_rc_alloc(arena, DEFAULT_CAPACITY * 2, 8).is_null());
+
+        // objects allocated on the Rust side block clearing
+        let vec: SliceVec<u8> = SliceVec::with_capacity((*arena).inner(), 8);
+        assert_eq!(arenavec_rc_clear(arena), -1);
+        drop(vec);
+
+        assert_eq!(arenavec_rc_clear(arena), 0);
+        assert_eq!(arenavec_rc_used(arena), 0);
+
+        arenavec_rc_destroy(arena);
+        arenavec_rc_destroy(ptr::null_mut());
+    }
+
+    assert!(arenavec_rc_create(0).is_null());
+}
+
+#[test]
+fn shared_with_rust() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let raw: *const Arena = &arena;
+
+    let mut vec = SliceVec::new(arena.inner());
+    vec.push(1u32);
+
+    let used = arena.used();
+    let buf = unsafe { arenavec_rc_alloc(raw, 32, 8) };
+
+    assert!(!buf.is_null());
+    assert!(arena.used() >= used + 32);
+    assert_eq!(&*vec, &[1]);
+}
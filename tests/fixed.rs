@@ -0,0 +1,52 @@
+use arenavec::common::ArenaError;
+use arenavec::fixed::{ArenaBox, SliceVec, StaticArena};
+
+static ARENA: StaticArena<4096> = StaticArena::new();
+
+#[test]
+fn static_arena() {
+    assert_eq!(ARENA.capacity(), 4096);
+
+    {
+        let token = ARENA.generation_token().unwrap();
+        assert!(matches!(
+            ARENA.generation_token(),
+            Err(ArenaError::AlreadyLocked)
+        ));
+
+        let mut vec = SliceVec::new(token.weak());
+
+        for i in 0..100u32 {
+            vec.push(i);
+        }
+
+        let boxed = ArenaBox::new(token.weak(), String::from("static"));
+
+        assert_eq!(vec.iter().sum::<u32>(), 4950);
+        assert_eq!(&*boxed, "static");
+        assert!(token.used() >= 400);
+
+        // the capacity is fixed
+        assert!(SliceVec::<u8>::try_with_capacity(token.weak(), 4096).is_err());
+    }
+
+    ARENA.scope(|token| {
+        assert_eq!(token.used(), 0);
+
+        let vec: SliceVec<u8> = SliceVec::with_capacity(token.weak(), 2048);
+        assert_eq!(vec.capacity(), 2048);
+    });
+}
+
+#[test]
+fn local_arena() {
+    let arena = StaticArena::<256>::new();
+
+    arena.scope(|token| {
+        let mut vec = SliceVec::new(token.weak());
+        vec.push(1u64);
+
+        assert_eq!(vec.as_ptr() as usize % std::mem::align_of::<u64>(), 0);
+        assert!(SliceVec::<u64>::try_with_capacity(token.weak(), 64).is_err());
+    });
+}
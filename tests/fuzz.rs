@@ -0,0 +1,52 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use arenavec::common::AllocHandle;
+use arenavec::rc::{Arena, Slice, SliceVec};
+use arenavec::{local, ArenaBacking};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn arbitrary_in() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let bytes: Vec<u8> = (0..=255).collect();
+
+    let mut u = Unstructured::new(&bytes);
+    let vec: SliceVec<u32> = SliceVec::arbitrary_in(&mut u, arena.inner()).unwrap();
+    let mut expected = Unstructured::new(&bytes);
+    let len = expected.arbitrary_len::<u32>().unwrap();
+    assert_eq!(vec.len(), len);
+    for elem in vec.iter() {
+        assert_eq!(*elem, u32::arbitrary(&mut expected).unwrap());
+    }
+    assert_eq!(vec.handle().arena_id(), arena.inner().arena_id());
+
+    // all of the input generates the same contents as for a standard vector
+    let expected = Vec::<u16>::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+    let slice: Slice<u16> =
+        Slice::arbitrary_take_rest_in(Unstructured::new(&bytes), arena.inner()).unwrap();
+    assert_eq!(&*slice, &expected[..]);
+
+    // the generated length doesn't fit into the arena
+    let small = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let bytes = vec![0x81; 1 << 16];
+    let mut u = Unstructured::new(&bytes);
+    assert!(SliceVec::<u64>::arbitrary_in(&mut u, small.inner()).is_err());
+    assert!(
+        SliceVec::<u64>::arbitrary_take_rest_in(Unstructured::new(&bytes), small.inner()).is_err()
+    );
+}
+
+#[test]
+fn arbitrary_default_handle() {
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    // each element is preceded by a byte deciding whether to continue, as for `Vec`
+    let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+    let vec = local::SliceVec::<u8>::arbitrary_take_rest(Unstructured::new(&bytes)).unwrap();
+    let slice = local::Slice::<u8>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+
+    assert_eq!(&*vec, &[2, 4, 6, 8]);
+    assert!(slice.len() <= bytes.len());
+}
@@ -0,0 +1,67 @@
+use arenavec::region::{Arena, GenArena};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn stale_indices() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+    let mut gen = GenArena::new(token.weak());
+
+    let a = gen.insert(1);
+    let b = gen.insert(2);
+
+    assert_eq!(gen[a], 1);
+    assert_eq!(gen.remove(a), Some(1));
+    assert_eq!(gen.remove(a), None);
+    assert_eq!(gen.get(a), None);
+
+    let c = gen.insert(3);
+
+    // the slot is reused, but the old index stays stale
+    assert_eq!(c.into_raw_parts().0, a.into_raw_parts().0);
+    assert_ne!(c, a);
+    assert_eq!(gen.get(a), None);
+    assert_eq!(gen[c], 3);
+
+    gen[b] += 10;
+
+    let entries: Vec<_> = gen.iter().map(|(i, v)| (i, *v)).collect();
+
+    assert_eq!(entries, vec![(c, 3), (b, 12)]);
+    assert_eq!(gen.len(), 2);
+
+    gen.clear();
+
+    assert!(gen.is_empty());
+    assert!(!gen.contains(b));
+    assert!(!gen.contains(c));
+
+    let d = gen.insert(4);
+    let e = gen.insert(5);
+
+    assert_ne!(d, c);
+    assert_ne!(e, b);
+    assert_eq!(gen.capacity(), 4);
+}
+
+#[test]
+fn drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    {
+        let mut gen = GenArena::new(token.weak());
+        let indices: Vec<_> = (0..10).map(|_| gen.insert(rc.clone())).collect();
+
+        gen.remove(indices[4]);
+
+        assert_eq!(Rc::strong_count(&rc), 10);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
@@ -0,0 +1,47 @@
+use std::alloc::{GlobalAlloc, Layout};
+use std::thread;
+
+use arenavec::ArenaGlobalAlloc;
+
+#[global_allocator]
+static ALLOC: ArenaGlobalAlloc = ArenaGlobalAlloc::new(1 << 30);
+
+#[test]
+fn global() {
+    let before = ALLOC.used();
+    let handles: Vec<_> = (0..4)
+        .map(|i| thread::spawn(move || (0..1000).map(|j| i * j).collect::<Vec<usize>>()))
+        .collect();
+
+    for (i, h) in handles.into_iter().enumerate() {
+        assert!(h.join().unwrap().into_iter().eq((0..1000).map(|j| i * j)));
+    }
+
+    assert!(ALLOC.used() >= before + 4 * 1000 * std::mem::size_of::<usize>());
+}
+
+#[test]
+fn local() {
+    let alloc = ArenaGlobalAlloc::new(4096);
+    let layout = Layout::from_size_align(100, 64).unwrap();
+
+    unsafe {
+        let a = alloc.alloc(layout);
+        let b = alloc.alloc(layout);
+
+        assert_eq!(a as usize % 64, 0);
+        assert_eq!(b as usize - a as usize, 128);
+
+        // the last allocation is extended in place
+        assert_eq!(alloc.realloc(b, layout, 1000), b);
+        assert_eq!(alloc.used(), 1128);
+
+        assert!(alloc
+            .alloc(Layout::from_size_align(4096, 1).unwrap())
+            .is_null());
+
+        alloc.reset();
+        assert_eq!(alloc.used(), 0);
+        assert_eq!(alloc.alloc(layout), a);
+    }
+}
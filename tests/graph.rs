@@ -0,0 +1,82 @@
+use arenavec::region::{Arena, ArenaGraph};
+use arenavec::{ArenaBacking, EdgeId, NodeId};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn build_and_traverse() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+    let mut graph = ArenaGraph::new(token.weak());
+
+    let a = graph.add_node("a");
+    let b = graph.add_node("b");
+    let c = graph.add_node("c");
+    let d = graph.add_node("d");
+
+    let ab = graph.add_edge(a, b, 1);
+    graph.add_edge(a, c, 2);
+    graph.add_edge(b, d, 3);
+    graph.add_edge(c, d, 4);
+    graph.add_edge(d, a, 5);
+
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 5);
+    assert_eq!(graph[a], "a");
+    assert_eq!(graph[ab], 1);
+    assert_eq!(graph.edge_endpoints(ab), Some((a, b)));
+    assert!(graph.neighbors(a).eq(vec![b, c]));
+    assert!(graph.neighbors(d).eq(vec![a]));
+    assert_eq!(graph.find_edge(c, d), Some(EdgeId::new(3)));
+    assert_eq!(graph.find_edge(d, c), None);
+
+    // breadth-first search from a
+    let mut seen = vec![false; graph.node_count()];
+    let mut queue = std::collections::VecDeque::new();
+    let mut order = Vec::new();
+
+    queue.push_back(a);
+    seen[a.index()] = true;
+
+    while let Some(n) = queue.pop_front() {
+        order.push(graph[n]);
+
+        for m in graph.neighbors(n) {
+            if !seen[m.index()] {
+                seen[m.index()] = true;
+                queue.push_back(m);
+            }
+        }
+    }
+
+    assert_eq!(order, vec!["a", "b", "c", "d"]);
+
+    let total: i32 = graph.edges(a).map(|e| graph[e]).sum();
+    assert_eq!(total, 3);
+
+    graph[NodeId::new(3)] = "e";
+    assert_eq!(graph.node_weight(d), Some(&"e"));
+    assert_eq!(graph.node_weight(NodeId::new(4)), None);
+}
+
+#[test]
+fn drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    {
+        let mut graph = ArenaGraph::new(token.weak());
+        let nodes: Vec<_> = (0..10).map(|_| graph.add_node(rc.clone())).collect();
+
+        for w in nodes.windows(2) {
+            graph.add_edge(w[0], w[1], rc.clone());
+        }
+
+        assert_eq!(Rc::strong_count(&rc), 20);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
@@ -0,0 +1,46 @@
+use arenavec::region::{Arena, ArenaGrid};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn from_fn() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let mut grid = ArenaGrid::from_fn(token.weak(), 4, 3, |x, y| x + 10 * y);
+
+    assert_eq!(grid.width(), 4);
+    assert_eq!(grid.height(), 3);
+    assert_eq!(grid[(3, 2)], 23);
+    assert_eq!(grid.get(4, 0), None);
+    assert_eq!(grid.get(0, 3), None);
+    assert_eq!(grid.row(1), &[10, 11, 12, 13]);
+    assert!(grid.column(2).cloned().eq(vec![2, 12, 22]));
+
+    grid[(0, 0)] = 100;
+
+    for row in grid.rows_mut() {
+        row[1] = 0;
+    }
+
+    let rows: Vec<_> = grid.rows().collect();
+
+    assert_eq!(
+        rows,
+        vec![&[100, 0, 2, 3][..], &[10, 0, 12, 13], &[20, 0, 22, 23]]
+    );
+    assert_eq!(grid.iter().nth(5), Some(((1, 1), &0)));
+}
+
+#[test]
+fn constructors() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let grid: ArenaGrid<u8> = ArenaGrid::new(token.weak(), 2, 2);
+    let filled = ArenaGrid::from_elem(token.weak(), 2, 2, 0u8);
+
+    assert_eq!(grid, filled);
+    assert_eq!(grid.clone().as_slice(), &[0; 4]);
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "hashbrown")]
+
+use arenavec::common::AllocHandle;
+use arenavec::hash;
+use arenavec::rc::{Arena, ArenaHashMap, ArenaHashSet};
+use arenavec::{region, ArenaBacking};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn map() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut map: ArenaHashMap<u32, String> = hash::map_in(arena.inner());
+
+    for i in 0..1000 {
+        map.insert(i, i.to_string());
+    }
+
+    assert_eq!(map.len(), 1000);
+    assert_eq!(map[&500], "500");
+    assert_eq!(map.remove(&3), Some(String::from("3")));
+    assert!(!map.contains_key(&3));
+    assert_eq!(
+        map.allocator().handle().arena_id(),
+        arena.inner().arena_id()
+    );
+}
+
+#[test]
+fn set_with_capacity() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut set: ArenaHashSet<u64> = hash::set_with_capacity_in(arena.inner(), 256);
+    let pos = arena.used();
+
+    for i in 0..256 {
+        set.insert(i * 7);
+    }
+
+    // the table has been allocated up front
+    assert_eq!(arena.used(), pos);
+    assert!(set.contains(&14));
+    assert!(!set.contains(&15));
+}
+
+#[test]
+fn exhausted() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    let mut map: ArenaHashMap<u64, u64> = hash::map_in(arena.inner());
+    assert!(map.try_reserve(1 << 20).is_err());
+    assert!(map.try_reserve(16).is_ok());
+}
+
+#[test]
+fn region() {
+    let arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    arena.scope(|token| {
+        let mut map: region::ArenaHashMap<&str, usize> = hash::map_in(token.weak());
+        map.insert("a", 1);
+        *map.entry("a").or_insert(0) += 1;
+
+        assert_eq!(map["a"], 2);
+    });
+}
@@ -0,0 +1,53 @@
+use proptest::collection;
+use proptest::prelude::*;
+
+use std::collections::BinaryHeap;
+
+use arenavec::rc::{Arena, ArenaBinaryHeap, SliceVec};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[cfg(not(miri))]
+mod prop {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn rand_push_pop(ops in collection::vec(proptest::option::of(0..1000u32), 0..500)) {
+            let arena =
+                Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+            let mut heap = BinaryHeap::new();
+            let mut arena_heap = ArenaBinaryHeap::new(arena.inner());
+
+            for op in ops {
+                match op {
+                    Some(e) => {
+                        heap.push(e);
+                        arena_heap.push(e);
+                    }
+                    None => assert_eq!(heap.pop(), arena_heap.pop()),
+                }
+
+                assert_eq!(heap.peek(), arena_heap.peek());
+                assert_eq!(heap.len(), arena_heap.len());
+            }
+
+            assert_eq!(&*heap.into_sorted_vec(), &*arena_heap.into_sorted_slice());
+        }
+    }
+}
+
+#[test]
+fn from_vec() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::new(arena.inner());
+    vec.extend_from_slice(&[5, 1, 8, 3, 9, 2, 7]);
+
+    let mut heap = ArenaBinaryHeap::from(vec);
+
+    assert_eq!(heap.peek(), Some(&9));
+    assert_eq!(heap.pop(), Some(9));
+    assert_eq!(heap.pop(), Some(8));
+    assert_eq!(&*heap.into_sorted_slice(), &[1, 2, 3, 5, 7]);
+}
@@ -0,0 +1,105 @@
+use arenavec::rc::{Arena, ChunkedVec};
+use arenavec::{ArenaBacking, IntrusiveList, Link, Linked};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[derive(Debug)]
+struct Task {
+    id: usize,
+    link: Link<Task>,
+}
+
+unsafe impl Linked for Task {
+    fn link(&self) -> &Link<Task> {
+        &self.link
+    }
+}
+
+fn task(id: usize) -> Task {
+    Task {
+        id,
+        link: Link::new(),
+    }
+}
+
+fn ids(list: &IntrusiveList<'_, Task>) -> Vec<usize> {
+    list.iter().map(|t| t.id).collect()
+}
+
+#[test]
+fn push_pop() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let tasks = ChunkedVec::new(arena.inner());
+    let mut list = IntrusiveList::new();
+
+    for i in 0..5 {
+        list.push_back(tasks.push(task(i)));
+    }
+
+    list.push_front(tasks.push(task(5)));
+
+    assert_eq!(list.len(), 6);
+    assert_eq!(ids(&list), vec![5, 0, 1, 2, 3, 4]);
+    assert_eq!(list.pop_back().map(|t| t.id), Some(4));
+    assert_eq!(list.pop_front().map(|t| t.id), Some(5));
+    assert!(!tasks[5].link.is_linked());
+    assert!(tasks[0].link.is_linked());
+
+    // unlinked elements can be reused
+    list.push_back(&tasks[5]);
+    assert_eq!(ids(&list), vec![0, 1, 2, 3, 5]);
+
+    list.clear();
+    assert!(list.is_empty());
+    assert!(tasks.iter().all(|t| !t.link.is_linked()));
+}
+
+#[test]
+fn cursor() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let tasks = ChunkedVec::new(arena.inner());
+    let mut list = IntrusiveList::new();
+
+    list.extend((0..6).map(|i| tasks.push(task(i))));
+
+    {
+        let mut cursor = list.cursor_front_mut();
+
+        // remove all odd elements
+        while let Some(t) = cursor.current() {
+            if t.id % 2 == 1 {
+                cursor.remove_current();
+            } else {
+                cursor.move_next();
+            }
+        }
+
+        // at the ghost position now
+        assert!(cursor.remove_current().is_none());
+        cursor.insert_after(tasks.push(task(6)));
+        cursor.insert_before(tasks.push(task(7)));
+        cursor.move_prev();
+        cursor.insert_before(tasks.push(task(8)));
+
+        assert_eq!(cursor.current().map(|t| t.id), Some(7));
+        assert_eq!(cursor.peek_prev().map(|t| t.id), Some(8));
+        assert!(cursor.peek_next().is_none());
+    }
+
+    assert_eq!(ids(&list), vec![6, 0, 2, 4, 8, 7]);
+    assert_eq!(list.len(), 6);
+    assert_eq!(list.back().map(|t| t.id), Some(7));
+}
+
+#[test]
+#[should_panic]
+fn double_link() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let tasks = ChunkedVec::new(arena.inner());
+    let t = tasks.push(task(0));
+    let mut a = IntrusiveList::new();
+    let mut b = IntrusiveList::new();
+
+    a.push_back(t);
+    b.push_back(t);
+}
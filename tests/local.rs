@@ -0,0 +1,84 @@
+use arenavec::local::{self, ArenaBox, Slice, SliceVec};
+use arenavec::{ArenaBacking, ThreadLocalHandle};
+
+use std::thread;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+/// Library code that doesn't know about arenas.
+fn squares(n: usize) -> SliceVec<usize> {
+    let mut vec = SliceVec::new_thread_local();
+
+    for i in 0..n {
+        vec.push(i * i);
+    }
+
+    vec
+}
+
+#[test]
+fn thread_local() {
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    assert!(local::has_thread_local());
+    assert!(local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).is_err());
+    assert_eq!(std::mem::size_of::<ThreadLocalHandle>(), 0);
+
+    let vec = squares(10);
+    let slice: Slice<u8> = Slice::new_thread_local(16);
+    let boxed = ArenaBox::new_thread_local(String::from("boxed"));
+
+    assert!(vec.iter().cloned().eq((0..10).map(|i| i * i)));
+    assert_eq!(slice.len(), 16);
+    assert_eq!(*boxed, "boxed");
+
+    std::mem::drop((vec, slice, boxed));
+
+    unsafe {
+        local::clear_thread_local();
+    }
+
+    assert_eq!(squares(3).len(), 3);
+}
+
+#[test]
+fn uninitialized() {
+    let res = thread::spawn(|| squares(10).len()).join();
+
+    assert!(res.is_err());
+}
+
+#[test]
+fn thread_local_destructors() {
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SUM: AtomicUsize = AtomicUsize::new(0);
+
+    struct Holder(RefCell<Option<SliceVec<usize>>>);
+
+    impl Drop for Holder {
+        fn drop(&mut self) {
+            if let Some(mut vec) = self.0.borrow_mut().take() {
+                vec.truncate(4);
+                vec.shrink_to_fit();
+                SUM.store(vec.iter().sum(), Ordering::SeqCst);
+            }
+        }
+    }
+
+    thread_local! {
+        static HOLDER: Holder = const { Holder(RefCell::new(None)) };
+    }
+
+    thread::spawn(|| {
+        // the holder is registered first, so it is usually destroyed after the arena.
+        HOLDER.with(|_| ());
+        local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+        HOLDER.with(|holder| *holder.0.borrow_mut() = Some(squares(10)));
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(SUM.load(Ordering::SeqCst), 14);
+}
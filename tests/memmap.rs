@@ -0,0 +1,45 @@
+#![cfg(feature = "memmap2")]
+
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::region;
+use arenavec::MemmapSource;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn memmap_backing() {
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = Arena::init_capacity(MemmapSource::backing(), DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut vec = SliceVec::with_capacity(arena.inner(), 1024);
+        vec.resize(1024, 7u64);
+        assert!(vec.iter().all(|&x| x == 7));
+    }
+
+    // growing moves the arena to a fresh mapping
+    arena.grow(DEFAULT_CAPACITY).unwrap();
+    assert_eq!(arena.capacity(), 2 * DEFAULT_CAPACITY);
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+    vec.push(1u8);
+    assert_eq!(&vec[..], &[1]);
+}
+
+#[test]
+fn memmap_region() {
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = region::Arena::init_capacity(MemmapSource::backing(), DEFAULT_CAPACITY).unwrap();
+
+    arena.scope(|token| {
+        let mut vec = region::SliceVec::new(token.weak());
+        vec.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(&vec[..], &[1, 2, 3]);
+    });
+}
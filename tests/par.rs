@@ -0,0 +1,82 @@
+#![cfg(feature = "rayon")]
+
+use arenavec::rc;
+use arenavec::sync::{Arena, Slice, SliceVec};
+use arenavec::ArenaBacking;
+
+use rayon::prelude::*;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+/// An element counting how many of its kind have been dropped.
+struct Counted(usize, Arc<AtomicUsize>);
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        self.1.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn borrowed() {
+    // borrowed iteration doesn't require the handle to be `Send`
+    let arena = rc::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = rc::SliceVec::new(arena.inner());
+    vec.extend_from_slice(&(0..10_000u64).collect::<Vec<_>>());
+
+    vec.par_iter_mut().for_each(|e| *e *= 2);
+    let sum: u64 = (&vec).into_par_iter().sum();
+    assert_eq!(sum, (0..10_000u64).map(|e| e * 2).sum::<u64>());
+
+    let chunks: Vec<u64> = vec.par_chunks(1000).map(|c| c.iter().sum()).collect();
+    assert_eq!(chunks.len(), 10);
+    assert_eq!(chunks.iter().sum::<u64>(), sum);
+
+    let mut slice: rc::Slice<u32> = rc::Slice::new(arena.inner(), 100);
+    slice
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(i, e)| *e = i as u32);
+    assert_eq!(slice.par_iter().max(), Some(&99));
+}
+
+#[test]
+fn owned() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::new(arena.inner());
+    for i in 0..1000 {
+        vec.push(i.to_string());
+    }
+
+    let mut res: Vec<String> = vec.into_par_iter().map(|s| s + "!").collect();
+    res.sort_by_key(|s| s.len());
+    assert_eq!(res.len(), 1000);
+    assert_eq!(res[0], "0!");
+
+    let slice: Slice<u8> = Slice::new(arena.inner(), 64);
+    assert_eq!(slice.into_par_iter().count(), 64);
+}
+
+#[test]
+fn owned_partial() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    let mut vec = SliceVec::new(arena.inner());
+    for i in 0..1000 {
+        vec.push(Counted(i, dropped.clone()));
+    }
+
+    // the elements not taken are dropped in place
+    let taken: Vec<_> = vec.into_par_iter().skip(100).take(10).collect();
+    assert_eq!(taken[0].0, 100);
+    assert_eq!(dropped.load(Ordering::SeqCst), 990);
+
+    drop(taken);
+    assert_eq!(dropped.load(Ordering::SeqCst), 1000);
+}
@@ -1,4 +1,4 @@
-use arenavec::rc::{Arena, SliceVec};
+use arenavec::rc::{Arena, Slice, SliceVec};
 use arenavec::ArenaBacking;
 
 const DEFAULT_CAPACITY: usize = 4096 << 16;
@@ -102,6 +102,20 @@ fn reserve_and_resize() {
     }
 }
 
+#[test]
+fn resize_with_index() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut squares = SliceVec::new(arena.inner());
+    squares.push(100);
+
+    squares.resize_with_index(6, |i| i * i);
+    assert_eq!(&*squares, &[100, 1, 4, 9, 16, 25]);
+
+    squares.resize_with_index(3, |_| unreachable!());
+    assert_eq!(&*squares, &[100, 1, 4]);
+}
+
 #[test]
 fn drop() {
     use std::rc::Rc;
@@ -121,3 +135,1465 @@ fn drop() {
 
     assert_eq!(Rc::strong_count(&rc), 1);
 }
+
+#[test]
+fn insert_remove() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::new(arena.inner());
+
+    vec.insert(0, 2);
+    vec.insert(0, 0);
+    vec.insert(1, 1);
+    vec.insert(3, 3);
+
+    assert_eq!(&*vec, &[0, 1, 2, 3]);
+    assert_eq!(vec.remove(1), 1);
+    assert_eq!(vec.remove(2), 3);
+    assert_eq!(&*vec, &[0, 2]);
+}
+
+#[test]
+fn cross_arena_append() {
+    use arenavec::common::AllocHandle;
+
+    let first = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let second = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    assert_eq!(first.inner().arena_id(), first.inner().arena_id());
+    assert_ne!(first.inner().arena_id(), second.inner().arena_id());
+
+    let mut vec = SliceVec::new(first.inner());
+    let mut other = SliceVec::new(second.inner());
+    vec.extend_from_slice(&[String::from("a"), String::from("b")]);
+    other.extend_from_slice(&[String::from("c"), String::from("d")]);
+
+    vec.append(&mut other);
+
+    assert_eq!(&*vec, &["a", "b", "c", "d"]);
+    assert!(other.is_empty());
+
+    // the split-off part lives in the arena of the original vector
+    let tail = vec.split_off(1);
+    assert_eq!(tail.handle().arena_id(), first.inner().arena_id());
+    assert_eq!(&*tail, &["b", "c", "d"]);
+
+    other.push(String::from("e"));
+    assert_eq!(&*other, &["e"]);
+}
+
+#[test]
+fn spare_capacity() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 8);
+    vec.push(0u32);
+
+    let spare = vec.spare_capacity_mut();
+    assert_eq!(spare.len(), 7);
+
+    for (i, elem) in spare.iter_mut().take(3).enumerate() {
+        elem.write(i as u32 + 1);
+    }
+
+    unsafe { vec.set_len(4) };
+    assert_eq!(&*vec, &[0, 1, 2, 3]);
+}
+
+#[test]
+fn as_ref_borrow() {
+    use std::borrow::Borrow;
+
+    fn sum(values: impl AsRef<[u32]>) -> u32 {
+        values.as_ref().iter().sum()
+    }
+
+    fn zero(mut values: impl AsMut<[u32]>) {
+        values.as_mut().iter_mut().for_each(|e| *e = 0);
+    }
+
+    fn first<B: Borrow<[u32]>>(values: &B) -> u32 {
+        values.borrow()[0]
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::new(arena.inner());
+    vec.extend_from_slice(&[1, 2, 3]);
+    let mut slice: Slice<u32> = Slice::new(arena.inner(), 2);
+    slice[0] = 5;
+
+    assert_eq!(sum(&vec), 6);
+    assert_eq!(sum(&slice), 5);
+    assert_eq!(first(&vec), 1);
+    assert_eq!(first(&slice), 5);
+
+    zero(&mut vec);
+    zero(&mut slice);
+    assert_eq!(&*vec, &[0, 0, 0]);
+    assert_eq!(&*slice, &[0, 0]);
+}
+
+#[test]
+fn mark_reset() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut persistent = SliceVec::with_capacity(arena.inner(), 16);
+    persistent.push(1);
+
+    let marker = arena.mark();
+
+    {
+        let mut temp = SliceVec::new(arena.inner());
+        temp.resize(1000, 0u64);
+
+        // leak the vector, as dropping the last allocation returns its memory to the arena
+        std::mem::forget(temp);
+    }
+
+    assert!(arena.mark() > marker);
+
+    unsafe {
+        arena.reset_to(marker);
+    }
+
+    assert_eq!(arena.mark(), marker);
+
+    // reusing the rewound memory doesn't affect objects allocated before the marker
+    let mut temp = SliceVec::new(arena.inner());
+    temp.resize(1000, 0xffu8);
+    assert_eq!(persistent[0], 1);
+}
+
+#[test]
+fn snapshot_restore() {
+    use arenavec::common::AllocHandle;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+    let board: std::ptr::NonNull<u32> = handle.allocate(64);
+
+    let read = |i| unsafe { *board.as_ptr().add(i) };
+    let write = |i, v| unsafe { *board.as_ptr().add(i) = v };
+
+    for i in 0..64 {
+        write(i, i as u32);
+    }
+
+    let marker = arena.mark();
+    let snapshot = arena.snapshot();
+    assert!(snapshot.len() >= 64 * 4);
+
+    // a speculative computation, which is undone afterwards
+    for i in 0..64 {
+        write(i, 0);
+    }
+
+    let _scratch: std::ptr::NonNull<u64> = handle.allocate(128);
+
+    unsafe {
+        arena.restore(&snapshot);
+    }
+
+    assert_eq!(arena.mark(), marker);
+    assert!((0..64).all(|i| read(i) == i as u32));
+}
+
+#[test]
+fn fallible_allocation() {
+    use arenavec::rc::Slice;
+    use arenavec::ArenaError;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    match SliceVec::<u64>::try_with_capacity(arena.inner(), 1024) {
+        Err(ArenaError::CapacityExceeded { .. }) => (),
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    assert!(Slice::<u8>::try_new(arena.inner(), 5000).is_err());
+    assert!(SliceVec::<u8>::try_with_capacity(arena.inner(), usize::MAX).is_err());
+
+    let mut vec = SliceVec::try_with_capacity(arena.inner(), 256).unwrap();
+
+    for i in 0..256u64 {
+        vec.push(i);
+    }
+
+    assert!(vec.try_reserve(1000).is_err());
+    assert_eq!(vec.capacity(), 256);
+    assert!(vec.try_reserve(usize::MAX).is_err());
+    assert!(vec.iter().cloned().eq(0..256));
+}
+
+#[test]
+fn capacity_overflow() {
+    use arenavec::rc::Slice;
+    use arenavec::ArenaError;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    // the size in bytes overflows, rather than wrapping around to a small allocation
+    match Slice::<u64>::try_new(arena.inner(), usize::MAX / 4) {
+        Err(ArenaError::CapacityOverflow) => (),
+        res => panic!("unexpected result: {:?}", res.map(|slice| slice.len())),
+    }
+
+    let mut vec = SliceVec::try_with_capacity(arena.inner(), 8).unwrap();
+    vec.push(1u32);
+
+    assert!(matches!(
+        vec.try_reserve(usize::MAX),
+        Err(ArenaError::CapacityOverflow)
+    ));
+    assert!(matches!(
+        vec.try_reserve(usize::MAX / 2),
+        Err(ArenaError::CapacityOverflow)
+    ));
+    assert!(matches!(
+        vec.try_reserve(4096),
+        Err(ArenaError::CapacityExceeded { .. })
+    ));
+    assert_eq!(vec.capacity(), 8);
+    assert_eq!(&*vec, &[1]);
+}
+
+#[test]
+fn error_reporting() {
+    use arenavec::rc::Slice;
+    use arenavec::ArenaError;
+    use std::error::Error;
+
+    fn allocate(arena: &Arena, len: usize) -> Result<Slice<u64>, Box<dyn Error>> {
+        Ok(Slice::try_new(arena.inner(), len)?)
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let _first: Slice<u8> = Slice::new(arena.inner(), 96);
+
+    let err = allocate(&arena, 1000).map(|_| ()).unwrap_err();
+
+    match err.downcast_ref::<ArenaError>() {
+        Some(&ArenaError::CapacityExceeded {
+            requested,
+            remaining,
+        }) => {
+            assert_eq!(requested, 8000);
+            assert_eq!(remaining, 4000);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    assert_eq!(
+        err.to_string(),
+        "arena capacity exceeded: requested 8000 bytes, 4000 bytes remaining"
+    );
+    assert_eq!(
+        ArenaError::CapacityOverflow.to_string(),
+        "capacity overflow"
+    );
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn reserve_overflow() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let mut vec = SliceVec::new(arena.inner());
+    vec.push(1u8);
+
+    vec.reserve(usize::MAX);
+}
+
+#[test]
+fn zeroed_allocation() {
+    use arenavec::rc::Slice;
+
+    for backing in [ArenaBacking::MemoryMap, ArenaBacking::SystemAllocation] {
+        if cfg!(miri) {
+            continue;
+        }
+
+        let arena = Arena::init_capacity(backing, DEFAULT_CAPACITY).unwrap();
+
+        {
+            let slice: Slice<u64> = unsafe { Slice::new_zeroed(arena.inner(), 4096) };
+            assert!(slice.iter().all(|&x| x == 0));
+
+            let mut dirty = SliceVec::new(arena.inner());
+            dirty.resize(4096, 0xffu8);
+        }
+
+        // the memory has been written to before the arena has been cleared
+        arena.clear().unwrap();
+
+        let slice: Slice<u64> = unsafe { Slice::new_zeroed(arena.inner(), 8192) };
+        assert!(slice.iter().all(|&x| x == 0));
+    }
+}
+
+#[test]
+fn aligned_allocation() {
+    use arenavec::common::AllocHandle;
+    use arenavec::ArenaError;
+    use std::alloc::Layout;
+    use std::ptr::NonNull;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+
+    for &align in &[64, 128, 4096] {
+        // misalign the arena position first
+        let _: NonNull<u8> = handle.allocate(3);
+
+        let ptr: NonNull<f32> = handle.allocate_aligned(16, align);
+        assert_eq!(ptr.as_ptr() as usize % align, 0);
+
+        let ptr = handle.allocate_layout(Layout::from_size_align(100, align).unwrap());
+        assert_eq!(ptr.as_ptr() as usize % align, 0);
+    }
+
+    match handle.try_allocate_aligned::<u8>(16, 3) {
+        Err(ArenaError::InvalidLayout) => (),
+        _ => panic!("invalid alignment accepted"),
+    }
+}
+
+#[test]
+fn over_aligned_types() {
+    use arenavec::rc::{ArenaBox, Slice};
+
+    #[derive(Clone, Copy, Default)]
+    #[repr(align(128))]
+    struct Align128(u8);
+
+    #[derive(Clone, Copy, Default)]
+    #[repr(align(256))]
+    struct Align256(u8);
+
+    #[derive(Clone, Copy, Default)]
+    #[repr(align(4096))]
+    struct Align4096(u8);
+
+    fn check<T>(ptr: *const T) {
+        assert_eq!(ptr as usize % std::mem::align_of::<T>(), 0);
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    for _ in 0..4 {
+        // misalign the arena position first
+        let _: Slice<u8> = Slice::new(arena.inner(), 3);
+
+        let mut vec: SliceVec<Align128> = SliceVec::new(arena.inner());
+        vec.resize(5, Align128(1));
+        check(vec.as_ptr());
+        assert!(vec.iter().all(|x| x.0 == 1));
+
+        let slice: Slice<Align256> = Slice::new(arena.inner(), 3);
+        check(slice.as_ptr());
+        assert!(slice.iter().all(|x| x.0 == 0));
+
+        let boxed = ArenaBox::new(arena.inner(), Align4096(7));
+        check(&*boxed);
+        assert_eq!(boxed.0, 7);
+
+        let slice: Slice<Align4096> = Slice::new(arena.inner(), 2);
+        check(slice.as_ptr());
+    }
+}
+
+#[test]
+fn reclaim_last_allocation() {
+    use arenavec::rc::Slice;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let full;
+
+    {
+        let mut temp = SliceVec::new(arena.inner());
+
+        for i in 0..100u64 {
+            temp.push(i);
+        }
+
+        full = arena.mark();
+    }
+
+    // the temporary was the last allocation, so its memory has been reclaimed
+    assert!(arena.mark() < full);
+
+    let temp: Slice<u64> = Slice::new(arena.inner(), 16);
+    let _other: Slice<u64> = Slice::new(arena.inner(), 16);
+    let full = arena.mark();
+
+    std::mem::drop(temp);
+
+    // the first slice wasn't the last allocation when it was dropped
+    assert_eq!(arena.mark(), full);
+}
+
+#[test]
+fn shrink_last_allocation() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::with_capacity(arena.inner(), 256);
+
+    for i in 0..100u64 {
+        vec.push(i);
+    }
+
+    let full = arena.mark();
+
+    vec.shrink_to(150);
+    assert_eq!(vec.capacity(), 150);
+    assert!(arena.mark() < full);
+
+    vec.truncate(10);
+    assert_eq!(vec.capacity(), 10);
+
+    let shrunk = arena.mark();
+    let slice = vec.into_slice();
+
+    assert_eq!(arena.mark(), shrunk);
+    assert!(slice.iter().cloned().eq(0..10));
+
+    // the released memory is reused by the next allocation
+    let mut other = SliceVec::with_capacity(arena.inner(), 100);
+    other.push(0u64);
+    assert!(arena.mark() < full);
+}
+
+#[test]
+fn shrink_buried_allocation() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::with_capacity(arena.inner(), 256);
+    let _other = SliceVec::<u64>::with_capacity(arena.inner(), 1);
+
+    for i in 0..100u64 {
+        vec.push(i);
+    }
+
+    let full = arena.mark();
+
+    // the buffer isn't the last allocation, so no memory is released and the capacity is kept.
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 256);
+
+    for _ in 0..10 {
+        vec.truncate(0);
+        assert_eq!(vec.capacity(), 256);
+
+        for i in 0..256u64 {
+            vec.push(i);
+        }
+    }
+
+    assert_eq!(arena.mark(), full);
+}
+
+#[test]
+fn quota() {
+    use arenavec::{ArenaError, Quota};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let warnings = Rc::new(Cell::new(0));
+    let w = warnings.clone();
+
+    arena.set_quota(
+        Quota::new()
+            .soft(1024, move |_| w.set(w.get() + 1))
+            .hard(4096),
+    );
+
+    {
+        let mut vec = SliceVec::new(arena.inner());
+
+        for i in 0..100u32 {
+            vec.push(i);
+        }
+
+        assert_eq!(warnings.get(), 0);
+
+        for i in 0..300u32 {
+            vec.push(i);
+        }
+
+        assert_eq!(warnings.get(), 1);
+
+        match vec.try_reserve(1024) {
+            Err(ArenaError::QuotaExceeded { .. }) => (),
+            _ => panic!("hard quota not enforced"),
+        }
+
+        // allocations within the quota still succeed
+        vec.push(0);
+        assert_eq!(warnings.get(), 1);
+    }
+
+    arena.clear().unwrap();
+
+    let _big: SliceVec<u8> = SliceVec::with_capacity(arena.inner(), 2048);
+    assert_eq!(warnings.get(), 2);
+}
+
+#[test]
+fn grow() {
+    use arenavec::ArenaError;
+
+    for backing in [ArenaBacking::MemoryMap, ArenaBacking::SystemAllocation] {
+        if cfg!(miri) {
+            continue;
+        }
+
+        let arena = Arena::init_capacity(backing, 4096).unwrap();
+
+        {
+            let mut vec = SliceVec::with_capacity(arena.inner(), 512);
+
+            for i in 0..512u64 {
+                vec.push(i);
+            }
+
+            assert!(vec.try_reserve(1).is_err());
+
+            // the arena can only be moved while no objects are live
+            match arena.grow(4096 * 3) {
+                Ok(()) => {
+                    vec.reserve(768);
+                    assert!(vec.iter().cloned().eq(0..512));
+                }
+                Err(ArenaError::CannotGrow) => (),
+                Err(err) => panic!("unexpected error: {:?}", err),
+            }
+        }
+
+        arena.grow(4096 * 3).unwrap();
+        assert!(arena.capacity() >= 4096 * 4);
+
+        let mut vec = SliceVec::with_capacity(arena.inner(), 1024);
+
+        for i in 0..1024u64 {
+            vec.push(i);
+        }
+
+        assert!(vec.iter().cloned().eq(0..1024));
+    }
+}
+
+#[test]
+fn guard_pages() {
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::GuardedMemoryMap, 4096 * 4).unwrap();
+
+    {
+        // the whole capacity is accessible, right up to the trailing guard page
+        let mut vec = SliceVec::with_capacity(arena.inner(), 4096 * 4 - 64);
+        vec.resize(4096 * 4 - 64, 0xffu8);
+
+        assert!(vec.iter().all(|&b| b == 0xff));
+        assert!(vec.try_reserve(1).is_err());
+    }
+
+    arena.grow(4096).unwrap();
+    assert_eq!(arena.capacity(), 4096 * 5);
+}
+
+#[test]
+fn freeze() {
+    use arenavec::ArenaError;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    for backing in [ArenaBacking::MemoryMap, ArenaBacking::SystemAllocation] {
+        let arena = Arena::init_capacity(backing, 4096 * 4).unwrap();
+
+        {
+            let mut table = SliceVec::with_capacity(arena.inner(), 16);
+            table.extend_from_slice(&[1u32, 2, 3, 4]);
+            let table = table.into_slice();
+
+            {
+                let _frozen = arena.freeze().unwrap();
+
+                assert!(arena.is_frozen());
+                assert!(matches!(arena.freeze(), Err(ArenaError::AlreadyLocked)));
+                assert_eq!(&table[..], &[1, 2, 3, 4]);
+
+                // allocations made while frozen land in writable memory after the frozen part
+                let mut vec = SliceVec::new(arena.inner());
+                vec.push(5u32);
+                vec[0] += 1;
+                assert_eq!(&vec[..], &[6]);
+            }
+
+            assert!(!arena.is_frozen());
+        }
+
+        let frozen = arena.freeze().unwrap();
+        assert!(matches!(
+            arena.clear(),
+            Err(ArenaError::CannotClear { live_handles: 0 })
+        ));
+
+        frozen.unfreeze().unwrap();
+        assert!(!arena.is_frozen());
+        arena.clear().unwrap();
+
+        // a leaked guard keeps the arena frozen until it is dropped
+        arena.freeze().unwrap().leak();
+        assert!(arena.is_frozen());
+    }
+}
+
+#[test]
+fn numa_binding() {
+    use arenavec::ArenaError;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    // NUMA support may be compiled out of the kernel, or the system call forbidden.
+    match Arena::init_capacity(ArenaBacking::NumaMemoryMap(0), DEFAULT_CAPACITY) {
+        Ok(arena) => {
+            let mut vec = SliceVec::with_capacity(arena.inner(), 1024);
+            vec.resize(1024, 0xffu8);
+            assert!(vec.iter().all(|&b| b == 0xff));
+        }
+        Err(ArenaError::AllocationFailed { .. }) => (),
+        Err(err) => panic!("unexpected error: {:?}", err),
+    }
+
+    if cfg!(target_os = "linux") {
+        assert!(
+            Arena::init_capacity(ArenaBacking::NumaMemoryMap(1 << 20), DEFAULT_CAPACITY).is_err()
+        );
+    }
+}
+
+#[test]
+fn custom_backing() {
+    use arenavec::common::{AllocHandle, BackingSource};
+    use std::alloc::{self, Layout};
+    use std::ptr::{self, NonNull};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // hands out memory filled with garbage, and keeps track of the memory in use
+    #[derive(Debug, Default)]
+    struct Counting {
+        in_use: AtomicUsize,
+    }
+
+    unsafe impl BackingSource for Counting {
+        fn allocate(&self, capacity: usize) -> Option<NonNull<u8>> {
+            let layout = Layout::from_size_align(capacity, 4096).ok()?;
+            let ptr = NonNull::new(unsafe { alloc::alloc(layout) })?;
+
+            unsafe { ptr::write_bytes(ptr.as_ptr(), 0xff, capacity) };
+            self.in_use.fetch_add(capacity, Ordering::Relaxed);
+
+            Some(ptr)
+        }
+
+        unsafe fn release(&self, base: NonNull<u8>, capacity: usize) {
+            alloc::dealloc(
+                base.as_ptr(),
+                Layout::from_size_align_unchecked(capacity, 4096),
+            );
+            self.in_use.fetch_sub(capacity, Ordering::Relaxed);
+        }
+    }
+
+    let source = Arc::new(Counting::default());
+
+    {
+        let arena = Arena::init_capacity(ArenaBacking::Custom(source.clone()), 4096).unwrap();
+        assert_eq!(source.in_use.load(Ordering::Relaxed), 4096);
+
+        // the memory isn't zeroed by the source, so the arena has to do it
+        let ptr: NonNull<u64> = arena.inner().allocate_zeroed(16);
+        let zeroed = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), 16) };
+        assert!(zeroed.iter().all(|&x| x == 0));
+
+        arena.grow(4096).unwrap();
+        assert_eq!(source.in_use.load(Ordering::Relaxed), 8192);
+    }
+
+    assert_eq!(source.in_use.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn append_split_off() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::new(arena.inner());
+    let mut other = SliceVec::new(arena.inner());
+
+    vec.extend_from_slice(&[1, 2, 3]);
+    other.extend_from_slice(&[4, 5]);
+    vec.append(&mut other);
+
+    assert_eq!(&vec[..], &[1, 2, 3, 4, 5]);
+    assert!(other.is_empty());
+
+    let tail = vec.split_off(3);
+
+    assert_eq!(&vec[..], &[1, 2, 3]);
+    assert_eq!(&tail[..], &[4, 5]);
+}
+
+#[test]
+fn populated_mapping() {
+    use arenavec::common::AllocHandle;
+    use arenavec::rc::Slice;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::PopulatedMemoryMap, 4096 * 4).unwrap();
+
+    {
+        let slice: Slice<u64> = unsafe { Slice::new_zeroed(arena.inner(), 512) };
+        assert!(slice.iter().all(|&x| x == 0));
+
+        let mut vec = SliceVec::with_capacity(arena.inner(), 1024);
+        vec.resize(1024, 0xffu8);
+        assert!(vec.iter().all(|&b| b == 0xff));
+    }
+
+    arena.grow(4096 * 4).unwrap();
+
+    let ptr: std::ptr::NonNull<u8> = arena.inner().allocate_zeroed(4096 * 6);
+    let zeroed = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), 4096 * 6) };
+    assert!(zeroed.iter().all(|&b| b == 0));
+}
+
+#[test]
+fn double_ended() {
+    use arenavec::common::{self, ArenaError};
+    use arenavec::rc::BackRef;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    let mut result = SliceVec::with_capacity(arena.inner(), 16);
+    {
+        let mut scratch: common::SliceVec<u64, BackRef> =
+            common::SliceVec::with_capacity(arena.back(), 16);
+        scratch.extend_from_slice(&[1, 2, 3, 4]);
+
+        for x in scratch.iter() {
+            result.push(x * 2);
+        }
+
+        // both ends meet in the middle
+        assert!(scratch.try_reserve(4096).is_err());
+        assert!(matches!(
+            arena.clear_back(),
+            Err(ArenaError::CannotClear { .. })
+        ));
+    }
+
+    // the back can be cleared while results from the front are live
+    assert!(matches!(
+        arena.clear_front(),
+        Err(ArenaError::CannotClear { .. })
+    ));
+    arena.clear_back().unwrap();
+    assert_eq!(&result[..], &[2, 4, 6, 8]);
+
+    {
+        let mut scratch: common::SliceVec<u8, BackRef> =
+            common::SliceVec::with_capacity(arena.back(), 4096 - 256);
+        scratch.resize(4096 - 256, 0xff);
+        assert!(matches!(
+            arena.clear_back(),
+            Err(ArenaError::CannotClear { .. })
+        ));
+    }
+
+    std::mem::drop(result);
+    arena.clear().unwrap();
+}
+
+#[test]
+fn carve() {
+    use arenavec::common::ArenaError;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096 * 4).unwrap();
+
+    let first = arena.carve(4096).unwrap();
+    let second = arena.carve(4096).unwrap();
+
+    {
+        let mut vec = SliceVec::with_capacity(first.inner(), 1024);
+        vec.resize(1024, 1u8);
+
+        // exhausting one child arena leaves the other one and the parent untouched
+        assert!(matches!(
+            vec.try_reserve(4096),
+            Err(ArenaError::CapacityExceeded { .. })
+        ));
+        assert!(matches!(first.grow(4096), Err(ArenaError::CannotGrow)));
+
+        let mut other = SliceVec::with_capacity(second.inner(), 1024);
+        other.resize(1024, 2u8);
+
+        let mut own = SliceVec::with_capacity(arena.inner(), 1024);
+        own.resize(1024, 3u8);
+
+        assert!(vec.iter().all(|&b| b == 1));
+        assert!(other.iter().all(|&b| b == 2));
+        assert!(own.iter().all(|&b| b == 3));
+
+        assert!(matches!(first.clear(), Err(ArenaError::CannotClear { .. })));
+    }
+
+    first.clear().unwrap();
+    assert!(matches!(
+        arena.carve(4096 * 3),
+        Err(ArenaError::CapacityExceeded { .. })
+    ));
+    assert!(matches!(arena.clear(), Err(ArenaError::CannotClear { .. })));
+
+    std::mem::drop((first, second));
+    arena.clear().unwrap();
+}
+
+#[test]
+fn fork() {
+    use arenavec::common::ArenaError;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let mut system = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    assert!(matches!(system.fork(), Err(ArenaError::CannotFork)));
+
+    if cfg!(not(target_os = "linux")) {
+        return;
+    }
+
+    let mut arena = Arena::init_capacity(ArenaBacking::MemoryMap, 4096 * 4).unwrap();
+    let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+    vec.extend_from_slice(&[1u64, 2, 3, 4]);
+
+    let offset = vec.as_ptr() as usize - arena.base().as_ptr() as usize;
+    let forked = arena.fork().unwrap();
+
+    let copy = unsafe {
+        std::slice::from_raw_parts_mut(forked.base().as_ptr().add(offset) as *mut u64, 4)
+    };
+    assert_eq!(copy, &[1, 2, 3, 4]);
+
+    // writes to either arena are private to it
+    copy[0] = 10;
+    vec[1] = 20;
+    assert_eq!(&vec[..], &[1, 20, 3, 4]);
+    assert_eq!(copy, &[10, 2, 3, 4]);
+
+    // allocations continue after the allocated part in both arenas
+    let other = SliceVec::<u64>::with_capacity(forked.inner(), 16);
+    assert!(other.as_ptr() as usize - forked.base().as_ptr() as usize >= offset + 128);
+
+    let mut forked = forked;
+    std::mem::drop(other);
+    let again = forked.fork().unwrap();
+    let copy =
+        unsafe { std::slice::from_raw_parts(again.base().as_ptr().add(offset) as *const u64, 4) };
+    assert_eq!(copy, &[10, 2, 3, 4]);
+
+    // forking would make the frozen memory writable again
+    let mut frozen = Arena::init_capacity(ArenaBacking::MemoryMap, 4096 * 4).unwrap();
+    SliceVec::<u64>::with_capacity(frozen.inner(), 4).into_slice();
+    std::mem::forget(frozen.freeze().unwrap());
+    assert!(matches!(frozen.fork(), Err(ArenaError::CannotFork)));
+    assert!(frozen.is_frozen());
+}
+
+#[test]
+fn carve_resize() {
+    use arenavec::common::{AllocHandle, ArenaError};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096 * 4).unwrap();
+    let child = arena.carve(4096 * 2).unwrap();
+
+    let ptr = child.try_allocate::<u64>(4).unwrap();
+    unsafe { std::ptr::copy_nonoverlapping([1u64, 2, 3, 4].as_ptr(), ptr.as_ptr(), 4) };
+
+    // the carved memory can't be replaced, so the child arena can't be moved
+    assert!(matches!(
+        child.resize(4096),
+        Err(ArenaError::AllocationFailed { .. })
+    ));
+    assert!(matches!(
+        child.resize(4096 * 4),
+        Err(ArenaError::AllocationFailed { .. })
+    ));
+    assert!(matches!(
+        child.grow(4096),
+        Err(ArenaError::AllocationFailed { .. })
+    ));
+
+    let copy = unsafe { std::slice::from_raw_parts(child.base().as_ptr() as *const u64, 4) };
+    assert_eq!(copy, &[1, 2, 3, 4]);
+
+    let mut vec = SliceVec::with_capacity(child.inner(), 16);
+    vec.extend_from_slice(&[5u64, 6]);
+    assert_eq!(&vec[..], &[5, 6]);
+}
+
+#[test]
+fn fork_resize() {
+    use arenavec::common::{AllocHandle, ArenaError};
+
+    if cfg!(any(miri, not(target_os = "linux"))) {
+        return;
+    }
+
+    let mut arena = Arena::init_capacity(ArenaBacking::MemoryMap, 4096 * 4).unwrap();
+
+    let ptr = arena.try_allocate::<u64>(4).unwrap();
+    unsafe { std::ptr::copy_nonoverlapping([1u64, 2, 3, 4].as_ptr(), ptr.as_ptr(), 4) };
+
+    let forked = arena.fork().unwrap();
+
+    // neither mapping can be replaced, so neither arena can be moved
+    for arena in &[&arena, &forked] {
+        assert!(matches!(
+            arena.resize(1 << 16),
+            Err(ArenaError::AllocationFailed { .. })
+        ));
+        assert!(matches!(
+            arena.resize(4096),
+            Err(ArenaError::AllocationFailed { .. })
+        ));
+        assert!(matches!(
+            arena.grow(4096),
+            Err(ArenaError::AllocationFailed { .. })
+        ));
+
+        let copy = unsafe { std::slice::from_raw_parts(arena.base().as_ptr() as *const u64, 4) };
+        assert_eq!(copy, &[1, 2, 3, 4]);
+
+        let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+        vec.extend_from_slice(&[5u64, 6]);
+        assert_eq!(&vec[..], &[5, 6]);
+    }
+}
+
+#[test]
+fn locked_mapping() {
+    if cfg!(miri) {
+        return;
+    }
+
+    // locking fails if the limit on locked memory is too low, which isn't worth failing over.
+    let arena = match Arena::init_capacity(ArenaBacking::LockedMemoryMap, 4096 * 4) {
+        Ok(arena) => arena,
+        Err(_) => return,
+    };
+
+    {
+        let mut vec = SliceVec::with_capacity(arena.inner(), 1024);
+        vec.resize(1024, 3u32);
+        assert!(vec.iter().all(|&x| x == 3));
+    }
+
+    arena.clear().unwrap();
+
+    if arena.grow(4096 * 4).is_ok() {
+        assert_eq!(arena.capacity(), 4096 * 8);
+    }
+}
+
+#[test]
+fn huge_page_mapping() {
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::HugePageMemoryMap, 1 << 22).unwrap();
+
+    {
+        let mut vec = SliceVec::with_capacity(arena.inner(), 1 << 18);
+        vec.resize(1 << 18, 5u64);
+        assert!(vec.iter().all(|&x| x == 5));
+    }
+
+    arena.clear().unwrap();
+    arena.grow(1 << 22).unwrap();
+    assert_eq!(arena.capacity(), 1 << 23);
+}
+
+#[test]
+fn capacity_rounding() {
+    use arenavec::common::ArenaError;
+
+    assert!(matches!(
+        Arena::init_capacity(ArenaBacking::SystemAllocation, 0),
+        Err(ArenaError::InvalidCapacity)
+    ));
+    assert!(matches!(
+        Arena::init_capacity(ArenaBacking::SystemAllocation, usize::MAX),
+        Err(ArenaError::InvalidCapacity)
+    ));
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 1000).unwrap();
+    assert!(arena.capacity() >= 1000);
+    assert_eq!(arena.capacity() % 4096, 0);
+
+    // the rest of the last page can be used
+    let vec = SliceVec::<u8>::try_with_capacity(arena.inner(), 2000);
+    assert!(vec.is_ok());
+}
+
+#[test]
+fn default_capacity() {
+    use arenavec::common::{ArenaError, DEFAULT_CAPACITY_VAR};
+
+    if cfg!(miri) {
+        return;
+    }
+
+    std::env::set_var(DEFAULT_CAPACITY_VAR, "64K");
+    assert_eq!(Arena::init().unwrap().capacity(), 64 << 10);
+
+    std::env::set_var(DEFAULT_CAPACITY_VAR, "12288");
+    assert_eq!(Arena::init().unwrap().capacity(), 12288);
+
+    std::env::set_var(DEFAULT_CAPACITY_VAR, "lots");
+    assert!(matches!(Arena::init(), Err(ArenaError::InvalidCapacity)));
+
+    std::env::remove_var(DEFAULT_CAPACITY_VAR);
+    assert_eq!(
+        Arena::init().unwrap().capacity(),
+        arenavec::DEFAULT_CAPACITY
+    );
+}
+
+#[test]
+fn clear_force() {
+    use arenavec::common::ArenaError;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 4);
+    vec.push(1u32);
+    let back = arena.back();
+
+    assert!(arena.clear().is_err());
+    unsafe { arena.clear_force() }.unwrap();
+    assert_eq!(arena.used(), 0);
+
+    // the old references can't allocate anymore, but the arena itself can
+    assert!(vec.handle().is_stale());
+    assert!(back.is_stale());
+    assert!(matches!(
+        SliceVec::<u8>::try_with_capacity(vec.handle().clone(), 1),
+        Err(ArenaError::StaleHandle)
+    ));
+    assert!(!arena.is_stale());
+    assert!(!arena.inner().is_stale());
+
+    let fresh = SliceVec::<u32>::with_capacity(arena.inner(), 4);
+    assert_eq!(fresh.capacity(), 4);
+
+    // stale vectors leak their contents when dropped
+    std::mem::drop(vec);
+    std::mem::drop(back);
+    std::mem::drop(fresh);
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn clear_diagnostics() {
+    use arenavec::common::ArenaError;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    assert_eq!(arena.live_handles(), 0);
+
+    let first = SliceVec::<u8>::with_capacity(arena.inner(), 4);
+    let second = SliceVec::<u8>::with_capacity(arena.inner(), 4);
+    let back = arena.back();
+    assert_eq!(arena.live_handles(), 3);
+
+    assert!(matches!(
+        arena.clear(),
+        Err(ArenaError::CannotClear { live_handles: 3 })
+    ));
+    assert!(matches!(
+        arena.clear_front(),
+        Err(ArenaError::CannotClear { live_handles: 2 })
+    ));
+    assert!(matches!(
+        arena.clear_back(),
+        Err(ArenaError::CannotClear { live_handles: 1 })
+    ));
+
+    std::mem::drop((first, second, back));
+    assert_eq!(arena.live_handles(), 0);
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn use_after_clear() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let rc = Rc::new(());
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 4);
+    vec.push(rc.clone());
+    assert_eq!(vec.len(), 1);
+
+    unsafe { arena.clear_force() }.unwrap();
+
+    // the memory of the vector is reused for other objects
+    let mut other = SliceVec::with_capacity(arena.inner(), 4);
+    other.push(0usize);
+
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| vec[0].clone())).is_err());
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| vec.iter().count())).is_err());
+
+    // the contents of the stale vector are leaked rather than dropped
+    std::mem::drop(vec);
+    assert_eq!(Rc::strong_count(&rc), 2);
+    assert_eq!(&other[..], &[0]);
+}
+
+#[test]
+fn into_inner() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let mut vec = SliceVec::with_capacity(arena.inner(), 4);
+    vec.push(7u32);
+    let ptr = vec.as_ptr() as *const u8;
+
+    // the arena is handed back while references to it are live
+    let arena = arena.into_inner().unwrap_err();
+    std::mem::drop(vec);
+
+    let owned = arena.into_inner().unwrap();
+    assert_eq!(owned.len(), DEFAULT_CAPACITY);
+    assert!(matches!(owned.backing(), ArenaBacking::SystemAllocation));
+
+    // the memory isn't released, and can be used for other purposes
+    let base = owned.as_ptr().as_ptr();
+    assert!((ptr as usize) - (base as usize) < owned.len());
+    unsafe {
+        *(ptr as *mut u8) = 1;
+        assert_eq!(*ptr, 1);
+    }
+
+    let (ptr, len, backing) = owned.into_raw_parts();
+    std::mem::drop(unsafe { arenavec::OwnedBacking::from_raw_parts(ptr, len, backing) });
+}
+
+#[test]
+fn clear_observers() {
+    use arenavec::ClearPhase;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let phases = Rc::new(RefCell::new(Vec::new()));
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let observed = phases.clone();
+    arena.on_clear(move |phase| observed.borrow_mut().push(phase));
+
+    // failed clears aren't observed
+    let vec = SliceVec::<u8>::with_capacity(arena.inner(), 8);
+    assert!(arena.clear().is_err());
+    assert!(phases.borrow().is_empty());
+
+    std::mem::drop(vec);
+    arena.clear().unwrap();
+    assert_eq!(*phases.borrow(), [ClearPhase::Before, ClearPhase::After]);
+}
+
+#[test]
+fn resize() {
+    use arenavec::common::{AllocHandle, ArenaError};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    // allocations through the arena itself don't keep it from moving
+    let front = arena.allocate::<u64>(1);
+    let back = arena.back().allocate::<u64>(1);
+    let front_offset = front.as_ptr() as usize - arena.base().as_ptr() as usize;
+    unsafe {
+        *front.as_ptr() = 1;
+        *back.as_ptr() = 2;
+    }
+
+    let vec = SliceVec::<u8>::with_capacity(arena.inner(), 1);
+    assert!(matches!(
+        arena.resize(2 * DEFAULT_CAPACITY),
+        Err(ArenaError::CannotGrow)
+    ));
+    std::mem::drop(vec);
+
+    for &cap in &[2 * DEFAULT_CAPACITY, 4096] {
+        arena.resize(cap).unwrap();
+        assert_eq!(arena.capacity(), cap);
+
+        let base = arena.base().as_ptr();
+        unsafe {
+            assert_eq!(*(base.add(front_offset) as *const u64), 1);
+            assert_eq!(*(base.add(cap - 8) as *const u64), 2);
+        }
+    }
+
+    assert!(matches!(arena.resize(1), Ok(())));
+    assert_eq!(arena.capacity(), 4096);
+
+    arena.clear().unwrap();
+    assert_eq!(arena.used(), 0);
+}
+
+#[test]
+fn child_budget() {
+    use arenavec::common::{ArenaError, SliceVec as GenericSliceVec};
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let child = arena.inner().child(64);
+
+    let mut vec = GenericSliceVec::with_capacity(child.clone(), 4);
+    vec.extend_from_slice(&[1u64, 2, 3, 4]);
+    assert_eq!(child.remaining(), 32);
+
+    // growing the vector in place only charges the additional elements
+    vec.reserve(4);
+    assert_eq!(child.remaining(), 0);
+
+    let other = child.clone();
+    assert!(matches!(
+        GenericSliceVec::<u8, _>::try_with_capacity(other, 1),
+        Err(ArenaError::QuotaExceeded {
+            requested: 1,
+            remaining: 0
+        })
+    ));
+
+    // dropping the last allocation credits its size back
+    std::mem::drop(vec);
+    assert_eq!(child.remaining(), 64);
+
+    // the parent arena isn't limited by the budget
+    let mut unlimited = SliceVec::with_capacity(arena.inner(), 64);
+    unlimited.push(0u64);
+    assert_eq!(child.remaining(), 64);
+}
+
+#[test]
+fn child_budget_shrink() {
+    use arenavec::common::SliceVec as GenericSliceVec;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let child = arena.inner().child(64);
+
+    let mut vec = GenericSliceVec::with_capacity(child.clone(), 8);
+    vec.extend_from_slice(&[1u64, 2]);
+    assert_eq!(child.remaining(), 0);
+
+    // shrinking the last allocation credits the released memory back
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 2);
+    assert_eq!(child.remaining(), 48);
+
+    vec.truncate(1);
+    assert_eq!(child.remaining(), 56);
+
+    // the freed budget can be used by other allocations
+    let mut other = GenericSliceVec::with_capacity(child.clone(), 7);
+    other.extend_from_slice(&[0u64; 7]);
+    assert_eq!(child.remaining(), 0);
+    assert_eq!(&vec[..], &[1]);
+}
+
+#[test]
+fn allocation_failure() {
+    use arenavec::common::ArenaError;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    // no system has this much address space to spare
+    let size = 1 << 62;
+
+    match Arena::init_capacity(ArenaBacking::MemoryMap, size) {
+        Err(ArenaError::AllocationFailed {
+            size: requested,
+            backing,
+            os_error,
+        }) => {
+            assert_eq!(requested, size);
+            assert_eq!(backing, "MemoryMap");
+            assert!(os_error.is_some());
+        }
+        res => panic!("unexpected result: {:?}", res.map(|_| ())),
+    }
+}
+
+#[test]
+fn panic_safety() {
+    use arenavec::rc::Slice;
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
+
+    thread_local! {
+        static LIVE: Cell<isize> = const { Cell::new(0) };
+        static CLONES_LEFT: Cell<usize> = const { Cell::new(usize::MAX) };
+    }
+
+    // counts live instances, and panics when cloned once the budget is used up.
+    struct Counted;
+
+    impl Counted {
+        fn new() -> Self {
+            LIVE.with(|live| live.set(live.get() + 1));
+            Counted
+        }
+    }
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES_LEFT.with(|left| {
+                assert!(left.get() > 0, "clone budget exhausted");
+                left.set(left.get() - 1);
+            });
+
+            Counted::new()
+        }
+    }
+
+    impl Default for Counted {
+        fn default() -> Self {
+            Counted::clone(&Counted::new())
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            LIVE.with(|live| live.set(live.get() - 1));
+        }
+    }
+
+    fn live() -> isize {
+        LIVE.with(|live| live.get())
+    }
+
+    fn fail_after<R>(clones: usize, f: impl FnOnce() -> R) {
+        CLONES_LEFT.with(|left| left.set(clones));
+        assert!(panic::catch_unwind(AssertUnwindSafe(f)).is_err());
+        CLONES_LEFT.with(|left| left.set(usize::MAX));
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::new(arena.inner());
+    vec.resize_with(4, Counted::new);
+    assert_eq!(live(), 4);
+
+    // every element cloned before the panic is dropped exactly once
+    fail_after(2, || vec.clone());
+    assert_eq!(live(), 4);
+
+    let slice: Slice<Counted> = Slice::new(arena.inner(), 4);
+    assert_eq!(live(), 8);
+    fail_after(3, || slice.clone());
+    assert_eq!(live(), 8);
+    std::mem::drop(slice);
+
+    fail_after(1, || Slice::<Counted>::new(arena.inner(), 4));
+    assert_eq!(live(), 4);
+
+    fail_after(2, || vec.resize(8, Counted::new()));
+    assert_eq!(vec.len(), 6);
+    assert_eq!(live(), 6);
+
+    let mut calls = 0;
+    fail_after(usize::MAX, || {
+        vec.resize_with(10, || {
+            calls += 1;
+            assert!(calls < 3);
+            Counted::new()
+        })
+    });
+    assert_eq!(vec.len(), 8);
+    assert_eq!(live(), 8);
+
+    let other = [Counted::new(), Counted::new(), Counted::new()];
+    fail_after(1, || vec.extend_from_slice(&other));
+    assert_eq!(vec.len(), 9);
+    assert_eq!(live(), 12);
+
+    std::mem::drop(other);
+    std::mem::drop(vec);
+    assert_eq!(live(), 0);
+}
+
+#[test]
+fn zero_sized_types() {
+    use arenavec::rc::{ArenaBox, Slice};
+    use std::cell::Cell;
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Marker;
+
+    thread_local! {
+        static DROPS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.with(|drops| drops.set(drops.get() + 1));
+        }
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let used = arena.used();
+
+    let mut vec = SliceVec::new(arena.inner());
+    assert_eq!(vec.capacity(), usize::MAX);
+
+    for _ in 0..100_000 {
+        vec.push(Marker);
+    }
+
+    vec.reserve(1 << 40);
+    vec.resize(200_000, Marker);
+    vec.shrink_to_fit();
+
+    assert_eq!(vec.len(), 200_000);
+    assert_eq!(vec.capacity(), usize::MAX);
+    assert_eq!(vec.pop(), Some(Marker));
+    assert_eq!(vec.clone().len(), 199_999);
+    assert_eq!(vec.as_ptr() as usize, std::mem::align_of::<Marker>());
+
+    let slice: Slice<Marker> = Slice::new(arena.inner(), 1 << 20);
+    let boxed = ArenaBox::new(arena.inner(), Marker);
+    assert_eq!(slice.len(), 1 << 20);
+    assert_eq!(*boxed, Marker);
+
+    // the elements are still dropped
+    let mut counted = SliceVec::new(arena.inner());
+    counted.resize_with(10, || Counted);
+    counted.truncate(4);
+    assert_eq!(DROPS.with(|drops| drops.get()), 6);
+    std::mem::drop(counted);
+    assert_eq!(DROPS.with(|drops| drops.get()), 10);
+
+    // none of them took up any arena space
+    assert_eq!(arena.used(), used);
+}
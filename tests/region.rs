@@ -127,3 +127,488 @@ fn drop() {
 
     assert_eq!(Rc::strong_count(&rc), 1);
 }
+
+#[test]
+fn insert_remove() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+
+    let mut vec = SliceVec::new(token.weak());
+
+    vec.insert(0, 2);
+    vec.insert(0, 0);
+    vec.insert(1, 1);
+    vec.insert(3, 3);
+
+    assert_eq!(&*vec, &[0, 1, 2, 3]);
+    assert_eq!(vec.remove(1), 1);
+    assert_eq!(vec.remove(2), 3);
+    assert_eq!(&*vec, &[0, 2]);
+}
+
+#[test]
+fn scope() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let sum = arena.scope(|token| {
+        let mut vec = SliceVec::new(token.weak());
+
+        for i in 0..10 {
+            vec.push(i);
+        }
+
+        assert!(arena.try_scope(|_| ()).is_err());
+
+        vec.iter().sum::<usize>()
+    });
+
+    assert_eq!(sum, 45);
+
+    // the arena has been unlocked again
+    let token = arena.generation_token().unwrap();
+    assert!(arena.try_scope(|_| ()).is_err());
+    std::mem::drop(token);
+    assert!(arena.try_scope(|_| ()).is_ok());
+}
+
+#[test]
+fn rewind_scope() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut token = arena.generation_token().unwrap();
+
+    {
+        let mut outer = SliceVec::new(token.weak());
+        outer.push(1);
+    }
+
+    let marker = token.mark();
+
+    for i in 0..10 {
+        let len = token.rewind_scope(|token| {
+            let mut vec = SliceVec::new(token.weak());
+            vec.resize(1000 * (i + 1), 0u64);
+            vec.len()
+        });
+
+        assert_eq!(len, 1000 * (i + 1));
+        assert_eq!(token.mark(), marker);
+    }
+
+    let vec: SliceVec<u8> = SliceVec::with_capacity(token.weak(), 100);
+    assert!(token.mark() > marker);
+    std::mem::drop(vec);
+
+    unsafe {
+        token.reset_to(marker);
+    }
+
+    assert_eq!(token.mark(), marker);
+}
+
+#[test]
+fn zeroed_allocation() {
+    use arenavec::region::Slice;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    for _ in 0..3 {
+        let token = arena.generation_token().unwrap();
+
+        let slice: Slice<u32> = unsafe { Slice::new_zeroed(token.weak(), 1024) };
+        assert!(slice.iter().all(|&x| x == 0));
+
+        let mut dirty = SliceVec::new(token.weak());
+        dirty.resize(4096, 0xffu8);
+
+        let slice: Slice<u32> = unsafe { Slice::new_zeroed(token.weak(), 1024) };
+        assert!(slice.iter().all(|&x| x == 0));
+    }
+}
+
+#[test]
+fn decommit_on_clear() {
+    use arenavec::region::Slice;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::MemoryMap, DEFAULT_CAPACITY).unwrap();
+    arena.set_decommit_on_clear(true);
+
+    let ptr = arena.scope(|token| {
+        let mut vec = SliceVec::new(token.weak());
+        vec.resize(4096 * 4, 0xffu8);
+
+        vec.as_ptr()
+    });
+
+    // the memory has been released, and reads as zeroed again
+    let released = unsafe { std::slice::from_raw_parts(ptr, 4096 * 4) };
+    assert!(released.iter().all(|&b| b == 0));
+
+    arena.scope(|token| {
+        let slice: Slice<u32> = unsafe { Slice::new_zeroed(token.weak(), 4096) };
+        assert!(slice.iter().all(|&x| x == 0));
+    });
+}
+
+#[test]
+fn drop_list() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Noisy(u32, Rc<RefCell<Vec<u32>>>);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    let dropped = Rc::new(RefCell::new(Vec::new()));
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut token = arena.generation_token().unwrap();
+
+        let first = token.alloc(Noisy(1, dropped.clone()));
+        first.0 = 10;
+
+        let name = token.alloc(String::from("arena"));
+        name.push_str("vec");
+        assert_eq!(name, "arenavec");
+
+        assert_eq!(*token.alloc(5u64), 5);
+
+        token.rewind_scope(|token| {
+            token.alloc(Noisy(2, dropped.clone()));
+            token.alloc(Noisy(3, dropped.clone()));
+        });
+
+        assert_eq!(&dropped.borrow()[..], &[3, 2]);
+
+        token.alloc(Noisy(4, dropped.clone()));
+    }
+
+    assert_eq!(&dropped.borrow()[..], &[3, 2, 4, 10]);
+}
+
+#[test]
+fn clear_observers() {
+    use arenavec::ClearPhase;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let phases = Rc::new(RefCell::new(Vec::new()));
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let observed = phases.clone();
+    arena.on_clear(move |phase| observed.borrow_mut().push(phase));
+
+    {
+        let token = arena.generation_token().unwrap();
+        let _vec: SliceVec<u8> = SliceVec::with_capacity(token.weak(), 8);
+
+        assert!(phases.borrow().is_empty());
+    }
+
+    assert_eq!(*phases.borrow(), [ClearPhase::Before, ClearPhase::After]);
+}
+
+#[test]
+fn subscope() {
+    fn fill(token: &mut arenavec::region::ArenaToken<'_>, depth: usize) -> usize {
+        let start = token.mark();
+        let mut child = token.subscope();
+
+        {
+            let mut vec = SliceVec::with_capacity(child.weak(), 16);
+            vec.resize(16, depth);
+            assert!(vec.iter().all(|&d| d == depth));
+        }
+
+        let below = if depth > 0 {
+            fill(&mut child, depth - 1)
+        } else {
+            0
+        };
+
+        std::mem::drop(child);
+        assert_eq!(token.mark(), start);
+
+        below + 1
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut token = arena.generation_token().unwrap();
+
+    let before = token.mark();
+    assert_eq!(fill(&mut token, 8), 9);
+    assert_eq!(token.mark(), before);
+
+    // dropping child tokens doesn't end the generation
+    assert!(arena.generation_token().is_err());
+}
+
+#[test]
+fn copy_handle() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let token = arena.generation_token().unwrap();
+    let handle = token.weak();
+
+    // the handle is passed by value without being cloned
+    let mut first = SliceVec::new(handle);
+    let mut second = SliceVec::new(handle);
+    first.push(1u8);
+    second.push(2u8);
+
+    assert_eq!(&first[..], &[1]);
+    assert_eq!(&second[..], &[2]);
+}
+
+#[test]
+fn scope_map() {
+    use arenavec::rc;
+
+    let scratch = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let results =
+        rc::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let squares = scratch.scope_map(results.inner(), |token, dest| {
+        let mut temp = SliceVec::new(token.weak());
+        temp.extend_from_slice(&[3u32, 1, 2]);
+        temp.sort_unstable();
+
+        let mut squares = rc::SliceVec::with_capacity(dest, temp.len());
+        for &x in temp.iter() {
+            squares.push(x * x);
+        }
+
+        squares
+    });
+
+    // the scratch generation has ended, while the result lives on in the other arena
+    assert!(scratch.generation_token().is_ok());
+    assert_eq!(&squares[..], &[1, 4, 9]);
+}
+
+#[test]
+fn generation_usage() {
+    use arenavec::region::GenerationUsage;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let last = {
+        let mut token = arena.generation_token().unwrap();
+
+        let scratch = {
+            let child = token.subscope();
+            let _tmp: SliceVec<u64> = SliceVec::with_capacity(child.weak(), 32);
+
+            child.usage()
+        };
+
+        assert!(scratch.allocated >= 256);
+        assert_eq!(scratch.peak, scratch.allocated);
+
+        // memory released by the child token still counts as allocated, but not towards the peak
+        let _vec: SliceVec<u64> = SliceVec::with_capacity(token.weak(), 16);
+        let usage = token.usage();
+
+        assert!(usage.allocated >= scratch.allocated + 128);
+        assert_eq!(usage.peak, scratch.peak);
+
+        usage
+    };
+
+    // the totals of the last generation remain available until the next one begins
+    assert_eq!(arena.usage(), last);
+
+    arena.scope(|token| assert_eq!(token.usage(), GenerationUsage::default()));
+}
+
+#[test]
+fn partitions() {
+    use arenavec::common::ArenaError;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096 * 8).unwrap();
+
+    assert!(matches!(
+        arena.partition(0),
+        Err(ArenaError::InvalidCapacity)
+    ));
+    assert!(matches!(
+        arena.partition(9),
+        Err(ArenaError::CapacityExceeded { .. })
+    ));
+
+    {
+        let partitions = arena.partition(3).unwrap();
+        assert_eq!(partitions.len(), 3);
+        assert!(arena.generation_token().is_err());
+
+        // every partition hands out a token at the same time
+        let tokens = partitions.tokens().unwrap();
+        assert!(partitions.tokens().is_err());
+
+        let mut vecs: Vec<SliceVec<usize>> = tokens
+            .iter()
+            .map(|token| SliceVec::with_capacity(token.weak(), 4))
+            .collect();
+
+        for (i, vec) in vecs.iter_mut().enumerate() {
+            vec.push(i);
+        }
+
+        for (i, (vec, part)) in vecs.iter().zip(partitions.iter()).enumerate() {
+            let start = part.base().as_ptr() as usize;
+            let addr = vec.as_ptr() as usize;
+
+            assert_eq!(vec[0], i);
+            assert!(addr >= start && addr < start + part.capacity());
+        }
+
+        // partitions are exhausted independently
+        assert_eq!(partitions[0].capacity(), 4096 * 2);
+        assert!(SliceVec::<u8>::try_with_capacity(tokens[1].weak(), 4096 * 4).is_err());
+        assert!(SliceVec::<u8>::try_with_capacity(tokens[2].weak(), 4096).is_ok());
+    }
+
+    // dropping the partitions ends the parent generation
+    assert!(arena.generation_token().is_ok());
+}
+
+#[test]
+fn growable() {
+    use std::rc::Rc;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+    let rc = Rc::new(());
+
+    {
+        let token = arena.generation_token().unwrap();
+        assert!(SliceVec::<u8>::try_with_capacity(token.weak(), 8192).is_err());
+    }
+
+    arena.set_growable(true);
+
+    {
+        let mut token = arena.generation_token().unwrap();
+        let marker = token.mark();
+        token.alloc(vec![rc.clone()]);
+
+        {
+            let child = token.subscope();
+            let mut vec = SliceVec::new(child.weak());
+
+            for i in 0..4096 {
+                vec.push(i);
+            }
+
+            child.alloc(vec![rc.clone()]);
+
+            assert_eq!(vec.iter().sum::<usize>(), 4095 * 4096 / 2);
+            assert!(arena.chunk_count() > 1);
+            assert!(arena.capacity() > 4096 * 8);
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+
+        // the child token has released the additional chunks and the objects in them
+        assert_eq!(arena.chunk_count(), 1);
+        assert_eq!(Rc::strong_count(&rc), 2);
+
+        let _vec: SliceVec<u64> = SliceVec::with_capacity(token.weak(), 1024);
+        assert_eq!(arena.chunk_count(), 2);
+
+        unsafe {
+            token.reset_to(marker);
+        }
+
+        assert_eq!(arena.chunk_count(), 1);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        token.alloc(vec![rc.clone()]);
+        let _vec: SliceVec<u64> = SliceVec::with_capacity(token.weak(), 1024);
+        assert_eq!(arena.chunk_count(), 2);
+    }
+
+    // only the last chunk is kept once the generation ends
+    assert_eq!(Rc::strong_count(&rc), 1);
+    assert_eq!(arena.chunk_count(), 1);
+    assert!(arena.capacity() >= 8192);
+
+    arena.scope(|token| {
+        let vec: SliceVec<u64> = SliceVec::with_capacity(token.weak(), 1024);
+        let start = arena.base().as_ptr() as usize;
+
+        assert!(vec.as_ptr() as usize + 8192 <= start + arena.capacity());
+        assert_eq!(arena.chunk_count(), 1);
+    });
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn stale_generation() {
+    use arenavec::common::ArenaError;
+    use std::panic::{self, AssertUnwindSafe};
+
+    // the smuggled handle refers to the dropped token, which Miri rightfully rejects.
+    if cfg!(miri) {
+        return;
+    }
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    assert_eq!(arena.generation(), 0);
+
+    let mut vec = arena.scope(|token| {
+        let mut vec = SliceVec::new(token.weak());
+        vec.push(1u32);
+
+        assert!(!vec.handle().is_stale());
+
+        unsafe { std::mem::transmute::<SliceVec<'_, u32>, SliceVec<'static, u32>>(vec) }
+    });
+
+    assert_eq!(arena.generation(), 1);
+    assert!(vec.handle().is_stale());
+
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| vec[0])).is_err());
+    assert!(matches!(vec.try_reserve(16), Err(ArenaError::StaleHandle)));
+
+    // the contents are leaked instead of being written to the arena
+    std::mem::drop(vec);
+
+    arena.scope(|token| assert!(!token.weak().is_stale()));
+    assert_eq!(arena.generation(), 2);
+}
+
+#[test]
+fn alloc_convenience() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    arena.scope(|token| {
+        let values = token.alloc_slice_copy(&[1u32, 2, 3]);
+        values[1] = 5;
+        assert_eq!(values, &[1, 5, 3]);
+
+        let s = token.alloc_str("hello");
+        s.make_ascii_uppercase();
+        assert_eq!(s, "HELLO");
+        assert_eq!(token.alloc_str(""), "");
+
+        // objects allocated through a handle live as long as the generation
+        let handle = token.weak();
+        let (name, point) = {
+            let name = handle.alloc_str("origin");
+            let point = handle.alloc((0i32, 0i32));
+
+            (name, point)
+        };
+
+        point.0 = 4;
+        assert_eq!(name, "origin");
+        assert_eq!(*point, (4, 0));
+        assert_eq!(handle.alloc_slice_copy(&[1.5f64]), &[1.5]);
+    });
+}
@@ -0,0 +1,75 @@
+use arenavec::common::ArenaError;
+use arenavec::rc::SliceVec;
+use arenavec::{ArenaBacking, ArenaRegistry, ArenaUsage};
+
+#[test]
+fn named_arenas() {
+    let mut registry = ArenaRegistry::new();
+
+    registry
+        .create("render", ArenaBacking::SystemAllocation, 4096)
+        .unwrap();
+    registry
+        .create("physics", ArenaBacking::SystemAllocation, 8192)
+        .unwrap();
+    assert!(matches!(
+        registry.create("render", ArenaBacking::SystemAllocation, 4096),
+        Err(ArenaError::AlreadyLocked)
+    ));
+
+    assert_eq!(registry.len(), 2);
+    assert!(registry.get("net").is_none());
+
+    let names: Vec<_> = registry.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, &["physics", "render"]);
+
+    {
+        let mut vec = SliceVec::with_capacity(registry.get("render").unwrap().inner(), 16);
+        vec.extend_from_slice(&[1u64; 16]);
+
+        let usage = registry.usage("render").unwrap();
+        assert!(usage.used >= 128);
+        assert_eq!(usage.capacity, 4096);
+
+        assert_eq!(registry.total_usage().capacity, 4096 + 8192);
+        assert_eq!(registry.total_usage().used, usage.used);
+
+        // the other arenas are cleared regardless
+        assert!(matches!(
+            registry.clear(),
+            Err(ArenaError::CannotClear { .. })
+        ));
+    }
+
+    registry.clear().unwrap();
+    assert_eq!(
+        registry.total_usage(),
+        ArenaUsage {
+            used: 0,
+            capacity: 4096 + 8192
+        }
+    );
+
+    assert!(registry.remove("render").is_some());
+    assert_eq!(registry.len(), 1);
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn aggregate_stats() {
+    let mut registry = ArenaRegistry::new();
+
+    registry
+        .create("a", ArenaBacking::SystemAllocation, 4096)
+        .unwrap();
+    registry
+        .create("b", ArenaBacking::SystemAllocation, 4096)
+        .unwrap();
+
+    let _a = SliceVec::<u64>::with_capacity(registry.get("a").unwrap().inner(), 4);
+    let _b = SliceVec::<u64>::with_capacity(registry.get("b").unwrap().inner(), 8);
+
+    let report = registry.stats();
+    assert_eq!(report.get::<u64>().unwrap().allocations, 2);
+    assert_eq!(report.get::<u64>().unwrap().bytes, 96);
+}
@@ -0,0 +1,63 @@
+use arenavec::rc::{Arena, ArenaRingBuffer};
+use arenavec::{ArenaBacking, RingMode};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn overwrite_oldest() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut ring = ArenaRingBuffer::new(arena.inner(), 3, RingMode::OverwriteOldest);
+
+    assert_eq!(ring.push(1), None);
+    assert_eq!(ring.push(2), None);
+    assert_eq!(ring.push(3), None);
+    assert!(ring.is_full());
+    assert_eq!(ring.push(4), Some(1));
+    assert_eq!(ring.push(5), Some(2));
+
+    assert!(ring.iter().cloned().eq(3..6));
+    assert_eq!(ring.oldest(), Some(&3));
+    assert_eq!(ring.newest(), Some(&5));
+    assert_eq!(ring[1], 4);
+    assert_eq!(ring.pop(), Some(3));
+    assert_eq!(ring.len(), 2);
+}
+
+#[test]
+fn fail_when_full() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut ring = ArenaRingBuffer::new(arena.inner(), 2, RingMode::FailWhenFull);
+
+    assert_eq!(ring.push(1), None);
+    assert_eq!(ring.try_push(2), Ok(()));
+    assert_eq!(ring.push(3), Some(3));
+    assert_eq!(ring.try_push(3), Err(3));
+    assert_eq!(ring.pop(), Some(1));
+    assert_eq!(ring.push(3), None);
+    assert!(ring.iter().cloned().eq(2..4));
+
+    let mut empty = ArenaRingBuffer::new(arena.inner(), 0, RingMode::OverwriteOldest);
+
+    assert_eq!(empty.push(1), Some(1));
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut ring = ArenaRingBuffer::new(arena.inner(), 4, RingMode::OverwriteOldest);
+
+        for _ in 0..10 {
+            ring.push(rc.clone());
+        }
+
+        assert_eq!(Rc::strong_count(&rc), 5);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "sanitize")]
+
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::{region, sync, ArenaBacking, POISON};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn poison_on_clear() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let stale = {
+        let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+        vec.resize(16, 1u8);
+
+        vec.as_ptr()
+    };
+
+    arena.clear().unwrap();
+
+    let bytes = unsafe { std::slice::from_raw_parts(stale, 16) };
+    assert!(bytes.iter().all(|&b| b == POISON));
+}
+
+#[test]
+fn poison_on_generation_end() {
+    let arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let stale = arena.scope(|token| {
+        let mut vec = region::SliceVec::with_capacity(token.weak(), 16);
+        vec.resize(16, 1u32);
+
+        vec.as_ptr()
+    });
+
+    let words = unsafe { std::slice::from_raw_parts(stale, 16) };
+    assert!(words.iter().all(|&w| w == u32::from_ne_bytes([POISON; 4])));
+}
+
+#[test]
+fn poison_on_sync_clear() {
+    let mut arena =
+        sync::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let stale = {
+        let mut vec = sync::SliceVec::with_capacity(arena.inner(), 16);
+        vec.resize(16, 1u64);
+
+        vec.as_ptr()
+    };
+
+    arena.clear().unwrap();
+
+    let words = unsafe { std::slice::from_raw_parts(stale, 16) };
+    assert!(words.iter().all(|&w| w == u64::from_ne_bytes([POISON; 8])));
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "serde")]
+
+use arenavec::common::AllocHandle;
+use arenavec::rc::{self, Arena, SliceVec};
+use arenavec::{local, ArenaBacking};
+
+use serde::Deserialize;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[derive(Debug, Deserialize)]
+struct Document {
+    title: String,
+    words: SliceVec<String>,
+    counts: SliceVec<SliceVec<u32>>,
+}
+
+#[test]
+fn derive_rc() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let json = r#"{"title": "doc", "words": ["a", "b", "c"], "counts": [[1, 2], [], [3]]}"#;
+
+    let doc: Document =
+        rc::with_deserialize_arena(&arena.inner(), || serde_json::from_str(json).unwrap());
+
+    assert_eq!(doc.title, "doc");
+    assert_eq!(&*doc.words, &["a", "b", "c"]);
+    assert_eq!(doc.counts.len(), 3);
+    assert_eq!(&*doc.counts[0], &[1, 2]);
+    assert!(doc.counts[1].is_empty());
+    assert_eq!(doc.words.handle().arena_id(), arena.inner().arena_id());
+
+    // without an arena set up, deserialization fails
+    assert!(serde_json::from_str::<Document>(json).is_err());
+}
+
+#[test]
+fn nested_arenas() {
+    let outer = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let inner = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    rc::with_deserialize_arena(&outer.inner(), || {
+        let a: SliceVec<u8> = serde_json::from_str("[1, 2]").unwrap();
+        let b: SliceVec<u8> =
+            rc::with_deserialize_arena(&inner.inner(), || serde_json::from_str("[3]").unwrap());
+        let c: SliceVec<u8> = serde_json::from_str("[4]").unwrap();
+
+        assert_eq!(a.handle().arena_id(), outer.inner().arena_id());
+        assert_eq!(b.handle().arena_id(), inner.inner().arena_id());
+        assert_eq!(c.handle().arena_id(), outer.inner().arena_id());
+    });
+}
+
+#[test]
+fn thread_local() {
+    local::init_thread_local(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let vec: local::SliceVec<i64> = serde_json::from_str("[-1, 0, 1]").unwrap();
+    assert_eq!(&*vec, &[-1, 0, 1]);
+    assert_eq!(serde_json::to_string(&vec).unwrap(), "[-1,0,1]");
+}
+
+#[test]
+fn arc_arena() {
+    use arenavec::arc;
+
+    let arena =
+        arc::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+
+    // the vector is deserialized on one thread and read on another
+    let vec: arc::SliceVec<u32> = std::thread::spawn(move || {
+        arc::with_deserialize_arena(&handle, || serde_json::from_str("[1, 2, 3]").unwrap())
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(&*vec, &[1, 2, 3]);
+    assert_eq!(vec.handle().arena_id(), arena.inner().arena_id());
+    assert!(serde_json::from_str::<arc::SliceVec<u32>>("[1]").is_err());
+}
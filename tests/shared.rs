@@ -0,0 +1,56 @@
+use arenavec::shared::{SharedArena, SliceVec};
+use arenavec::ArenaBacking;
+
+use std::sync::mpsc;
+use std::thread;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn send_handles() {
+    let arena =
+        SharedArena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let (sender, receiver) = mpsc::channel();
+
+    for t in 0..4 {
+        let handle = arena.inner();
+        let sender = sender.clone();
+
+        thread::spawn(move || {
+            let mut vec = SliceVec::new(handle);
+
+            for i in 0..1000 {
+                vec.push(t * 1000 + i);
+            }
+
+            sender.send((t, vec)).unwrap();
+        });
+    }
+
+    std::mem::drop(sender);
+
+    let mut results: Vec<_> = receiver.iter().collect();
+    results.sort_by_key(|&(t, _)| t);
+
+    for (t, vec) in &results {
+        assert!(vec.iter().cloned().eq(t * 1000..(t + 1) * 1000));
+    }
+
+    assert!(arena.clear().is_err());
+    std::mem::drop(results);
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn extend_in_place() {
+    let arena =
+        SharedArena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut vec = SliceVec::with_capacity(arena.inner(), 4);
+
+    vec.extend_from_slice(&[0, 1, 2, 3]);
+    let ptr = vec.as_ptr();
+    vec.push(4);
+
+    assert_eq!(vec.as_ptr(), ptr);
+    assert_eq!(vec.capacity(), 8);
+}
@@ -0,0 +1,108 @@
+#![cfg(unix)]
+
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::{ArenaBacking, ArenaOffset, SharedMapping};
+
+const DEFAULT_CAPACITY: usize = 4096 << 4;
+
+struct Node {
+    value: u32,
+    next: Option<ArenaOffset<Node>>,
+}
+
+#[test]
+fn offsets_across_mappings() {
+    if cfg!(miri) {
+        return;
+    }
+
+    let name = format!("/arenavec-test-{}", std::process::id());
+    let arena =
+        Arena::init_capacity(ArenaBacking::SharedMemory(name.clone()), DEFAULT_CAPACITY).unwrap();
+    let base = arena.inner().base();
+
+    // the name is taken while the arena is live
+    assert!(Arena::init_capacity(ArenaBacking::SharedMemory(name.clone()), 4096).is_err());
+
+    let mut nodes = SliceVec::with_capacity(arena.inner(), 3);
+    let mut next = None;
+
+    for value in 0..3 {
+        nodes.push(Node { value, next });
+        next = Some(ArenaOffset::new(base, &nodes[value as usize]));
+    }
+
+    // the second mapping lives at a different address, but the offsets remain valid
+    let mapping = SharedMapping::open(&name).unwrap();
+    assert_ne!(mapping.base(), base);
+    assert_eq!(mapping.len(), DEFAULT_CAPACITY);
+
+    let mut values = Vec::new();
+
+    while let Some(offset) = next {
+        let node = unsafe { mapping.get(offset) };
+
+        values.push(node.value);
+        next = node.next;
+    }
+
+    assert_eq!(values, [2, 1, 0]);
+
+    // writes are visible through both mappings
+    nodes[0].value = 7;
+    let first = ArenaOffset::new(base, &nodes[0]);
+    assert_eq!(unsafe { mapping.get(first) }.value, 7);
+
+    drop(nodes);
+    drop(arena);
+
+    assert!(SharedMapping::open(&name).is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sealed_arena() {
+    use arenavec::common::ArenaError;
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::io::BorrowedFd;
+
+    if cfg!(miri) {
+        return;
+    }
+
+    let unsealable = Arena::init_capacity(ArenaBacking::MemoryMap, DEFAULT_CAPACITY).unwrap();
+    assert!(matches!(unsealable.seal(), Err(ArenaError::CannotProtect)));
+
+    let arena = Arena::init_capacity(ArenaBacking::SealableMemoryMap, DEFAULT_CAPACITY).unwrap();
+    let base = arena.inner().base();
+
+    let mut nodes = SliceVec::with_capacity(arena.inner(), 2);
+    nodes.push(Node {
+        value: 1,
+        next: None,
+    });
+    let first = ArenaOffset::new(base, &nodes[0]);
+    nodes.push(Node {
+        value: 2,
+        next: Some(first),
+    });
+    let second = ArenaOffset::new(base, &nodes[1]);
+
+    let fd = arena.seal().unwrap();
+    assert!(arena.is_frozen());
+    assert!(arena.clear().is_err());
+    assert!(matches!(arena.seal(), Err(ArenaError::AlreadyLocked)));
+    assert!(SliceVec::<u8>::try_with_capacity(arena.inner(), 1).is_err());
+
+    let mapping = SharedMapping::from_sealed_fd(fd).unwrap();
+    let node = unsafe { mapping.get(second) };
+    assert_eq!(node.value, 2);
+    assert_eq!(unsafe { mapping.get(node.next.unwrap()) }.value, 1);
+
+    // the contents can't be changed through the file either
+    let file: std::fs::File = unsafe { BorrowedFd::borrow_raw(fd) }
+        .try_clone_to_owned()
+        .unwrap()
+        .into();
+    assert!(file.write_at(&[0], 4096).is_err());
+}
@@ -0,0 +1,61 @@
+use arenavec::rc::{Arena, ArenaSlab};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn insert_remove() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut slab = ArenaSlab::new(arena.inner());
+
+    let a = slab.insert("a");
+    let b = slab.insert("b");
+    let c = slab.insert("c");
+
+    assert_eq!((a, b, c), (0, 1, 2));
+    assert_eq!(slab.len(), 3);
+    assert_eq!(slab[b], "b");
+
+    assert_eq!(slab.remove(b), "b");
+    assert_eq!(slab.try_remove(b), None);
+    assert!(!slab.contains(b));
+    assert_eq!(slab.vacant_key(), b);
+
+    slab.remove(a);
+
+    // slots are reused in LIFO order
+    assert_eq!(slab.insert("d"), a);
+    assert_eq!(slab.insert("e"), b);
+    assert_eq!(slab.insert("f"), 3);
+
+    slab[c] = "g";
+
+    let entries: Vec<_> = slab.iter().collect();
+
+    assert_eq!(entries, vec![(0, &"d"), (1, &"e"), (2, &"g"), (3, &"f")]);
+}
+
+#[test]
+fn drop() {
+    use std::rc::Rc;
+
+    let rc = Rc::new(());
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut slab = ArenaSlab::new(arena.inner());
+
+        for _ in 0..10 {
+            slab.insert(rc.clone());
+        }
+
+        slab.remove(3);
+        let clone = slab.clone();
+
+        assert_eq!(Rc::strong_count(&rc), 19);
+        assert_eq!(clone.len(), 9);
+    }
+
+    assert_eq!(Rc::strong_count(&rc), 1);
+    assert!(arena.clear().is_ok());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "stats")]
+
+use arenavec::rc::{Arena, ArenaBox, SliceVec};
+use arenavec::region;
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn per_type_stats() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    {
+        let mut vec = SliceVec::with_capacity(arena.inner(), 16);
+
+        for i in 0..16u64 {
+            vec.push(i);
+        }
+
+        let _a = ArenaBox::new(arena.inner(), 1u32);
+        let _b = ArenaBox::new(arena.inner(), 2u32);
+
+        let report = arena.stats();
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report.get::<u64>().unwrap().allocations, 1);
+        assert_eq!(report.get::<u64>().unwrap().bytes, 128);
+        assert_eq!(report.get::<u32>().unwrap().allocations, 2);
+        assert_eq!(report.get::<u32>().unwrap().bytes, 8);
+        assert!(report.get::<u8>().is_none());
+        assert_eq!(report.total().bytes, 136);
+
+        // the largest consumer comes first
+        assert_eq!(report.iter().next().unwrap().0, "u64");
+        assert!(report.to_string().contains("u32"));
+    }
+
+    arena.clear().unwrap();
+    assert!(arena.stats().is_empty());
+}
+
+#[test]
+fn region_stats() {
+    let arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    for _ in 0..2 {
+        let token = arena.generation_token().unwrap();
+        let _vec: region::SliceVec<u16> = region::SliceVec::with_capacity(token.weak(), 8);
+
+        assert_eq!(arena.stats().get::<u16>().unwrap().bytes, 16);
+    }
+}
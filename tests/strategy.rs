@@ -0,0 +1,40 @@
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+
+use arenavec::rc::{Arena, Slice, SliceVec};
+use arenavec::strategy;
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+thread_local! {
+    static ARENA: Arena =
+        Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+}
+
+fn arb_vec() -> impl Strategy<Value = SliceVec<u32>> {
+    ARENA.with(|arena| strategy::slice_vec(arena.inner(), any::<u32>(), 0..100))
+}
+
+fn arb_slice() -> impl Strategy<Value = Slice<u16>> {
+    ARENA.with(|arena| strategy::slice(arena.inner(), 0..1000u16, 5..10))
+}
+
+proptest! {
+    #[test]
+    fn generated_vecs(mut vec in arb_vec()) {
+        prop_assert!(vec.len() < 100);
+        prop_assert_eq!(vec.capacity(), vec.len());
+
+        let len = vec.len();
+        vec.push(1);
+        prop_assert_eq!(vec[len], 1);
+    }
+
+    #[test]
+    fn generated_slices(slice in arb_slice()) {
+        prop_assert!((5..10).contains(&slice.len()));
+        prop_assert!(slice.iter().all(|&x| x < 1000));
+    }
+}
@@ -0,0 +1,83 @@
+use arenavec::sync::{Arena, ArenaBox, SliceVec};
+use arenavec::ArenaBacking;
+
+use std::thread;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn concurrent_allocation() {
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let threads: Vec<_> = (0..8)
+        .map(|t| {
+            let handle = arena.inner();
+
+            thread::spawn(move || {
+                let mut vec = SliceVec::new(handle.clone());
+                let mut boxes = Vec::new();
+
+                for i in 0..1000 {
+                    vec.push(t * 1000 + i);
+                    boxes.push(ArenaBox::new(handle.clone(), i));
+                }
+
+                (vec, boxes)
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+
+    for (t, (vec, boxes)) in results.iter().enumerate() {
+        assert!(vec.iter().cloned().eq(t * 1000..(t + 1) * 1000));
+        assert!(boxes.iter().map(|b| **b).eq(0..1000));
+    }
+
+    assert!(arena.clear().is_err());
+    std::mem::drop(results);
+    assert!(arena.clear().is_ok());
+}
+
+#[test]
+fn shared_handle() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let handle = arena.inner();
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let mut vec = SliceVec::new(handle.clone());
+                vec.resize(100, 1u8);
+                assert_eq!(vec.iter().map(|&b| b as usize).sum::<usize>(), 100);
+            });
+        }
+    });
+}
+
+#[test]
+fn moved_between_threads() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    // the arena is built on one thread, its contents consumed and the arena cleared on another
+    let (mut arena, vec) = thread::spawn(move || {
+        let mut vec = SliceVec::new(arena.inner());
+        for i in 0..100usize {
+            vec.push(i);
+        }
+
+        (arena, vec)
+    })
+    .join()
+    .unwrap();
+
+    thread::spawn(move || {
+        assert_eq!(vec.iter().sum::<usize>(), 4950);
+        assert!(arena.clear().is_err());
+
+        std::mem::drop(vec);
+        assert!(arena.clear().is_ok());
+    })
+    .join()
+    .unwrap();
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "testing")]
+
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::testing::{Differential, SliceVecOp, VecModel};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn slice_vec_harness() {
+    use SliceVecOp::*;
+
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut harness = Differential::new(4, || SliceVec::new(arena.inner()));
+
+    harness.run(vec![
+        (0, Push(1)),
+        (0, Resize(10, 7)),
+        (1, Clone(0)),
+        (1, Pop),
+        (2, Reserve(100)),
+        (2, Push(3)),
+        (0, Truncate(4)),
+        (3, Pop),
+        (1, Delete),
+        (1, Resize(3, 2)),
+    ]);
+
+    assert_eq!(harness.len(), 4);
+}
+
+/// A wrapper that ignores truncation.
+#[derive(Clone)]
+struct Broken(SliceVec<u32>);
+
+impl VecModel<u32> for Broken {
+    fn push(&mut self, elem: u32) {
+        self.0.push(elem)
+    }
+
+    fn pop(&mut self) -> Option<u32> {
+        self.0.pop()
+    }
+
+    fn resize(&mut self, len: usize, value: u32) {
+        self.0.resize(len, value)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    fn truncate(&mut self, _len: usize) {}
+
+    fn as_slice(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+#[test]
+#[should_panic(expected = "contents of 0 differ")]
+fn wrapper_mismatch() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut harness = Differential::new(1, || Broken(SliceVec::new(arena.inner())));
+
+    harness.apply(0, SliceVecOp::Resize(5, 1));
+    harness.apply(0, SliceVecOp::Truncate(2));
+}
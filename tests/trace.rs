@@ -0,0 +1,80 @@
+#![cfg(feature = "trace")]
+
+use arenavec::rc::{Arena, SliceVec};
+use arenavec::region;
+use arenavec::{trace, ArenaBacking, TraceEvent};
+
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn trace_events() {
+    let rc_arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let region_arena =
+        region::Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    assert_ne!(rc_arena.id(), region_arena.id());
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let ids = [rc_arena.id(), region_arena.id()];
+
+    {
+        let events = events.clone();
+
+        trace::set_hook(move |event: &TraceEvent| {
+            let arena = match *event {
+                TraceEvent::Allocate { arena, .. }
+                | TraceEvent::Extend { arena, .. }
+                | TraceEvent::Clear { arena, .. } => arena,
+            };
+
+            // other tests may run concurrently
+            if ids.contains(&arena) {
+                events.lock().unwrap().push(*event);
+            }
+        });
+    }
+
+    {
+        let mut vec = SliceVec::with_capacity(rc_arena.inner(), 4);
+        vec.extend_from_slice(&[1u32, 2, 3, 4, 5]);
+    }
+
+    rc_arena.clear().unwrap();
+
+    {
+        let token = region_arena.generation_token().unwrap();
+        let _vec: region::SliceVec<u64> = region::SliceVec::with_capacity(token.weak(), 2);
+    }
+
+    trace::clear_hook();
+
+    let events = events.lock().unwrap();
+    let rc_id = rc_arena.id();
+    let region_id = region_arena.id();
+
+    assert_eq!(events.len(), 5);
+    assert_eq!(
+        events[0],
+        TraceEvent::Allocate {
+            arena: rc_id,
+            type_name: "u32",
+            bytes: 16
+        }
+    );
+    assert!(matches!(
+        events[1],
+        TraceEvent::Extend { arena, old_bytes: 16, in_place: true, .. } if arena == rc_id
+    ));
+    // the vector was the last allocation, so its memory has been handed back when it was dropped
+    assert!(matches!(events[2], TraceEvent::Clear { arena, used } if arena == rc_id && used == 0));
+    assert_eq!(
+        events[3],
+        TraceEvent::Allocate {
+            arena: region_id,
+            type_name: "u64",
+            bytes: 16
+        }
+    );
+    assert!(matches!(events[4], TraceEvent::Clear { arena, .. } if arena == region_id));
+}
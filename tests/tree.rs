@@ -0,0 +1,60 @@
+use arenavec::rc::{Arena, NodeArena};
+use arenavec::{ArenaBacking, Id};
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[derive(Debug)]
+enum Expr {
+    Num(i64),
+    Add,
+    Mul,
+}
+
+fn eval(ast: &NodeArena<Expr>, id: Id<Expr>) -> i64 {
+    match ast[id] {
+        Expr::Num(n) => n,
+        Expr::Add => ast.children(id).map(|c| eval(ast, c)).sum(),
+        Expr::Mul => ast.children(id).map(|c| eval(ast, c)).product(),
+    }
+}
+
+#[test]
+fn ast() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut ast = NodeArena::new(arena.inner());
+
+    // (1 + 2) * 3 * 4
+    let mul = ast.alloc(Expr::Mul);
+    let add = ast.alloc_child(mul, Expr::Add);
+    let one = ast.alloc_child(add, Expr::Num(1));
+    ast.alloc_child(add, Expr::Num(2));
+    ast.alloc_child(mul, Expr::Num(3));
+    let four = ast.alloc(Expr::Num(4));
+    ast.append_child(mul, four);
+
+    assert_eq!(ast.len(), 6);
+    assert_eq!(eval(&ast, mul), 36);
+    assert_eq!(ast.parent(one), Some(add));
+    assert_eq!(ast.parent(mul), None);
+    assert!(ast.ancestors(one).eq(vec![add, mul]));
+    assert_eq!(ast.children(mul).count(), 3);
+    assert_eq!(ast.first_child(add), Some(one));
+
+    ast[one] = Expr::Num(5);
+    assert_eq!(eval(&ast, mul), 84);
+    assert_eq!(Id::<Expr>::from_raw(one.into_raw()), one);
+    assert!(ast.get(Id::from_raw(6)).is_none());
+}
+
+#[test]
+#[should_panic]
+fn cycle() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut tree = NodeArena::new(arena.inner());
+
+    let a = tree.alloc(0);
+    let b = tree.alloc_child(a, 1);
+    let c = tree.alloc_child(b, 2);
+
+    tree.append_child(c, a);
+}
@@ -0,0 +1,58 @@
+use arenavec::typed::Arena;
+use arenavec::ArenaBacking;
+
+use std::rc::Rc;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[test]
+fn references() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    let a = arena.alloc(String::from("a"));
+    let b = arena.alloc(String::from("b"));
+
+    a.push('c');
+    b.push_str(a);
+
+    assert_eq!(a, "ac");
+    assert_eq!(b, "bac");
+    assert_eq!(arena.len(), 2);
+}
+
+#[test]
+fn drop_on_clear() {
+    let rc = Rc::new(());
+    let mut arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+
+    for _ in 0..10 {
+        arena.alloc(rc.clone());
+    }
+
+    let slice = arena.alloc_extend((0..5).map(|_| rc.clone()));
+    assert_eq!(slice.len(), 5);
+    assert_eq!(Rc::strong_count(&rc), 16);
+
+    arena.clear();
+    assert_eq!(Rc::strong_count(&rc), 1);
+    assert!(arena.is_empty());
+
+    arena.alloc(rc.clone());
+    assert_eq!(arena.iter_mut().count(), 1);
+
+    std::mem::drop(arena);
+    assert_eq!(Rc::strong_count(&rc), 1);
+}
+
+#[test]
+fn overflow() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, 4096).unwrap();
+
+    assert_eq!(arena.capacity(), 512);
+
+    for i in 0..512u64 {
+        *arena.alloc(i) += 1;
+    }
+
+    assert_eq!(arena.try_alloc(0), Err(0));
+}
@@ -0,0 +1,59 @@
+use proptest::collection;
+use proptest::prelude::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use arenavec::rc::{Arena, ArenaVecMap, ArenaVecSet};
+use arenavec::ArenaBacking;
+
+const DEFAULT_CAPACITY: usize = 4096 << 16;
+
+#[cfg(not(miri))]
+mod prop {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn rand_ops(ops in collection::vec((any::<bool>(), 0..200u8, any::<u32>()), 0..500)) {
+            let arena =
+                Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+            let mut map = BTreeMap::new();
+            let mut arena_map = ArenaVecMap::new(arena.inner());
+            let mut set = BTreeSet::new();
+            let mut arena_set = ArenaVecSet::new(arena.inner());
+
+            for (insert, k, v) in ops {
+                if insert {
+                    assert_eq!(map.insert(k, v), arena_map.insert(k, v));
+                    assert_eq!(set.insert(k), arena_set.insert(k));
+                } else {
+                    assert_eq!(map.remove(&k), arena_map.remove(&k));
+                    assert_eq!(set.remove(&k), arena_set.remove(&k));
+                }
+
+                assert_eq!(map.get(&k), arena_map.get(&k));
+                assert_eq!(set.contains(&k), arena_set.contains(&k));
+            }
+
+            assert!(map.iter().eq(arena_map.iter()));
+            assert!(set.iter().eq(arena_set.iter()));
+        }
+    }
+}
+
+#[test]
+fn borrowed_lookup() {
+    let arena = Arena::init_capacity(ArenaBacking::SystemAllocation, DEFAULT_CAPACITY).unwrap();
+    let mut map = ArenaVecMap::new(arena.inner());
+
+    map.insert(String::from("b"), 2);
+    map.insert(String::from("a"), 1);
+
+    *map.get_mut("b").unwrap() += 1;
+
+    assert_eq!(map.get("a"), Some(&1));
+    assert_eq!(map.get("b"), Some(&3));
+    assert!(map.keys().eq(["a", "b"].iter()));
+    assert_eq!(map.remove("a"), Some(1));
+    assert_eq!(map.len(), 1);
+}